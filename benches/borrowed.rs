@@ -0,0 +1,43 @@
+// owned against borrowed parsing of a 100k line palette. `cargo bench`
+// prints the best of a few runs of each
+#[path = "../tests/common/fixtures.rs"]
+mod fixtures;
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use cool_rs::reader::{parse_borrowed_with, parse_from_reader, Limits, ReaderOptions};
+
+const LINES: usize = 100_000;
+const RUNS: usize = 5;
+
+fn best_of(mut f: impl FnMut()) -> Duration {
+    (0..RUNS)
+        .map(|_| {
+            let start = Instant::now();
+            f();
+            start.elapsed()
+        })
+        .min()
+        .unwrap()
+}
+
+fn main() {
+    let palette = fixtures::palette(LINES);
+    let options = ReaderOptions {
+        limits: Limits {
+            max_colors: LINES,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+
+    let owned = best_of(|| {
+        black_box(parse_from_reader(palette.as_bytes(), &options).unwrap());
+    });
+    let borrowed = best_of(|| {
+        black_box(parse_borrowed_with(&palette, &options).unwrap());
+    });
+    println!("owned    {:>10.2?}", owned);
+    println!("borrowed {:>10.2?}", borrowed);
+}
@@ -0,0 +1,98 @@
+// the hot paths of color math on 10k inputs each: parsing hex, rgba() through
+// the byte scanner against the regex of the float parser, packing, HSL and
+// back, CIEDE2000, and a 10k line scheme. `cargo bench` prints the best of a
+// few runs of each, tests/perf.rs guards the same work against regressions
+#[path = "../tests/common/fixtures.rs"]
+mod fixtures;
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use cool_rs::color::{Canonical, DistanceMetric, Metric, Packed};
+use cool_rs::formats::{ColorFormat, HexFormat, RGBFloatFormat, RGBu8Format};
+use cool_rs::reader::parse_from_reader;
+
+const INPUTS: usize = 10_000;
+const RUNS: usize = 5;
+
+fn best_of(mut f: impl FnMut()) -> Duration {
+    (0..RUNS)
+        .map(|_| {
+            let start = Instant::now();
+            f();
+            start.elapsed()
+        })
+        .min()
+        .unwrap()
+}
+
+fn main() {
+    let colors = fixtures::colors(INPUTS);
+    let hex = fixtures::hex_strings(INPUTS);
+    let (rgb_u8, rgb_f) = fixtures::rgb_strings(INPUTS);
+    let palette = fixtures::palette(INPUTS);
+
+    let results = [
+        (
+            "hex",
+            best_of(|| {
+                for s in &hex {
+                    black_box(HexFormat::parse(s).unwrap());
+                }
+            }),
+        ),
+        (
+            "rgb scanned",
+            best_of(|| {
+                for s in &rgb_u8 {
+                    black_box(RGBu8Format::parse(s).unwrap());
+                }
+            }),
+        ),
+        (
+            "rgb regex",
+            best_of(|| {
+                for s in &rgb_f {
+                    black_box(RGBFloatFormat::parse(s).unwrap());
+                }
+            }),
+        ),
+        (
+            "pack",
+            best_of(|| {
+                let packed: Vec<Packed> = colors.iter().map(Canonical::pack).collect();
+                black_box(
+                    packed
+                        .into_iter()
+                        .map(Canonical::unpack)
+                        .collect::<Vec<_>>(),
+                );
+            }),
+        ),
+        (
+            "hsl",
+            best_of(|| {
+                for color in &colors {
+                    black_box(Canonical::from_hsl(color.to_hsl(), 1.0));
+                }
+            }),
+        ),
+        (
+            "ciede2000",
+            best_of(|| {
+                for pair in colors.windows(2) {
+                    black_box(Metric::DeltaE2000.distance(&pair[0], &pair[1]));
+                }
+            }),
+        ),
+        (
+            "scheme",
+            best_of(|| {
+                black_box(parse_from_reader(palette.as_bytes(), &Default::default()).unwrap());
+            }),
+        ),
+    ];
+    for (name, elapsed) in results {
+        println!("{:<12}{:>10.2?}", name, elapsed);
+    }
+}
@@ -0,0 +1,47 @@
+// Scheme::get on a large named palette, through the name index against a
+// linear scan of the entries. `cargo bench` prints the best of a few runs
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use cool_rs::color::{Canonical, Scheme};
+
+const ENTRIES: usize = 10_000;
+const RUNS: usize = 5;
+
+fn best_of(mut f: impl FnMut()) -> Duration {
+    (0..RUNS)
+        .map(|_| {
+            let start = Instant::now();
+            f();
+            start.elapsed()
+        })
+        .min()
+        .unwrap()
+}
+
+fn main() {
+    let mut scheme = Scheme::new("large");
+    let names: Vec<String> = (0..ENTRIES).map(|i| format!("token.color{}", i)).collect();
+    for (i, name) in names.iter().enumerate() {
+        let color = Canonical::new(i as u8, (i / 256) as u8, 0, 255);
+        scheme.push_named(name, color).unwrap();
+    }
+
+    let indexed = best_of(|| {
+        for name in &names {
+            black_box(scheme.get(name));
+        }
+    });
+    let scanned = best_of(|| {
+        for name in &names {
+            black_box(
+                scheme
+                    .entries
+                    .iter()
+                    .find(|e| e.name.as_deref() == Some(name)),
+            );
+        }
+    });
+    println!("indexed {:>10.2?}", indexed);
+    println!("scanned {:>10.2?}", scanned);
+}
@@ -0,0 +1,18 @@
+// cargo +nightly fuzz run roundtrip
+// parse_any must not panic, and a parsed color has to survive
+// to_hex_string and parsing again. tests/roundtrip.rs runs the same check
+// bounded on stable
+#![no_main]
+
+use cool_rs::prelude::*;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(input) = std::str::from_utf8(data) else {
+        return;
+    };
+    if let Ok(color) = parse_any(input) {
+        let hex = color.to_hex_string();
+        assert_eq!(parse_any(&hex).ok(), Some(color), "{:?} -> {}", input, hex);
+    }
+});
@@ -1,16 +1,125 @@
-use crate::errors::ColorError;
+use crate::errors::{ColorError, ComponentError};
+
+pub mod alpha;
+pub mod borrowed;
+pub mod builder;
+pub mod classify;
+pub mod compact;
+pub mod component;
+pub mod contrast;
+mod convert;
+pub mod cvd;
+pub mod dedup;
+pub mod describe;
+pub mod diff;
+pub mod distance;
+pub mod dither;
+pub mod edit;
+pub mod fingerprint;
+mod float;
+pub mod gamut;
+pub mod grayscale;
+pub mod hsl;
+pub mod hue;
+mod index;
+pub mod lab;
+pub mod linear;
+pub mod matrix;
+pub mod oklch;
+pub mod packed;
+pub mod pages;
+pub mod palettes;
+pub mod quantize;
+pub mod recolor;
+pub mod resample;
+pub mod role;
+pub mod shades;
+pub mod similarity;
+pub mod source;
+pub mod space;
+pub mod stats;
+pub mod table;
+pub mod temperature;
+pub mod validate;
+pub mod xyz;
+
+pub use alpha::AlphaPolicy;
+pub use borrowed::{EntryRef, SchemeRef};
+pub use builder::SchemeBuilder;
+pub use classify::{EntryClass, SchemeClassification};
+pub use compact::CompactScheme;
+pub use component::Component;
+pub use convert::{FromColor, IntoColor};
+pub use cvd::Cvd;
+pub use dedup::Merge;
+pub use describe::ColorDescription;
+pub use diff::{Change, EntryKey, MergeConflict, SchemeDiff};
+pub use fingerprint::EntryOrder;
+pub use distance::{DistanceMetric, Metric};
+pub use grayscale::GrayscaleMethod;
+pub use hsl::HSL;
+pub use hue::Hue;
+pub use lab::{Lab, LabF64};
+pub use linear::AverageSpace;
+pub use matrix::ColorMatrix;
+pub use oklch::Oklch;
+pub use recolor::LengthPolicy;
+pub use role::Role;
+#[allow(deprecated)]
+pub use packed::{dump_hex, parse_hex_dump, Packed, PackedU32};
+pub use pages::PagePrefix;
+pub use source::SourceLocation;
+pub use space::{ColorSpace, GamutPolicy};
+pub use stats::SchemeStats;
+pub use table::TableOptions;
+pub use temperature::Warmth;
+pub use validate::{ContrastRule, ValidationReport, ValidationRules, Violation};
+pub use xyz::{adapt, WhitePoint, XYZ};
+
+use index::NameIndex;
+
+#[derive(Debug, Clone)]
+pub struct Entry {
+    pub name: Option<String>,
+    pub color: Canonical,
+    pub role: Option<Role>,
+    // what a scheme file says about the entry in `//` comments, lines joined
+    // with \n
+    pub comment: Option<String>,
+    // where a text reader found the entry, None for entries built in code
+    pub source: Option<SourceLocation>,
+}
+
+// where an entry was read from is no part of it, a parsed scheme equals the
+// same scheme built in code
+impl PartialEq for Entry {
+    fn eq(&self, other: &Entry) -> bool {
+        self.name == other.name
+            && self.color == other.color
+            && self.role == other.role
+            && self.comment == other.comment
+    }
+}
 
+#[derive(PartialEq, Debug, Clone)]
 pub struct Scheme {
     pub name: String,
-    pub colors: Vec<Canonical>,
+    pub author: Option<String>,
+    pub entries: Vec<Entry>,
+    index: NameIndex,
 }
 
+// schemes are shared between threads, e.g. by the registry
+const _: fn() = || {
+    fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<Scheme>();
+};
+
 const BIT_SHIFT_RED: usize =   3 * 8;
 const BIT_SHIFT_GREEN: usize = 2 * 8;
 const BIT_SHIFT_BLUE: usize =      8;
 
 pub type Canonical = RGBA<u8>;
-pub type Packed = u32;
 
 #[derive(PartialEq, Eq, Debug, Clone)]
 pub struct RGB<T> {
@@ -19,7 +128,7 @@ pub struct RGB<T> {
     pub b: T,
 }
 
-#[derive(PartialEq, Eq, Debug, Clone)]
+#[derive(PartialEq, Eq, Hash, Debug, Clone)]
 pub struct RGBA<T> {
     pub r: T,
     pub g: T,
@@ -30,35 +139,183 @@ pub struct RGBA<T> {
 impl<T: Copy> Copy for RGBA<T> {}
 impl<T: Copy> Copy for RGB<T> {}
 
+impl Entry {
+    pub fn new(color: Canonical) -> Entry {
+        Entry { name: None, color, role: None, comment: None, source: None }
+    }
+
+    // the role is inferred from the name, see Role::from_name
+    pub fn named(name: &str, color: Canonical) -> Entry {
+        Entry {
+            name: Some(name.into()),
+            color,
+            role: Role::from_name(name),
+            comment: None,
+            source: None,
+        }
+    }
+
+    pub fn with_role(self, role: Option<Role>) -> Entry {
+        Entry { role, ..self }
+    }
+
+    pub fn with_comment(self, comment: &str) -> Entry {
+        Entry { comment: Some(comment.into()), ..self }
+    }
+}
+
+impl Scheme {
+    pub fn new(name: &str) -> Scheme {
+        Scheme::from_entries(name.into(), None, Vec::new())
+    }
+
+    pub fn from_colors(name: &str, colors: Vec<Canonical>) -> Scheme {
+        Scheme::from_entries(name.into(), None, colors.into_iter().map(Entry::new).collect())
+    }
+
+    pub(crate) fn from_entries(name: String, author: Option<String>, entries: Vec<Entry>) -> Scheme {
+        let index = NameIndex::build(&entries);
+        Scheme { name, author, entries, index }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn colors(&self) -> impl Iterator<Item = &Canonical> {
+        self.entries.iter().map(|e| &e.color)
+    }
+
+    // color of the first entry with the given name
+    pub fn get(&self, name: &str) -> Option<&Canonical> {
+        self.position(name).map(|i| &self.entries[i].color)
+    }
+
+    pub fn has_duplicate_names(&self) -> bool {
+        let names: Vec<&str> = self.entries.iter().filter_map(|e| e.name.as_deref()).collect();
+        names
+            .iter()
+            .enumerate()
+            .any(|(i, name)| names[..i].contains(name))
+    }
+
+    // a scheme of the same name and entry names, with every color mapped by `f`
+    pub fn map_colors<F>(&self, f: F) -> Scheme
+    where
+        F: Fn(&Canonical) -> Canonical,
+    {
+        Scheme {
+            name: self.name.clone(),
+            author: self.author.clone(),
+            entries: self
+                .entries
+                .iter()
+                .map(|e| Entry {
+                    color: f(&e.color),
+                    ..e.clone()
+                })
+                .collect(),
+            index: self.index.clone(),
+        }
+    }
+}
+
 impl Canonical {
 
+    // components in [0, 1]. anything outside is clamped and NaN becomes 0,
+    // try_from_f refuses them instead
     pub fn from_f(r: f32, g: f32, b: f32, a: f32) -> Canonical {
-        Self::new(
-            (r * 255.0).round() as u8,
-            (g * 255.0).round() as u8,
-            (b * 255.0).round() as u8,
-            (a * 255.0).round() as u8
-        )
+        RGBA::new_clamped(r, g, b, a).convert()
+    }
+
+    pub fn try_from_f(r: f32, g: f32, b: f32, a: f32) -> Result<Canonical, ComponentError> {
+        RGBA::try_new(r, g, b, a).map(|c| c.convert())
     }
 
     pub fn parse_from_hex(input: &str) -> Result<Self, ColorError> {
-        let hex_str = input.trim_start_matches('#');
-        if hex_str.len() == 6 {
-            let r = u8::from_str_radix(&hex_str[..2], 16)?;
-            let g = u8::from_str_radix(&hex_str[2..4], 16)?;
-            let b = u8::from_str_radix(&hex_str[4..6], 16)?;
+        Self::parse_from_hex_bytes(input.as_bytes())
+    }
+
+    // hex is ASCII, so the input doesn't have to be valid UTF-8 and nothing
+    // is allocated unless parsing fails
+    pub fn parse_from_hex_bytes(input: &[u8]) -> Result<Self, ColorError> {
+        let mut hex = input;
+        while let [b'#', rest @ ..] = hex {
+            hex = rest;
+        }
+        let digit = |byte: u8| (byte as char).to_digit(16);
+        let channel = |digits: &[u8], component| match (digit(digits[0]), digit(digits[1])) {
+            (Some(high), Some(low)) => Ok((high << 4 | low) as u8),
+            // from_str_radix for the error, and for what it accepts beyond
+            // two digits like `+f`
+            _ => {
+                let digits = String::from_utf8_lossy(digits);
+                u8::from_str_radix(&digits, 16).map_err(|source| ColorError::ParseToIntError {
+                    input: digits.into_owned(),
+                    component,
+                    source,
+                })
+            }
+        };
+        if hex.len() == 6 {
+            let r = channel(&hex[..2], "red")?;
+            let g = channel(&hex[2..4], "green")?;
+            let b = channel(&hex[4..6], "blue")?;
             Ok(RGBA::new(r, g, b, u8::MAX))
-        } else if hex_str.len() == 8 {
-            let r = u8::from_str_radix(&hex_str[..2], 16)?;
-            let g = u8::from_str_radix(&hex_str[2..4], 16)?;
-            let b = u8::from_str_radix(&hex_str[4..6], 16)?;
-            let a = u8::from_str_radix(&hex_str[6..8], 16)?;
+        } else if hex.len() == 8 {
+            let r = channel(&hex[..2], "red")?;
+            let g = channel(&hex[2..4], "green")?;
+            let b = channel(&hex[4..6], "blue")?;
+            let a = channel(&hex[6..8], "alpha")?;
             Ok(RGBA::new(r, g, b, a))
         } else {
-            Err(ColorError::ParseHexError(format!(
-                "String argument {} does not have the correct length of 6 or 8",
-                input
-            )))
+            Err(ColorError::ParseHexError {
+                input: String::from_utf8_lossy(input).into_owned(),
+            })
+        }
+    }
+
+    // #rrggbbaa, always with alpha so parse_from_hex reads back the same color
+    pub fn to_hex_string(&self) -> String {
+        format!("#{}", self.pack())
+    }
+
+    // for const items, see the hex! macro. takes #rgb, #rgba, #rrggbb and
+    // #rrggbbaa, the # is optional. panics on anything else, which in a
+    // const item is a compile error
+    pub const fn from_hex_const(input: &str) -> Canonical {
+        const fn digit(byte: u8) -> u8 {
+            match byte {
+                b'0'..=b'9' => byte - b'0',
+                b'a'..=b'f' => byte - b'a' + 10,
+                b'A'..=b'F' => byte - b'A' + 10,
+                _ => panic!("invalid hex digit in color literal"),
+            }
+        }
+        let hex = match input.as_bytes() {
+            [b'#', rest @ ..] => rest,
+            hex => hex,
+        };
+        match *hex {
+            [r, g, b] => Self::new(digit(r) * 17, digit(g) * 17, digit(b) * 17, u8::MAX),
+            [r, g, b, a] => Self::new(digit(r) * 17, digit(g) * 17, digit(b) * 17, digit(a) * 17),
+            [r1, r2, g1, g2, b1, b2] => Self::new(
+                digit(r1) << 4 | digit(r2),
+                digit(g1) << 4 | digit(g2),
+                digit(b1) << 4 | digit(b2),
+                u8::MAX,
+            ),
+            [r1, r2, g1, g2, b1, b2, a1, a2] => Self::new(
+                digit(r1) << 4 | digit(r2),
+                digit(g1) << 4 | digit(g2),
+                digit(b1) << 4 | digit(b2),
+                digit(a1) << 4 | digit(a2),
+            ),
+            _ => panic!("color literal must have 3, 4, 6 or 8 hex digits"),
         }
     }
 
@@ -67,10 +324,11 @@ impl Canonical {
         let g = (self.g as u32) << BIT_SHIFT_GREEN;
         let b = (self.b as u32) << BIT_SHIFT_BLUE;
         let a = self.a as u32;
-        r | g | b | a
+        Packed(r | g | b | a)
     }
 
-    pub fn unpack(rgba: Packed) -> Canonical {
+    pub fn unpack(packed: Packed) -> Canonical {
+        let rgba = packed.0;
         // shifts the packed u32 by X bits to the right, so the desired
         // color component is represented by the 8 least significant bits.
         // then by casting it to an u8, it cuts off all but those 8 bits.
@@ -81,6 +339,14 @@ impl Canonical {
         let a = rgba as u8;
         RGBA { r, g, b, a }
     }
+
+    // euclidean distance in the rgb cube, alpha is ignored
+    pub fn distance(&self, other: &Canonical) -> f32 {
+        let dr = self.r as f32 - other.r as f32;
+        let dg = self.g as f32 - other.g as f32;
+        let db = self.b as f32 - other.b as f32;
+        (dr * dr + dg * dg + db * db).sqrt()
+    }
 }
 
 impl<T> RGB<T> {
@@ -124,7 +390,7 @@ impl<T> From<RGBA<T>> for RGB<T> {
 }
 
 impl<T> RGBA<T> {
-    pub fn new(red: T, green: T, blue: T, alpha: T) -> RGBA<T> {
+    pub const fn new(red: T, green: T, blue: T, alpha: T) -> RGBA<T> {
         RGBA {
             r: red,
             g: green,
@@ -169,6 +435,7 @@ impl From<RGB<u8>> for RGBA<u8> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::errors::ErrorKind;
 
     fn assert_roughly_equal(a: f32, b: f32) {
         let diff = f32::abs(a - b);
@@ -242,8 +509,98 @@ mod tests {
                 a: 0
             }
         );
-        assert!(matches!(too_short, ColorError::ParseHexError(_)));
-        assert!(matches!(wrong_format, ColorError::ParseToIntError(_, _)));
+        assert_eq!(too_short.kind(), ErrorKind::ParseHex);
+        assert_eq!(wrong_format.kind(), ErrorKind::ParseInt);
+
+        for (input, component) in [
+            ("#zx0000", "red"),
+            ("#00zx00", "green"),
+            ("#0000zx", "blue"),
+            ("#000000zx", "alpha"),
+        ] {
+            let e = Canonical::parse_from_hex(input).unwrap_err();
+            assert!(
+                matches!(e, ColorError::ParseToIntError { component: c, .. } if c == component),
+                "{}",
+                input
+            );
+            assert_eq!(
+                e.to_string(),
+                format!("Failed to parse string \"zx\" (component {}) into Int", component)
+            );
+        }
+    }
+
+    #[test]
+    fn test_canonical_parse_from_hex_bytes() {
+        let buffer = b"\xff\xfe #00aa11 \xc3";
+        assert_eq!(
+            Canonical::parse_from_hex_bytes(&buffer[3..10]).unwrap(),
+            Canonical::new(0, 170, 17, 255)
+        );
+        let e = Canonical::parse_from_hex_bytes(b"#00aa\xc3\xa9").unwrap_err();
+        assert_eq!(e.kind(), ErrorKind::ParseInt);
+        assert_eq!(
+            e.to_string(),
+            "Failed to parse string \"\u{e9}\" (component blue) into Int"
+        );
+    }
+
+    #[test]
+    fn test_from_hex_const() {
+        const ACCENT: Canonical = crate::hex!("#fe8019");
+        assert_eq!(ACCENT, Canonical::new(254, 128, 25, 255));
+        assert_eq!(crate::hex!("fe801980"), Canonical::new(254, 128, 25, 128));
+        assert_eq!(crate::hex!("#FE8"), Canonical::new(255, 238, 136, 255));
+        assert_eq!(crate::hex!("#fe88"), Canonical::new(255, 238, 136, 136));
+        for input in ["#000000", "1d2021", "#a89984cc", "#ABCDEF"] {
+            let expected = Canonical::parse_from_hex(input).unwrap();
+            assert_eq!(Canonical::from_hex_const(input), expected);
+        }
+    }
+
+    #[test]
+    fn test_from_f() {
+        assert_eq!(Canonical::from_f(0.0, 0.5, 1.0, 1.0), Canonical::new(0, 128, 255, 255));
+        assert_eq!(
+            Canonical::from_f(1.5, -0.2, f32::NAN, 2.0),
+            Canonical::new(255, 0, 0, 255)
+        );
+        assert_eq!(
+            Canonical::try_from_f(0.0, 0.5, 1.0, 1.0),
+            Ok(Canonical::new(0, 128, 255, 255))
+        );
+        assert_eq!(
+            Canonical::try_from_f(0.0, 0.0, f32::NAN, 1.0).unwrap_err().component(),
+            "blue"
+        );
+        assert_eq!(
+            Canonical::try_from_f(1.5, 0.0, 0.0, 1.0),
+            Err(ComponentError::OutOfRange { component: "red", value: 1.5 })
+        );
+        // NaN through the float conversions doesn't give garbage either
+        assert_eq!(
+            Canonical::from_hsl(crate::color::HSL::new(f32::NAN, f32::NAN, 0.5), f32::NAN),
+            Canonical::new(128, 128, 128, 0)
+        );
+        assert_eq!(
+            Canonical::from_linear(RGB::new(f32::NAN, 2.0, -1.0), 255),
+            Canonical::new(0, 255, 0, 255)
+        );
+    }
+
+    #[test]
+    fn test_to_hex_string() {
+        let c = Canonical::new(254, 128, 25, 255);
+        assert_eq!(c.to_hex_string(), "#fe8019ff");
+        assert_eq!(Canonical::parse_from_hex(&c.to_hex_string()).unwrap(), c);
+        assert_eq!(Canonical::new(0, 0, 0, 0).to_hex_string(), "#00000000");
+    }
+
+    #[test]
+    #[should_panic(expected = "invalid hex digit")]
+    fn test_from_hex_const_invalid() {
+        Canonical::from_hex_const("#fe80zz");
     }
 
     #[test]
@@ -254,13 +611,13 @@ mod tests {
 
         // let expected = u32::from_str_radix("808000ff", 16).unwrap();
 
-        assert_eq!(2_155_872_511u32, packed);
+        assert_eq!(Packed(2_155_872_511), packed);
     }
 
     #[test]
     fn test_canonical_unpack() {
         // AC AB AC AB = 2_896_932_011
-        let packed = 2_896_932_011u32;
+        let packed = Packed(2_896_932_011);
         let unpacked = Canonical::unpack(packed);
 
         assert_eq!(unpacked, Canonical::new(172, 171, 172, 171));
@@ -277,4 +634,51 @@ mod tests {
         assert_eq!(canonical, unpacked);
         assert_eq!(packed, packed_again);
     }
+
+    #[test]
+    fn test_scheme_accessors() {
+        let scheme = Scheme::from_entries(
+            "named".into(),
+            None,
+            vec![
+                Entry::named("background", Canonical::new(0, 0, 0, 255)),
+                Entry::new(Canonical::new(1, 1, 1, 255)),
+                Entry::named("foreground", Canonical::new(255, 255, 255, 255)),
+            ],
+        );
+
+        assert_eq!(scheme.len(), 3);
+        assert_eq!(scheme.get("foreground"), Some(&Canonical::new(255, 255, 255, 255)));
+        assert_eq!(scheme.get("cursor"), None);
+        assert_eq!(
+            scheme.colors().copied().collect::<Vec<_>>(),
+            vec![
+                Canonical::new(0, 0, 0, 255),
+                Canonical::new(1, 1, 1, 255),
+                Canonical::new(255, 255, 255, 255)
+            ]
+        );
+
+        let inverted = scheme.map_colors(|c| Canonical::new(255 - c.r, 255 - c.g, 255 - c.b, c.a));
+        assert_eq!(inverted.entries[0].name.as_deref(), Some("background"));
+        assert_eq!(inverted.get("background"), Some(&Canonical::new(255, 255, 255, 255)));
+
+        // unnamed entries never count as duplicates
+        let mut scheme = scheme;
+        assert!(!scheme.has_duplicate_names());
+        scheme.entries.push(Entry::new(Canonical::new(1, 1, 1, 255)));
+        assert!(!scheme.has_duplicate_names());
+        scheme.entries.push(Entry::named("background", Canonical::new(2, 2, 2, 255)));
+        assert!(scheme.has_duplicate_names());
+    }
+
+    #[test]
+    fn test_canonical_distance() {
+        let black = Canonical::new(0, 0, 0, 255);
+        let white = Canonical::new(255, 255, 255, 0);
+
+        assert_eq!(black.distance(&black), 0.0);
+        assert_roughly_equal(black.distance(&white), 441.673);
+        assert_eq!(black.distance(&white), white.distance(&black));
+    }
 }
@@ -0,0 +1,131 @@
+// what exporters to formats without alpha do with translucent colors.
+// Xresources, kitty and GIMP palettes only know opaque colors, they used to
+// drop alpha and still do by default
+use super::diff::EntryKey;
+use super::Canonical;
+use crate::errors::ColorError;
+
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Default)]
+pub enum AlphaPolicy {
+    // the color channels as they are, the color shows as if opaque
+    #[default]
+    Drop,
+    // what the color looks like over the given background, which is made
+    // opaque first. usually the scheme's background entry
+    CompositeOver(Canonical),
+    // ColorError::AlphaLost for the first translucent entry
+    Error,
+}
+
+impl AlphaPolicy {
+    // an opaque color for the entry at `key`
+    pub fn apply(
+        &self,
+        key: impl Into<EntryKey>,
+        color: &Canonical,
+    ) -> Result<Canonical, ColorError> {
+        if color.a == u8::MAX {
+            return Ok(*color);
+        }
+        match self {
+            AlphaPolicy::Drop => Ok(Canonical {
+                a: u8::MAX,
+                ..*color
+            }),
+            AlphaPolicy::CompositeOver(background) => Ok(color.over(&Canonical {
+                a: u8::MAX,
+                ..*background
+            })),
+            AlphaPolicy::Error => Err(ColorError::AlphaLost {
+                entry: key.into(),
+                alpha: color.a,
+            }),
+        }
+    }
+}
+
+impl Canonical {
+    // source over compositing in sRGB values, as terminals and browsers
+    // blend. the result is opaque over an opaque background
+    pub fn over(&self, background: &Canonical) -> Canonical {
+        let alpha = self.a as f32 / 255.0;
+        let behind = background.a as f32 / 255.0 * (1.0 - alpha);
+        let out = alpha + behind;
+        if out == 0.0 {
+            return Canonical::new(0, 0, 0, 0);
+        }
+        let channel = |front: u8, back: u8| {
+            ((front as f32 * alpha + back as f32 * behind) / out).round() as u8
+        };
+        Canonical::new(
+            channel(self.r, background.r),
+            channel(self.g, background.g),
+            channel(self.b, background.b),
+            (out * 255.0).round() as u8,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::errors::ErrorKind;
+
+    #[test]
+    fn test_over() {
+        let black = Canonical::new(0, 0, 0, 255);
+        let white = Canonical::new(255, 255, 255, 255);
+        assert_eq!(
+            Canonical::new(255, 255, 255, 128).over(&black),
+            Canonical::new(128, 128, 128, 255)
+        );
+        assert_eq!(Canonical::new(255, 0, 0, 0).over(&white), white);
+        assert_eq!(
+            Canonical::new(255, 0, 0, 255).over(&white),
+            Canonical::new(255, 0, 0, 255)
+        );
+        // translucent over translucent stays translucent
+        let over = Canonical::new(255, 0, 0, 128).over(&Canonical::new(0, 0, 255, 128));
+        assert_eq!(over, Canonical::new(170, 0, 85, 192));
+        assert_eq!(
+            Canonical::new(1, 2, 3, 0)
+                .over(&Canonical::new(4, 5, 6, 0))
+                .a,
+            0
+        );
+    }
+
+    #[test]
+    fn test_apply() {
+        let red = Canonical::new(204, 36, 29, 128);
+        let background = Canonical::new(40, 40, 40, 255);
+        assert_eq!(
+            AlphaPolicy::default().apply("red", &red).unwrap(),
+            Canonical::new(204, 36, 29, 255)
+        );
+        assert_eq!(
+            AlphaPolicy::CompositeOver(background)
+                .apply("red", &red)
+                .unwrap(),
+            Canonical::new(122, 38, 34, 255)
+        );
+        // the background's own alpha doesn't count
+        let faded = Canonical { a: 0, ..background };
+        assert_eq!(
+            AlphaPolicy::CompositeOver(faded)
+                .apply("red", &red)
+                .unwrap(),
+            Canonical::new(122, 38, 34, 255)
+        );
+
+        let e = AlphaPolicy::Error.apply(3, &red).unwrap_err();
+        assert_eq!(e.kind(), ErrorKind::AlphaLost);
+        assert_eq!(
+            e.to_string(),
+            "[3] is translucent, alpha 128, and the format has no alpha"
+        );
+        // opaque colors pass every policy
+        let opaque = Canonical { a: 255, ..red };
+        assert_eq!(AlphaPolicy::Error.apply("red", &opaque).unwrap(), opaque);
+    }
+}
@@ -0,0 +1,137 @@
+// a Scheme whose names may borrow from the text it was parsed from, see
+// reader::parse_borrowed. into_owned or to_owned give a Scheme to keep
+use std::borrow::Cow;
+
+use super::{Canonical, Entry, Role, Scheme, SourceLocation};
+
+#[derive(Debug, Clone)]
+pub struct EntryRef<'a> {
+    pub name: Option<Cow<'a, str>>,
+    pub color: Canonical,
+    pub role: Option<Role>,
+    pub comment: Option<Cow<'a, str>>,
+    pub source: Option<SourceLocation>,
+}
+
+// ignores the source, like Entry
+impl PartialEq for EntryRef<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.name == other.name
+            && self.color == other.color
+            && self.role == other.role
+            && self.comment == other.comment
+    }
+}
+
+#[derive(PartialEq, Debug, Clone)]
+pub struct SchemeRef<'a> {
+    pub name: Cow<'a, str>,
+    pub author: Option<Cow<'a, str>>,
+    pub entries: Vec<EntryRef<'a>>,
+}
+
+impl<'a> EntryRef<'a> {
+    // the role is inferred from the name, like Entry::named
+    pub fn new(name: Option<Cow<'a, str>>, color: Canonical) -> Self {
+        let role = name.as_deref().and_then(Role::from_name);
+        EntryRef {
+            name,
+            color,
+            role,
+            comment: None,
+            source: None,
+        }
+    }
+
+    pub fn into_owned(self) -> Entry {
+        Entry {
+            name: self.name.map(Cow::into_owned),
+            color: self.color,
+            role: self.role,
+            comment: self.comment.map(Cow::into_owned),
+            source: self.source,
+        }
+    }
+}
+
+impl<'a> From<Entry> for EntryRef<'a> {
+    fn from(entry: Entry) -> Self {
+        EntryRef {
+            name: entry.name.map(Cow::Owned),
+            color: entry.color,
+            role: entry.role,
+            comment: entry.comment.map(Cow::Owned),
+            source: entry.source,
+        }
+    }
+}
+
+impl<'a> SchemeRef<'a> {
+    pub fn new(name: Cow<'a, str>) -> Self {
+        SchemeRef {
+            name,
+            author: None,
+            entries: Vec::new(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    // color of the first entry with the given name
+    pub fn get(&self, name: &str) -> Option<&Canonical> {
+        self.entries
+            .iter()
+            .find(|e| e.name.as_deref() == Some(name))
+            .map(|e| &e.color)
+    }
+
+    pub fn into_owned(self) -> Scheme {
+        Scheme::from_entries(
+            self.name.into_owned(),
+            self.author.map(Cow::into_owned),
+            self.entries.into_iter().map(EntryRef::into_owned).collect(),
+        )
+    }
+
+    // copies whatever is borrowed
+    pub fn to_owned(&self) -> Scheme {
+        self.clone().into_owned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_into_owned() {
+        let text = String::from("background");
+        let scheme = SchemeRef {
+            name: Cow::Borrowed("mono"),
+            author: Some(Cow::Owned("someone".into())),
+            entries: vec![
+                EntryRef::new(Some(Cow::Borrowed(&text)), Canonical::new(0, 0, 0, 255)),
+                Entry::new(Canonical::new(255, 255, 255, 255)).into(),
+            ],
+        };
+        assert_eq!(
+            scheme.get("background"),
+            Some(&Canonical::new(0, 0, 0, 255))
+        );
+
+        let mut expected = Scheme::new("mono");
+        expected.author = Some("someone".into());
+        expected.entries = vec![
+            Entry::named("background", Canonical::new(0, 0, 0, 255)),
+            Entry::new(Canonical::new(255, 255, 255, 255)),
+        ];
+        assert_eq!(scheme.to_owned(), expected);
+        assert_eq!(scheme.into_owned(), expected);
+    }
+}
@@ -0,0 +1,192 @@
+// builds a Scheme and checks it before handing it out, so a scheme from
+// build() never has duplicate names unless the policy collapsed them, and
+// always passes the rules given to validate_with
+use super::{Canonical, Entry, Scheme, ValidationRules};
+use crate::errors::SchemeBuildError;
+use crate::reader::DuplicateNamePolicy;
+
+pub struct SchemeBuilder {
+    scheme: Scheme,
+    duplicate_names: DuplicateNamePolicy,
+    rules: Option<ValidationRules>,
+}
+
+impl Scheme {
+    pub fn builder(name: &str) -> SchemeBuilder {
+        SchemeBuilder {
+            scheme: Scheme::new(name),
+            duplicate_names: DuplicateNamePolicy::default(),
+            rules: None,
+        }
+    }
+}
+
+impl SchemeBuilder {
+    pub fn color(mut self, color: Canonical) -> Self {
+        self.scheme.entries.push(Entry::new(color));
+        self
+    }
+
+    pub fn named(mut self, name: &str, color: Canonical) -> Self {
+        self.scheme.entries.push(Entry::named(name, color));
+        self
+    }
+
+    pub fn entries<I: IntoIterator<Item = Entry>>(mut self, entries: I) -> Self {
+        self.scheme.entries.extend(entries);
+        self
+    }
+
+    pub fn author(mut self, author: &str) -> Self {
+        self.scheme.author = Some(author.into());
+        self
+    }
+
+    // the same policies as the reader's, an error by default
+    pub fn duplicate_names(mut self, policy: DuplicateNamePolicy) -> Self {
+        self.duplicate_names = policy;
+        self
+    }
+
+    pub fn validate_with(mut self, rules: ValidationRules) -> Self {
+        self.rules = Some(rules);
+        self
+    }
+
+    // duplicate names are resolved before the rules are checked
+    pub fn build(self) -> Result<Scheme, SchemeBuildError> {
+        let mut scheme = Scheme::from_entries(
+            self.scheme.name,
+            self.scheme.author,
+            Vec::with_capacity(self.scheme.entries.len()),
+        );
+        for (index, entry) in self.scheme.entries.into_iter().enumerate() {
+            let first = entry.name.as_deref().and_then(|name| scheme.position(name));
+            match (first, self.duplicate_names) {
+                (None, _) => {
+                    scheme.index.push(entry.name.as_deref());
+                    scheme.entries.push(entry);
+                }
+                (Some(first), DuplicateNamePolicy::Error) => {
+                    return Err(SchemeBuildError::DuplicateName {
+                        name: entry.name.unwrap_or_default(),
+                        first,
+                        index,
+                    })
+                }
+                (Some(_), DuplicateNamePolicy::FirstWins) => {}
+                (Some(first), DuplicateNamePolicy::LastWins) => {
+                    scheme.entries[first].color = entry.color
+                }
+            }
+        }
+        if let Some(rules) = self.rules {
+            let report = scheme.validate(&rules);
+            if !report.is_ok() {
+                return Err(SchemeBuildError::Validation { report });
+            }
+        }
+        Ok(scheme)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::Violation;
+    use crate::errors::ErrorKind;
+
+    fn gray(v: u8) -> Canonical {
+        Canonical::new(v, v, v, 255)
+    }
+
+    #[test]
+    fn test_build() {
+        let scheme = Scheme::builder("mono")
+            .author("someone")
+            .named("bg", gray(0))
+            .color(gray(128))
+            .named("fg", gray(255))
+            .build()
+            .unwrap();
+        assert_eq!(scheme.name, "mono");
+        assert_eq!(scheme.author.as_deref(), Some("someone"));
+        assert_eq!(
+            scheme.entries,
+            vec![
+                Entry::named("bg", gray(0)),
+                Entry::new(gray(128)),
+                Entry::named("fg", gray(255))
+            ]
+        );
+    }
+
+    #[test]
+    fn test_duplicate_names() {
+        let builder = || {
+            Scheme::builder("dup")
+                .named("bg", gray(0))
+                .color(gray(1))
+                .named("bg", gray(2))
+        };
+        let e = builder().build().unwrap_err();
+        assert_eq!(e.kind(), ErrorKind::DuplicateName);
+        assert!(matches!(
+            e,
+            SchemeBuildError::DuplicateName { ref name, first: 0, index: 2 } if name == "bg"
+        ));
+        assert_eq!(e.to_string(), "bg names entry 0 and again entry 2");
+
+        let first = builder()
+            .duplicate_names(DuplicateNamePolicy::FirstWins)
+            .build()
+            .unwrap();
+        assert_eq!(
+            first.colors().copied().collect::<Vec<_>>(),
+            [gray(0), gray(1)]
+        );
+        let last = builder()
+            .duplicate_names(DuplicateNamePolicy::LastWins)
+            .build()
+            .unwrap();
+        assert_eq!(
+            last.colors().copied().collect::<Vec<_>>(),
+            [gray(2), gray(1)]
+        );
+    }
+
+    #[test]
+    fn test_validate_with() {
+        let rules = ValidationRules {
+            required_names: vec!["bg".into(), "fg".into()],
+            opaque: true,
+            ..Default::default()
+        };
+        let e = Scheme::builder("partial")
+            .named("bg", Canonical::new(0, 0, 0, 128))
+            .validate_with(rules.clone())
+            .build()
+            .unwrap_err();
+        assert_eq!(e.kind(), ErrorKind::Validation);
+        let SchemeBuildError::Validation { report } = e else {
+            panic!("{:?}", e);
+        };
+        assert_eq!(
+            report.violations,
+            vec![
+                Violation::MissingName("fg".into()),
+                Violation::NotOpaque {
+                    index: 0,
+                    alpha: 128
+                }
+            ]
+        );
+
+        assert!(Scheme::builder("full")
+            .named("bg", gray(0))
+            .named("fg", gray(255))
+            .validate_with(rules)
+            .build()
+            .is_ok());
+    }
+}
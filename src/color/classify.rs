@@ -0,0 +1,227 @@
+// which entries of a scheme are chrome, the backgrounds, foregrounds and
+// grays in between, and which are accents. nothing is assumed about the
+// scheme's layout and the thresholds come from the scheme itself:
+//
+// - the grays of a scheme are rarely neutral, they are tinted and the tint
+//   may change from dark to light, solarized goes from blue to yellow. the
+//   tint is fitted as a line through Oklab, a and b as functions of
+//   lightness, over the less colorful half of the colors. the quarter of
+//   those furthest from the line is dropped and the line fitted again, so
+//   that a muted accent among them can't pull it away
+// - an entry is an accent if it is further from that line than
+//   ACCENT_FACTOR times the furthest entry it was fitted to
+// - the rest is chrome and split by lightness. the quarter of its range on
+//   the side of the background is Background, the quarter on the other side
+//   Foreground and the middle Neutral. the background's side is that of the
+//   entry with the background role, or else of the first chrome entry, as
+//   schemes tend to start with their background
+use super::role::Role;
+use super::{Entry, Scheme};
+
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum EntryClass {
+    Background,
+    Foreground,
+    Neutral,
+    Accent,
+}
+
+const ACCENT_FACTOR: f32 = 3.0;
+
+// how far from the tint an accent is at least, whatever the spread of the
+// grays. without it a scheme of perfect grays would have the slightest tint
+// count as an accent
+const MIN_ACCENT_DISTANCE: f32 = 0.02;
+
+// the share of the chrome's lightness range at either end
+const EDGE: f32 = 0.25;
+
+#[derive(Debug, Clone)]
+pub struct SchemeClassification<'a> {
+    scheme: &'a Scheme,
+    classes: Vec<EntryClass>,
+}
+
+impl<'a> SchemeClassification<'a> {
+    // in the order of the scheme's entries
+    pub fn classes(&self) -> &[EntryClass] {
+        &self.classes
+    }
+
+    pub fn class(&self, index: usize) -> Option<EntryClass> {
+        self.classes.get(index).copied()
+    }
+
+    pub fn entries(&self, class: EntryClass) -> Vec<&'a Entry> {
+        let scheme = self.scheme;
+        self.classes
+            .iter()
+            .zip(&scheme.entries)
+            .filter(|(c, _)| **c == class)
+            .map(|(_, entry)| entry)
+            .collect()
+    }
+
+    pub fn accents(&self) -> Vec<&'a Entry> {
+        self.entries(EntryClass::Accent)
+    }
+
+    pub fn neutrals(&self) -> Vec<&'a Entry> {
+        self.entries(EntryClass::Neutral)
+    }
+}
+
+// Oklab lightness, a and b
+type Point = [f32; 3];
+
+// least squares y = slope * x + intercept, flat through the mean if all x
+// are the same
+fn line(points: &[(f32, f32)]) -> (f32, f32) {
+    let n = points.len() as f32;
+    let mean_x = points.iter().map(|p| p.0).sum::<f32>() / n;
+    let mean_y = points.iter().map(|p| p.1).sum::<f32>() / n;
+    let sxx: f32 = points.iter().map(|p| (p.0 - mean_x).powi(2)).sum();
+    let sxy: f32 = points.iter().map(|p| (p.0 - mean_x) * (p.1 - mean_y)).sum();
+    let slope = if sxx > f32::EPSILON { sxy / sxx } else { 0.0 };
+    (slope, mean_y - slope * mean_x)
+}
+
+// distances of all points from the tint line fitted to those of `fitted`
+fn tint_distances(points: &[Point], fitted: &[usize]) -> Vec<f32> {
+    let (a, b) = (
+        line(
+            &fitted
+                .iter()
+                .map(|&i| (points[i][0], points[i][1]))
+                .collect::<Vec<_>>(),
+        ),
+        line(
+            &fitted
+                .iter()
+                .map(|&i| (points[i][0], points[i][2]))
+                .collect::<Vec<_>>(),
+        ),
+    );
+    points
+        .iter()
+        .map(|[l, pa, pb]| (pa - (a.0 * l + a.1)).hypot(pb - (b.0 * l + b.1)))
+        .collect()
+}
+
+impl Scheme {
+    pub fn classify(&self) -> SchemeClassification<'_> {
+        let mut classes = vec![EntryClass::Neutral; self.len()];
+        let points: Vec<Point> = self
+            .colors()
+            .map(|c| {
+                let oklch = c.to_oklch();
+                let (sin, cos) = oklch.h.radians().sin_cos();
+                [oklch.l, oklch.c * cos, oklch.c * sin]
+            })
+            .collect();
+        if points.is_empty() {
+            return SchemeClassification {
+                scheme: self,
+                classes,
+            };
+        }
+
+        // a color repeated as, say, background and base03 counts once
+        let colors: Vec<_> = self.colors().collect();
+        let mut fitted: Vec<usize> = (0..points.len())
+            .filter(|&i| !colors[..i].contains(&colors[i]))
+            .collect();
+        let chroma = |i: usize| points[i][1].hypot(points[i][2]);
+        fitted.sort_by(|&x, &y| chroma(x).total_cmp(&chroma(y)));
+        fitted.truncate(fitted.len().div_ceil(2));
+        for _ in 0..fitted.len() / 4 {
+            let distances = tint_distances(&points, &fitted);
+            let furthest = (0..fitted.len())
+                .max_by(|&x, &y| distances[fitted[x]].total_cmp(&distances[fitted[y]]))
+                .unwrap();
+            fitted.remove(furthest);
+        }
+        let distances = tint_distances(&points, &fitted);
+        let spread = fitted.iter().map(|&i| distances[i]).fold(0.0, f32::max);
+        let threshold = (spread * ACCENT_FACTOR).max(MIN_ACCENT_DISTANCE);
+
+        let chrome: Vec<usize> = (0..points.len())
+            .filter(|&i| distances[i] <= threshold)
+            .collect();
+        for (i, class) in classes.iter_mut().enumerate() {
+            if distances[i] > threshold {
+                *class = EntryClass::Accent;
+            }
+        }
+        let lightness = |i: usize| points[i][0];
+        let (min, max) = chrome
+            .iter()
+            .map(|&i| lightness(i))
+            .fold((f32::INFINITY, f32::NEG_INFINITY), |(min, max), l| {
+                (min.min(l), max.max(l))
+            });
+        let background = self
+            .entries
+            .iter()
+            .position(|e| e.role == Some(Role::Background))
+            .or(chrome.first().copied());
+        let dark = match background {
+            Some(i) => lightness(i) - min <= max - lightness(i),
+            None => true,
+        };
+        let edge = (max - min) * EDGE;
+        for &i in &chrome {
+            let (low, high) = (lightness(i) < min + edge, lightness(i) > max - edge);
+            classes[i] = match (low, high) {
+                (true, false) if dark => EntryClass::Background,
+                (true, false) => EntryClass::Foreground,
+                (false, true) if dark => EntryClass::Foreground,
+                (false, true) => EntryClass::Background,
+                _ => EntryClass::Neutral,
+            };
+        }
+        SchemeClassification {
+            scheme: self,
+            classes,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::Canonical;
+
+    #[test]
+    fn test_classify_flat() {
+        let empty = Scheme::new("empty");
+        assert!(empty.classify().classes().is_empty());
+
+        // all grays, nothing stands out
+        let grays = Scheme::from_colors(
+            "grays",
+            (0..8)
+                .map(|i| Canonical::new(i * 32, i * 32, i * 32, 255))
+                .collect(),
+        );
+        let classification = grays.classify();
+        assert!(classification.accents().is_empty());
+        assert_eq!(classification.class(0), Some(EntryClass::Background));
+        assert_eq!(classification.class(7), Some(EntryClass::Foreground));
+        assert_eq!(classification.class(4), Some(EntryClass::Neutral));
+        assert_eq!(classification.class(8), None);
+
+        // starting with white makes it a light scheme
+        let mut light = Scheme::from_colors("light", vec![Canonical::new(255, 255, 255, 255)]);
+        light.push(Canonical::new(0, 0, 0, 255));
+        light.push(Canonical::new(220, 40, 40, 255));
+        assert_eq!(
+            light.classify().classes(),
+            [
+                EntryClass::Background,
+                EntryClass::Foreground,
+                EntryClass::Accent
+            ]
+        );
+    }
+}
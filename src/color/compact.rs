@@ -0,0 +1,187 @@
+// a scheme with every distinct color stored once, for the long palettes of
+// quantizers and images that repeat a few hundred colors over thousands of
+// entries. entries become u32 indices into the table, the table is in order
+// of first use, so the same entries always give the same compact scheme and
+// comparing two compares tables and indices. names, roles and comments are
+// rare in such palettes and kept aside with the position of their entry
+use std::collections::HashMap;
+use std::mem::size_of;
+
+use super::{Canonical, Entry, Packed, Scheme};
+
+#[derive(PartialEq, Debug, Clone)]
+pub struct CompactScheme {
+    pub name: String,
+    pub author: Option<String>,
+    table: Vec<Canonical>,
+    indices: Vec<u32>,
+    // entries with a name, role or comment, by position
+    details: Vec<(u32, Entry)>,
+}
+
+fn string_footprint(s: &Option<String>) -> usize {
+    s.as_ref().map_or(0, String::capacity)
+}
+
+fn entry_heap(entry: &Entry) -> usize {
+    string_footprint(&entry.name) + string_footprint(&entry.comment)
+}
+
+impl CompactScheme {
+    // unnamed entries, as the binary palette readers give them
+    pub fn from_colors(
+        name: &str,
+        author: Option<String>,
+        colors: impl IntoIterator<Item = Canonical>,
+    ) -> CompactScheme {
+        let mut compact = CompactScheme {
+            name: name.into(),
+            author,
+            table: Vec::new(),
+            indices: Vec::new(),
+            details: Vec::new(),
+        };
+        let mut seen = HashMap::new();
+        for color in colors {
+            compact.push(&mut seen, color);
+        }
+        compact.table.shrink_to_fit();
+        compact.indices.shrink_to_fit();
+        compact
+    }
+
+    fn push(&mut self, seen: &mut HashMap<Packed, u32>, color: Canonical) {
+        let index = *seen.entry(color.pack()).or_insert_with(|| {
+            self.table.push(color);
+            (self.table.len() - 1) as u32
+        });
+        self.indices.push(index);
+    }
+
+    pub fn len(&self) -> usize {
+        self.indices.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.indices.is_empty()
+    }
+
+    // the distinct colors, in order of first use
+    pub fn table(&self) -> &[Canonical] {
+        &self.table
+    }
+
+    pub fn get(&self, index: usize) -> Option<&Canonical> {
+        self.indices.get(index).map(|&i| &self.table[i as usize])
+    }
+
+    // the colors of the entries in order, as Scheme::colors
+    pub fn iter(&self) -> impl Iterator<Item = &Canonical> + '_ {
+        self.indices.iter().map(|&i| &self.table[i as usize])
+    }
+
+    pub fn to_scheme(&self) -> Scheme {
+        let mut entries: Vec<Entry> = self.iter().map(|&color| Entry::new(color)).collect();
+        for (position, entry) in &self.details {
+            entries[*position as usize] = entry.clone();
+        }
+        Scheme::from_entries(self.name.clone(), self.author.clone(), entries)
+    }
+
+    // an estimate of the bytes held, the struct and what it owns on the heap
+    pub fn memory_footprint(&self) -> usize {
+        size_of::<Self>()
+            + self.name.capacity()
+            + string_footprint(&self.author)
+            + self.table.capacity() * size_of::<Canonical>()
+            + self.indices.capacity() * size_of::<u32>()
+            + self.details.capacity() * size_of::<(u32, Entry)>()
+            + self
+                .details
+                .iter()
+                .map(|(_, e)| entry_heap(e))
+                .sum::<usize>()
+    }
+}
+
+impl Scheme {
+    pub fn compact(&self) -> CompactScheme {
+        let mut compact = CompactScheme::from_colors(&self.name, self.author.clone(), []);
+        let mut seen = HashMap::new();
+        for (i, entry) in self.entries.iter().enumerate() {
+            compact.push(&mut seen, entry.color);
+            if entry.name.is_some() || entry.role.is_some() || entry.comment.is_some() {
+                compact.details.push((i as u32, entry.clone()));
+            }
+        }
+        compact.table.shrink_to_fit();
+        compact.indices.shrink_to_fit();
+        compact
+    }
+
+    // an estimate of the bytes held, as CompactScheme::memory_footprint.
+    // the name index counts its entries, not its spare capacity
+    pub fn memory_footprint(&self) -> usize {
+        size_of::<Self>()
+            + self.name.capacity()
+            + string_footprint(&self.author)
+            + self.entries.capacity() * size_of::<Entry>()
+            + self.entries.iter().map(entry_heap).sum::<usize>()
+            + self.index.footprint()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 60k entries cycling through 300 colors, a few of them named
+    fn repetitive() -> Scheme {
+        let mut entries: Vec<Entry> = (0..60_000u32)
+            .map(|i| {
+                let c = (i * 7 % 300) as u16;
+                Entry::new(Canonical::new((c % 256) as u8, (c / 256) as u8, 9, 255))
+            })
+            .collect();
+        entries[3] = Entry::named("background", entries[3].color).with_comment("darkest");
+        entries[59_999].name = Some("last".into());
+        Scheme::from_entries("quantized".into(), Some("me".into()), entries)
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let scheme = repetitive();
+        let compact = scheme.compact();
+        assert_eq!(compact.len(), 60_000);
+        assert_eq!(compact.table().len(), 300);
+        assert!(compact.iter().eq(scheme.colors()));
+        assert_eq!(compact.get(1), scheme.entries.get(1).map(|e| &e.color));
+        assert_eq!(compact.get(60_000), None);
+
+        let back = compact.to_scheme();
+        assert_eq!(back, scheme);
+        assert_eq!(back.get("last"), Some(&scheme.entries[59_999].color));
+        assert_eq!(back.compact(), compact);
+
+        let empty = Scheme::new("empty").compact();
+        assert!(empty.is_empty());
+        assert_eq!(empty.to_scheme(), Scheme::new("empty"));
+    }
+
+    #[test]
+    fn test_footprint() {
+        let scheme = repetitive();
+        let compact = scheme.compact();
+        // 4 bytes an entry rather than a whole Entry
+        assert!(
+            compact.memory_footprint() * 10 < scheme.memory_footprint(),
+            "{} and {}",
+            compact.memory_footprint(),
+            scheme.memory_footprint()
+        );
+        assert!(compact.memory_footprint() > 60_000 * size_of::<u32>());
+        let colors = CompactScheme::from_colors("x", None, scheme.colors().copied());
+        assert_eq!(colors.table(), compact.table());
+        assert!(colors.iter().eq(compact.iter()));
+    }
+}
@@ -0,0 +1,307 @@
+// the channel types of RGB and RGBA, so color math is written once for u8,
+// u16 and f32 channels. integer channels span 0..=MAX, float channels 0..=1
+use super::linear::{decode_srgb, encode_srgb, AverageSpace};
+use super::{RGB, RGBA};
+use crate::errors::ComponentError;
+
+mod sealed {
+    pub trait Sealed {}
+
+    impl Sealed for u8 {}
+    impl Sealed for u16 {}
+    impl Sealed for f32 {}
+}
+
+pub trait Component: Copy + sealed::Sealed {
+    // full intensity
+    const MAX: Self;
+
+    // the plain value, not divided by MAX
+    fn to_f32(self) -> f32;
+
+    // rounded and clamped to 0..=MAX for integer channels, unchanged for floats
+    fn from_f32_rounded(value: f32) -> Self;
+
+    fn is_float() -> bool;
+
+    // in [0, 1] for channels within 0..=MAX
+    fn normalized(self) -> f32 {
+        self.to_f32() / Self::MAX.to_f32()
+    }
+
+    fn from_normalized(value: f32) -> Self {
+        Self::from_f32_rounded(value * Self::MAX.to_f32())
+    }
+}
+
+impl Component for u8 {
+    const MAX: Self = u8::MAX;
+
+    fn to_f32(self) -> f32 {
+        self as f32
+    }
+
+    fn from_f32_rounded(value: f32) -> Self {
+        value.round().clamp(0.0, u8::MAX as f32) as u8
+    }
+
+    fn is_float() -> bool {
+        false
+    }
+}
+
+impl Component for u16 {
+    const MAX: Self = u16::MAX;
+
+    fn to_f32(self) -> f32 {
+        self as f32
+    }
+
+    fn from_f32_rounded(value: f32) -> Self {
+        value.round().clamp(0.0, u16::MAX as f32) as u16
+    }
+
+    fn is_float() -> bool {
+        false
+    }
+}
+
+impl Component for f32 {
+    const MAX: Self = 1.0;
+
+    fn to_f32(self) -> f32 {
+        self
+    }
+
+    fn from_f32_rounded(value: f32) -> Self {
+        value
+    }
+
+    fn is_float() -> bool {
+        true
+    }
+}
+
+impl<T: Component> RGB<T> {
+    pub fn normalized(&self) -> RGB<f32> {
+        (*self).map(T::normalized)
+    }
+
+    // the same color with channels of another type
+    pub fn convert<U: Component>(&self) -> RGB<U> {
+        (*self).map(|c| U::from_normalized(c.normalized()))
+    }
+}
+
+impl<T: Component> RGBA<T> {
+    pub fn normalized(&self) -> RGBA<f32> {
+        self.map(T::normalized)
+    }
+
+    // the same color with channels of another type
+    pub fn convert<U: Component>(&self) -> RGBA<U> {
+        self.map(|c| U::from_normalized(c.normalized()))
+    }
+
+    // interpolated in linear light. `t` is clamped to [0, 1], 0 gives self
+    // and 1 gives other. a NaN `t` counts as 0
+    pub fn mix(&self, other: &RGBA<T>, t: f32) -> RGBA<T> {
+        self.mix_with(other, t, AverageSpace::Linear)
+    }
+
+    // alpha is interpolated in the stored values either way
+    pub fn mix_with(&self, other: &RGBA<T>, t: f32, space: AverageSpace) -> RGBA<T> {
+        let t = clamp_unit(t);
+        // exact ends, the round trip through linear light isn't
+        if t == 0.0 {
+            return *self;
+        } else if t == 1.0 {
+            return *other;
+        }
+        let lerp = |a: f32, b: f32| a + (b - a) * t;
+        let stored = |a: T, b: T| T::from_f32_rounded(lerp(a.to_f32(), b.to_f32()));
+        let channel = |a: T, b: T| match space {
+            AverageSpace::Gamma => stored(a, b),
+            AverageSpace::Linear => T::from_normalized(encode_srgb(lerp(
+                decode_srgb(a.normalized()),
+                decode_srgb(b.normalized()),
+            ))),
+        };
+        RGBA::new(
+            channel(self.r, other.r),
+            channel(self.g, other.g),
+            channel(self.b, other.b),
+            stored(self.a, other.a),
+        )
+    }
+
+    // channels limited to 0..=MAX, which only float channels can leave.
+    // NaN becomes 0
+    pub fn clamped(&self) -> RGBA<T> {
+        self.map(|c| match c.to_f32() {
+            c if c.is_nan() => T::from_f32_rounded(0.0),
+            c => T::from_f32_rounded(c.clamp(0.0, T::MAX.to_f32())),
+        })
+    }
+}
+
+// in [0, 1]. NaN becomes 0, it would otherwise end up as whatever the
+// conversion after it makes of NaN
+pub(crate) fn clamp_unit(value: f32) -> f32 {
+    if value.is_nan() {
+        0.0
+    } else {
+        value.clamp(0.0, 1.0)
+    }
+}
+
+fn check_unit(component: &'static str, value: f32) -> Result<f32, ComponentError> {
+    match value {
+        value if !value.is_finite() => Err(ComponentError::NotFinite { component, value }),
+        value if (0.0..=1.0).contains(&value) => Ok(value),
+        value => Err(ComponentError::OutOfRange { component, value }),
+    }
+}
+
+// float colors are only in range with channels in [0, 1], `new` doesn't check
+impl RGB<f32> {
+    pub fn new_clamped(red: f32, green: f32, blue: f32) -> RGB<f32> {
+        RGB::new(clamp_unit(red), clamp_unit(green), clamp_unit(blue))
+    }
+
+    pub fn try_new(red: f32, green: f32, blue: f32) -> Result<RGB<f32>, ComponentError> {
+        Ok(RGB::new(
+            check_unit("red", red)?,
+            check_unit("green", green)?,
+            check_unit("blue", blue)?,
+        ))
+    }
+}
+
+impl RGBA<f32> {
+    pub fn new_clamped(red: f32, green: f32, blue: f32, alpha: f32) -> RGBA<f32> {
+        RGBA::new(
+            clamp_unit(red),
+            clamp_unit(green),
+            clamp_unit(blue),
+            clamp_unit(alpha),
+        )
+    }
+
+    pub fn try_new(
+        red: f32,
+        green: f32,
+        blue: f32,
+        alpha: f32,
+    ) -> Result<RGBA<f32>, ComponentError> {
+        Ok(RGBA::new(
+            check_unit("red", red)?,
+            check_unit("green", green)?,
+            check_unit("blue", blue)?,
+            check_unit("alpha", alpha)?,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::Canonical;
+
+    // one generic op, instantiated for every channel type
+    fn mid_gray<T: Component>(space: AverageSpace) -> RGBA<T> {
+        let black = RGBA::new(
+            T::from_f32_rounded(0.0),
+            T::from_f32_rounded(0.0),
+            T::from_f32_rounded(0.0),
+            T::MAX,
+        );
+        let white = RGBA::new(T::MAX, T::MAX, T::MAX, T::MAX);
+        black.mix_with(&white, 0.5, space)
+    }
+
+    #[test]
+    fn test_generic_ops() {
+        let gamma = AverageSpace::Gamma;
+        assert_eq!(mid_gray::<u8>(gamma), Canonical::new(128, 128, 128, 255));
+        assert_eq!(
+            mid_gray::<u16>(gamma),
+            RGBA::new(32768, 32768, 32768, u16::MAX)
+        );
+        assert_eq!(mid_gray::<f32>(gamma), RGBA::new(0.5, 0.5, 0.5, 1.0));
+
+        // u8 can't hold exactly half
+        let close = |a: f32, b: f32| (a - b).abs() < 0.0001;
+        assert!(close(mid_gray::<u8>(gamma).luminance(), 0.21586));
+        assert!(close(mid_gray::<u16>(gamma).luminance(), 0.21404));
+        assert!(close(mid_gray::<f32>(gamma).luminance(), 0.21404));
+
+        // half the light of white, whatever the channel type
+        let linear = AverageSpace::Linear;
+        assert_eq!(mid_gray::<u8>(linear), Canonical::new(188, 188, 188, 255));
+        assert!(close(mid_gray::<u8>(linear).luminance(), 0.5029));
+        assert!(close(mid_gray::<u16>(linear).luminance(), 0.5));
+        assert!(close(mid_gray::<f32>(linear).luminance(), 0.5));
+    }
+
+    #[test]
+    fn test_convert() {
+        let c = Canonical::new(255, 128, 0, 51);
+        assert_eq!(c.convert::<u16>(), RGBA::new(65535, 32896, 0, 13107));
+        assert_eq!(c.convert::<u16>().convert::<u8>(), c);
+        assert_eq!(c.convert::<f32>().convert::<u8>(), c);
+        assert_eq!(c.normalized(), RGBA::new(1.0, 128.0 / 255.0, 0.0, 0.2));
+        assert!(f32::is_float() && !u8::is_float() && !u16::is_float());
+    }
+
+    #[test]
+    fn test_checked_constructors() {
+        assert_eq!(
+            RGBA::new_clamped(1.5, -0.2, 0.0, 2.0),
+            RGBA::new(1.0, 0.0, 0.0, 1.0)
+        );
+        assert_eq!(
+            RGBA::new_clamped(f32::NAN, f32::INFINITY, f32::NEG_INFINITY, 0.5),
+            RGBA::new(0.0, 1.0, 0.0, 0.5)
+        );
+        assert_eq!(RGB::new_clamped(1.0001, 0.5, -0.0), RGB::new(1.0, 0.5, 0.0));
+
+        assert_eq!(
+            RGBA::try_new(0.0, 1.0, 0.5, 1.0),
+            Ok(RGBA::new(0.0, 1.0, 0.5, 1.0))
+        );
+        let e = RGBA::try_new(0.0, 1.0, 0.5, 1.0001).unwrap_err();
+        assert_eq!(e.kind(), crate::errors::ErrorKind::ComponentOutOfRange);
+        assert_eq!(e.to_string(), "alpha component 1.0001 is outside [0, 1]");
+        let e = RGB::try_new(0.5, f32::NAN, 0.5).unwrap_err();
+        assert_eq!(e.kind(), crate::errors::ErrorKind::NotFinite);
+        assert_eq!(e.component(), "green");
+        assert_eq!(
+            RGB::try_new(0.5, 0.5, f32::NEG_INFINITY)
+                .unwrap_err()
+                .to_string(),
+            "blue component is -inf"
+        );
+        assert!(RGB::try_new(-f32::EPSILON, 0.0, 0.0).is_err());
+    }
+
+    #[test]
+    fn test_clamped() {
+        assert_eq!(
+            RGBA::new(1.5f32, -0.5, 0.5, 1.0).clamped(),
+            RGBA::new(1.0, 0.0, 0.5, 1.0)
+        );
+        assert_eq!(
+            Canonical::new(1, 2, 3, 4).clamped(),
+            Canonical::new(1, 2, 3, 4)
+        );
+        assert_eq!(
+            RGBA::new(f32::NAN, 0.5, 0.5, 1.0).clamped(),
+            RGBA::new(0.0, 0.5, 0.5, 1.0)
+        );
+        assert_eq!(
+            RGBA::new(0.0f32, 0.0, 0.0, 0.0).mix(&RGBA::new(1.0, 1.0, 1.0, 1.0), 2.0),
+            RGBA::new(1.0, 1.0, 1.0, 1.0)
+        );
+    }
+}
@@ -0,0 +1,99 @@
+use super::{Canonical, Scheme};
+
+// WCAG 2 level AA minimum for normal sized text
+pub const AA_NORMAL_TEXT: f32 = 4.5;
+
+const BLACK: Canonical = Canonical {
+    r: 0,
+    g: 0,
+    b: 0,
+    a: u8::MAX,
+};
+
+const WHITE: Canonical = Canonical {
+    r: u8::MAX,
+    g: u8::MAX,
+    b: u8::MAX,
+    a: u8::MAX,
+};
+
+impl Canonical {
+    // WCAG 2 contrast ratio between 1 and 21. alpha is ignored
+    pub fn contrast_ratio(&self, other: &Canonical) -> f32 {
+        let a = self.luminance();
+        let b = other.luminance();
+        (a.max(b) + 0.05) / (a.min(b) + 0.05)
+    }
+
+    // black or white, whichever contrasts more with this color. on a tie,
+    // which happens around a relative luminance of 0.179, black is chosen
+    pub fn contrasting_text(&self) -> Canonical {
+        if self.contrast_ratio(&BLACK) >= self.contrast_ratio(&WHITE) {
+            BLACK
+        } else {
+            WHITE
+        }
+    }
+}
+
+impl Scheme {
+    // the color with the highest contrast to `bg`. with `require_aa`, None is
+    // returned when not even the best candidate reaches AA_NORMAL_TEXT. ties
+    // are resolved in favor of the first color
+    pub fn best_foreground_for(&self, bg: Canonical, require_aa: bool) -> Option<&Canonical> {
+        let mut best: Option<(&Canonical, f32)> = None;
+        for c in self.colors() {
+            let ratio = c.contrast_ratio(&bg);
+            match best {
+                Some((_, max)) if max >= ratio => {}
+                _ => best = Some((c, ratio)),
+            }
+        }
+        best.filter(|(_, ratio)| !require_aa || *ratio >= AA_NORMAL_TEXT)
+            .map(|(c, _)| c)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn gray(v: u8) -> Canonical {
+        Canonical::new(v, v, v, 255)
+    }
+
+    #[test]
+    fn test_contrast_ratio() {
+        assert!(f32::abs(BLACK.contrast_ratio(&WHITE) - 21.0) < 0.001);
+        assert_eq!(WHITE.contrast_ratio(&BLACK), BLACK.contrast_ratio(&WHITE));
+        assert_eq!(gray(99).contrast_ratio(&gray(99)), 1.0);
+    }
+
+    #[test]
+    fn test_contrasting_text() {
+        assert_eq!(BLACK.contrasting_text(), WHITE);
+        assert_eq!(WHITE.contrasting_text(), BLACK);
+        // the decision flips between these two mid grays
+        assert_eq!(gray(117).contrasting_text(), WHITE);
+        assert_eq!(gray(118).contrasting_text(), BLACK);
+        // mid gray by value is still better served by black text
+        assert_eq!(gray(128).contrasting_text(), BLACK);
+    }
+
+    #[test]
+    fn test_best_foreground_for() {
+        let scheme = Scheme::from_colors("ui", vec![gray(100), gray(200), gray(30), gray(200)]);
+
+        assert_eq!(scheme.best_foreground_for(gray(0), false), Some(&gray(200)));
+        assert_eq!(scheme.best_foreground_for(gray(255), true), Some(&gray(30)));
+        // nothing reaches AA against a mid gray
+        assert_eq!(
+            scheme.best_foreground_for(gray(118), false),
+            Some(&gray(30))
+        );
+        assert_eq!(scheme.best_foreground_for(gray(118), true), None);
+
+        let empty = Scheme::new("empty");
+        assert_eq!(empty.best_foreground_for(gray(0), false), None);
+    }
+}
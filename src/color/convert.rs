@@ -0,0 +1,231 @@
+// conversions between every pair of color types, so generic code doesn't
+// have to know which to_x and from_y methods exist:
+//
+//   let lab: Lab = hsl.into_color();
+//
+// every conversion goes through linear sRGB in f32, so adding a type takes
+// two functions rather than one per other type. the types are RGBA of any
+// channel type, HSL, XYZ, Lab and LabF64 relative to D65 as Canonical::to_lab
+// is, and Oklch
+//
+// accuracy: f32 all the way, a chain of conversions between float types
+// stays well within a step of 8 bit sRGB, going through a type doesn't
+// degrade a color noticeably. integer channels round to their steps, a color
+// through RGBA<u8> is up to half a step off. alpha is kept between RGBA
+// types and 1 otherwise. colors sRGB can't show lose chroma, keeping their
+// hue, on the way into integer RGBA and HSL, see gamut::map_linear, while
+// float RGBA, XYZ, Lab and Oklch keep them as they are. LabF64 converts in
+// f32, it is no more precise here than Lab
+use super::component::Component;
+use super::gamut::map_linear;
+use super::hsl::{hsl_to_rgb, rgb_to_hsl};
+use super::linear::{decode_srgb, encode_srgb};
+use super::xyz::{WhitePoint, SRGB_TO_XYZ, XYZ_TO_SRGB};
+use super::{Lab, LabF64, Oklch, HSL, RGB, RGBA, XYZ};
+
+pub trait FromColor<T> {
+    fn from_color(color: T) -> Self;
+}
+
+pub trait IntoColor<T> {
+    fn into_color(self) -> T;
+}
+
+mod hub {
+    use super::RGB;
+
+    // to and from the hub, linear sRGB and alpha in [0, 1]
+    pub trait Hub {
+        fn to_hub(&self) -> (RGB<f32>, f32);
+        fn from_hub(linear: RGB<f32>, alpha: f32) -> Self;
+    }
+}
+
+use hub::Hub;
+
+impl<T: Hub, U: Hub> FromColor<T> for U {
+    fn from_color(color: T) -> U {
+        let (linear, alpha) = color.to_hub();
+        U::from_hub(linear, alpha)
+    }
+}
+
+impl<T, U: FromColor<T>> IntoColor<U> for T {
+    fn into_color(self) -> U {
+        U::from_color(self)
+    }
+}
+
+impl<C: Component> Hub for RGBA<C> {
+    fn to_hub(&self) -> (RGB<f32>, f32) {
+        (self.to_linear(), self.a.normalized())
+    }
+
+    fn from_hub(linear: RGB<f32>, alpha: f32) -> Self {
+        let linear = if C::is_float() {
+            linear
+        } else {
+            map_linear(linear)
+        };
+        let rgb = linear.map(|c| C::from_normalized(encode_srgb(c)));
+        rgb.into_rgba(C::from_normalized(alpha))
+    }
+}
+
+impl Hub for HSL {
+    fn to_hub(&self) -> (RGB<f32>, f32) {
+        let (r, g, b) = hsl_to_rgb(self.h.degrees(), self.s, self.l);
+        (RGB::new(r, g, b).map(decode_srgb), 1.0)
+    }
+
+    fn from_hub(linear: RGB<f32>, _: f32) -> Self {
+        let rgb = map_linear(linear).map(encode_srgb);
+        rgb_to_hsl(rgb.r, rgb.g, rgb.b)
+    }
+}
+
+impl Hub for XYZ {
+    fn to_hub(&self) -> (RGB<f32>, f32) {
+        (XYZ_TO_SRGB.apply(self.as_rgb()), 1.0)
+    }
+
+    fn from_hub(linear: RGB<f32>, _: f32) -> Self {
+        let xyz = SRGB_TO_XYZ.apply(linear);
+        XYZ::new(xyz.r, xyz.g, xyz.b)
+    }
+}
+
+impl Hub for Lab {
+    fn to_hub(&self) -> (RGB<f32>, f32) {
+        self.to_xyz(WhitePoint::D65).to_hub()
+    }
+
+    fn from_hub(linear: RGB<f32>, alpha: f32) -> Self {
+        Lab::from_xyz(XYZ::from_hub(linear, alpha), WhitePoint::D65)
+    }
+}
+
+impl Hub for LabF64 {
+    fn to_hub(&self) -> (RGB<f32>, f32) {
+        Lab::from(*self).to_hub()
+    }
+
+    fn from_hub(linear: RGB<f32>, alpha: f32) -> Self {
+        Lab::from_hub(linear, alpha).into()
+    }
+}
+
+impl Hub for Oklch {
+    fn to_hub(&self) -> (RGB<f32>, f32) {
+        (self.to_linear(), 1.0)
+    }
+
+    fn from_hub(linear: RGB<f32>, _: f32) -> Self {
+        Oklch::from_linear(linear)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::Canonical;
+
+    // some of every hue, grays, black and white
+    fn samples() -> Vec<Canonical> {
+        let steps = [0, 51, 128, 200, 255];
+        let mut samples = Vec::new();
+        for r in steps {
+            for g in steps {
+                for b in steps {
+                    samples.push(Canonical::new(r, g, b, 255));
+                }
+            }
+        }
+        samples
+    }
+
+    // S to T and back, within a step of 8 bit sRGB
+    fn round_trip<S, T>(tolerance: f32)
+    where
+        S: Copy + std::fmt::Debug + FromColor<Canonical> + FromColor<T>,
+        T: FromColor<S>,
+        RGBA<f32>: FromColor<S>,
+    {
+        for sample in samples() {
+            let source: S = sample.into_color();
+            let back: S = T::from_color(source).into_color();
+            let (a, b): (RGBA<f32>, RGBA<f32>) = (source.into_color(), back.into_color());
+            let difference = [a.r - b.r, a.g - b.g, a.b - b.b, a.a - b.a]
+                .iter()
+                .fold(0.0f32, |max, d| max.max(d.abs()));
+            assert!(
+                difference <= tolerance,
+                "{:?} to {} and back is {:?}",
+                source,
+                std::any::type_name::<T>(),
+                back
+            );
+        }
+    }
+
+    macro_rules! all_pairs {
+        ($($source:ty),*) => {
+            $(all_pairs!(@to $source;
+                Canonical, RGBA<u16>, RGBA<f32>, HSL, XYZ, Lab, LabF64, Oklch);)*
+        };
+        (@to $source:ty; $($target:ty),*) => {
+            $(round_trip::<$source, $target>(0.5 / 255.0 + 0.0001);)*
+        };
+    }
+
+    #[test]
+    fn test_round_trip_all_pairs() {
+        all_pairs!(
+            Canonical,
+            RGBA<u16>,
+            RGBA<f32>,
+            HSL,
+            XYZ,
+            Lab,
+            LabF64,
+            Oklch
+        );
+    }
+
+    #[test]
+    fn test_matches_methods() {
+        for sample in samples() {
+            let lab: Lab = sample.into_color();
+            let expected = sample.to_lab();
+            assert!((lab.l - expected.l).abs() < 0.001, "{:?}", sample);
+            assert!((lab.a - expected.a).abs() < 0.001, "{:?}", sample);
+            let hsl: HSL = sample.into_color();
+            let expected = sample.to_hsl();
+            assert!((hsl.l - expected.l).abs() < 0.0001, "{:?}", sample);
+            assert!((hsl.s - expected.s).abs() < 0.0001, "{:?}", sample);
+            assert_eq!(Canonical::from_color(sample.to_oklch()), sample);
+            assert_eq!(Canonical::from_color(sample.to_xyz()), sample);
+        }
+        let hsl = HSL::new(120.0, 1.0, 0.25);
+        let lab: Lab = hsl.into_color();
+        assert_eq!(Canonical::from_color(lab), Canonical::from_hsl(hsl, 1.0));
+    }
+
+    #[test]
+    fn test_alpha_and_gamut() {
+        let translucent = Canonical::new(10, 20, 30, 128);
+        let float: RGBA<f32> = translucent.into_color();
+        assert!((float.a - 128.0 / 255.0).abs() < 0.0001);
+        assert_eq!(Canonical::from_color(float), translucent);
+        // other types have no alpha, colors come back opaque
+        let oklch: Oklch = translucent.into_color();
+        assert_eq!(Canonical::from_color(oklch).a, 255);
+
+        // beyond sRGB: float RGBA keeps the color, Canonical maps it
+        let beyond = Oklch::new(0.7, 0.4, 145.0);
+        let float: RGBA<f32> = beyond.into_color();
+        assert!(float.r < 0.0);
+        let canonical: Canonical = beyond.into_color();
+        assert_eq!(canonical, Canonical::from_oklch(beyond, 255));
+    }
+}
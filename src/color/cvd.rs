@@ -0,0 +1,229 @@
+use super::distance::{DistanceMetric, RgbEuclidean};
+use super::matrix::ColorMatrix;
+use super::{Canonical, Scheme};
+
+// color vision deficiencies, all simulated as the full dichromacy
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum Cvd {
+    Protanopia,
+    Deuteranopia,
+    Tritanopia,
+}
+
+// Viénot, Brettel & Mollon 1999, for linear sRGB
+const PROTAN_VIENOT: ColorMatrix = ColorMatrix([
+    [0.112_38, 0.887_62, 0.0],
+    [0.112_38, 0.887_62, 0.0],
+    [0.004_01, -0.004_01, 1.0],
+]);
+
+const DEUTAN_VIENOT: ColorMatrix = ColorMatrix([
+    [0.292_75, 0.707_25, 0.0],
+    [0.292_75, 0.707_25, 0.0],
+    [-0.022_34, 0.022_34, 1.0],
+]);
+
+// Brettel, Viénot & Mollon 1997. tritanopia needs two half planes, the
+// normal of the separating plane decides which one to project onto
+const TRITAN_BRETTEL_1: ColorMatrix = ColorMatrix([
+    [1.013_54, 0.142_68, -0.156_22],
+    [-0.011_81, 0.875_61, 0.136_19],
+    [0.077_07, 0.812_08, 0.110_85],
+]);
+
+const TRITAN_BRETTEL_2: ColorMatrix = ColorMatrix([
+    [0.933_37, 0.199_99, -0.133_36],
+    [0.058_09, 0.825_65, 0.116_26],
+    [-0.379_23, 1.138_25, 0.240_98],
+]);
+
+const TRITAN_SEPARATION: [f32; 3] = [0.039_60, -0.028_31, -0.011_29];
+
+impl Canonical {
+    // simulates how the color appears to a dichromat. alpha is kept as is
+    pub fn simulate(&self, cvd: Cvd) -> Canonical {
+        let linear = self.to_linear();
+        let simulated = match cvd {
+            Cvd::Protanopia => PROTAN_VIENOT.apply(linear),
+            Cvd::Deuteranopia => DEUTAN_VIENOT.apply(linear),
+            Cvd::Tritanopia => {
+                let [x, y, z] = TRITAN_SEPARATION;
+                if x * linear.r + y * linear.g + z * linear.b >= 0.0 {
+                    TRITAN_BRETTEL_1.apply(linear)
+                } else {
+                    TRITAN_BRETTEL_2.apply(linear)
+                }
+            }
+        };
+        Canonical::from_linear(simulated, self.a)
+    }
+}
+
+impl Scheme {
+    pub fn simulate(&self, cvd: Cvd) -> Scheme {
+        self.map_colors(|c| c.simulate(cvd))
+    }
+
+    // index pairs that are at least `min_distance` apart for normal vision,
+    // but closer than that under the simulated deficiency. pairs which are
+    // already too close without simulation are not reported
+    pub fn cvd_conflicts(&self, cvd: Cvd, min_distance: f32) -> Vec<(usize, usize)> {
+        self.cvd_conflicts_with(cvd, min_distance, &RgbEuclidean)
+    }
+
+    pub fn cvd_conflicts_with(
+        &self,
+        cvd: Cvd,
+        min_distance: f32,
+        metric: &impl DistanceMetric,
+    ) -> Vec<(usize, usize)> {
+        let simulated = self.simulate(cvd);
+        let mut conflicts = Vec::new();
+        let original: Vec<_> = self.colors().collect();
+        let simulated: Vec<_> = simulated.colors().collect();
+        for i in 0..original.len() {
+            for j in (i + 1)..original.len() {
+                let distance = metric.distance(original[i], original[j]);
+                let collapsed = metric.distance(simulated[i], simulated[j]);
+                if distance >= min_distance && collapsed < min_distance {
+                    conflicts.push((i, j));
+                }
+            }
+        }
+        conflicts
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::RGB;
+
+    // Viénot, Brettel & Mollon 1999, "Digital video colourmaps for checking
+    // the legibility of displays by dichromats", Color Research and
+    // Application 24(4): linear rgb to LMS, and the missing cone of a
+    // protanope or deuteranope as a mix of the other two. the rgb matrices
+    // above fold these into one step, simulating through LMS checks them
+    const VIENOT_LMS: ColorMatrix = ColorMatrix([
+        [17.8824, 43.5161, 4.119_35],
+        [3.455_65, 27.1554, 3.867_14],
+        [0.029_956_6, 0.184_309, 1.467_09],
+    ]);
+    const VIENOT_PROTAN: ColorMatrix =
+        ColorMatrix([[0.0, 2.023_44, -2.525_81], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]]);
+    const VIENOT_DEUTAN: ColorMatrix =
+        ColorMatrix([[1.0, 0.0, 0.0], [0.494_207, 0.0, 1.248_27], [0.0, 0.0, 1.0]]);
+
+    // sRGB to XYZ as IEC 61966-2-1 has it, then the Smith & Pokorny 1975
+    // cone fundamentals from Judd-Vos XYZ, the LMS space of the tritan
+    // matrices
+    const SRGB_XYZ: ColorMatrix = ColorMatrix([
+        [0.4124, 0.3576, 0.1805],
+        [0.2126, 0.7152, 0.0722],
+        [0.0193, 0.1192, 0.9505],
+    ]);
+    const SMITH_POKORNY: ColorMatrix = ColorMatrix([
+        [0.155_14, 0.543_12, -0.032_86],
+        [-0.155_14, 0.456_84, 0.032_86],
+        [0.0, 0.0, 0.016_08],
+    ]);
+
+    fn through_lms(color: Canonical, projection: &ColorMatrix) -> Canonical {
+        let to_rgb = VIENOT_LMS.inverse().unwrap();
+        let simulated = VIENOT_LMS.then(projection).then(&to_rgb);
+        Canonical::from_linear(simulated.apply(color.to_linear()), color.a)
+    }
+
+    #[test]
+    fn test_simulate_reference_values() {
+        let colors = [
+            Canonical::new(255, 0, 0, 255),
+            Canonical::new(0, 255, 0, 255),
+            Canonical::new(0, 0, 255, 255),
+            Canonical::new(255, 255, 0, 255),
+            Canonical::new(0, 255, 255, 255),
+            Canonical::new(255, 0, 255, 255),
+            Canonical::new(254, 128, 25, 255),
+            Canonical::new(131, 165, 152, 128),
+        ];
+        let cases = [
+            (Cvd::Protanopia, VIENOT_PROTAN),
+            (Cvd::Deuteranopia, VIENOT_DEUTAN),
+        ];
+        for color in colors {
+            for (cvd, projection) in cases {
+                let expected = through_lms(color, &projection);
+                let simulated = color.simulate(cvd);
+                let off = [
+                    simulated.r.abs_diff(expected.r),
+                    simulated.g.abs_diff(expected.g),
+                    simulated.b.abs_diff(expected.b),
+                ];
+                assert!(
+                    off.iter().all(|&d| d <= 1) && simulated.a == color.a,
+                    "{:?} of {:?} is {:?}, expected {:?}",
+                    cvd,
+                    color,
+                    simulated,
+                    expected
+                );
+            }
+        }
+    }
+
+    // Brettel, Viénot & Mollon 1997: a tritanope's L and M cones respond as
+    // usual, both half planes only replace S, keep white, and meet where the
+    // separating plane divides them
+    #[test]
+    fn test_tritan_keeps_long_and_medium_cones() {
+        let to_lms = SRGB_XYZ.then(&SMITH_POKORNY);
+        let to_rgb = to_lms.inverse().unwrap();
+        let close = |a: f32, b: f32| (a - b).abs() < 1e-3;
+        for half in [TRITAN_BRETTEL_1, TRITAN_BRETTEL_2] {
+            let in_lms = to_rgb.then(&half).then(&to_lms).0;
+            for (row, expected) in in_lms[..2].iter().zip([[1.0, 0.0, 0.0], [0.0, 1.0, 0.0]]) {
+                assert!(
+                    row.iter().zip(expected).all(|(&a, b)| close(a, b)),
+                    "{:?}",
+                    row
+                );
+            }
+            let white = half.apply(RGB::new(1.0, 1.0, 1.0));
+            assert!([white.r, white.g, white.b].iter().all(|&c| close(c, 1.0)));
+        }
+
+        // a color on the separating plane, normal x white
+        let [x, y, z] = TRITAN_SEPARATION;
+        let on_plane = RGB::new(y - z, z - x, x - y);
+        let (one, two) = (
+            TRITAN_BRETTEL_1.apply(on_plane),
+            TRITAN_BRETTEL_2.apply(on_plane),
+        );
+        assert!(close(one.r, two.r) && close(one.g, two.g) && close(one.b, two.b));
+    }
+
+    #[test]
+    fn test_simulate_keeps_grays_and_alpha() {
+        let gray = Canonical::new(128, 128, 128, 77);
+        for cvd in [Cvd::Protanopia, Cvd::Deuteranopia, Cvd::Tritanopia] {
+            let simulated = gray.simulate(cvd);
+            assert_eq!(simulated.a, 77);
+            assert!(gray.distance(&simulated) < 3.0);
+        }
+    }
+
+    #[test]
+    fn test_scheme_cvd_conflicts() {
+        let scheme = Scheme::from_colors(
+            "diff",
+            vec![
+                Canonical::new(200, 40, 40, 255),
+                Canonical::new(80, 160, 40, 255),
+                Canonical::new(40, 40, 200, 255),
+            ],
+        );
+
+        assert_eq!(scheme.cvd_conflicts(Cvd::Deuteranopia, 60.0), vec![(0, 1)]);
+        assert!(scheme.cvd_conflicts(Cvd::Deuteranopia, 10.0).is_empty());
+    }
+}
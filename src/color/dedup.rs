@@ -0,0 +1,153 @@
+use super::{DistanceMetric, Entry, Scheme};
+
+// what collapsed into one surviving entry. `kept` is its index after the
+// dedup, `count` how many entries it took in and `merged_names` their names,
+// in order, except the one an unnamed survivor took on
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct Merge {
+    pub kept: usize,
+    pub merged_names: Vec<String>,
+    pub count: usize,
+}
+
+impl Scheme {
+    // removes entries with a color identical to an earlier entry and returns
+    // a merge for every entry that took others in. see dedup_within for how
+    // names are merged
+    pub fn dedup(&mut self) -> Vec<Merge> {
+        self.merge_entries(|kept, candidate| kept.color == candidate.color)
+    }
+
+    // collapses every entry closer than `threshold` to an earlier surviving
+    // entry into that entry, and returns a merge for every survivor that
+    // took others in, in the order of the survivors. survivors keep their
+    // order. an unnamed survivor takes the name and role of the first named
+    // entry merged into it, the other names are kept in its merge
+    pub fn dedup_within(&mut self, threshold: f32, metric: &impl DistanceMetric) -> Vec<Merge> {
+        self.merge_entries(|kept, candidate| {
+            metric.distance(&kept.color, &candidate.color) < threshold
+        })
+    }
+
+    fn merge_entries<F>(&mut self, same: F) -> Vec<Merge>
+    where
+        F: Fn(&Entry, &Entry) -> bool,
+    {
+        let mut survivors: Vec<Entry> = Vec::with_capacity(self.entries.len());
+        let mut merges: Vec<Merge> = Vec::new();
+        for entry in self.entries.drain(..) {
+            let Some(kept) = survivors.iter().position(|kept| same(kept, &entry)) else {
+                survivors.push(entry);
+                continue;
+            };
+            let merge = match merges.iter_mut().position(|m| m.kept == kept) {
+                Some(at) => &mut merges[at],
+                None => {
+                    merges.push(Merge {
+                        kept,
+                        merged_names: Vec::new(),
+                        count: 0,
+                    });
+                    merges.last_mut().unwrap()
+                }
+            };
+            merge.count += 1;
+            let survivor = &mut survivors[kept];
+            match (survivor.name.is_none(), entry.name) {
+                (true, Some(name)) => {
+                    survivor.name = Some(name);
+                    survivor.role = entry.role;
+                }
+                (false, Some(name)) => merge.merged_names.push(name),
+                (_, None) => {}
+            }
+        }
+        merges.sort_by_key(|m| m.kept);
+        self.entries = survivors;
+        self.reindex();
+        merges
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::distance::RgbEuclidean;
+    use crate::color::{Canonical, Metric};
+
+    fn gray(v: u8) -> Canonical {
+        Canonical::new(v, v, v, 255)
+    }
+
+    #[test]
+    fn test_dedup() {
+        let mut scheme = Scheme::from_entries(
+            "dupes".into(),
+            None,
+            vec![
+                Entry::new(gray(10)),
+                Entry::named("black", gray(0)),
+                Entry::named("dark", gray(10)),
+                Entry::named("background", gray(0)),
+                Entry::new(gray(11)),
+            ],
+        );
+
+        let merges = scheme.dedup();
+        assert_eq!(
+            scheme.entries,
+            vec![
+                Entry::named("dark", gray(10)),
+                Entry::named("black", gray(0)),
+                Entry::new(gray(11)),
+            ]
+        );
+        // background survives as an alias of black
+        assert_eq!(
+            merges,
+            vec![
+                Merge {
+                    kept: 0,
+                    merged_names: vec![],
+                    count: 1,
+                },
+                Merge {
+                    kept: 1,
+                    merged_names: vec!["background".into()],
+                    count: 1,
+                },
+            ]
+        );
+        assert!(scheme.dedup().is_empty());
+    }
+
+    #[test]
+    fn test_dedup_within() {
+        // gray(0) to gray(3) is sqrt(27) ~ 5.2, gray(0) to gray(4) sqrt(48) ~ 6.9
+        let mut scheme = Scheme::from_colors(
+            "near",
+            vec![gray(0), gray(100), gray(3), gray(4), gray(103), gray(0)],
+        );
+
+        let merges = scheme.dedup_within(6.0, &RgbEuclidean);
+        let counts: Vec<_> = merges.iter().map(|m| (m.kept, m.count)).collect();
+        assert_eq!(counts, [(0, 2), (1, 1)]);
+        assert_eq!(
+            scheme.colors().copied().collect::<Vec<_>>(),
+            vec![gray(0), gray(100), gray(4)]
+        );
+    }
+
+    #[test]
+    fn test_dedup_within_metric() {
+        let colors = vec![gray(0), gray(4)];
+
+        let mut euclidean = Scheme::from_colors("a", colors.clone());
+        let mut lab = Scheme::from_colors("b", colors);
+        euclidean.dedup_within(6.0, &RgbEuclidean);
+        lab.dedup_within(6.0, &Metric::DeltaE76);
+
+        assert_eq!(euclidean.len(), 2);
+        assert_eq!(lab.len(), 1);
+    }
+}
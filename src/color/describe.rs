@@ -0,0 +1,204 @@
+use std::fmt::Display;
+
+use super::Canonical;
+
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum Lightness {
+    VeryDark,
+    Dark,
+    Light,
+    VeryLight,
+}
+
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum Saturation {
+    Grayish,
+    Desaturated,
+    Vivid,
+}
+
+// achromatic names followed by a 12 segment color wheel, starting at red
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum HueName {
+    Black,
+    White,
+    Gray,
+    Red,
+    Orange,
+    Yellow,
+    Chartreuse,
+    Green,
+    SpringGreen,
+    Cyan,
+    Azure,
+    Blue,
+    Violet,
+    Magenta,
+    Rose,
+}
+
+// structured form of Canonical::describe. adjectives are None for the
+// medium buckets, which are not worth mentioning
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub struct ColorDescription {
+    pub lightness: Option<Lightness>,
+    pub saturation: Option<Saturation>,
+    pub hue_name: HueName,
+}
+
+const WHEEL: [HueName; 12] = [
+    HueName::Red,
+    HueName::Orange,
+    HueName::Yellow,
+    HueName::Chartreuse,
+    HueName::Green,
+    HueName::SpringGreen,
+    HueName::Cyan,
+    HueName::Azure,
+    HueName::Blue,
+    HueName::Violet,
+    HueName::Magenta,
+    HueName::Rose,
+];
+
+impl Canonical {
+    pub fn description(&self) -> ColorDescription {
+        let hsl = self.to_hsl();
+
+        let lightness = match hsl.l {
+            l if l < 0.2 => Some(Lightness::VeryDark),
+            l if l < 0.4 => Some(Lightness::Dark),
+            l if l < 0.65 => None,
+            l if l < 0.85 => Some(Lightness::Light),
+            _ => Some(Lightness::VeryLight),
+        };
+
+        let (lightness, saturation, hue_name) = if hsl.l < 0.08 {
+            (None, None, HueName::Black)
+        } else if hsl.l > 0.95 {
+            (None, None, HueName::White)
+        } else if hsl.s < 0.1 {
+            (lightness, None, HueName::Gray)
+        } else {
+            let saturation = match hsl.s {
+                s if s < 0.3 => Some(Saturation::Grayish),
+                s if s < 0.6 => Some(Saturation::Desaturated),
+                s if s < 0.85 => None,
+                _ => Some(Saturation::Vivid),
+            };
+            // segments are 30° wide and centered on their hue, so red
+            // covers [345°, 15°)
+            let segment = ((hsl.h.degrees() + 15.0) / 30.0) as usize % WHEEL.len();
+            (lightness, saturation, WHEEL[segment])
+        };
+
+        ColorDescription {
+            lightness,
+            saturation,
+            hue_name,
+        }
+    }
+
+    // e.g. "dark desaturated blue" or "vivid orange"
+    pub fn describe(&self) -> String {
+        self.description().to_string()
+    }
+}
+
+impl Display for Lightness {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match *self {
+            Lightness::VeryDark => write!(f, "very dark"),
+            Lightness::Dark => write!(f, "dark"),
+            Lightness::Light => write!(f, "light"),
+            Lightness::VeryLight => write!(f, "very light"),
+        }
+    }
+}
+
+impl Display for Saturation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match *self {
+            Saturation::Grayish => write!(f, "grayish"),
+            Saturation::Desaturated => write!(f, "desaturated"),
+            Saturation::Vivid => write!(f, "vivid"),
+        }
+    }
+}
+
+impl Display for HueName {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match *self {
+            HueName::Black => "black",
+            HueName::White => "white",
+            HueName::Gray => "gray",
+            HueName::Red => "red",
+            HueName::Orange => "orange",
+            HueName::Yellow => "yellow",
+            HueName::Chartreuse => "chartreuse",
+            HueName::Green => "green",
+            HueName::SpringGreen => "spring green",
+            HueName::Cyan => "cyan",
+            HueName::Azure => "azure",
+            HueName::Blue => "blue",
+            HueName::Violet => "violet",
+            HueName::Magenta => "magenta",
+            HueName::Rose => "rose",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl Display for ColorDescription {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(lightness) = self.lightness {
+            write!(f, "{} ", lightness)?;
+        }
+        if let Some(saturation) = self.saturation {
+            write!(f, "{} ", saturation)?;
+        }
+        write!(f, "{}", self.hue_name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_describe() {
+        let cases = [
+            ((0, 0, 0), "black"),
+            ((255, 255, 255), "white"),
+            ((128, 128, 128), "gray"),
+            ((60, 60, 60), "dark gray"),
+            ((200, 200, 200), "light gray"),
+            ((255, 0, 0), "vivid red"),
+            ((255, 128, 0), "vivid orange"),
+            ((50, 60, 100), "dark desaturated blue"),
+            ((40, 130, 40), "dark desaturated green"),
+            ((150, 200, 255), "light vivid azure"),
+            ((128, 0, 128), "dark vivid magenta"),
+            ((255, 182, 193), "very light vivid red"),
+            ((200, 80, 140), "desaturated rose"),
+            ((140, 120, 110), "grayish orange"),
+            ((0, 255, 255), "vivid cyan"),
+        ];
+
+        for ((r, g, b), expected) in cases {
+            assert_eq!(Canonical::new(r, g, b, 255).describe(), expected);
+        }
+    }
+
+    #[test]
+    fn test_description() {
+        assert_eq!(
+            Canonical::new(50, 60, 100, 255).description(),
+            ColorDescription {
+                lightness: Some(Lightness::Dark),
+                saturation: Some(Saturation::Desaturated),
+                hue_name: HueName::Blue,
+            }
+        );
+    }
+}
@@ -0,0 +1,307 @@
+// entry-wise differences between schemes, and the three-way merge used to
+// take upstream theme updates while keeping local overrides. entries are
+// matched by name, unnamed ones by position
+use std::fmt::Display;
+
+use super::{Canonical, Entry, Scheme};
+
+#[derive(PartialEq, Eq, Hash, Debug, Clone)]
+pub enum EntryKey {
+    Name(String),
+    // position of an entry. diffs only use it for unnamed ones
+    Index(usize),
+}
+
+impl From<&str> for EntryKey {
+    fn from(name: &str) -> Self {
+        EntryKey::Name(name.into())
+    }
+}
+
+impl From<usize> for EntryKey {
+    fn from(index: usize) -> Self {
+        EntryKey::Index(index)
+    }
+}
+
+#[derive(PartialEq, Debug, Clone)]
+pub enum Change {
+    Added {
+        key: EntryKey,
+        color: Canonical,
+    },
+    Removed {
+        key: EntryKey,
+        color: Canonical,
+    },
+    Changed {
+        key: EntryKey,
+        from: Canonical,
+        to: Canonical,
+    },
+}
+
+#[derive(PartialEq, Debug, Clone, Default)]
+pub struct SchemeDiff {
+    pub changes: Vec<Change>,
+}
+
+// an entry both sides changed, each to something else. None where the
+// entry doesn't exist
+#[derive(PartialEq, Debug, Clone)]
+pub struct MergeConflict {
+    pub key: EntryKey,
+    pub base: Option<Canonical>,
+    pub mine: Option<Canonical>,
+    pub theirs: Option<Canonical>,
+}
+
+fn key(index: usize, entry: &Entry) -> EntryKey {
+    match entry.name {
+        Some(ref name) => EntryKey::Name(name.clone()),
+        None => EntryKey::Index(index),
+    }
+}
+
+impl Scheme {
+    fn lookup(&self, key: &EntryKey) -> Option<Canonical> {
+        match *key {
+            EntryKey::Name(ref name) => self.get(name).copied(),
+            EntryKey::Index(i) => self
+                .entries
+                .get(i)
+                .filter(|e| e.name.is_none())
+                .map(|e| e.color),
+        }
+    }
+
+    fn keys(&self) -> impl Iterator<Item = EntryKey> + '_ {
+        self.entries.iter().enumerate().map(|(i, e)| key(i, e))
+    }
+
+    // what changes self into `other`: changed and removed entries in the
+    // order of self, then the ones only `other` has
+    pub fn diff(&self, other: &Scheme) -> SchemeDiff {
+        let mut changes = Vec::new();
+        for key in self.keys() {
+            let from = self.lookup(&key).unwrap();
+            match other.lookup(&key) {
+                Some(to) if to == from => {}
+                Some(to) => changes.push(Change::Changed { key, from, to }),
+                None => changes.push(Change::Removed { key, color: from }),
+            }
+        }
+        for key in other.keys() {
+            if self.lookup(&key).is_none() {
+                let color = other.lookup(&key).unwrap();
+                changes.push(Change::Added { key, color });
+            }
+        }
+        SchemeDiff { changes }
+    }
+
+    // entries changed on one side only take that side, entries both sides
+    // changed the same way are kept. name and author come from `mine`, the
+    // order is mine's with entries only theirs has appended
+    pub fn merge3(
+        base: &Scheme,
+        mine: &Scheme,
+        theirs: &Scheme,
+    ) -> Result<Scheme, Vec<MergeConflict>> {
+        let mut merged = Scheme::from_entries(mine.name.clone(), mine.author.clone(), Vec::new());
+        let mut conflicts = Vec::new();
+        let only_theirs = theirs.keys().filter(|key| mine.lookup(key).is_none());
+        for key in mine.keys().chain(only_theirs) {
+            let (b, m, t) = (base.lookup(&key), mine.lookup(&key), theirs.lookup(&key));
+            let color = if m == t || t == b {
+                m
+            } else if m == b {
+                t
+            } else {
+                conflicts.push(MergeConflict {
+                    key,
+                    base: b,
+                    mine: m,
+                    theirs: t,
+                });
+                continue;
+            };
+            match (color, key) {
+                // a name mine has twice is only merged once
+                (Some(color), EntryKey::Name(name)) => {
+                    let _ = merged.push_named(&name, color);
+                }
+                (Some(color), EntryKey::Index(_)) => merged.push(color),
+                (None, _) => {}
+            }
+        }
+        if conflicts.is_empty() {
+            Ok(merged)
+        } else {
+            Err(conflicts)
+        }
+    }
+}
+
+impl SchemeDiff {
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+}
+
+// #rrggbb for opaque colors, #rrggbbaa otherwise
+pub(crate) fn hex(color: &Canonical) -> String {
+    let hex = color.to_hex_string();
+    match color.a {
+        u8::MAX => hex[..7].into(),
+        _ => hex,
+    }
+}
+
+fn option_hex(color: &Option<Canonical>) -> String {
+    color.as_ref().map_or_else(|| "(none)".into(), hex)
+}
+
+impl Display for EntryKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match *self {
+            EntryKey::Name(ref name) => write!(f, "{}", name),
+            EntryKey::Index(i) => write!(f, "[{}]", i),
+        }
+    }
+}
+
+impl Display for Change {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match *self {
+            Change::Added { ref key, ref color } => write!(f, "+ {}: {}", key, hex(color)),
+            Change::Removed { ref key, ref color } => write!(f, "- {}: {}", key, hex(color)),
+            Change::Changed {
+                ref key,
+                ref from,
+                ref to,
+            } => write!(f, "~ {}: {} -> {}", key, hex(from), hex(to)),
+        }
+    }
+}
+
+// one change per line
+impl Display for SchemeDiff {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for change in &self.changes {
+            writeln!(f, "{}", change)?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for MergeConflict {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "! {}: {} -> mine {}, theirs {}",
+            self.key,
+            option_hex(&self.base),
+            option_hex(&self.mine),
+            option_hex(&self.theirs)
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::Packed;
+
+    fn scheme(entries: &[(&str, u32)]) -> Scheme {
+        let mut scheme = Scheme::new("gruvbox");
+        for &(name, rgb) in entries {
+            scheme
+                .push_named(name, Canonical::unpack(Packed(rgb << 8 | 0xFF)))
+                .unwrap();
+        }
+        scheme
+    }
+
+    fn name(name: &str) -> EntryKey {
+        EntryKey::Name(name.into())
+    }
+
+    #[test]
+    fn test_diff() {
+        let old = scheme(&[("bg", 0x282828), ("color4", 0x458588), ("color5", 0xb16286)]);
+        let mut new = scheme(&[("bg", 0x282828), ("color4", 0x83a598), ("fg", 0xebdbb2)]);
+        new.push(Canonical::new(1, 2, 3, 128));
+
+        let diff = old.diff(&new);
+        assert_eq!(
+            diff.to_string(),
+            "~ color4: #458588 -> #83a598
+- color5: #b16286
++ fg: #ebdbb2
++ [3]: #01020380
+"
+        );
+        assert!(old.diff(&old).is_empty());
+        assert_eq!(old.diff(&old).to_string(), "");
+    }
+
+    #[test]
+    fn test_merge3_clean() {
+        let base = scheme(&[("bg", 0x282828), ("fg", 0xebdbb2), ("color4", 0x458588)]);
+        // a local override and a local addition
+        let mine = scheme(&[
+            ("bg", 0x1d2021),
+            ("fg", 0xebdbb2),
+            ("color4", 0x458588),
+            ("cursor", 0xfe8019),
+        ]);
+        // an upstream fix, the same override and an upstream addition
+        let theirs = scheme(&[
+            ("bg", 0x1d2021),
+            ("fg", 0xfbf1c7),
+            ("color4", 0x458588),
+            ("color5", 0xb16286),
+        ]);
+        let merged = Scheme::merge3(&base, &mine, &theirs).unwrap();
+        assert_eq!(
+            merged,
+            scheme(&[
+                ("bg", 0x1d2021),
+                ("fg", 0xfbf1c7),
+                ("color4", 0x458588),
+                ("cursor", 0xfe8019),
+                ("color5", 0xb16286),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_merge3_removals() {
+        let base = scheme(&[("bg", 0x282828), ("fg", 0xebdbb2), ("color4", 0x458588)]);
+        let mine = scheme(&[("bg", 0x282828), ("color4", 0x458588)]);
+        let theirs = scheme(&[("bg", 0x282828), ("fg", 0xebdbb2)]);
+        let merged = Scheme::merge3(&base, &mine, &theirs).unwrap();
+        assert_eq!(merged, scheme(&[("bg", 0x282828)]));
+    }
+
+    #[test]
+    fn test_merge3_conflicts() {
+        let base = scheme(&[("bg", 0x282828), ("fg", 0xebdbb2), ("color4", 0x458588)]);
+        let mine = scheme(&[("bg", 0x1d2021), ("fg", 0xebdbb2), ("accent", 0xfe8019)]);
+        let theirs = scheme(&[("bg", 0x32302f), ("color4", 0x83a598), ("accent", 0xd65d0e)]);
+        let conflicts = Scheme::merge3(&base, &mine, &theirs).unwrap_err();
+        let keys: Vec<_> = conflicts.iter().map(|c| c.key.clone()).collect();
+        // fg was only removed by theirs, so it merges cleanly
+        assert_eq!(keys, [name("bg"), name("accent"), name("color4")]);
+        let shown: Vec<_> = conflicts.iter().map(|c| c.to_string()).collect();
+        assert_eq!(
+            shown,
+            [
+                "! bg: #282828 -> mine #1d2021, theirs #32302f",
+                "! accent: (none) -> mine #fe8019, theirs #d65d0e",
+                "! color4: #458588 -> mine (none), theirs #83a598",
+            ]
+        );
+    }
+}
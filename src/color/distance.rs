@@ -0,0 +1,297 @@
+use super::float::Float;
+use super::Canonical;
+
+// how far apart two colors are. every metric ignores alpha
+pub trait DistanceMetric {
+    fn distance(&self, a: &Canonical, b: &Canonical) -> f32;
+}
+
+// straight line in the rgb cube, see Canonical::distance
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Default)]
+pub struct RgbEuclidean;
+
+// the "redmean" approximation of perceptual distance in rgb
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Default)]
+pub struct WeightedRgb;
+
+// euclidean distance in CIE Lab. both deltaE metrics convert to Lab and
+// compute in f64, only the result is rounded to f32. Lab in f32 is off by
+// up to about 1e-4, enough to reorder near identical colors. CIEDE2000 in
+// f64 matches all of Sharma et al.'s test data to its 4 decimals
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Default)]
+pub struct DeltaE76;
+
+// CIEDE2000, the most uniform of them and the slowest
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Default)]
+pub struct DeltaE2000;
+
+// one of the metrics above as a value, to store or choose at runtime
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Default)]
+pub enum Metric {
+    #[default]
+    RgbEuclidean,
+    WeightedRgb,
+    DeltaE76,
+    DeltaE2000,
+}
+
+impl DistanceMetric for RgbEuclidean {
+    fn distance(&self, a: &Canonical, b: &Canonical) -> f32 {
+        a.distance(b)
+    }
+}
+
+impl DistanceMetric for WeightedRgb {
+    fn distance(&self, a: &Canonical, b: &Canonical) -> f32 {
+        let r_mean = (a.r as f32 + b.r as f32) / 2.0;
+        let dr = a.r as f32 - b.r as f32;
+        let dg = a.g as f32 - b.g as f32;
+        let db = a.b as f32 - b.b as f32;
+        ((2.0 + r_mean / 256.0) * dr * dr
+            + 4.0 * dg * dg
+            + (2.0 + (255.0 - r_mean) / 256.0) * db * db)
+            .sqrt()
+    }
+}
+
+impl DistanceMetric for DeltaE76 {
+    fn distance(&self, a: &Canonical, b: &Canonical) -> f32 {
+        let a = a.to_lab_f64();
+        let b = b.to_lab_f64();
+        ((a.l - b.l).powi(2) + (a.a - b.a).powi(2) + (a.b - b.b).powi(2)).sqrt() as f32
+    }
+}
+
+impl DistanceMetric for DeltaE2000 {
+    fn distance(&self, a: &Canonical, b: &Canonical) -> f32 {
+        let (a, b) = (a.to_lab_f64(), b.to_lab_f64());
+        delta_e_2000([a.l, a.a, a.b], [b.l, b.a, b.b]) as f32
+    }
+}
+
+impl DistanceMetric for Metric {
+    fn distance(&self, a: &Canonical, b: &Canonical) -> f32 {
+        match self {
+            Metric::RgbEuclidean => RgbEuclidean.distance(a, b),
+            Metric::WeightedRgb => WeightedRgb.distance(a, b),
+            Metric::DeltaE76 => DeltaE76.distance(a, b),
+            Metric::DeltaE2000 => DeltaE2000.distance(a, b),
+        }
+    }
+}
+
+// Sharma, Wu and Dalal, "The CIEDE2000 Color-Difference Formula:
+// Implementation Notes, Supplementary Test Data, and Mathematical
+// Observations", with kL = kC = kH = 1. hues are in degrees, Lab is
+// [l, a, b] in f32 or f64
+fn delta_e_2000<T: Float>(x: [T; 3], y: [T; 3]) -> T {
+    let k = T::of;
+    let zero = k(0.0);
+    let pow7 = |v: T| (v.powi(7) / (v.powi(7) + k(25.0).powi(7))).sqrt();
+    let c_mean = (x[1].hypot(x[2]) + y[1].hypot(y[2])) / k(2.0);
+    let g = k(0.5) * (k(1.0) - pow7(c_mean));
+    let (a1, a2) = (x[1] * (k(1.0) + g), y[1] * (k(1.0) + g));
+    let (c1, c2) = (a1.hypot(x[2]), a2.hypot(y[2]));
+    let hue = |b: T, a: T| {
+        if a == zero && b == zero {
+            zero
+        } else {
+            b.atan2(a).to_degrees().rem_euclid(k(360.0))
+        }
+    };
+    let (h1, h2) = (hue(x[2], a1), hue(y[2], a2));
+    let achromatic = c1 * c2 == zero;
+
+    let dl = y[0] - x[0];
+    let dc = c2 - c1;
+    let dh = match h2 - h1 {
+        _ if achromatic => zero,
+        d if d > k(180.0) => d - k(360.0),
+        d if d < k(-180.0) => d + k(360.0),
+        d => d,
+    };
+    let dh = k(2.0) * (c1 * c2).sqrt() * (dh.to_radians() / k(2.0)).sin();
+
+    let l_mean = (x[0] + y[0]) / k(2.0);
+    let c_mean = (c1 + c2) / k(2.0);
+    let h_mean = match (h1 - h2).abs() {
+        _ if achromatic => h1 + h2,
+        d if d <= k(180.0) => (h1 + h2) / k(2.0),
+        _ if h1 + h2 < k(360.0) => (h1 + h2 + k(360.0)) / k(2.0),
+        _ => (h1 + h2 - k(360.0)) / k(2.0),
+    };
+    let cos = |degrees: T| degrees.to_radians().cos();
+    let t = k(1.0) - k(0.17) * cos(h_mean - k(30.0))
+        + k(0.24) * cos(k(2.0) * h_mean)
+        + k(0.32) * cos(k(3.0) * h_mean + k(6.0))
+        - k(0.20) * cos(k(4.0) * h_mean - k(63.0));
+    let d_theta = k(30.0) * (-((h_mean - k(275.0)) / k(25.0)).powi(2)).exp();
+    let rt = -(k(2.0) * d_theta).to_radians().sin() * k(2.0) * pow7(c_mean);
+    let l50 = (l_mean - k(50.0)).powi(2);
+    let sl = k(1.0) + k(0.015) * l50 / (k(20.0) + l50).sqrt();
+    let sc = k(1.0) + k(0.045) * c_mean;
+    let sh = k(1.0) + k(0.015) * c_mean * t;
+
+    let (l, c, h) = (dl / sl, dc / sc, dh / sh);
+    (l * l + c * c + h * h + rt * c * h).sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const METRICS: [Metric; 4] = [
+        Metric::RgbEuclidean,
+        Metric::WeightedRgb,
+        Metric::DeltaE76,
+        Metric::DeltaE2000,
+    ];
+
+    #[test]
+    fn test_metrics() {
+        let black = Canonical::new(0, 0, 0, 255);
+        let white = Canonical::new(255, 255, 255, 0);
+
+        for metric in METRICS {
+            assert_eq!(metric.distance(&black, &black), 0.0);
+            assert_eq!(
+                metric.distance(&black, &white),
+                metric.distance(&white, &black)
+            );
+        }
+        assert!(f32::abs(DeltaE76.distance(&black, &white) - 100.0) < 0.01);
+        assert!(f32::abs(DeltaE2000.distance(&black, &white) - 100.0) < 0.01);
+        // sqrt(3 * 255^2 * 3)
+        assert!(f32::abs(WeightedRgb.distance(&black, &white) - 764.83) < 0.1);
+    }
+
+    // which of b and c is closer to a
+    fn closer(
+        metric: &impl DistanceMetric,
+        a: (u8, u8, u8),
+        b: (u8, u8, u8),
+        c: (u8, u8, u8),
+    ) -> char {
+        let rgb = |(r, g, b)| Canonical::new(r, g, b, 255);
+        let (ab, ac) = (
+            metric.distance(&rgb(a), &rgb(b)),
+            metric.distance(&rgb(a), &rgb(c)),
+        );
+        match ab.partial_cmp(&ac).unwrap() {
+            std::cmp::Ordering::Less => 'b',
+            std::cmp::Ordering::Greater => 'c',
+            std::cmp::Ordering::Equal => '=',
+        }
+    }
+
+    #[test]
+    fn test_metrics_disagree() {
+        let gray = (128, 128, 128);
+        // a hint of green or a darker gray
+        let ranks: String = METRICS
+            .iter()
+            .map(|m| closer(m, gray, (128, 150, 128), (100, 100, 100)))
+            .collect();
+        assert_eq!(ranks, "bbcc");
+        // as much red as blue is a tie in the rgb cube only
+        let ranks: String = METRICS
+            .iter()
+            .map(|m| closer(m, gray, (160, 128, 128), (128, 128, 160)))
+            .collect();
+        assert_eq!(ranks, "=cbb");
+        // only CIEDE2000 finds the blue closer
+        let ranks: String = METRICS
+            .iter()
+            .map(|m| closer(m, gray, (128, 160, 128), (128, 128, 170)))
+            .collect();
+        assert_eq!(ranks, "bbbc");
+    }
+
+    // the supplementary test data of Sharma et al., pairs of Lab colors and
+    // their CIEDE2000 difference to 4 decimals
+    const SHARMA: [([f64; 3], [f64; 3], f64); 34] = [
+        ([50.0, 2.6772, -79.7751], [50.0, 0.0, -82.7485], 2.0425),
+        ([50.0, 3.1571, -77.2803], [50.0, 0.0, -82.7485], 2.8615),
+        ([50.0, 2.8361, -74.02], [50.0, 0.0, -82.7485], 3.4412),
+        ([50.0, -1.3802, -84.2814], [50.0, 0.0, -82.7485], 1.0),
+        ([50.0, -1.1848, -84.8006], [50.0, 0.0, -82.7485], 1.0),
+        ([50.0, -0.9009, -85.5211], [50.0, 0.0, -82.7485], 1.0),
+        ([50.0, 0.0, 0.0], [50.0, -1.0, 2.0], 2.3669),
+        ([50.0, -1.0, 2.0], [50.0, 0.0, 0.0], 2.3669),
+        ([50.0, 2.49, -0.001], [50.0, -2.49, 0.0009], 7.1792),
+        ([50.0, 2.49, -0.001], [50.0, -2.49, 0.001], 7.1792),
+        ([50.0, 2.49, -0.001], [50.0, -2.49, 0.0011], 7.2195),
+        ([50.0, 2.49, -0.001], [50.0, -2.49, 0.0012], 7.2195),
+        ([50.0, -0.001, 2.49], [50.0, 0.0009, -2.49], 4.8045),
+        ([50.0, -0.001, 2.49], [50.0, 0.001, -2.49], 4.8045),
+        ([50.0, -0.001, 2.49], [50.0, 0.0011, -2.49], 4.7461),
+        ([50.0, 2.5, 0.0], [50.0, 0.0, -2.5], 4.3065),
+        ([50.0, 2.5, 0.0], [73.0, 25.0, -18.0], 27.1492),
+        ([50.0, 2.5, 0.0], [61.0, -5.0, 29.0], 22.8977),
+        ([50.0, 2.5, 0.0], [56.0, -27.0, -3.0], 31.903),
+        ([50.0, 2.5, 0.0], [58.0, 24.0, 15.0], 19.4535),
+        ([50.0, 2.5, 0.0], [50.0, 3.1736, 0.5854], 1.0),
+        ([50.0, 2.5, 0.0], [50.0, 3.2972, 0.0], 1.0),
+        ([50.0, 2.5, 0.0], [50.0, 1.8634, 0.5757], 1.0),
+        ([50.0, 2.5, 0.0], [50.0, 3.2592, 0.335], 1.0),
+        (
+            [60.2574, -34.0099, 36.2677],
+            [60.4626, -34.1751, 39.4387],
+            1.2644,
+        ),
+        (
+            [63.0109, -31.0961, -5.8663],
+            [62.8187, -29.7946, -4.0864],
+            1.263,
+        ),
+        ([61.2901, 3.7196, -5.3901], [61.4292, 2.248, -4.962], 1.8731),
+        (
+            [35.0831, -44.1164, 3.7933],
+            [35.0232, -40.0716, 1.5901],
+            1.8645,
+        ),
+        (
+            [22.7233, 20.0904, -46.694],
+            [23.0331, 14.973, -42.5619],
+            2.0373,
+        ),
+        (
+            [36.4612, 47.858, 18.3852],
+            [36.2715, 50.5065, 21.2231],
+            1.4146,
+        ),
+        (
+            [90.8027, -2.0831, 1.441],
+            [91.1528, -1.6435, 0.0447],
+            1.4441,
+        ),
+        (
+            [90.9257, -0.5406, -0.9208],
+            [88.6381, -0.8985, -0.7239],
+            1.5381,
+        ),
+        (
+            [6.7747, -0.2908, -2.4247],
+            [5.8714, -0.0985, -2.2286],
+            0.6377,
+        ),
+        ([2.0776, 0.0795, -1.135], [0.9033, -0.0636, -0.5514], 0.9082),
+    ];
+
+    #[test]
+    fn test_delta_e_2000_reference() {
+        let to_f32 = |lab: [f64; 3]| lab.map(|v| v as f32);
+        // summed over all pairs
+        let (mut error_f32, mut error_f64) = (0f64, 0f64);
+        for (x, y, expected) in SHARMA {
+            let d = delta_e_2000(x, y);
+            assert!((d - expected).abs() < 0.00005, "{} != {}", d, expected);
+            assert!((delta_e_2000(y, x) - expected).abs() < 0.00005);
+            error_f64 += (d - expected).abs();
+
+            let d = delta_e_2000(to_f32(x), to_f32(y)) as f64;
+            error_f32 += (d - expected).abs();
+        }
+        assert!(error_f64 < error_f32, "{} {}", error_f64, error_f32);
+    }
+}
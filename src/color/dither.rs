@@ -0,0 +1,155 @@
+// RGB565, the 16 bit colors of small displays: 5 bits of red, 6 of green and
+// 5 of blue, red in the top bits. truncating to those few levels bands
+// gradients, dithering rounds some pixels up and some down so that an area
+// averages out to the color it should have. alpha is dropped
+use super::Canonical;
+
+// the order in which the pixels of a 4x4 tile round up
+const BAYER: [[u8; 4]; 4] = [[0, 8, 2, 10], [12, 4, 14, 6], [3, 11, 1, 9], [15, 7, 13, 5]];
+
+// the 8 bit value a level of `bits` bits stands for, its bits repeated the
+// way displays widen them, so 0 and the top level are 0 and 255
+fn expand(level: u8, bits: u32) -> u8 {
+    level << (8 - bits) | level >> (2 * bits - 8)
+}
+
+// the level at or below value and how far value is towards the next one
+fn split(value: u8, bits: u32) -> (u8, f32) {
+    let mut level = value >> (8 - bits);
+    if expand(level, bits) > value {
+        level -= 1;
+    }
+    if level == (1 << bits) - 1 {
+        return (level, 0.0);
+    }
+    let (low, high) = (expand(level, bits), expand(level + 1, bits));
+    (level, (value - low) as f32 / (high - low) as f32)
+}
+
+// a channel rounds up when it is further towards the next level than the
+// threshold, so a value that is a level exactly never does
+fn quantize(color: &Canonical, mut threshold: impl FnMut() -> f32) -> u16 {
+    let mut channel = |value: u8, bits: u32| {
+        let (level, fraction) = split(value, bits);
+        (level + (fraction > threshold()) as u8) as u16
+    };
+    channel(color.r, 5) << 11 | channel(color.g, 6) << 5 | channel(color.b, 5)
+}
+
+impl Canonical {
+    // straight truncation, what most display drivers do
+    pub fn to_rgb565(&self) -> u16 {
+        (self.r as u16 >> 3) << 11 | (self.g as u16 >> 2) << 5 | self.b as u16 >> 3
+    }
+
+    pub fn from_rgb565(packed: u16) -> Canonical {
+        Canonical::new(
+            expand((packed >> 11) as u8, 5),
+            expand((packed >> 5 & 0x3f) as u8, 6),
+            expand((packed & 0x1f) as u8, 5),
+            255,
+        )
+    }
+
+    // dithered with random thresholds, noise is called once per channel and
+    // should be uniform in [0, 1) for the color to come out right on average
+    pub fn to_rgb565_with_noise(&self, noise: impl FnMut() -> f32) -> u16 {
+        quantize(self, noise)
+    }
+}
+
+// ordered dithering of an image `width` pixels wide, its pixels row by row
+pub fn to_rgb565_dithered(colors: &[Canonical], width: usize) -> Vec<u16> {
+    let width = width.max(1);
+    colors
+        .iter()
+        .enumerate()
+        .map(|(i, color)| {
+            let (x, y) = (i % width, i / width);
+            let threshold = (BAYER[y % 4][x % 4] as f32 + 0.5) / 16.0;
+            quantize(color, || threshold)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    #[test]
+    fn test_rgb565() {
+        let orange = Canonical::new(255, 130, 7, 100);
+        assert_eq!(orange.to_rgb565(), 0xfc00);
+        assert_eq!(
+            Canonical::from_rgb565(0xffff),
+            Canonical::new(255, 255, 255, 255)
+        );
+        assert_eq!(
+            Canonical::from_rgb565(0x8401),
+            Canonical::new(132, 130, 8, 255)
+        );
+        for packed in 0..=u16::MAX {
+            assert_eq!(Canonical::from_rgb565(packed).to_rgb565(), packed);
+        }
+    }
+
+    #[test]
+    fn test_dither_exact_colors() {
+        // colors RGB565 has exactly come out the same everywhere
+        let mut rng = StdRng::seed_from_u64(0x00c0_ffee);
+        for packed in [0, 0xffff, 0x8401, 0x1234] {
+            let color = Canonical::from_rgb565(packed);
+            let dithered = to_rgb565_dithered(&[color; 64], 8);
+            assert!(dithered.iter().all(|&p| p == packed), "{:?}", dithered);
+            for _ in 0..64 {
+                assert_eq!(color.to_rgb565_with_noise(|| rng.gen()), packed);
+            }
+        }
+        assert!(to_rgb565_dithered(&[], 4).is_empty());
+    }
+
+    #[test]
+    fn test_dither_gradient() {
+        // a gray gradient 256 pixels wide and 4 high, averaged over 4x4 tiles
+        let width = 256;
+        let pixels: Vec<Canonical> = (0..width * 4)
+            .map(|i| {
+                let v = (i % width) as u8;
+                Canonical::new(v, v, v, 255)
+            })
+            .collect();
+        let tile_error = |packed: &[u16]| {
+            let mut total = 0.0;
+            for tile in 0..width / 4 {
+                let mut error = 0.0;
+                for y in 0..4 {
+                    for x in tile * 4..tile * 4 + 4 {
+                        let i = y * width + x;
+                        let color = Canonical::from_rgb565(packed[i]);
+                        error += color.r as f32 - pixels[i].r as f32;
+                        error += color.g as f32 - pixels[i].g as f32;
+                    }
+                }
+                total += (error / 16.0).abs();
+            }
+            total / (width / 4) as f32
+        };
+        let truncated: Vec<u16> = pixels.iter().map(Canonical::to_rgb565).collect();
+        let mut rng = StdRng::seed_from_u64(0x00c0_ffee);
+        let noisy: Vec<u16> = pixels
+            .iter()
+            .map(|c| c.to_rgb565_with_noise(|| rng.gen()))
+            .collect();
+        let dithered = to_rgb565_dithered(&pixels, width);
+
+        let (truncated, noisy, dithered) = (
+            tile_error(&truncated),
+            tile_error(&noisy),
+            tile_error(&dithered),
+        );
+        assert!(dithered < truncated / 4.0, "{} and {}", dithered, truncated);
+        assert!(noisy < truncated / 2.0, "{} and {}", noisy, truncated);
+    }
+}
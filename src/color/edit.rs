@@ -0,0 +1,141 @@
+// mutators that keep entry names unique, unlike editing `entries` directly
+use super::{Canonical, Entry, Scheme};
+use crate::errors::DuplicateName;
+
+impl Scheme {
+    fn check_unused(&self, name: &str) -> Result<(), DuplicateName> {
+        match self.position(name) {
+            Some(index) => Err(DuplicateName {
+                name: name.into(),
+                index,
+            }),
+            None => Ok(()),
+        }
+    }
+
+    pub fn push(&mut self, color: Canonical) {
+        self.entries.push(Entry::new(color));
+        self.index.push(None);
+    }
+
+    pub fn push_named(&mut self, name: &str, color: Canonical) -> Result<(), DuplicateName> {
+        self.check_unused(name)?;
+        self.entries.push(Entry::named(name, color));
+        self.index.push(Some(name));
+        Ok(())
+    }
+
+    // panics if `index` is out of bounds, like Vec::remove
+    pub fn remove(&mut self, index: usize) -> Entry {
+        let entry = self.entries.remove(index);
+        self.reindex();
+        entry
+    }
+
+    pub fn remove_named(&mut self, name: &str) -> Option<Canonical> {
+        let index = self.position(name)?;
+        Some(self.remove(index).color)
+    }
+
+    pub fn retain<F>(&mut self, f: F)
+    where
+        F: FnMut(&Entry) -> bool,
+    {
+        self.entries.retain(f);
+        self.reindex();
+    }
+
+    // false if no entry is called `old`. renaming an entry to its own name
+    // is fine, taking the name of another entry is not
+    pub fn rename(&mut self, old: &str, new: &str) -> Result<bool, DuplicateName> {
+        let Some(index) = self.position(old) else {
+            return Ok(false);
+        };
+        if old != new {
+            self.check_unused(new)?;
+        }
+        self.entries[index].name = Some(new.into());
+        self.index.rename(&self.entries, old, new, index);
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::errors::ErrorKind;
+
+    fn gray(v: u8) -> Canonical {
+        Canonical::new(v, v, v, 255)
+    }
+
+    #[test]
+    fn test_push() {
+        let mut scheme = Scheme::new("edit");
+        scheme.push(gray(1));
+        scheme.push_named("bg", gray(0)).unwrap();
+        scheme.push(gray(2));
+        let e = scheme.push_named("bg", gray(3)).unwrap_err();
+        assert_eq!(e.kind(), ErrorKind::DuplicateName);
+        assert_eq!(e.to_string(), "bg already names entry 1");
+        assert_eq!(scheme.len(), 3);
+        assert_eq!(scheme.get("bg"), Some(&gray(0)));
+    }
+
+    #[test]
+    fn test_remove() {
+        let mut scheme = Scheme::new("edit");
+        scheme.push_named("bg", gray(0)).unwrap();
+        scheme.push_named("fg", gray(255)).unwrap();
+        scheme.push(gray(128));
+
+        assert_eq!(scheme.remove_named("bg"), Some(gray(0)));
+        assert_eq!(scheme.remove_named("bg"), None);
+        assert_eq!(scheme.remove(1), Entry::new(gray(128)));
+        assert_eq!(scheme.entries, vec![Entry::named("fg", gray(255))]);
+        // the name is free again
+        scheme.push_named("bg", gray(1)).unwrap();
+    }
+
+    #[test]
+    fn test_retain() {
+        let mut scheme = Scheme::new("edit");
+        scheme.push_named("black", gray(0)).unwrap();
+        scheme.push(gray(100));
+        scheme.push_named("white", gray(255)).unwrap();
+        scheme.push_named("dark", gray(20)).unwrap();
+
+        scheme.retain(|e| e.color.r < 128);
+        assert_eq!(
+            scheme.entries,
+            vec![
+                Entry::named("black", gray(0)),
+                Entry::new(gray(100)),
+                Entry::named("dark", gray(20)),
+            ]
+        );
+        assert!(!scheme.has_duplicate_names());
+        assert_eq!(scheme.get("dark"), Some(&gray(20)));
+        scheme.push_named("white", gray(250)).unwrap();
+    }
+
+    #[test]
+    fn test_rename() {
+        let mut scheme = Scheme::new("edit");
+        scheme.push_named("bg", gray(0)).unwrap();
+        scheme.push_named("fg", gray(255)).unwrap();
+
+        assert_eq!(scheme.rename("bg", "background"), Ok(true));
+        assert_eq!(scheme.rename("bg", "x"), Ok(false));
+        assert_eq!(scheme.rename("fg", "fg"), Ok(true));
+        assert_eq!(
+            scheme.rename("fg", "background"),
+            Err(DuplicateName {
+                name: "background".into(),
+                index: 0
+            })
+        );
+        assert_eq!(scheme.get("background"), Some(&gray(0)));
+        assert_eq!(scheme.get("fg"), Some(&gray(255)));
+    }
+}
@@ -0,0 +1,157 @@
+// hashes of what a scheme is rather than how it was written: the names and
+// colors of its entries. the scheme's own name, author, roles and the text
+// the colors came from don't count, a color written as hex and as rgb() is
+// the same Canonical by the time it gets here
+//
+// both hash the same bytes: a version byte, the number of entries as a
+// big endian u32, then per entry its name's length in bytes plus one as a
+// big endian u32, 0 for no name, the name's utf-8 and the rgba bytes. with
+// EntryOrder::Ignored the entries' bytes are sorted first
+use super::Scheme;
+
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Default)]
+pub enum EntryOrder {
+    // the same entries in another order are another scheme
+    #[default]
+    Significant,
+    Ignored,
+}
+
+// bumped if the bytes ever have to change, which changes every digest
+const VERSION: u8 = 1;
+
+const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+impl Scheme {
+    fn fingerprint_bytes(&self, order: EntryOrder) -> Vec<u8> {
+        let mut entries: Vec<Vec<u8>> = self
+            .entries
+            .iter()
+            .map(|entry| {
+                let mut bytes = Vec::new();
+                let name = entry.name.as_deref().map(str::as_bytes);
+                let length = name.map_or(0, |n| n.len() as u32 + 1);
+                bytes.extend_from_slice(&length.to_be_bytes());
+                bytes.extend_from_slice(name.unwrap_or_default());
+                let c = entry.color;
+                bytes.extend_from_slice(&[c.r, c.g, c.b, c.a]);
+                bytes
+            })
+            .collect();
+        if order == EntryOrder::Ignored {
+            entries.sort_unstable();
+        }
+        let mut bytes = vec![VERSION];
+        bytes.extend_from_slice(&(entries.len() as u32).to_be_bytes());
+        bytes.extend(entries.concat());
+        bytes
+    }
+
+    // FNV-1a, the same on every platform and run but only meant for caches
+    // and lookups, it may change between versions of the crate
+    pub fn fingerprint(&self) -> u64 {
+        self.fingerprint_with(EntryOrder::Significant)
+    }
+
+    pub fn fingerprint_with(&self, order: EntryOrder) -> u64 {
+        self.fingerprint_bytes(order)
+            .iter()
+            .fold(FNV_OFFSET, |hash, &byte| {
+                (hash ^ byte as u64).wrapping_mul(FNV_PRIME)
+            })
+    }
+
+    // SHA-256 of the bytes above. stable: a scheme gets the same digest from
+    // every version of the crate, digests can be stored and compared later.
+    // changing them is a breaking change and bumps VERSION
+    #[cfg(feature = "digest")]
+    pub fn digest(&self) -> [u8; 32] {
+        self.digest_with(EntryOrder::Significant)
+    }
+
+    #[cfg(feature = "digest")]
+    pub fn digest_with(&self, order: EntryOrder) -> [u8; 32] {
+        crate::sha256::sha256(&self.fingerprint_bytes(order))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::{Canonical, Entry};
+    use crate::reader::{parse_from_str, NameSource};
+
+    fn parse(s: &str) -> Scheme {
+        parse_from_str(NameSource::FirstLine, s).unwrap()
+    }
+
+    #[test]
+    fn test_fingerprint_format_insensitive() {
+        let hex = parse("hex\nbackground: #282828\nred: #cc241d80\n#458588\n");
+        let rgb = parse(
+            "other name\n\nbackground: rgb(40, 40, 40)\n\
+             red:  rgba(204, 36, 29, 128)\nrgb(69,133,136)\n",
+        );
+        let mut authored = hex.clone();
+        authored.author = Some("someone".into());
+        for order in [EntryOrder::Significant, EntryOrder::Ignored] {
+            assert_eq!(hex.fingerprint_with(order), rgb.fingerprint_with(order));
+            assert_eq!(
+                hex.fingerprint_with(order),
+                authored.fingerprint_with(order)
+            );
+        }
+
+        // names and colors do count
+        let renamed = parse("hex\nbg: #282828\nred: #cc241d80\n#458588\n");
+        let recolored = parse("hex\nbackground: #282828\nred: #cc241d\n#458588\n");
+        assert_ne!(hex.fingerprint(), renamed.fingerprint());
+        assert_ne!(hex.fingerprint(), recolored.fingerprint());
+        // an unnamed entry isn't one named ""
+        let unnamed = Scheme::from_colors("s", vec![Canonical::new(1, 2, 3, 4)]);
+        let empty_name = Scheme::from_entries(
+            "s".into(),
+            None,
+            vec![Entry::named("", Canonical::new(1, 2, 3, 4))],
+        );
+        assert_ne!(unnamed.fingerprint(), empty_name.fingerprint());
+    }
+
+    #[test]
+    fn test_fingerprint_order() {
+        let scheme = parse("s\nbackground: #282828\nred: #cc241d\n#458588\n");
+        let reordered = parse("s\n#458588\nred: #cc241d\nbackground: #282828\n");
+        assert_ne!(scheme.fingerprint(), reordered.fingerprint());
+        assert_eq!(
+            scheme.fingerprint_with(EntryOrder::Ignored),
+            reordered.fingerprint_with(EntryOrder::Ignored)
+        );
+        assert_ne!(
+            scheme.fingerprint_with(EntryOrder::Ignored),
+            Scheme::new("s").fingerprint_with(EntryOrder::Ignored)
+        );
+    }
+
+    #[cfg(feature = "digest")]
+    #[test]
+    fn test_digest() {
+        let scheme = parse("s\nbackground: #282828\nred: #cc241d\n#458588\n");
+        let reordered = parse("t\n#458588\nred: rgb(204, 36, 29)\nbackground: #282828\n");
+        assert_ne!(scheme.digest(), reordered.digest());
+        assert_eq!(
+            scheme.digest_with(EntryOrder::Ignored),
+            reordered.digest_with(EntryOrder::Ignored)
+        );
+        // pinned, digests must not change between versions
+        let hex: String = scheme
+            .digest()
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect();
+        assert_eq!(
+            hex,
+            "c00d4b54fa62bbb3fc8bba7ed54029ec63a409804d4e8f56b18a70ec8db66eed"
+        );
+    }
+}
@@ -0,0 +1,77 @@
+// the float math shared by the f32 and f64 versions of the Lab and deltaE
+// code, so each formula is written once
+use std::ops::{Add, Div, Mul, Neg, Sub};
+
+pub(crate) trait Float:
+    Copy
+    + PartialOrd
+    + Add<Output = Self>
+    + Sub<Output = Self>
+    + Mul<Output = Self>
+    + Div<Output = Self>
+    + Neg<Output = Self>
+{
+    // constants are written as f64 and rounded once
+    fn of(value: f64) -> Self;
+    fn powi(self, n: i32) -> Self;
+    fn sqrt(self) -> Self;
+    fn cbrt(self) -> Self;
+    fn hypot(self, other: Self) -> Self;
+    fn atan2(self, other: Self) -> Self;
+    fn sin(self) -> Self;
+    fn cos(self) -> Self;
+    fn exp(self) -> Self;
+    fn abs(self) -> Self;
+    fn to_degrees(self) -> Self;
+    fn to_radians(self) -> Self;
+    fn rem_euclid(self, rhs: Self) -> Self;
+}
+
+macro_rules! float {
+    ($t:ident) => {
+        impl Float for $t {
+            fn of(value: f64) -> Self {
+                value as $t
+            }
+            fn powi(self, n: i32) -> Self {
+                $t::powi(self, n)
+            }
+            fn sqrt(self) -> Self {
+                $t::sqrt(self)
+            }
+            fn cbrt(self) -> Self {
+                $t::cbrt(self)
+            }
+            fn hypot(self, other: Self) -> Self {
+                $t::hypot(self, other)
+            }
+            fn atan2(self, other: Self) -> Self {
+                $t::atan2(self, other)
+            }
+            fn sin(self) -> Self {
+                $t::sin(self)
+            }
+            fn cos(self) -> Self {
+                $t::cos(self)
+            }
+            fn exp(self) -> Self {
+                $t::exp(self)
+            }
+            fn abs(self) -> Self {
+                $t::abs(self)
+            }
+            fn to_degrees(self) -> Self {
+                $t::to_degrees(self)
+            }
+            fn to_radians(self) -> Self {
+                $t::to_radians(self)
+            }
+            fn rem_euclid(self, rhs: Self) -> Self {
+                $t::rem_euclid(self, rhs)
+            }
+        }
+    };
+}
+
+float!(f32);
+float!(f64);
@@ -0,0 +1,143 @@
+// bringing colors sRGB can't show into its gamut. clamping every channel on
+// its own keeps lightness and hue only roughly, a bright yellow beyond the
+// gamut loses more red than green and turns greenish. reducing chroma in
+// OKLCH until the color fits keeps the hue and lightness as they were, as
+// CSS Color 4 maps colors
+use super::component::clamp_unit;
+use super::linear::{decode_srgb, encode_srgb};
+use super::oklch::Oklch;
+use super::{RGB, RGBA};
+
+// how closely fit_chroma gets to the edge of the gamut
+const CHROMA_PRECISION: f32 = 0.0005;
+
+// float imprecision at the gamut boundary that still counts as inside
+const TOLERANCE: f32 = 0.0001;
+
+// colors this close to the gamut in Oklab are clamped per channel, which
+// changes them less than fitting chroma to CHROMA_PRECISION would
+const CLAMP_DISTANCE: f32 = 0.002;
+
+pub(crate) fn in_gamut(linear: RGB<f32>) -> bool {
+    [linear.r, linear.g, linear.b]
+        .iter()
+        .all(|c| (-TOLERANCE..=1.0 + TOLERANCE).contains(c))
+}
+
+// the largest chroma not above oklch.c that sRGB can show with the
+// lightness and hue of oklch
+pub(crate) fn fit_chroma(oklch: Oklch) -> Oklch {
+    let fits = |c: f32| in_gamut(Oklch { c, ..oklch }.to_linear());
+    if fits(oklch.c) {
+        return oklch;
+    }
+    let (mut low, mut high) = (0.0, oklch.c);
+    while high - low > CHROMA_PRECISION {
+        let mid = (low + high) / 2.0;
+        if fits(mid) {
+            low = mid;
+        } else {
+            high = mid;
+        }
+    }
+    Oklch { c: low, ..oklch }
+}
+
+// linear sRGB, components possibly outside [0, 1], into the gamut with its
+// hue kept. lightness beyond black or white gives black or white. non finite
+// components can't be mapped and are clamped per channel, NaN to 0, as are
+// colors within CLAMP_DISTANCE
+pub(crate) fn map_linear(linear: RGB<f32>) -> RGB<f32> {
+    let finite = linear.r.is_finite() && linear.g.is_finite() && linear.b.is_finite();
+    let clamped = RGB::new_clamped(linear.r, linear.g, linear.b);
+    if !finite || in_gamut(linear) {
+        return clamped;
+    }
+    let oklch = Oklch::from_linear(linear);
+    if oklab_distance(oklch, Oklch::from_linear(clamped)) < CLAMP_DISTANCE {
+        return clamped;
+    }
+    let oklch = Oklch {
+        l: oklch.l.clamp(0.0, 1.0),
+        ..oklch
+    };
+    let fitted = fit_chroma(oklch).to_linear();
+    RGB::new_clamped(fitted.r, fitted.g, fitted.b)
+}
+
+fn oklab_distance(x: Oklch, y: Oklch) -> f32 {
+    let ab = |o: Oklch| {
+        let (sin, cos) = o.h.radians().sin_cos();
+        (o.c * cos, o.c * sin)
+    };
+    let ((xa, xb), (ya, yb)) = (ab(x), ab(y));
+    (x.l - y.l).hypot(xa - ya).hypot(xb - yb)
+}
+
+// the transfer function continued below 0, mirrored
+fn decode_extended(encoded: f32) -> f32 {
+    decode_srgb(encoded.abs()).copysign(encoded)
+}
+
+fn encode_extended(linear: f32) -> f32 {
+    encode_srgb(linear.abs()).copysign(linear)
+}
+
+// gamma encoded sRGB with components outside [0, 1] into the gamut, keeping
+// its hue. alpha is only clamped
+pub fn clamp_preserve_hue(color: RGBA<f32>) -> RGBA<f32> {
+    if ![color.r, color.g, color.b].iter().all(|c| c.is_finite()) {
+        return color.clamped();
+    }
+    let linear = RGB::from(color).map(decode_extended);
+    map_linear(linear)
+        .map(encode_extended)
+        .into_rgba(clamp_unit(color.a))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hue(color: RGBA<f32>) -> f32 {
+        Oklch::from_linear(RGB::from(color).map(decode_extended)).h.degrees()
+    }
+
+    #[test]
+    fn test_clamp_preserve_hue() {
+        // a yellow beyond sRGB, clamping channels shifts it towards green
+        let yellow = RGBA::new(1.1, 0.95, -0.3, 0.5);
+        let mapped = clamp_preserve_hue(yellow);
+        let clamped = yellow.clamped();
+        assert!((hue(mapped) - hue(yellow)).abs() < 0.5, "{:?}", mapped);
+        assert!((hue(clamped) - hue(yellow)).abs() > 3.0, "{:?}", clamped);
+        assert_eq!(mapped.a, 0.5);
+        for c in [mapped.r, mapped.g, mapped.b] {
+            assert!((0.0..=1.0).contains(&c), "{:?}", mapped);
+        }
+
+        // colors inside the gamut stay as they are
+        let inside = RGBA::new(0.2, 0.5, 0.9, 1.0);
+        let kept = clamp_preserve_hue(inside);
+        for (a, b) in [(kept.r, 0.2), (kept.g, 0.5), (kept.b, 0.9)] {
+            assert!((a - b).abs() < 0.0001, "{:?}", kept);
+        }
+
+        // too light or too dark for any hue
+        let white = clamp_preserve_hue(RGBA::new(1.5, 1.4, 1.6, 1.0));
+        assert!(
+            white.r > 0.999 && white.g > 0.999 && white.b > 0.999,
+            "{:?}",
+            white
+        );
+        let black = clamp_preserve_hue(RGBA::new(-0.5, -0.4, -0.6, 1.0));
+        assert!(
+            black.r < 0.001 && black.g < 0.001 && black.b < 0.001,
+            "{:?}",
+            black
+        );
+
+        let nan = clamp_preserve_hue(RGBA::new(f32::NAN, 2.0, 0.5, f32::NAN));
+        assert_eq!((nan.r, nan.g, nan.a), (0.0, 1.0, 0.0));
+    }
+}
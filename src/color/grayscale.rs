@@ -0,0 +1,134 @@
+use super::linear::{encode_srgb, AverageSpace};
+use super::{Canonical, Component, Scheme};
+
+// how a color is reduced to a single gray level. Average weighs the channels
+// equally, so pure blue and pure green both become #555555 although green is
+// about ten times brighter to the eye. Luminosity keeps that difference,
+// blue becomes #4c4c4c and green #dcdcdc
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Default)]
+pub enum GrayscaleMethod {
+    // relative luminance with the Rec. 709 weights, in linear light
+    #[default]
+    Luminosity,
+    // the mean of the stored channels
+    Average,
+    // (max + min) / 2 of the stored channels, the L of HSL
+    Lightness,
+}
+
+impl Canonical {
+    // alpha is kept as it is
+    pub fn grayscale(&self, method: GrayscaleMethod) -> Canonical {
+        let gray = match method {
+            GrayscaleMethod::Luminosity => u8::from_normalized(encode_srgb(self.luminance())),
+            GrayscaleMethod::Average => {
+                ((self.r as u16 + self.g as u16 + self.b as u16) as f32 / 3.0).round() as u8
+            }
+            GrayscaleMethod::Lightness => {
+                let max = self.r.max(self.g).max(self.b);
+                let min = self.r.min(self.g).min(self.b);
+                ((max as u16 + min as u16) as f32 / 2.0).round() as u8
+            }
+        };
+        Canonical::new(gray, gray, gray, self.a)
+    }
+}
+
+impl Scheme {
+    pub fn grayscale(&self, method: GrayscaleMethod) -> Scheme {
+        self.map_colors(|c| c.grayscale(method))
+    }
+
+    // every entry's luminance picks a point on the gradient from `dark` to
+    // `light`, interpolated in linear light. black gives `dark` and white
+    // `light` exactly, the entry's alpha is kept
+    pub fn duotone(&self, dark: Canonical, light: Canonical) -> Scheme {
+        self.map_colors(|c| {
+            let toned = dark.mix_with(&light, c.luminance(), AverageSpace::Linear);
+            Canonical { a: c.a, ..toned }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn gray(v: u8) -> Canonical {
+        Canonical::new(v, v, v, 255)
+    }
+
+    #[test]
+    fn test_primaries() {
+        let red = Canonical::new(255, 0, 0, 255);
+        let green = Canonical::new(0, 255, 0, 255);
+        let blue = Canonical::new(0, 0, 255, 255);
+
+        let luminosity = GrayscaleMethod::Luminosity;
+        assert_eq!(red.grayscale(luminosity), gray(127));
+        assert_eq!(green.grayscale(luminosity), gray(220));
+        assert_eq!(blue.grayscale(luminosity), gray(76));
+
+        for c in [red, green, blue] {
+            assert_eq!(c.grayscale(GrayscaleMethod::Average), gray(85));
+            assert_eq!(c.grayscale(GrayscaleMethod::Lightness), gray(128));
+        }
+    }
+
+    #[test]
+    fn test_duotone() {
+        let dark = Canonical::new(20, 30, 80, 255);
+        let light = Canonical::new(250, 220, 150, 255);
+        let scheme = Scheme::from_colors(
+            "flat",
+            vec![
+                gray(0),
+                gray(255),
+                Canonical::new(255, 255, 255, 64),
+                Canonical::new(0, 255, 0, 255),
+            ],
+        );
+        let toned = scheme.duotone(dark, light);
+        let colors: Vec<_> = toned.colors().copied().collect();
+        assert_eq!(colors[0], dark);
+        assert_eq!(colors[1], light);
+        assert_eq!(colors[2], Canonical { a: 64, ..light });
+        // green's luminance is 0.7152, most of the way to light
+        assert_eq!(colors[3], Canonical::new(216, 190, 135, 255));
+        assert_eq!(toned.name, "flat");
+    }
+
+    #[test]
+    fn test_grayscale() {
+        let c = Canonical::new(200, 100, 50, 77);
+        assert_eq!(GrayscaleMethod::default(), GrayscaleMethod::Luminosity);
+        assert_eq!(
+            c.grayscale(GrayscaleMethod::Average),
+            Canonical::new(117, 117, 117, 77)
+        );
+        assert_eq!(
+            c.grayscale(GrayscaleMethod::Lightness),
+            Canonical::new(125, 125, 125, 77)
+        );
+        // grays stay as they are
+        for v in [0, 1, 128, 254, 255] {
+            for method in [
+                GrayscaleMethod::Luminosity,
+                GrayscaleMethod::Average,
+                GrayscaleMethod::Lightness,
+            ] {
+                assert_eq!(gray(v).grayscale(method), gray(v));
+            }
+        }
+
+        let scheme = Scheme::from_colors("rgb", vec![Canonical::new(0, 0, 255, 255), c]);
+        assert_eq!(
+            scheme
+                .grayscale(GrayscaleMethod::Average)
+                .colors()
+                .copied()
+                .collect::<Vec<_>>(),
+            [gray(85), Canonical::new(117, 117, 117, 77)]
+        );
+    }
+}
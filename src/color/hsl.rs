@@ -0,0 +1,193 @@
+use super::component::clamp_unit;
+use super::hue::Hue;
+use super::Canonical;
+
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub struct HSL {
+    pub h: Hue,
+    pub s: f32,
+    pub l: f32,
+}
+
+impl HSL {
+    // hue in degrees or a Hue
+    pub fn new(hue: impl Into<Hue>, saturation: f32, lightness: f32) -> HSL {
+        HSL {
+            h: hue.into(),
+            s: saturation,
+            l: lightness,
+        }
+    }
+
+    // the short way round the hue circle, saturation and lightness in a
+    // straight line. `t` is clamped to [0, 1]. a gray has no hue of its own
+    // and takes the other's, so mixing with gray doesn't pass through red
+    pub fn mix(&self, other: &HSL, t: f32) -> HSL {
+        let t = clamp_unit(t);
+        let (from, to) = match (self.s == 0.0, other.s == 0.0) {
+            (true, false) => (other.h, other.h),
+            (false, true) => (self.h, self.h),
+            _ => (self.h, other.h),
+        };
+        HSL::new(
+            from.lerp_short(to, t),
+            self.s + (other.s - self.s) * t,
+            self.l + (other.l - self.l) * t,
+        )
+    }
+}
+
+impl Canonical {
+    pub fn to_hsl(&self) -> HSL {
+        let [r, g, b] = [self.r, self.g, self.b].map(|c| c as f32 / 255.0);
+        rgb_to_hsl(r, g, b)
+    }
+
+    // `alpha` in [0, 1]. saturation and lightness are clamped to [0, 1] and a
+    // hue that isn't finite counts as 0, like the powerless hue of a gray
+    pub fn from_hsl(hsl: HSL, alpha: f32) -> Canonical {
+        let (r, g, b) = hsl_to_rgb(hsl.h.degrees(), hsl.s, hsl.l);
+        Canonical::from_f(r, g, b, alpha)
+    }
+
+    // whiteness, blackness and alpha in [0, 1], clamped like from_hsl's
+    // components. whiteness and blackness adding up to 1 or more give a gray
+    pub fn from_hwb(hue: f32, whiteness: f32, blackness: f32, alpha: f32) -> Canonical {
+        let (whiteness, blackness) = (clamp_unit(whiteness), clamp_unit(blackness));
+        let sum = whiteness + blackness;
+        if sum >= 1.0 {
+            let gray = whiteness / sum;
+            return Canonical::from_f(gray, gray, gray, alpha);
+        }
+        let (r, g, b) = hsl_to_rgb(hue, 1.0, 0.5);
+        let tint = |c: f32| c * (1.0 - sum) + whiteness;
+        Canonical::from_f(tint(r), tint(g), tint(b), alpha)
+    }
+
+    // difference between the largest and smallest channel, in [0, 1]
+    pub fn chroma(&self) -> f32 {
+        let max = self.r.max(self.g).max(self.b);
+        let min = self.r.min(self.g).min(self.b);
+        (max - min) as f32 / 255.0
+    }
+}
+
+// channels in [0, 1]
+pub(crate) fn rgb_to_hsl(r: f32, g: f32, b: f32) -> HSL {
+    let max = r.max(g).max(b);
+    let min = r.min(g).min(b);
+    let chroma = max - min;
+    let l = (max + min) / 2.0;
+
+    if chroma == 0.0 {
+        return HSL::new(0.0, 0.0, l);
+    }
+
+    let s = chroma / (1.0 - f32::abs(2.0 * l - 1.0));
+    let h = if max == r {
+        60.0 * ((g - b) / chroma).rem_euclid(6.0)
+    } else if max == g {
+        60.0 * ((b - r) / chroma + 2.0)
+    } else {
+        60.0 * ((r - g) / chroma + 4.0)
+    };
+
+    HSL::new(h, s, l)
+}
+
+// the CSS Color 4 reference conversion, hue in degrees
+pub(crate) fn hsl_to_rgb(hue: f32, saturation: f32, lightness: f32) -> (f32, f32, f32) {
+    let hue = if hue.is_finite() {
+        hue.rem_euclid(360.0)
+    } else {
+        0.0
+    };
+    let (saturation, lightness) = (clamp_unit(saturation), clamp_unit(lightness));
+    let a = saturation * lightness.min(1.0 - lightness);
+    let f = |n: f32| {
+        let k = (n + hue / 30.0) % 12.0;
+        lightness - a * (k - 3.0).min(9.0 - k).clamp(-1.0, 1.0)
+    };
+    (f(0.0), f(8.0), f(4.0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_hsl_roughly_equal(a: HSL, b: HSL) {
+        let diff = a.h.distance_to(b.h) + f32::abs(a.s - b.s) + f32::abs(a.l - b.l);
+        assert!(diff < 0.01, "{:?} is not roughly {:?}", a, b)
+    }
+
+    #[test]
+    fn test_mix() {
+        let (a, b) = (HSL::new(350.0, 1.0, 0.5), HSL::new(10.0, 0.5, 0.3));
+        let mid = a.mix(&b, 0.5);
+        assert_eq!(mid.h.degrees(), 0.0);
+        assert_hsl_roughly_equal(mid, HSL::new(0.0, 0.75, 0.4));
+        assert_eq!(a.mix(&b, 2.0), b);
+        // gray keeps the hue of the other color
+        let gray = HSL::new(180.0, 0.0, 0.5);
+        assert_eq!(gray.mix(&HSL::new(30.0, 1.0, 0.5), 0.5).h.degrees(), 30.0);
+    }
+
+    #[test]
+    fn test_canonical_to_hsl() {
+        assert_hsl_roughly_equal(
+            Canonical::new(255, 0, 0, 255).to_hsl(),
+            HSL::new(0.0, 1.0, 0.5),
+        );
+        assert_hsl_roughly_equal(
+            Canonical::new(0, 0, 255, 255).to_hsl(),
+            HSL::new(240.0, 1.0, 0.5),
+        );
+        assert_hsl_roughly_equal(
+            Canonical::new(255, 0, 128, 255).to_hsl(),
+            HSL::new(329.882, 1.0, 0.5),
+        );
+        assert_hsl_roughly_equal(
+            Canonical::new(128, 128, 128, 255).to_hsl(),
+            HSL::new(0.0, 0.0, 0.502),
+        );
+    }
+
+    #[test]
+    fn test_from_hsl() {
+        for color in [
+            Canonical::new(255, 0, 0, 255),
+            Canonical::new(0, 0, 255, 255),
+            Canonical::new(255, 0, 128, 255),
+            Canonical::new(12, 200, 97, 255),
+            Canonical::new(128, 128, 128, 255),
+        ] {
+            assert_eq!(Canonical::from_hsl(color.to_hsl(), 1.0), color);
+        }
+        assert_eq!(
+            Canonical::from_hsl(HSL::new(-240.0, 1.0, 0.5), 0.5),
+            Canonical::new(0, 255, 0, 128)
+        );
+    }
+
+    #[test]
+    fn test_from_hwb() {
+        assert_eq!(
+            Canonical::from_hwb(0.0, 0.0, 0.0, 1.0),
+            Canonical::new(255, 0, 0, 255)
+        );
+        assert_eq!(
+            Canonical::from_hwb(120.0, 0.2, 0.2, 1.0),
+            Canonical::new(51, 204, 51, 255)
+        );
+        assert_eq!(
+            Canonical::from_hwb(200.0, 0.6, 0.6, 1.0),
+            Canonical::new(128, 128, 128, 255)
+        );
+    }
+
+    #[test]
+    fn test_canonical_chroma() {
+        assert_eq!(Canonical::new(10, 10, 10, 255).chroma(), 0.0);
+        assert_eq!(Canonical::new(255, 0, 10, 255).chroma(), 1.0);
+    }
+}
@@ -0,0 +1,157 @@
+// an angle on the color wheel in degrees, always within [0, 360), so 370°
+// and 10° are the same hue and compare equal. arithmetic wraps around, and
+// interpolating takes the short or the long way round explicitly, which is
+// the difference between a red to magenta mix going through pink or
+// through green
+use std::fmt::Display;
+use std::ops::{Add, Sub};
+
+#[derive(PartialEq, PartialOrd, Debug, Clone, Copy, Default)]
+pub struct Hue(f32);
+
+impl Hue {
+    // any angle, wrapped into [0, 360). an angle that isn't finite is 0, the
+    // powerless hue of a gray
+    pub fn new(degrees: f32) -> Hue {
+        if !degrees.is_finite() {
+            return Hue(0.0);
+        }
+        let wrapped = degrees.rem_euclid(360.0);
+        // tiny negative angles round up to 360
+        Hue(if wrapped >= 360.0 { 0.0 } else { wrapped })
+    }
+
+    pub fn degrees(self) -> f32 {
+        self.0
+    }
+
+    pub fn radians(self) -> f32 {
+        self.0.to_radians()
+    }
+
+    // the signed way from self to other, within (-180, 180]
+    fn delta(self, other: Hue) -> f32 {
+        let delta = (other.0 - self.0).rem_euclid(360.0);
+        if delta > 180.0 {
+            delta - 360.0
+        } else {
+            delta
+        }
+    }
+
+    // the shorter arc in degrees, within [0, 180]
+    pub fn distance_to(self, other: Hue) -> f32 {
+        self.delta(other).abs()
+    }
+
+    // `t` 0 gives self and 1 gives other, not clamped. opposite hues go
+    // clockwise
+    pub fn lerp_short(self, other: Hue, t: f32) -> Hue {
+        Hue::new(self.0 + self.delta(other) * t)
+    }
+
+    // the other way round. the same hue stays where it is
+    pub fn lerp_long(self, other: Hue, t: f32) -> Hue {
+        let delta = self.delta(other);
+        let long = match delta {
+            d if d > 0.0 => d - 360.0,
+            d if d < 0.0 => d + 360.0,
+            _ => 0.0,
+        };
+        Hue::new(self.0 + long * t)
+    }
+}
+
+impl From<f32> for Hue {
+    fn from(degrees: f32) -> Self {
+        Hue::new(degrees)
+    }
+}
+
+impl Add for Hue {
+    type Output = Hue;
+
+    fn add(self, other: Hue) -> Hue {
+        Hue::new(self.0 + other.0)
+    }
+}
+
+impl Add<f32> for Hue {
+    type Output = Hue;
+
+    fn add(self, degrees: f32) -> Hue {
+        Hue::new(self.0 + degrees)
+    }
+}
+
+impl Sub for Hue {
+    type Output = Hue;
+
+    fn sub(self, other: Hue) -> Hue {
+        Hue::new(self.0 - other.0)
+    }
+}
+
+impl Sub<f32> for Hue {
+    type Output = Hue;
+
+    fn sub(self, degrees: f32) -> Hue {
+        Hue::new(self.0 - degrees)
+    }
+}
+
+impl Display for Hue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}°", self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_new_wraps() {
+        assert_eq!(Hue::new(370.0), Hue::new(10.0));
+        assert_eq!(Hue::new(-90.0).degrees(), 270.0);
+        assert_eq!(Hue::new(360.0).degrees(), 0.0);
+        assert_eq!(Hue::new(-1e-6).degrees(), 0.0);
+        assert_eq!(Hue::new(f32::NAN), Hue::new(0.0));
+        assert_eq!(Hue::new(f32::INFINITY), Hue::new(0.0));
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        assert_eq!(Hue::new(350.0) + 20.0, Hue::new(10.0));
+        assert_eq!(Hue::new(10.0) - 20.0, Hue::new(350.0));
+        assert_eq!(Hue::new(200.0) + Hue::new(200.0), Hue::new(40.0));
+        assert_eq!(Hue::new(10.0) - Hue::new(350.0), Hue::new(20.0));
+        assert_eq!(Hue::new(350.0).distance_to(Hue::new(10.0)), 20.0);
+        assert_eq!(Hue::new(10.0).distance_to(Hue::new(350.0)), 20.0);
+        assert_eq!(Hue::new(0.0).distance_to(Hue::new(180.0)), 180.0);
+        assert_eq!(Hue::new(30.0).to_string(), "30°");
+    }
+
+    #[test]
+    fn test_lerp() {
+        let (from, to) = (Hue::new(350.0), Hue::new(10.0));
+        assert_eq!(from.lerp_short(to, 0.5), Hue::new(0.0));
+        assert_eq!(from.lerp_long(to, 0.5), Hue::new(180.0));
+        assert_eq!(to.lerp_short(from, 0.5), Hue::new(0.0));
+        assert_eq!(from.lerp_short(to, 0.0), from);
+        assert_eq!(from.lerp_short(to, 1.0), to);
+        assert_eq!(from.lerp_long(to, 1.0), to);
+        assert_eq!(
+            Hue::new(0.0).lerp_short(Hue::new(180.0), 0.5),
+            Hue::new(90.0)
+        );
+        assert_eq!(
+            Hue::new(0.0).lerp_long(Hue::new(180.0), 0.5),
+            Hue::new(270.0)
+        );
+        assert_eq!(
+            Hue::new(40.0).lerp_long(Hue::new(40.0), 0.5),
+            Hue::new(40.0)
+        );
+    }
+}
@@ -0,0 +1,193 @@
+// name -> position of the first entry with that name, so Scheme::get stays
+// O(1) on palettes with thousands of named entries. entries keep their
+// insertion order, the index only points into them
+use std::collections::HashMap;
+use std::fmt::Debug;
+
+use super::{Entry, Scheme};
+
+#[derive(Default, Clone)]
+pub(crate) struct NameIndex {
+    names: HashMap<String, usize>,
+    // how many entries the index covers. `entries` is pub, so a scheme edited
+    // directly is caught here for pushes and removals
+    len: usize,
+}
+
+impl NameIndex {
+    pub(crate) fn build(entries: &[Entry]) -> NameIndex {
+        let mut index = NameIndex::default();
+        for entry in entries {
+            index.push(entry.name.as_deref());
+        }
+        index
+    }
+
+    pub(crate) fn push(&mut self, name: Option<&str>) {
+        if let Some(name) = name {
+            self.names.entry(name.into()).or_insert(self.len);
+        }
+        self.len += 1;
+    }
+
+    // `index` is the first entry called `old`, already renamed in `entries`.
+    // a later entry may still be called `old` and takes over its key
+    pub(crate) fn rename(&mut self, entries: &[Entry], old: &str, new: &str, index: usize) {
+        let later = entries[index + 1..]
+            .iter()
+            .position(|e| e.name.as_deref() == Some(old));
+        match later {
+            Some(later) => self.names.insert(old.into(), index + 1 + later),
+            None => self.names.remove(old),
+        };
+        self.names.insert(new.into(), index);
+    }
+
+    // bytes on the heap, roughly: a key and a position per name
+    pub(crate) fn footprint(&self) -> usize {
+        self.names
+            .keys()
+            .map(|name| name.capacity() + std::mem::size_of::<(String, usize)>())
+            .sum()
+    }
+}
+
+// derived from the entries, which are compared anyway
+impl PartialEq for NameIndex {
+    fn eq(&self, _: &Self) -> bool {
+        true
+    }
+}
+
+impl Debug for NameIndex {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "NameIndex({} names)", self.names.len())
+    }
+}
+
+impl Scheme {
+    // position of the first entry called `name`. hits are checked against the
+    // entry, anything the index can't vouch for falls back to a scan. that
+    // includes misses, an entry renamed through `entries` isn't in the index,
+    // so looking up a name that isn't there costs a scan
+    pub(crate) fn position(&self, name: &str) -> Option<usize> {
+        let scan = || {
+            self.entries
+                .iter()
+                .position(|e| e.name.as_deref() == Some(name))
+        };
+        if self.index.len != self.entries.len() {
+            return scan();
+        }
+        match self.index.names.get(name) {
+            Some(&i) if self.entries[i].name.as_deref() == Some(name) => Some(i),
+            Some(_) | None => scan(),
+        }
+    }
+
+    // lookups stay correct after renaming or reordering `entries` directly
+    // but fall back to scans, this makes them O(1) again. the mutators in
+    // color::edit keep the index up to date on their own
+    pub fn reindex(&mut self) {
+        self.index = NameIndex::build(&self.entries);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::Canonical;
+
+    fn gray(v: u8) -> Canonical {
+        Canonical::new(v, v, v, 255)
+    }
+
+    fn scheme(n: usize) -> Scheme {
+        let mut scheme = Scheme::new("indexed");
+        for i in 0..n {
+            scheme
+                .push_named(&format!("c{}", i), gray(i as u8))
+                .unwrap();
+        }
+        scheme
+    }
+
+    #[test]
+    fn test_order_after_removal() {
+        let mut scheme = scheme(6);
+        scheme.remove_named("c1");
+        scheme.remove(2);
+        scheme.retain(|e| e.name.as_deref() != Some("c5"));
+        scheme.push_named("c1", gray(100)).unwrap();
+        let names: Vec<_> = scheme
+            .entries
+            .iter()
+            .map(|e| e.name.as_deref().unwrap())
+            .collect();
+        assert_eq!(names, ["c0", "c2", "c4", "c1"]);
+        for (i, name) in names.iter().enumerate() {
+            assert_eq!(scheme.position(name), Some(i));
+        }
+        assert_eq!(scheme.get("c1"), Some(&gray(100)));
+        assert_eq!(scheme.get("c3"), None);
+    }
+
+    #[test]
+    fn test_get_after_rename() {
+        let mut scheme = scheme(3);
+        scheme.rename("c1", "accent").unwrap();
+        assert_eq!(scheme.get("accent"), Some(&gray(1)));
+        assert_eq!(scheme.get("c1"), None);
+        scheme.push_named("c1", gray(7)).unwrap();
+        assert_eq!(scheme.get("c1"), Some(&gray(7)));
+    }
+
+    #[test]
+    fn test_direct_edits() {
+        let mut scheme = scheme(3);
+        scheme.entries.push(Entry::named("late", gray(9)));
+        assert_eq!(scheme.get("late"), Some(&gray(9)));
+        scheme.entries.swap(0, 2);
+        assert_eq!(scheme.get("c0"), Some(&gray(0)));
+
+        scheme.entries.truncate(3);
+        scheme.entries[0].name = Some("renamed".into());
+        scheme.reindex();
+        assert_eq!(scheme.get("renamed"), Some(&gray(2)));
+        assert_eq!(scheme.get("c2"), None);
+        assert_eq!(scheme.get("c0"), Some(&gray(0)));
+    }
+
+    #[test]
+    fn test_first_name_wins() {
+        let mut scheme = Scheme::new("dupes");
+        scheme.entries = vec![Entry::named("bg", gray(0)), Entry::named("bg", gray(1))];
+        scheme.reindex();
+        assert_eq!(scheme.get("bg"), Some(&gray(0)));
+        scheme.remove(0);
+        assert_eq!(scheme.get("bg"), Some(&gray(1)));
+    }
+
+    #[test]
+    fn test_rename_first_of_two() {
+        let mut scheme = Scheme::new("dupes");
+        scheme.entries = vec![Entry::named("a", gray(0)), Entry::named("a", gray(1))];
+        scheme.reindex();
+        assert!(scheme.rename("a", "b").unwrap());
+        assert_eq!(scheme.get("b"), Some(&gray(0)));
+        assert_eq!(scheme.get("a"), Some(&gray(1)));
+        assert_eq!(scheme.index.names.get("a"), Some(&1));
+        assert!(scheme.rename("a", "c").unwrap());
+        assert_eq!(scheme.get("a"), None);
+        assert_eq!(scheme.get("c"), Some(&gray(1)));
+    }
+
+    #[test]
+    fn test_direct_rename_without_reindex() {
+        let mut scheme = scheme(3);
+        scheme.entries[0].name = Some("bg".into());
+        assert_eq!(scheme.get("bg"), Some(&gray(0)));
+        assert_eq!(scheme.get("c0"), None);
+        assert_eq!(scheme.position("c2"), Some(2));
+    }
+}
@@ -0,0 +1,200 @@
+use super::float::Float;
+use super::gamut::map_linear;
+use super::xyz::{adapt, adapt_f64, WhitePoint, XYZ, XYZ_TO_SRGB};
+use super::Canonical;
+
+// CIE 1976 L*a*b*
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub struct Lab {
+    pub l: f32,
+    pub a: f32,
+    pub b: f32,
+}
+
+// Lab in f64 for comparing many near identical colors, where the error of
+// f32 adds up. the f32 conversion is good to about 1e-4 in L, a and b, this
+// one to the precision of the sRGB and white point constants
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub struct LabF64 {
+    pub l: f64,
+    pub a: f64,
+    pub b: f64,
+}
+
+const EPSILON: f32 = 216.0 / 24389.0;
+const KAPPA: f32 = 24389.0 / 27.0;
+
+impl Lab {
+    pub fn new(l: f32, a: f32, b: f32) -> Lab {
+        Lab { l, a, b }
+    }
+
+    pub fn from_xyz(xyz: XYZ, white: WhitePoint) -> Lab {
+        let w = white.xyz();
+        let [l, a, b] = lab_from_xyz([xyz.x, xyz.y, xyz.z], [w.x, w.y, w.z]);
+        Lab::new(l, a, b)
+    }
+
+    pub fn to_xyz(&self, white: WhitePoint) -> XYZ {
+        let w = white.xyz();
+        let fy = (self.l + 16.0) / 116.0;
+        let fx = self.a / 500.0 + fy;
+        let fz = fy - self.b / 200.0;
+        let f_inv = |f: f32| {
+            if f.powi(3) > EPSILON {
+                f.powi(3)
+            } else {
+                (116.0 * f - 16.0) / KAPPA
+            }
+        };
+        let y = if self.l > KAPPA * EPSILON {
+            fy.powi(3)
+        } else {
+            self.l / KAPPA
+        };
+        XYZ::new(f_inv(fx) * w.x, y * w.y, f_inv(fz) * w.z)
+    }
+}
+
+fn lab_from_xyz<T: Float>(xyz: [T; 3], white: [T; 3]) -> [T; 3] {
+    let (epsilon, kappa) = (T::of(216.0 / 24389.0), T::of(24389.0 / 27.0));
+    let f = |t: T| {
+        if t > epsilon {
+            t.cbrt()
+        } else {
+            (kappa * t + T::of(16.0)) / T::of(116.0)
+        }
+    };
+    let fx = f(xyz[0] / white[0]);
+    let fy = f(xyz[1] / white[1]);
+    let fz = f(xyz[2] / white[2]);
+    [
+        T::of(116.0) * fy - T::of(16.0),
+        T::of(500.0) * (fx - fy),
+        T::of(200.0) * (fy - fz),
+    ]
+}
+
+impl From<LabF64> for Lab {
+    fn from(lab: LabF64) -> Lab {
+        Lab::new(lab.l as f32, lab.a as f32, lab.b as f32)
+    }
+}
+
+impl From<Lab> for LabF64 {
+    fn from(lab: Lab) -> LabF64 {
+        LabF64 {
+            l: lab.l as f64,
+            a: lab.a as f64,
+            b: lab.b as f64,
+        }
+    }
+}
+
+impl Canonical {
+    // relative to D65, the white point of sRGB
+    pub fn to_lab(&self) -> Lab {
+        self.to_lab_with(WhitePoint::D65)
+    }
+
+    pub fn to_lab_with(&self, white: WhitePoint) -> Lab {
+        let xyz = adapt(self.to_xyz(), WhitePoint::D65, white);
+        Lab::from_xyz(xyz, white)
+    }
+
+    pub fn to_lab_f64(&self) -> LabF64 {
+        self.to_lab_f64_with(WhitePoint::D65)
+    }
+
+    pub fn to_lab_f64_with(&self, white: WhitePoint) -> LabF64 {
+        let xyz = adapt_f64(self.to_xyz_f64(), WhitePoint::D65, white);
+        let [l, a, b] = lab_from_xyz(xyz, white.xyz_f64());
+        LabF64 { l, a, b }
+    }
+
+    pub fn from_lab(lab: Lab, alpha: u8) -> Canonical {
+        Self::from_lab_with(lab, alpha, WhitePoint::D65)
+    }
+
+    // out of gamut colors lose chroma until they fit, keeping their hue
+    pub fn from_lab_with(lab: Lab, alpha: u8, white: WhitePoint) -> Canonical {
+        let xyz = adapt(lab.to_xyz(white), white, WhitePoint::D65);
+        Canonical::from_linear(map_linear(XYZ_TO_SRGB.apply(xyz.as_rgb())), alpha)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_lab_roughly_equal(a: Lab, b: Lab) {
+        let diff = f32::abs(a.l - b.l) + f32::abs(a.a - b.a) + f32::abs(a.b - b.b);
+        assert!(diff < 0.1, "{:?} is not roughly {:?}", a, b)
+    }
+
+    #[test]
+    fn test_to_lab() {
+        let white = Canonical::new(255, 255, 255, 255);
+        let red = Canonical::new(255, 0, 0, 255);
+
+        assert_lab_roughly_equal(white.to_lab(), Lab::new(100.0, 0.0, 0.0));
+        assert_lab_roughly_equal(
+            white.to_lab_with(WhitePoint::D50),
+            Lab::new(100.0, 0.0, 0.0),
+        );
+        assert_lab_roughly_equal(red.to_lab(), Lab::new(53.24, 80.09, 67.20));
+        assert_lab_roughly_equal(
+            red.to_lab_with(WhitePoint::D50),
+            Lab::new(54.29, 80.80, 69.89),
+        );
+    }
+
+    #[test]
+    fn test_from_lab() {
+        assert_eq!(
+            Canonical::from_lab_with(Lab::new(54.29, 80.80, 69.89), 255, WhitePoint::D50),
+            Canonical::new(255, 0, 0, 255)
+        );
+        assert_eq!(
+            Canonical::from_lab(Lab::new(53.24, 80.09, 67.20), 255),
+            Canonical::new(255, 0, 0, 255)
+        );
+    }
+
+    #[test]
+    fn test_lab_roundtrip() {
+        let colors = [
+            Canonical::new(0, 0, 0, 255),
+            Canonical::new(12, 200, 99, 255),
+            Canonical::new(250, 250, 3, 255),
+            Canonical::new(1, 2, 3, 255),
+        ];
+        for c in colors {
+            assert_eq!(Canonical::from_lab(c.to_lab(), 255), c);
+            assert_eq!(
+                Canonical::from_lab_with(c.to_lab_with(WhitePoint::D50), 255, WhitePoint::D50),
+                c
+            );
+        }
+    }
+
+    #[test]
+    fn test_lab_f64() {
+        for c in [
+            Canonical::new(255, 255, 255, 255),
+            Canonical::new(255, 0, 0, 255),
+            Canonical::new(12, 200, 99, 255),
+            Canonical::new(1, 2, 3, 255),
+        ] {
+            for white in [WhitePoint::D65, WhitePoint::D50] {
+                let (lab, precise) = (c.to_lab_with(white), c.to_lab_f64_with(white));
+                assert_lab_roughly_equal(lab, precise.into());
+                assert!((lab.l as f64 - precise.l).abs() < 1e-3, "{:?}", precise);
+                assert!((lab.a as f64 - precise.a).abs() < 1e-3, "{:?}", precise);
+                assert!((lab.b as f64 - precise.b).abs() < 1e-3, "{:?}", precise);
+            }
+        }
+        let lab = Lab::new(53.24, 80.09, 67.2);
+        assert_eq!(Lab::from(LabF64::from(lab)), lab);
+    }
+}
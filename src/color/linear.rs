@@ -0,0 +1,84 @@
+use super::{Canonical, Component, RGB, RGBA};
+
+// sRGB transfer function, see IEC 61966-2-1
+pub fn decode_srgb(encoded: f32) -> f32 {
+    if encoded <= 0.04045 {
+        encoded / 12.92
+    } else {
+        ((encoded + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+pub(crate) fn decode_srgb_f64(encoded: f64) -> f64 {
+    if encoded <= 0.04045 {
+        encoded / 12.92
+    } else {
+        ((encoded + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+pub fn encode_srgb(linear: f32) -> f32 {
+    if linear <= 0.003_130_8 {
+        linear * 12.92
+    } else {
+        1.055 * linear.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+// where colors are averaged and mixed. averaging gamma encoded values
+// darkens, red and green give #808000 instead of #bcbc00
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Default)]
+pub enum AverageSpace {
+    #[default]
+    Linear,
+    // the stored values as they are, how it was done before
+    Gamma,
+}
+
+impl<T: Component> RGBA<T> {
+    pub fn to_linear(&self) -> RGB<f32> {
+        RGB::from(*self).map(|c| decode_srgb(c.normalized()))
+    }
+
+    // relative luminance as defined by WCAG 2, in [0, 1]
+    pub fn luminance(&self) -> f32 {
+        let linear = self.to_linear();
+        0.2126 * linear.r + 0.7152 * linear.g + 0.0722 * linear.b
+    }
+}
+
+impl Canonical {
+    // out of range components are clamped
+    pub fn from_linear(rgb: RGB<f32>, alpha: u8) -> Canonical {
+        RGB::new_clamped(rgb.r, rgb.g, rgb.b)
+            .map(|c| u8::from_normalized(encode_srgb(c)))
+            .into_rgba(alpha)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_srgb_transfer_roundtrip() {
+        for i in 0..=255u8 {
+            let c = Canonical::new(i, i, i, 255);
+            assert_eq!(Canonical::from_linear(c.to_linear(), 255), c);
+        }
+    }
+
+    #[test]
+    fn test_canonical_luminance() {
+        assert_eq!(Canonical::new(0, 0, 0, 255).luminance(), 0.0);
+        assert!(f32::abs(Canonical::new(255, 255, 255, 255).luminance() - 1.0) < 0.0001);
+        assert!(f32::abs(Canonical::new(0, 0, 255, 255).luminance() - 0.0722) < 0.0001);
+    }
+
+    #[test]
+    fn test_decode_srgb() {
+        assert_eq!(decode_srgb(0.0), 0.0);
+        assert_eq!(decode_srgb(1.0), 1.0);
+        assert!(f32::abs(decode_srgb(0.5) - 0.214) < 0.001);
+    }
+}
@@ -0,0 +1,245 @@
+use std::ops::Mul;
+
+use super::component::clamp_unit;
+use super::{Canonical, Scheme, RGB};
+
+// Rec. 709 luminance weights, matching Canonical::luminance
+const LUMA: [f32; 3] = [0.2126, 0.7152, 0.0722];
+
+const MAX_SATURATION: f32 = 255.0;
+
+// row major 3x3 matrix operating on rgb column vectors
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub struct ColorMatrix(pub [[f32; 3]; 3]);
+
+impl ColorMatrix {
+    pub const IDENTITY: ColorMatrix =
+        ColorMatrix([[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]]);
+
+    pub fn apply(&self, c: RGB<f32>) -> RGB<f32> {
+        let m = &self.0;
+        RGB::new(
+            m[0][0] * c.r + m[0][1] * c.g + m[0][2] * c.b,
+            m[1][0] * c.r + m[1][1] * c.g + m[1][2] * c.b,
+            m[2][0] * c.r + m[2][1] * c.g + m[2][2] * c.b,
+        )
+    }
+
+    // a matrix applying `self` first and `next` afterwards
+    pub fn then(&self, next: &ColorMatrix) -> ColorMatrix {
+        *next * *self
+    }
+
+    pub fn determinant(&self) -> f32 {
+        let m = &self.0;
+        m[0][0] * (m[1][1] * m[2][2] - m[1][2] * m[2][1])
+            - m[0][1] * (m[1][0] * m[2][2] - m[1][2] * m[2][0])
+            + m[0][2] * (m[1][0] * m[2][1] - m[1][1] * m[2][0])
+    }
+
+    // None for singular matrices, e.g. grayscale
+    pub fn inverse(&self) -> Option<ColorMatrix> {
+        let det = self.determinant();
+        if det.abs() < f32::EPSILON {
+            return None;
+        }
+        let m = &self.0;
+        let cofactor = |r0: usize, r1: usize, c0: usize, c1: usize| {
+            m[r0][c0] * m[r1][c1] - m[r0][c1] * m[r1][c0]
+        };
+        // transposed cofactors (adjugate) divided by the determinant
+        Some(ColorMatrix([
+            [
+                cofactor(1, 2, 1, 2) / det,
+                -cofactor(0, 2, 1, 2) / det,
+                cofactor(0, 1, 1, 2) / det,
+            ],
+            [
+                -cofactor(1, 2, 0, 2) / det,
+                cofactor(0, 2, 0, 2) / det,
+                -cofactor(0, 1, 0, 2) / det,
+            ],
+            [
+                cofactor(1, 2, 0, 1) / det,
+                -cofactor(0, 2, 0, 1) / det,
+                cofactor(0, 1, 0, 1) / det,
+            ],
+        ]))
+    }
+
+    // the classic sepia tone matrix
+    pub fn sepia() -> ColorMatrix {
+        ColorMatrix([
+            [0.393, 0.769, 0.189],
+            [0.349, 0.686, 0.168],
+            [0.272, 0.534, 0.131],
+        ])
+    }
+
+    pub fn grayscale() -> ColorMatrix {
+        ColorMatrix([LUMA, LUMA, LUMA])
+    }
+
+    // 0 is grayscale, 1 the identity and values above 1 oversaturate. amounts
+    // are clamped to ±255, which already pushes any u8 color to the gamut edge,
+    // and NaN gives the identity
+    pub fn saturation(amount: f32) -> ColorMatrix {
+        let amount = if amount.is_nan() {
+            1.0
+        } else {
+            amount.clamp(-MAX_SATURATION, MAX_SATURATION)
+        };
+        let inv = 1.0 - amount;
+        let [r, g, b] = LUMA;
+        ColorMatrix([
+            [r * inv + amount, g * inv, b * inv],
+            [r * inv, g * inv + amount, b * inv],
+            [r * inv, g * inv, b * inv + amount],
+        ])
+    }
+}
+
+impl Default for ColorMatrix {
+    fn default() -> Self {
+        ColorMatrix::IDENTITY
+    }
+}
+
+impl Mul for ColorMatrix {
+    type Output = ColorMatrix;
+
+    fn mul(self, rhs: ColorMatrix) -> ColorMatrix {
+        let mut out = [[0.0; 3]; 3];
+        for (i, row) in out.iter_mut().enumerate() {
+            for (j, cell) in row.iter_mut().enumerate() {
+                *cell = (0..3).map(|k| self.0[i][k] * rhs.0[k][j]).sum();
+            }
+        }
+        ColorMatrix(out)
+    }
+}
+
+impl Canonical {
+    // applies the matrix to the gamma encoded components or, with `linear`,
+    // to linear light. results are clamped, alpha is kept
+    pub fn transformed(&self, matrix: &ColorMatrix, linear: bool) -> Canonical {
+        if linear {
+            Canonical::from_linear(matrix.apply(self.to_linear()), self.a)
+        } else {
+            let encoded = RGB::from(*self).map(|c| c as f32 / 255.0);
+            let RGB { r, g, b } = matrix.apply(encoded);
+            RGB::new_clamped(r, g, b)
+                .map(|c| (c * 255.0).round() as u8)
+                .into_rgba(self.a)
+        }
+    }
+
+    // `intensity` 0 leaves the color as it is and 1 is full sepia, in between
+    // the matrices are interpolated like CSS's sepia(). clamped to [0, 1],
+    // NaN counts as 0
+    pub fn sepia(&self, intensity: f32) -> Canonical {
+        let t = clamp_unit(intensity);
+        if t == 0.0 {
+            return *self;
+        }
+        let (identity, sepia) = (ColorMatrix::IDENTITY.0, ColorMatrix::sepia().0);
+        let mut m = [[0.0; 3]; 3];
+        for (i, row) in m.iter_mut().enumerate() {
+            for (j, cell) in row.iter_mut().enumerate() {
+                *cell = identity[i][j] + (sepia[i][j] - identity[i][j]) * t;
+            }
+        }
+        self.transformed(&ColorMatrix(m), false)
+    }
+}
+
+impl Scheme {
+    pub fn transformed(&self, matrix: &ColorMatrix, linear: bool) -> Scheme {
+        self.map_colors(|c| c.transformed(matrix, linear))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_matrix_roughly_equal(a: &ColorMatrix, b: &ColorMatrix) {
+        for i in 0..3 {
+            for j in 0..3 {
+                let diff = f32::abs(a.0[i][j] - b.0[i][j]);
+                assert!(diff < 0.0001, "{:?} is not roughly {:?}", a, b);
+            }
+        }
+    }
+
+    fn samples() -> Vec<Canonical> {
+        vec![
+            Canonical::new(0, 0, 0, 255),
+            Canonical::new(255, 0, 0, 128),
+            Canonical::new(12, 200, 99, 255),
+            Canonical::new(255, 255, 255, 0),
+        ]
+    }
+
+    #[test]
+    fn test_identity_is_noop() {
+        for c in samples() {
+            assert_eq!(c.transformed(&ColorMatrix::IDENTITY, false), c);
+            assert_eq!(c.transformed(&ColorMatrix::IDENTITY, true), c);
+        }
+    }
+
+    #[test]
+    fn test_grayscale() {
+        for c in samples() {
+            for linear in [false, true] {
+                let gray = c.transformed(&ColorMatrix::grayscale(), linear);
+                assert!(gray.r == gray.g && gray.g == gray.b, "{:?}", gray);
+                assert_eq!(gray.a, c.a);
+            }
+        }
+        assert_eq!(ColorMatrix::saturation(0.0), ColorMatrix::grayscale());
+    }
+
+    #[test]
+    fn test_sepia() {
+        for c in samples() {
+            assert_eq!(c.sepia(0.0), c);
+            assert_eq!(c.sepia(f32::NAN), c);
+            assert_eq!(c.sepia(1.0), c.transformed(&ColorMatrix::sepia(), false));
+            assert_eq!(c.sepia(7.0), c.sepia(1.0));
+        }
+        let c = Canonical::new(255, 0, 0, 128);
+        assert_eq!(c.sepia(1.0), Canonical::new(100, 89, 69, 128));
+        assert_eq!(c.sepia(0.5), Canonical::new(178, 44, 35, 128));
+    }
+
+    #[test]
+    fn test_composition_and_inverse() {
+        let sepia = ColorMatrix::sepia();
+        let saturate = ColorMatrix::saturation(1.5);
+        let combined = sepia.then(&saturate);
+
+        let c = RGB::new(0.2, 0.5, 0.7);
+        let stepwise = saturate.apply(sepia.apply(c));
+        let at_once = combined.apply(c);
+        assert!(f32::abs(stepwise.r - at_once.r) < 0.0001);
+        assert!(f32::abs(stepwise.g - at_once.g) < 0.0001);
+        assert!(f32::abs(stepwise.b - at_once.b) < 0.0001);
+
+        let inverse = saturate.inverse().unwrap();
+        assert_matrix_roughly_equal(&(saturate * inverse), &ColorMatrix::IDENTITY);
+        assert_matrix_roughly_equal(&(inverse * saturate), &ColorMatrix::IDENTITY);
+        assert_eq!(ColorMatrix::grayscale().inverse(), None);
+    }
+
+    #[test]
+    fn test_scheme_transformed() {
+        let scheme = Scheme::from_colors("film", samples());
+        let sepia = scheme.transformed(&ColorMatrix::sepia(), false);
+
+        assert_eq!(sepia.name, "film");
+        assert_eq!(sepia.entries[0].color, Canonical::new(0, 0, 0, 255));
+        assert_eq!(sepia.entries[1].color, Canonical::new(100, 89, 69, 128));
+    }
+}
@@ -0,0 +1,91 @@
+use super::gamut::map_linear;
+use super::hue::Hue;
+use super::{Canonical, RGB};
+
+// Oklab in polar form, Björn Ottosson's perceptual space on top of linear
+// sRGB. l is in [0, 1], c is 0 for grays and rarely above 0.4 for colors
+// sRGB can show
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub struct Oklch {
+    pub l: f32,
+    pub c: f32,
+    pub h: Hue,
+}
+
+impl Oklch {
+    // h in degrees or a Hue
+    pub fn new(l: f32, c: f32, h: impl Into<Hue>) -> Oklch {
+        Oklch { l, c, h: h.into() }
+    }
+
+    pub(crate) fn from_linear(rgb: RGB<f32>) -> Oklch {
+        let l = 0.41222147 * rgb.r + 0.53633254 * rgb.g + 0.051445993 * rgb.b;
+        let m = 0.2119035 * rgb.r + 0.6806995 * rgb.g + 0.10739696 * rgb.b;
+        let s = 0.08830246 * rgb.r + 0.28171884 * rgb.g + 0.6299787 * rgb.b;
+        let (l, m, s) = (l.cbrt(), m.cbrt(), s.cbrt());
+
+        let a = 1.9779985 * l - 2.4285922 * m + 0.4505937 * s;
+        let b = 0.025904037 * l + 0.78277177 * m - 0.80867577 * s;
+        Oklch {
+            l: 0.21045426 * l + 0.7936178 * m - 0.004072047 * s,
+            c: a.hypot(b),
+            h: Hue::new(b.atan2(a).to_degrees()),
+        }
+    }
+
+    // not clamped, components outside [0, 1] mean sRGB can't show the color
+    pub(crate) fn to_linear(self) -> RGB<f32> {
+        let (sin, cos) = self.h.radians().sin_cos();
+        let (a, b) = (self.c * cos, self.c * sin);
+        let l = (self.l + 0.39633778 * a + 0.21580376 * b).powi(3);
+        let m = (self.l - 0.105561346 * a - 0.06385417 * b).powi(3);
+        let s = (self.l - 0.08948418 * a - 1.2914855 * b).powi(3);
+        RGB::new(
+            4.0767417 * l - 3.3077116 * m + 0.23096993 * s,
+            -1.268438 * l + 2.6097574 * m - 0.3413194 * s,
+            -0.0042119996 * l - 0.7034186 * m + 1.7076147 * s,
+        )
+    }
+}
+
+impl Canonical {
+    pub fn to_oklch(&self) -> Oklch {
+        Oklch::from_linear(self.to_linear())
+    }
+
+    // out of gamut colors lose chroma until they fit, keeping their hue
+    pub fn from_oklch(oklch: Oklch, alpha: u8) -> Canonical {
+        Canonical::from_linear(map_linear(oklch.to_linear()), alpha)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_oklch() {
+        let white = Canonical::new(255, 255, 255, 255).to_oklch();
+        assert!(
+            (white.l - 1.0).abs() < 0.001 && white.c < 0.001,
+            "{:?}",
+            white
+        );
+
+        // the reference values of the css color 4 spec
+        let red = Canonical::new(255, 0, 0, 255).to_oklch();
+        assert!((red.l - 0.628).abs() < 0.001, "{:?}", red);
+        assert!((red.c - 0.2577).abs() < 0.001, "{:?}", red);
+        assert!((red.h.degrees() - 29.23).abs() < 0.1, "{:?}", red);
+    }
+
+    #[test]
+    fn test_oklch_roundtrip() {
+        for hex in [
+            0x000000, 0xffffff, 0xff0000, 0x0c6f63, 0xfafa03, 0x010203, 0x7f7f7f,
+        ] {
+            let c = RGB::unpack_rgb(hex).into_rgba(200);
+            assert_eq!(Canonical::from_oklch(c.to_oklch(), 200), c);
+        }
+    }
+}
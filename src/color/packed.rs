@@ -0,0 +1,250 @@
+// a Canonical packed into a u32 as 0xRRGGBBAA. a newtype rather than a bare
+// u32 so lengths and indices can't be unpacked as colors by accident
+use std::fmt::Display;
+use std::str::FromStr;
+
+use super::{Canonical, Scheme, RGB};
+use crate::errors::{ColorError, ParseFormatError};
+
+/// The two packings, most significant byte first:
+///
+/// ```text
+///            31     24 23     16 15      8 7       0
+///           +---------+---------+---------+---------+
+/// Packed    |   red   |  green  |  blue   |  alpha  |
+///           +---------+---------+---------+---------+
+/// pack_rgb  | 0000000 |   red   |  green  |  blue   |
+///           +---------+---------+---------+---------+
+/// ```
+///
+/// `Canonical::pack`/`unpack` give the first and `RGB::pack_rgb`/`unpack_rgb`
+/// the second, the same as a `#rrggbbaa` and a `#rrggbb` literal.
+#[derive(PartialEq, Eq, Hash, PartialOrd, Ord, Debug, Clone, Copy, Default)]
+pub struct Packed(pub u32);
+
+// the old `Packed = u32` alias, for code that still passes raw u32s around
+#[deprecated(note = "use the Packed newtype, its .0 is the raw u32")]
+pub type PackedU32 = u32;
+
+impl Packed {
+    pub fn from_canonical(color: &Canonical) -> Self {
+        color.pack()
+    }
+
+    pub fn to_canonical(self) -> Canonical {
+        Canonical::unpack(self)
+    }
+
+    // red first, alpha last
+    pub fn to_be_bytes(self) -> [u8; 4] {
+        self.0.to_be_bytes()
+    }
+
+    pub fn from_be_bytes(bytes: [u8; 4]) -> Self {
+        Packed(u32::from_be_bytes(bytes))
+    }
+}
+
+impl From<Canonical> for Packed {
+    fn from(color: Canonical) -> Self {
+        color.pack()
+    }
+}
+
+impl From<Packed> for Canonical {
+    fn from(packed: Packed) -> Self {
+        Canonical::unpack(packed)
+    }
+}
+
+impl RGB<u8> {
+    // 0x00RRGGBB, see Packed for the layout
+    pub fn pack_rgb(&self) -> u32 {
+        (self.r as u32) << 16 | (self.g as u32) << 8 | self.b as u32
+    }
+
+    // the top 8 bits are ignored
+    pub fn unpack_rgb(packed: u32) -> RGB<u8> {
+        RGB::new((packed >> 16) as u8, (packed >> 8) as u8, packed as u8)
+    }
+}
+
+// rrggbbaa, always 8 lowercase digits
+impl Display for Packed {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:08x}", self.0)
+    }
+}
+
+// exactly 8 hex digits, what Display writes
+impl FromStr for Packed {
+    type Err = ColorError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.len() != 8 {
+            return Err(ColorError::ParseHexError { input: s.into() });
+        }
+        Canonical::parse_from_hex(s).map(|c| c.pack())
+    }
+}
+
+impl Scheme {
+    pub fn to_packed_vec(&self) -> Vec<Packed> {
+        self.colors().map(Canonical::pack).collect()
+    }
+}
+
+// a whole palette as `0xRRGGBBAA,0xRRGGBBAA,..`, lowercase and without
+// spaces, to paste into an array literal. no names, nothing but the colors
+pub fn dump_hex(colors: &[Canonical]) -> String {
+    let mut dump = String::with_capacity(colors.len() * 11);
+    for (i, color) in colors.iter().enumerate() {
+        if i > 0 {
+            dump.push(',');
+        }
+        dump.push_str("0x");
+        dump.push_str(&color.pack().to_string());
+    }
+    dump
+}
+
+// what dump_hex writes. whitespace and newlines around entries and a
+// trailing comma are fine, the digits may be uppercase
+pub fn parse_hex_dump(s: &str) -> Result<Vec<Canonical>, ParseFormatError> {
+    let s = s.trim();
+    let s = s.strip_suffix(',').unwrap_or(s);
+    if s.trim().is_empty() {
+        return Ok(Vec::new());
+    }
+    s.split(',')
+        .enumerate()
+        .map(|(index, entry)| {
+            let entry = entry.trim();
+            entry
+                .strip_prefix("0x")
+                .or_else(|| entry.strip_prefix("0X"))
+                .filter(|digits| digits.len() == 8 && digits.bytes().all(|b| b.is_ascii_hexdigit()))
+                .and_then(|digits| u32::from_str_radix(digits, 16).ok())
+                .map(|packed| Canonical::unpack(Packed(packed)))
+                .ok_or_else(|| ParseFormatError::InvalidDumpEntry {
+                    index,
+                    input: entry.into(),
+                })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::errors::ErrorKind;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    #[test]
+    fn test_conversions() {
+        let color = Canonical::new(0x12, 0x34, 0x56, 0x78);
+        let packed = Packed::from_canonical(&color);
+        assert_eq!(packed, Packed(0x1234_5678));
+        assert_eq!(packed.to_canonical(), color);
+        assert_eq!(Packed::from(color), packed);
+        assert_eq!(Canonical::from(packed), color);
+
+        assert_eq!(packed.to_be_bytes(), [0x12, 0x34, 0x56, 0x78]);
+        assert_eq!(Packed::from_be_bytes([0x12, 0x34, 0x56, 0x78]), packed);
+    }
+
+    #[test]
+    fn test_pack_roundtrip() {
+        let mut rng = StdRng::seed_from_u64(0x00c0_ffee);
+        for _ in 0..1_000_000 {
+            let x: u32 = rng.gen();
+            assert_eq!(Canonical::unpack(Packed(x)).pack(), Packed(x));
+
+            let c = Canonical::new(rng.gen(), rng.gen(), rng.gen(), rng.gen());
+            assert_eq!(Canonical::unpack(c.pack()), c);
+
+            let rgb = RGB::from(c);
+            assert_eq!(RGB::unpack_rgb(rgb.pack_rgb()), rgb);
+            assert_eq!(RGB::unpack_rgb(x).pack_rgb(), x & 0x00ff_ffff);
+        }
+        for x in [0, 1, 0xff, 0xffff_ff00, u32::MAX] {
+            assert_eq!(Canonical::unpack(Packed(x)).pack(), Packed(x));
+        }
+    }
+
+    #[test]
+    fn test_pack_rgb() {
+        let rgb = RGB::new(0x12, 0x34, 0x56);
+        assert_eq!(rgb.pack_rgb(), 0x12_3456);
+        assert_eq!(RGB::unpack_rgb(0xff12_3456), rgb);
+        // the same bits as the alpha-less top of Packed
+        assert_eq!(rgb.into_rgba(0xff).pack().0 >> 8, rgb.pack_rgb());
+    }
+
+    #[test]
+    fn test_display_from_str() {
+        assert_eq!(Packed(0xFE80_19FF).to_string(), "fe8019ff");
+        assert_eq!(Packed(0xFF).to_string(), "000000ff");
+        assert_eq!("fe8019ff".parse::<Packed>().unwrap(), Packed(0xFE80_19FF));
+        assert_eq!("000000FF".parse::<Packed>().unwrap(), Packed(0xFF));
+
+        for bad in ["fe8019", "#fe8019f", "fe8019ff0", "zz8019ff", ""] {
+            let e = bad.parse::<Packed>().unwrap_err();
+            assert!(
+                matches!(e.kind(), ErrorKind::ParseHex | ErrorKind::ParseInt),
+                "{bad}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_hex_dump() {
+        let mut rng = StdRng::seed_from_u64(0x0dab_0dab);
+        let colors: Vec<_> = (0..1000)
+            .map(|_| Canonical::new(rng.gen(), rng.gen(), rng.gen(), rng.gen()))
+            .collect();
+        let dump = dump_hex(&colors);
+        assert_eq!(parse_hex_dump(&dump).unwrap(), colors);
+
+        let scheme = Scheme::from_colors("random", colors.clone());
+        let packed: Vec<_> = colors.iter().map(Canonical::pack).collect();
+        assert_eq!(scheme.to_packed_vec(), packed);
+
+        let two = [
+            Canonical::new(0xfe, 0x80, 0x19, 0xff),
+            Canonical::new(0, 0, 0, 0),
+        ];
+        assert_eq!(dump_hex(&two), "0xfe8019ff,0x00000000");
+        assert_eq!(dump_hex(&[]), "");
+        assert_eq!(
+            parse_hex_dump("  0xFE8019FF,\n\t0x00000000 ,\n").unwrap(),
+            two
+        );
+        assert_eq!(parse_hex_dump(" \n").unwrap(), []);
+    }
+
+    #[test]
+    fn test_hex_dump_errors() {
+        let e = parse_hex_dump("0xfe8019ff, 0x00000000, 0xfe8019, 0x00000000").unwrap_err();
+        assert_eq!(e.kind(), ErrorKind::InvalidDumpEntry);
+        assert_eq!(
+            e.to_string(),
+            "Entry 2 of the hex dump, 0xfe8019, is not 0xRRGGBBAA"
+        );
+        for (bad, index) in [
+            ("0xfe8019ff,,0x00000000", 1),
+            ("fe8019ff", 0),
+            ("0x+e8019ff", 0),
+            ("0xfe8019ff,0xfe8019zz", 1),
+            ("0xfe8019ff,,", 1),
+        ] {
+            match parse_hex_dump(bad) {
+                Err(ParseFormatError::InvalidDumpEntry { index: i, .. }) => {
+                    assert_eq!(i, index, "{bad}")
+                }
+                other => panic!("{bad}: {other:?}"),
+            }
+        }
+    }
+}
@@ -0,0 +1,148 @@
+// schemes as pages of a bigger one, like a 256 color table built from
+// 16 color pages. concatenated schemes are named by the caller and take the
+// author of the first page that has one, every chunk has the author of the
+// scheme it was cut from
+use super::{Entry, Scheme};
+
+// how concat names entries of one page that another page has names for too
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Default)]
+pub enum PagePrefix {
+    // `page.name` for names more than one page uses, others stay as they are
+    #[default]
+    OnCollision,
+    // `page.name` for every named entry
+    Always,
+    // names as they are, lookups by a repeated name find the first page's
+    Never,
+}
+
+impl Scheme {
+    pub fn concat(schemes: &[&Scheme], name: &str) -> Scheme {
+        Scheme::concat_with(schemes, name, PagePrefix::default())
+    }
+
+    // `page` is the name of the scheme the entry comes from. pages of the
+    // same name can still collide. roles and comments stay with their entries
+    pub fn concat_with(schemes: &[&Scheme], name: &str, prefix: PagePrefix) -> Scheme {
+        let used_elsewhere = |page: usize, entry_name: &str| {
+            schemes
+                .iter()
+                .enumerate()
+                .any(|(other, scheme)| other != page && scheme.get(entry_name).is_some())
+        };
+        let mut entries = Vec::new();
+        for (page, scheme) in schemes.iter().enumerate() {
+            for entry in &scheme.entries {
+                let prefixed = match (&entry.name, prefix) {
+                    (Some(entry_name), PagePrefix::Always) => Some(entry_name),
+                    (Some(entry_name), PagePrefix::OnCollision)
+                        if used_elsewhere(page, entry_name) =>
+                    {
+                        Some(entry_name)
+                    }
+                    _ => None,
+                };
+                entries.push(match prefixed {
+                    Some(entry_name) => Entry {
+                        name: Some(format!("{}.{}", scheme.name, entry_name)),
+                        ..entry.clone()
+                    },
+                    None => entry.clone(),
+                });
+            }
+        }
+        let author = schemes.iter().find_map(|scheme| scheme.author.clone());
+        Scheme::from_entries(name.into(), author, entries)
+    }
+
+    // pages of `size` entries, the last one may have fewer. each is named
+    // `name [page/pages]`. no pages for an empty scheme, panics if size is 0
+    // as slice::chunks does
+    pub fn chunks(&self, size: usize) -> Vec<Scheme> {
+        let pages = self.entries.chunks(size);
+        let count = pages.len();
+        pages
+            .enumerate()
+            .map(|(i, entries)| {
+                Scheme::from_entries(
+                    format!("{} [{}/{}]", self.name, i + 1, count),
+                    self.author.clone(),
+                    entries.to_vec(),
+                )
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::{Canonical, Role};
+
+    fn page(name: &str, author: Option<&str>, names: &[&str], base: u8) -> Scheme {
+        let entries = names
+            .iter()
+            .enumerate()
+            .map(|(i, n)| Entry::named(n, Canonical::new(base + i as u8, 0, 0, 255)))
+            .collect();
+        Scheme::from_entries(name.into(), author.map(String::from), entries)
+    }
+
+    fn names(scheme: &Scheme) -> Vec<&str> {
+        scheme
+            .entries
+            .iter()
+            .map(|e| e.name.as_deref().unwrap_or("-"))
+            .collect()
+    }
+
+    #[test]
+    fn test_concat_prefixes() {
+        let low = page("low", None, &["bg", "color1"], 0);
+        let high = page("high", Some("me"), &["bg", "fg"], 10);
+        let mut third = page("third", Some("someone"), &["x"], 20);
+        third.push(Canonical::new(1, 1, 1, 255));
+
+        let table = Scheme::concat(&[&low, &high, &third], "table");
+        assert_eq!(table.name, "table");
+        assert_eq!(table.author.as_deref(), Some("me"));
+        assert_eq!(
+            names(&table),
+            ["low.bg", "color1", "high.bg", "fg", "x", "-"]
+        );
+        assert_eq!(table.get("high.bg"), Some(&Canonical::new(10, 0, 0, 255)));
+        // the role stays with the prefixed entry
+        assert_eq!(table.entries[0].role, Some(Role::Background));
+
+        let always = Scheme::concat_with(&[&low, &high], "t", PagePrefix::Always);
+        assert_eq!(
+            names(&always),
+            ["low.bg", "low.color1", "high.bg", "high.fg"]
+        );
+        let never = Scheme::concat_with(&[&low, &high], "t", PagePrefix::Never);
+        assert_eq!(names(&never), ["bg", "color1", "bg", "fg"]);
+        assert_eq!(never.get("bg"), Some(&Canonical::new(0, 0, 0, 255)));
+        assert!(Scheme::concat(&[], "none").is_empty());
+    }
+
+    #[test]
+    fn test_chunks_round_trip() {
+        let mut entries: Vec<Entry> = (0..=255)
+            .map(|i| Entry::new(Canonical::new(i, i, i, 255)))
+            .collect();
+        entries[0].name = Some("black".into());
+        let big = Scheme::from_entries("grays".into(), Some("me".into()), entries);
+
+        let pages = big.chunks(100);
+        assert_eq!(pages.len(), 3);
+        assert_eq!(pages[0].name, "grays [1/3]");
+        assert_eq!(pages[2].name, "grays [3/3]");
+        assert_eq!(pages[2].len(), 56);
+        assert!(pages.iter().all(|p| p.author.as_deref() == Some("me")));
+
+        let refs: Vec<&Scheme> = pages.iter().collect();
+        let back = Scheme::concat(&refs, "grays");
+        assert_eq!(back, big);
+        assert!(Scheme::new("empty").chunks(16).is_empty());
+    }
+}
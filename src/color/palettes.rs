@@ -0,0 +1,250 @@
+use std::convert::TryFrom;
+
+use super::role::Role;
+use super::{Canonical, Scheme};
+use crate::errors::NotEnoughColors;
+
+// the 16 system colors as set by xterm's defaults
+const XTERM_SYSTEM: [u32; 16] = [
+    0x000000, 0xcd0000, 0x00cd00, 0xcdcd00, 0x0000ee, 0xcd00cd, 0x00cdcd, 0xe5e5e5, 0x7f7f7f,
+    0xff0000, 0x00ff00, 0xffff00, 0x5c5cff, 0xff00ff, 0x00ffff, 0xffffff,
+];
+
+// channel levels of the 6x6x6 color cube in slots 16 to 231
+pub const XTERM_CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+pub const WEB_SAFE_STEP: u8 = 51;
+
+// slots 0-15 are the system colors, 16-231 the color cube with index
+// 16 + 36 * r + 6 * g + b, and 232-255 a gray ramp from 8 to 238 in steps of 10
+pub const XTERM_256: [Canonical; 256] = xterm_256();
+
+// all combinations of the channel values 0, 51, .., 255, red changing slowest
+pub const WEB_SAFE: [Canonical; 216] = web_safe();
+
+const fn gray(v: u8) -> Canonical {
+    Canonical::new(v, v, v, u8::MAX)
+}
+
+const fn xterm_256() -> [Canonical; 256] {
+    let mut table = [gray(0); 256];
+
+    let mut i = 0;
+    while i < 16 {
+        let hex = XTERM_SYSTEM[i];
+        table[i] = Canonical::new((hex >> 16) as u8, (hex >> 8) as u8, hex as u8, u8::MAX);
+        i += 1;
+    }
+
+    let mut i = 0;
+    while i < 216 {
+        table[16 + i] = Canonical::new(
+            XTERM_CUBE_LEVELS[i / 36],
+            XTERM_CUBE_LEVELS[(i / 6) % 6],
+            XTERM_CUBE_LEVELS[i % 6],
+            u8::MAX,
+        );
+        i += 1;
+    }
+
+    let mut i = 0;
+    while i < 24 {
+        table[232 + i] = gray(8 + 10 * i as u8);
+        i += 1;
+    }
+
+    table
+}
+
+const fn web_safe() -> [Canonical; 216] {
+    let mut table = [gray(0); 216];
+    let mut i = 0;
+    while i < 216 {
+        table[i] = Canonical::new(
+            (i / 36) as u8 * WEB_SAFE_STEP,
+            ((i / 6) % 6) as u8 * WEB_SAFE_STEP,
+            (i % 6) as u8 * WEB_SAFE_STEP,
+            u8::MAX,
+        );
+        i += 1;
+    }
+    table
+}
+
+impl Canonical {
+    // rounds every channel to the nearest web safe level, alpha is kept
+    pub fn snap_web_safe(&self) -> Canonical {
+        let step = WEB_SAFE_STEP as u16;
+        let snap = |c: u8| ((c as u16 + step / 2) / step * step) as u8;
+        Canonical::new(snap(self.r), snap(self.g), snap(self.b), self.a)
+    }
+}
+
+impl Scheme {
+    // the colors of the entries with the roles AnsiColor(0) to (15) if the
+    // scheme has all of them, else its first 16 colors
+    fn ansi_colors(&self) -> Vec<Canonical> {
+        let by_role: Option<Vec<_>> = (0..16)
+            .map(|i| self.by_role(Role::AnsiColor(i)).copied())
+            .collect();
+        by_role.unwrap_or_else(|| self.colors().take(16).copied().collect())
+    }
+
+    // XTERM_256 with the scheme's colors over it: the entries with an
+    // AnsiColor role in their slots, or the first 16 colors in slots 0 to 15
+    // when no entry has one
+    pub fn to_ansi_table(&self) -> [Canonical; 256] {
+        let mut table = XTERM_256;
+        let mut roles = false;
+        for entry in self.entries.iter().rev() {
+            if let Some(Role::AnsiColor(i)) = entry.role {
+                // reversed so the first entry with a role wins, like by_role
+                table[i as usize] = entry.color;
+                roles = true;
+            }
+        }
+        if !roles {
+            for (slot, color) in table[..16].iter_mut().zip(self.colors()) {
+                *slot = *color;
+            }
+        }
+        table
+    }
+}
+
+// see Scheme::ansi_colors, fewer than 16 colors are an error
+impl TryFrom<&Scheme> for [Canonical; 16] {
+    type Error = NotEnoughColors;
+
+    fn try_from(scheme: &Scheme) -> Result<Self, Self::Error> {
+        let colors = scheme.ansi_colors();
+        colors.try_into().map_err(|colors: Vec<_>| NotEnoughColors {
+            needed: 16,
+            found: colors.len(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_xterm_system_colors() {
+        assert_eq!(XTERM_256[0], gray(0));
+        assert_eq!(XTERM_256[1], Canonical::new(0xcd, 0, 0, 255));
+        assert_eq!(XTERM_256[12], Canonical::new(0x5c, 0x5c, 0xff, 255));
+        assert_eq!(XTERM_256[15], gray(255));
+    }
+
+    #[test]
+    fn test_xterm_cube_index_math() {
+        for r in 0..6 {
+            for g in 0..6 {
+                for b in 0..6 {
+                    let c = XTERM_256[16 + 36 * r + 6 * g + b];
+                    assert_eq!(
+                        c,
+                        Canonical::new(
+                            XTERM_CUBE_LEVELS[r],
+                            XTERM_CUBE_LEVELS[g],
+                            XTERM_CUBE_LEVELS[b],
+                            255
+                        )
+                    );
+                }
+            }
+        }
+        assert_eq!(XTERM_256[196], Canonical::new(255, 0, 0, 255));
+    }
+
+    #[test]
+    fn test_xterm_gray_ramp() {
+        let ramp = &XTERM_256[232..];
+        assert_eq!(ramp.len(), 24);
+        assert_eq!(ramp[0], gray(8));
+        assert_eq!(ramp[23], gray(238));
+        for pair in ramp.windows(2) {
+            assert_eq!(pair[1].r - pair[0].r, 10);
+            assert!(pair[1].r == pair[1].g && pair[1].g == pair[1].b);
+        }
+    }
+
+    #[test]
+    fn test_ansi_table() {
+        let colors: Vec<_> = (0..20).map(|i| Canonical::new(i, 1, 2, 255)).collect();
+        let scheme = Scheme::from_colors("positional", colors.clone());
+        let table = scheme.to_ansi_table();
+        assert_eq!(table[..16], colors[..16]);
+        assert_eq!(table[16..], XTERM_256[16..]);
+        assert_eq!(table[196], Canonical::new(255, 0, 0, 255));
+        let ansi: [Canonical; 16] = (&scheme).try_into().unwrap();
+        assert_eq!(ansi, table[..16]);
+
+        // by role, wherever the entries are
+        let mut named = Scheme::new("named");
+        named.push_named("background", gray(1)).unwrap();
+        named.push_named("color196", gray(196)).unwrap();
+        for (i, name) in [
+            "black", "red", "green", "yellow", "blue", "magenta", "cyan", "white",
+        ]
+        .iter()
+        .enumerate()
+        {
+            named.push_named(name, gray(i as u8)).unwrap();
+            named
+                .push_named(&format!("bright_{}", name), gray(i as u8 + 8))
+                .unwrap();
+        }
+        let table = named.to_ansi_table();
+        for (i, color) in table[..16].iter().enumerate() {
+            assert_eq!(*color, gray(i as u8));
+        }
+        assert_eq!(table[196], gray(196));
+        assert_eq!(table[197], XTERM_256[197]);
+        let ansi: [Canonical; 16] = (&named).try_into().unwrap();
+        assert_eq!(ansi, table[..16]);
+    }
+
+    #[test]
+    fn test_ansi_too_short() {
+        let scheme = Scheme::from_colors("short", vec![gray(1), gray(2)]);
+        let error = <[Canonical; 16]>::try_from(&scheme).unwrap_err();
+        assert_eq!(error.kind(), crate::errors::ErrorKind::NotEnoughColors);
+        assert_eq!((error.needed, error.found), (16, 2));
+        let table = scheme.to_ansi_table();
+        assert_eq!(table[..2], [gray(1), gray(2)]);
+        assert_eq!(table[2..], XTERM_256[2..]);
+
+        // some ansi roles but not all, the first 16 colors it is
+        let mut partial = Scheme::new("partial");
+        partial.push_named("red", gray(1)).unwrap();
+        assert!(<[Canonical; 16]>::try_from(&partial).is_err());
+        assert_eq!(partial.to_ansi_table()[1], gray(1));
+        assert_eq!(partial.to_ansi_table()[0], XTERM_256[0]);
+    }
+
+    #[test]
+    fn test_web_safe() {
+        for (i, c) in WEB_SAFE.iter().enumerate() {
+            assert_eq!(c.r % WEB_SAFE_STEP, 0);
+            assert_eq!(c.g % WEB_SAFE_STEP, 0);
+            assert_eq!(c.b % WEB_SAFE_STEP, 0);
+            let index = (c.r / 51) as usize * 36 + (c.g / 51) as usize * 6 + (c.b / 51) as usize;
+            assert_eq!(index, i);
+            assert_eq!(c.snap_web_safe(), *c);
+        }
+    }
+
+    #[test]
+    fn test_snap_web_safe() {
+        assert_eq!(
+            Canonical::new(25, 26, 254, 9).snap_web_safe(),
+            Canonical::new(0, 51, 255, 9)
+        );
+        assert_eq!(
+            Canonical::new(127, 128, 230, 255).snap_web_safe(),
+            Canonical::new(102, 153, 255, 255)
+        );
+    }
+}
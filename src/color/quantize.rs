@@ -0,0 +1,173 @@
+use super::linear::AverageSpace;
+use super::{stats, Canonical, Scheme};
+
+struct ColorBox {
+    colors: Vec<Canonical>,
+}
+
+impl ColorBox {
+    fn channel(c: &Canonical, axis: usize) -> u8 {
+        match axis {
+            0 => c.r,
+            1 => c.g,
+            _ => c.b,
+        }
+    }
+
+    // (axis, range) of the longest side of the box. ties prefer r over g over b
+    fn longest_axis(&self) -> (usize, u8) {
+        let mut longest = (0, 0);
+        for axis in 0..3 {
+            let values = self.colors.iter().map(|c| Self::channel(c, axis));
+            let min = values.clone().min().unwrap_or(0);
+            let max = values.max().unwrap_or(0);
+            if max - min > longest.1 {
+                longest = (axis, max - min);
+            }
+        }
+        longest
+    }
+
+    fn split(mut self, axis: usize) -> (ColorBox, ColorBox) {
+        // sort by the full color as secondary key, so that the result does not
+        // depend on the input order of colors with equal split channel
+        self.colors
+            .sort_by_key(|c| (Self::channel(c, axis), c.r, c.g, c.b, c.a));
+        let upper = self.colors.split_off(self.colors.len() / 2);
+        (self, ColorBox { colors: upper })
+    }
+
+    fn average(&self, space: AverageSpace) -> Canonical {
+        // boxes are never empty
+        stats::mean(&self.colors, space).unwrap()
+    }
+}
+
+// reduces `colors` to at most `n` representative colors. boxes are split along
+// their longest rgb axis at the median, and each resulting box is represented
+// by the average of its colors, in linear light. alpha does not influence the
+// splitting, but is averaged like the other channels. fewer than `n` colors
+// are returned when the input does not contain enough distinct colors
+pub fn median_cut(colors: &[Canonical], n: usize) -> Vec<Canonical> {
+    median_cut_with(colors, n, AverageSpace::Linear)
+}
+
+pub fn median_cut_with(colors: &[Canonical], n: usize, space: AverageSpace) -> Vec<Canonical> {
+    if colors.is_empty() || n == 0 {
+        return Vec::new();
+    }
+
+    let mut boxes = vec![ColorBox {
+        colors: colors.to_vec(),
+    }];
+
+    while boxes.len() < n {
+        let candidate = boxes
+            .iter()
+            .enumerate()
+            .map(|(i, b)| (i, b.longest_axis()))
+            .filter(|(_, (_, range))| *range > 0)
+            // max_by_key returns the last maximum, reverse to prefer the first box
+            .rev()
+            .max_by_key(|(_, (_, range))| *range);
+
+        let (index, (axis, _)) = match candidate {
+            Some(candidate) => candidate,
+            None => break,
+        };
+
+        let (lower, upper) = boxes.remove(index).split(axis);
+        boxes.insert(index, upper);
+        boxes.insert(index, lower);
+    }
+
+    boxes.iter().map(|b| b.average(space)).collect()
+}
+
+impl Scheme {
+    // the quantized colors do not correspond to entries, so names are dropped
+    pub fn quantized(&self, n: usize) -> Scheme {
+        self.quantized_with(n, AverageSpace::Linear)
+    }
+
+    pub fn quantized_with(&self, n: usize, space: AverageSpace) -> Scheme {
+        let colors: Vec<_> = self.colors().copied().collect();
+        Scheme::from_colors(&self.name, median_cut_with(&colors, n, space))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn synthetic() -> Vec<Canonical> {
+        vec![
+            Canonical::new(250, 10, 10, 255),
+            Canonical::new(10, 10, 240, 255),
+            Canonical::new(240, 20, 0, 255),
+            Canonical::new(0, 30, 250, 255),
+            Canonical::new(10, 240, 10, 255),
+            Canonical::new(20, 250, 30, 127),
+            Canonical::new(120, 120, 120, 255),
+            Canonical::new(130, 130, 130, 255),
+        ]
+    }
+
+    #[test]
+    fn test_median_cut() {
+        let colors = synthetic();
+
+        let gamma = AverageSpace::Gamma;
+        assert_eq!(
+            median_cut_with(&colors, 1, gamma),
+            vec![Canonical::new(98, 101, 99, 239)]
+        );
+        // the same boxes, averaged in linear light
+        assert_eq!(
+            median_cut(&colors, 1),
+            vec![Canonical::new(145, 145, 145, 239)]
+        );
+        assert_eq!(
+            median_cut_with(&colors, 4, gamma),
+            vec![
+                Canonical::new(5, 20, 245, 255),
+                Canonical::new(15, 245, 20, 191),
+                Canonical::new(125, 125, 125, 255),
+                Canonical::new(245, 15, 5, 255),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_median_cut_is_deterministic() {
+        let colors = synthetic();
+        let mut reversed = colors.clone();
+        reversed.reverse();
+
+        assert_eq!(median_cut(&colors, 4), median_cut(&colors, 4));
+        assert_eq!(median_cut(&colors, 4), median_cut(&reversed, 4));
+    }
+
+    #[test]
+    fn test_median_cut_edge_cases() {
+        let colors = synthetic();
+        let same = vec![Canonical::new(1, 2, 3, 4); 5];
+
+        assert!(median_cut(&[], 4).is_empty());
+        assert!(median_cut(&colors, 0).is_empty());
+        assert_eq!(median_cut(&same, 3), vec![Canonical::new(1, 2, 3, 4)]);
+        assert_eq!(median_cut(&colors, 100).len(), colors.len());
+    }
+
+    #[test]
+    fn test_scheme_quantized() {
+        let scheme = Scheme::from_colors("synthetic", synthetic());
+        let quantized = scheme.quantized(3);
+
+        assert_eq!(quantized.name, "synthetic");
+        assert_eq!(
+            quantized.colors().copied().collect::<Vec<_>>(),
+            median_cut(&synthetic(), 3)
+        );
+    }
+}
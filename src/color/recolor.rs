@@ -0,0 +1,191 @@
+// a scheme's lightness structure with the hues of another: every entry keeps
+// its own L* and takes a* and b* from its counterpart in the hue source.
+// counterparts are found by role, then name, then position
+use super::lab::Lab;
+use super::xyz::{WhitePoint, XYZ_TO_SRGB};
+use super::{Canonical, Entry, Scheme, RGB};
+use crate::errors::NoCounterpart;
+
+// what happens to entries the hue source has no counterpart for
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Default)]
+pub enum LengthPolicy {
+    // drop them, so the result is as long as the shorter scheme
+    #[default]
+    Truncate,
+    Error,
+}
+
+// slack for the rounding of the matrices
+const GAMUT_EPSILON: f32 = 1e-4;
+
+fn in_gamut(lab: Lab) -> bool {
+    let xyz = lab.to_xyz(WhitePoint::D65);
+    let rgb = XYZ_TO_SRGB.apply(RGB::new(xyz.x, xyz.y, xyz.z));
+    [rgb.r, rgb.g, rgb.b]
+        .iter()
+        .all(|c| (-GAMUT_EPSILON..=1.0 + GAMUT_EPSILON).contains(c))
+}
+
+// lightness of `light`, hue and chroma of `hue`. chroma is reduced until the
+// color fits into sRGB, clamping would change the lightness instead
+pub(crate) fn transfer(light: &Canonical, hue: &Canonical) -> Canonical {
+    let l = light.to_lab().l;
+    let Lab { a, b, .. } = hue.to_lab();
+    let at = |scale: f32| Lab::new(l, a * scale, b * scale);
+    let scale = if in_gamut(at(1.0)) {
+        1.0
+    } else {
+        let (mut low, mut high) = (0.0, 1.0);
+        for _ in 0..16 {
+            let mid = (low + high) / 2.0;
+            if in_gamut(at(mid)) {
+                low = mid;
+            } else {
+                high = mid;
+            }
+        }
+        low
+    };
+    Canonical::from_lab(at(scale), light.a)
+}
+
+impl Scheme {
+    fn counterpart(&self, index: usize, entry: &Entry) -> Option<&Canonical> {
+        entry
+            .role
+            .and_then(|role| self.by_role(role))
+            .or_else(|| entry.name.as_deref().and_then(|name| self.get(name)))
+            .or_else(|| self.entries.get(index).map(|e| &e.color))
+    }
+
+    // names, roles and alpha stay those of self. entries without a
+    // counterpart are dropped
+    pub fn recolor_like(&self, hue_source: &Scheme) -> Scheme {
+        self.recolor_like_with(hue_source, LengthPolicy::Truncate)
+            .unwrap()
+    }
+
+    pub fn recolor_like_with(
+        &self,
+        hue_source: &Scheme,
+        policy: LengthPolicy,
+    ) -> Result<Scheme, NoCounterpart> {
+        let mut entries = Vec::with_capacity(self.len());
+        for (index, entry) in self.entries.iter().enumerate() {
+            match hue_source.counterpart(index, entry) {
+                Some(hue) => entries.push(Entry {
+                    color: transfer(&entry.color, hue),
+                    ..entry.clone()
+                }),
+                None if policy == LengthPolicy::Truncate => {}
+                None => {
+                    return Err(NoCounterpart {
+                        index,
+                        name: entry.name.clone(),
+                    })
+                }
+            }
+        }
+        Ok(Scheme::from_entries(
+            self.name.clone(),
+            self.author.clone(),
+            entries,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::errors::ErrorKind;
+
+    fn rgb(hex: u32) -> Canonical {
+        Canonical::new((hex >> 16) as u8, (hex >> 8) as u8, hex as u8, 255)
+    }
+
+    fn hue(c: &Canonical) -> f32 {
+        let lab = c.to_lab();
+        lab.b.atan2(lab.a).to_degrees()
+    }
+
+    fn gruvbox() -> Scheme {
+        let mut scheme = Scheme::new("gruvbox");
+        scheme.push_named("background", rgb(0x282828)).unwrap();
+        scheme.push_named("foreground", rgb(0xebdbb2)).unwrap();
+        scheme.push_named("red", rgb(0xcc241d)).unwrap();
+        scheme.push_named("blue", rgb(0x458588)).unwrap();
+        scheme.push(rgb(0xfe8019));
+        scheme
+    }
+
+    fn nord() -> Scheme {
+        let mut scheme = Scheme::new("nord");
+        scheme.push_named("blue", rgb(0x81a1c1)).unwrap();
+        scheme.push_named("red", rgb(0xbf616a)).unwrap();
+        scheme.push_named("fg", rgb(0xd8dee9)).unwrap();
+        scheme.push_named("bg", rgb(0x2e3440)).unwrap();
+        scheme
+    }
+
+    #[test]
+    fn test_recolor_like() {
+        let gruvbox = gruvbox();
+        let nord = nord();
+        let hybrid = gruvbox.recolor_like(&nord);
+        // the unnamed orange is at index 4, nord has nothing there
+        assert_eq!(hybrid.len(), 4);
+        assert_eq!(hybrid.name, "gruvbox");
+
+        for (mine, new) in gruvbox.entries.iter().zip(&hybrid.entries) {
+            assert_eq!(new.name, mine.name);
+            let (l, new_l) = (mine.color.to_lab().l, new.color.to_lab().l);
+            assert!(
+                (l - new_l).abs() < 1.0,
+                "{:?}: {} -> {}",
+                new.name,
+                l,
+                new_l
+            );
+        }
+        // by role, bg and fg are Background and Foreground too
+        for (name, source) in [("background", "bg"), ("foreground", "fg"), ("red", "red")] {
+            let (new, source) = (hybrid.get(name).unwrap(), nord.get(source).unwrap());
+            assert!((hue(new) - hue(source)).abs() < 3.0, "{}", name);
+        }
+        // nord's blue is light, at gruvbox's darker blue it still fits sRGB
+        let blue = hybrid.get("blue").unwrap();
+        assert!((hue(blue) - hue(nord.get("blue").unwrap())).abs() < 3.0);
+    }
+
+    #[test]
+    fn test_out_of_gamut() {
+        // a saturated blue at the lightness of a pale yellow can't be shown,
+        // lightness wins over chroma
+        let pale = rgb(0xfbf1c7);
+        let blue = rgb(0x0000ff);
+        let c = transfer(&pale, &blue);
+        assert!((c.to_lab().l - pale.to_lab().l).abs() < 1.0);
+        assert!(c.b > c.r && c.b > c.g, "{:?}", c);
+        assert!(c.to_lab().a.hypot(c.to_lab().b) < blue.to_lab().a.hypot(blue.to_lab().b));
+    }
+
+    #[test]
+    fn test_length_policy() {
+        let e = gruvbox()
+            .recolor_like_with(&nord(), LengthPolicy::Error)
+            .unwrap_err();
+        assert_eq!(e.kind(), ErrorKind::NoCounterpart);
+        assert_eq!(e.index, 4);
+        assert_eq!(
+            e.to_string(),
+            "Entry 4 has no counterpart in the hue source"
+        );
+
+        let mut short = gruvbox();
+        short.remove(4);
+        let hybrid = short
+            .recolor_like_with(&nord(), LengthPolicy::Error)
+            .unwrap();
+        assert_eq!(hybrid.len(), 4);
+    }
+}
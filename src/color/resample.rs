@@ -0,0 +1,162 @@
+// changing the number of colors of a palette. resampling treats the colors
+// as the stops of a gradient, evenly spaced from first to last, and samples
+// it, decimating keeps the entries that are most different from each other
+use super::linear::AverageSpace;
+use super::{DistanceMetric, Metric, Scheme};
+use crate::errors::ResampleError;
+
+impl Scheme {
+    // the samples don't correspond to entries, so names are dropped. 1 gives
+    // the first stop, a single stop is repeated
+    pub fn resampled(&self, n: usize) -> Result<Scheme, ResampleError> {
+        self.resampled_with(n, AverageSpace::Linear)
+    }
+
+    pub fn resampled_with(&self, n: usize, space: AverageSpace) -> Result<Scheme, ResampleError> {
+        let stops: Vec<_> = self.colors().collect();
+        if n == 0 {
+            return Err(ResampleError::NoSamples);
+        } else if stops.is_empty() {
+            return Err(ResampleError::NoStops);
+        }
+        let last = (stops.len() - 1) as f32;
+        let colors = (0..n)
+            .map(|i| {
+                let position = if n == 1 {
+                    0.0
+                } else {
+                    i as f32 / (n - 1) as f32 * last
+                };
+                let stop = (position.floor() as usize).min(stops.len() - 1);
+                match stops.get(stop + 1) {
+                    Some(next) => stops[stop].mix_with(next, position - stop as f32, space),
+                    None => *stops[stop],
+                }
+            })
+            .collect();
+        Ok(Scheme::from_colors(&self.name, colors))
+    }
+
+    // the n entries furthest apart, by RgbEuclidean, in their order. picked
+    // greedily from the first entry on, each the one furthest from all
+    // picked so far. schemes with n entries or fewer are returned as they are
+    pub fn decimated(&self, n: usize) -> Result<Scheme, ResampleError> {
+        self.decimated_with(n, &Metric::RgbEuclidean)
+    }
+
+    pub fn decimated_with(
+        &self,
+        n: usize,
+        metric: &impl DistanceMetric,
+    ) -> Result<Scheme, ResampleError> {
+        if n == 0 {
+            return Err(ResampleError::NoSamples);
+        } else if self.len() <= n {
+            return Ok(self.clone());
+        }
+        let mut picked = vec![0];
+        // distance of every entry to the nearest picked one
+        let mut nearest: Vec<f32> = self
+            .entries
+            .iter()
+            .map(|e| metric.distance(&e.color, &self.entries[0].color))
+            .collect();
+        while picked.len() < n {
+            let furthest = (0..self.len())
+                .filter(|i| !picked.contains(i))
+                .max_by(|&a, &b| nearest[a].total_cmp(&nearest[b]).then(b.cmp(&a)))
+                .unwrap();
+            picked.push(furthest);
+            for (entry, distance) in self.entries.iter().zip(&mut nearest) {
+                let to_new = metric.distance(&entry.color, &self.entries[furthest].color);
+                *distance = distance.min(to_new);
+            }
+        }
+        picked.sort_unstable();
+        Ok(Scheme::from_entries(
+            self.name.clone(),
+            self.author.clone(),
+            picked.iter().map(|&i| self.entries[i].clone()).collect(),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::Canonical;
+    use crate::errors::ErrorKind;
+
+    fn gray(v: u8) -> Canonical {
+        Canonical::new(v, v, v, 255)
+    }
+
+    #[test]
+    fn test_resampled() {
+        let scheme = Scheme::from_colors("bw", vec![gray(0), gray(255)]);
+        let gamma = scheme.resampled_with(5, AverageSpace::Gamma).unwrap();
+        assert_eq!(
+            gamma.colors().copied().collect::<Vec<_>>(),
+            [gray(0), gray(64), gray(128), gray(191), gray(255)]
+        );
+        // the midpoint in linear light is lighter
+        let linear = scheme.resampled(5).unwrap();
+        assert_eq!(linear.name, "bw");
+        assert_eq!(linear.colors().nth(2), Some(&gray(188)));
+        assert_eq!(linear.colors().nth(4), Some(&gray(255)));
+
+        // stops land on samples
+        let three = Scheme::from_colors("three", vec![gray(0), gray(200), gray(100)]);
+        let resampled = three.resampled_with(5, AverageSpace::Gamma).unwrap();
+        assert_eq!(
+            resampled.colors().copied().collect::<Vec<_>>(),
+            [gray(0), gray(100), gray(200), gray(150), gray(100)]
+        );
+    }
+
+    #[test]
+    fn test_resampled_edge_cases() {
+        let scheme = Scheme::from_colors("bw", vec![gray(10), gray(255)]);
+        assert_eq!(
+            scheme.resampled(0).unwrap_err().kind(),
+            ErrorKind::NoSamples
+        );
+        assert_eq!(
+            Scheme::new("empty").resampled(3).unwrap_err().kind(),
+            ErrorKind::NoStops
+        );
+        assert_eq!(
+            scheme.resampled(1).unwrap().colors().collect::<Vec<_>>(),
+            [&gray(10)]
+        );
+        let single = Scheme::from_colors("single", vec![gray(42)]);
+        assert_eq!(
+            single.resampled(3).unwrap().colors().collect::<Vec<_>>(),
+            [&gray(42); 3]
+        );
+    }
+
+    #[test]
+    fn test_decimated() {
+        let mut scheme = Scheme::new("grays");
+        for v in [0, 10, 128, 250, 120, 255] {
+            scheme.push_named(&v.to_string(), gray(v)).unwrap();
+        }
+        let decimated = scheme.decimated(3).unwrap();
+        assert_eq!(
+            decimated
+                .entries
+                .iter()
+                .map(|e| e.name.as_deref().unwrap())
+                .collect::<Vec<_>>(),
+            ["0", "128", "255"]
+        );
+        let lab = scheme.decimated_with(2, &Metric::DeltaE2000).unwrap();
+        assert_eq!(lab.colors().collect::<Vec<_>>(), [&gray(0), &gray(255)]);
+        assert_eq!(scheme.decimated(10).unwrap(), scheme);
+        assert_eq!(
+            scheme.decimated(0).unwrap_err().kind(),
+            ErrorKind::NoSamples
+        );
+    }
+}
@@ -0,0 +1,150 @@
+// what an entry is for, beyond its free-form name. inferred from the names
+// terminal formats use, e.g. Windows Terminal's brightRed or kitty's color9
+// and cursor. names that mean nothing known get no role
+use super::{Canonical, Scheme};
+
+#[derive(PartialEq, Eq, Hash, Debug, Clone, Copy)]
+pub enum Role {
+    Background,
+    Foreground,
+    Cursor,
+    Accent,
+    // 0-15 are the ansi colors, brights from 8. up to 255 for 256-color tables
+    AnsiColor(u8),
+}
+
+const ANSI_NAMES: [&str; 8] = [
+    "black", "red", "green", "yellow", "blue", "purple", "cyan", "white",
+];
+
+impl Role {
+    // case and `_`, `-` or space separators don't matter, so brightRed,
+    // bright_red and bright-red are all AnsiColor(9)
+    pub fn from_name(name: &str) -> Option<Role> {
+        let name: String = name
+            .chars()
+            .filter(|c| !matches!(c, '_' | '-' | ' '))
+            .map(|c| c.to_ascii_lowercase())
+            .collect();
+        let ansi = |name: &str| {
+            let name = if name == "magenta" { "purple" } else { name };
+            ANSI_NAMES.iter().position(|&n| n == name).map(|i| i as u8)
+        };
+        match name.as_str() {
+            "background" | "bg" => Some(Role::Background),
+            "foreground" | "fg" => Some(Role::Foreground),
+            "cursor" | "cursorcolor" => Some(Role::Cursor),
+            "accent" => Some(Role::Accent),
+            name => {
+                if let Some(n) = name.strip_prefix("color") {
+                    // color0..color255, without leading zeros
+                    return match n.parse::<u8>() {
+                        Ok(i) if i.to_string() == n => Some(Role::AnsiColor(i)),
+                        _ => None,
+                    };
+                }
+                match name.strip_prefix("bright") {
+                    Some(base) => ansi(base).map(|i| Role::AnsiColor(i + 8)),
+                    None => ansi(name).map(Role::AnsiColor),
+                }
+            }
+        }
+    }
+}
+
+impl Scheme {
+    // color of the first entry with the given role
+    pub fn by_role(&self, role: Role) -> Option<&Canonical> {
+        self.entries
+            .iter()
+            .find(|e| e.role == Some(role))
+            .map(|e| &e.color)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::Entry;
+    use crate::reader::{parse_from_str, NameSource};
+
+    #[test]
+    fn test_from_name() {
+        assert_eq!(Role::from_name("background"), Some(Role::Background));
+        assert_eq!(Role::from_name("cursorColor"), Some(Role::Cursor));
+        assert_eq!(Role::from_name("red"), Some(Role::AnsiColor(1)));
+        assert_eq!(Role::from_name("brightRed"), Some(Role::AnsiColor(9)));
+        assert_eq!(Role::from_name("bright_magenta"), Some(Role::AnsiColor(13)));
+        assert_eq!(Role::from_name("color9"), Some(Role::AnsiColor(9)));
+        assert_eq!(Role::from_name("color255"), Some(Role::AnsiColor(255)));
+        for name in [
+            "color256",
+            "color09",
+            "colorful",
+            "brightness",
+            "selection",
+            "",
+        ] {
+            assert_eq!(Role::from_name(name), None, "{}", name);
+        }
+        assert_eq!(
+            Entry::named("brightBlack", Canonical::new(0, 0, 0, 255)).role,
+            Some(Role::AnsiColor(8))
+        );
+        assert_eq!(Entry::new(Canonical::new(0, 0, 0, 255)).role, None);
+    }
+
+    #[test]
+    fn test_terminal_names() {
+        let windows_terminal = parse_from_str(
+            NameSource::FirstLine,
+            "Campbell
+background: #0c0c0c
+foreground: #cccccc
+cursorColor: #ffffff
+red: #c50f1f
+brightRed: #e74856
+purple: #881798
+brightBlue: #3b78ff
+selectionBackground: #ffffff
+",
+        )
+        .unwrap();
+        let kitty = parse_from_str(
+            NameSource::FirstLine,
+            "Campbell
+color1: #c50f1f
+color9: #e74856
+color5: #881798
+color12: #3b78ff
+cursor: #ffffff
+foreground: #cccccc
+background: #0c0c0c
+selection_background: #ffffff
+",
+        )
+        .unwrap();
+
+        let roles = [
+            Role::Background,
+            Role::Foreground,
+            Role::Cursor,
+            Role::AnsiColor(1),
+            Role::AnsiColor(9),
+            Role::AnsiColor(5),
+            Role::AnsiColor(12),
+        ];
+        for role in roles {
+            assert!(windows_terminal.by_role(role).is_some(), "{:?}", role);
+            assert_eq!(
+                windows_terminal.by_role(role),
+                kitty.by_role(role),
+                "{:?}",
+                role
+            );
+        }
+        // no role for selections, they are only found by name
+        assert!(kitty.entries.iter().any(|e| e.role.is_none()));
+        assert_eq!(kitty.by_role(Role::Accent), None);
+    }
+}
@@ -0,0 +1,119 @@
+// shade ramps as Tailwind palettes have them, 50 the lightest and 900 the
+// darkest. there is no standard way of making one, this is ours:
+//
+// - the base color goes to OKLCH and keeps its hue throughout
+// - every step has a fixed OKLCH lightness, from 0.97 for 50 to 0.30 for 900,
+//   closer together at the light end where the eye tells them apart better
+// - the step with the lightness nearest the base's is the base itself, so a
+//   brand color is always in its own ramp
+// - the chroma is the base's scaled by a factor per step, full around 500
+//   and 600 and tapering off to either end. light tints at full chroma are
+//   outside sRGB and clamping channels turns them into flat pastels of the
+//   wrong hue, dark ones would turn muddy
+// - what is still outside sRGB loses chroma until it fits, never hue or
+//   lightness
+use super::gamut::fit_chroma;
+use super::oklch::Oklch;
+use super::{Canonical, Entry, Scheme};
+
+pub const SHADE_STEPS: [u16; 10] = [50, 100, 200, 300, 400, 500, 600, 700, 800, 900];
+
+const LIGHTNESS: [f32; 10] = [0.97, 0.93, 0.87, 0.79, 0.70, 0.62, 0.54, 0.46, 0.38, 0.30];
+
+const CHROMA: [f32; 10] = [0.1, 0.2, 0.38, 0.62, 0.88, 1.0, 1.0, 0.9, 0.78, 0.64];
+
+impl Canonical {
+    // the ramp for SHADE_STEPS, the base's alpha on every shade
+    pub fn shades(&self) -> [Canonical; 10] {
+        let base = self.to_oklch();
+        let nearest = (0..LIGHTNESS.len())
+            .min_by(|&a, &b| {
+                let distance = |i: usize| (LIGHTNESS[i] - base.l).abs();
+                distance(a).total_cmp(&distance(b))
+            })
+            .unwrap();
+        std::array::from_fn(|i| {
+            if i == nearest {
+                return *self;
+            }
+            let shade = Oklch::new(LIGHTNESS[i], base.c * CHROMA[i], base.h);
+            Canonical::from_oklch(fit_chroma(shade), self.a)
+        })
+    }
+}
+
+impl Scheme {
+    // entries named by their step, "50" to "900"
+    pub fn from_shades(name: &str, base: Canonical) -> Scheme {
+        let entries = SHADE_STEPS
+            .iter()
+            .zip(base.shades())
+            .map(|(step, color)| Entry::named(&step.to_string(), color))
+            .collect();
+        Scheme::from_entries(name.into(), None, entries)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::RGB;
+
+    fn hex(colors: &[Canonical]) -> Vec<String> {
+        colors.iter().map(crate::color::diff::hex).collect()
+    }
+
+    #[test]
+    fn test_shades() {
+        // Tailwind's blue-600
+        let blue = RGB::unpack_rgb(0x2563eb).into_rgba(255);
+        let shades = blue.shades();
+        assert_eq!(
+            hex(&shades),
+            [
+                "#f0f5ff", "#dce8ff", "#bed5ff", "#96baff", "#699bff", "#3f7dff", "#2563eb",
+                "#154bc2", "#0a3799", "#032471",
+            ]
+        );
+        assert_eq!(shades[6], blue);
+
+        // lighter at every step, with the hue kept where there is any chroma
+        for pair in shades.windows(2) {
+            assert!(pair[0].to_oklch().l > pair[1].to_oklch().l);
+        }
+        for shade in shades {
+            assert!(
+                shade.to_oklch().h.distance_to(blue.to_oklch().h) < 3.0,
+                "{:?}",
+                shade
+            );
+        }
+    }
+
+    #[test]
+    fn test_shades_extremes() {
+        // grays stay gray
+        for shade in Canonical::new(128, 128, 128, 255).shades() {
+            assert!(shade.r.abs_diff(shade.g) <= 1 && shade.g.abs_diff(shade.b) <= 1);
+        }
+        // a very light base takes the place of 50, a dark one of 900
+        let white = Canonical::new(255, 255, 255, 128);
+        assert_eq!(white.shades()[0], white);
+        assert!(white.shades().iter().all(|c| c.a == 128));
+        let black = Canonical::new(0, 0, 0, 255);
+        assert_eq!(black.shades()[9], black);
+    }
+
+    #[test]
+    fn test_from_shades() {
+        let base = Canonical::new(220, 38, 38, 255);
+        let scheme = Scheme::from_shades("red", base);
+        assert_eq!(scheme.name, "red");
+        assert_eq!(scheme.len(), 10);
+        assert_eq!(scheme.get("600"), Some(&base));
+        assert_eq!(
+            scheme.colors().copied().collect::<Vec<_>>(),
+            base.shades().to_vec()
+        );
+    }
+}
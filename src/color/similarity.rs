@@ -0,0 +1,89 @@
+// how alike two schemes look as a whole, whatever their sizes. each scheme is
+// reduced to a histogram over hue and lightness bins, every entry adding
+// 1 / len to its bin, and the score is the overlap of the two histograms:
+// the sum over all bins of the smaller share. bins have hard edges, so two
+// colors either side of one count as different however close they are
+use super::{Canonical, Scheme};
+
+const HUE_BINS: usize = 12;
+const LIGHTNESS_BINS: usize = 4;
+
+// below this chroma the hue is noise, grays only go by their lightness
+const GRAY_CHROMA: f32 = 0.1;
+
+// hue bins of every lightness, then one bin per lightness for grays
+const BINS: usize = (HUE_BINS + 1) * LIGHTNESS_BINS;
+
+fn bin(color: &Canonical) -> usize {
+    let hsl = color.to_hsl();
+    let lightness = ((hsl.l * LIGHTNESS_BINS as f32) as usize).min(LIGHTNESS_BINS - 1);
+    let hue = if color.chroma() < GRAY_CHROMA {
+        HUE_BINS
+    } else {
+        ((hsl.h.degrees() / (360.0 / HUE_BINS as f32)) as usize).min(HUE_BINS - 1)
+    };
+    hue * LIGHTNESS_BINS + lightness
+}
+
+fn histogram(scheme: &Scheme) -> [f32; BINS] {
+    let mut bins = [0.0; BINS];
+    let share = 1.0 / scheme.len() as f32;
+    for color in scheme.colors() {
+        bins[bin(color)] += share;
+    }
+    bins
+}
+
+impl Scheme {
+    // in [0, 1], 1 for schemes with the same spread of colors. two empty
+    // schemes are alike, an empty and a non empty one are not
+    pub fn similarity(&self, other: &Scheme) -> f32 {
+        match (self.is_empty(), other.is_empty()) {
+            (true, true) => return 1.0,
+            (true, false) | (false, true) => return 0.0,
+            (false, false) => {}
+        }
+        let (a, b) = (histogram(self), histogram(other));
+        let overlap: f32 = a.iter().zip(b.iter()).map(|(a, b)| a.min(*b)).sum();
+        overlap.min(1.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::RGB;
+
+    fn scheme(colors: &[u32]) -> Scheme {
+        let colors = colors
+            .iter()
+            .map(|rgb| RGB::unpack_rgb(*rgb).into_rgba(0xff))
+            .collect();
+        Scheme::from_colors("test", colors)
+    }
+
+    #[test]
+    fn test_similarity() {
+        let dark = scheme(&[0x1d2021, 0x282828, 0xcc241d, 0x98971a, 0x458588, 0xebdbb2]);
+        assert!((dark.similarity(&dark) - 1.0).abs() < 0.0001);
+
+        let inverted = dark.map_colors(|c| Canonical::new(255 - c.r, 255 - c.g, 255 - c.b, c.a));
+        assert!(
+            dark.similarity(&inverted) < 0.2,
+            "{}",
+            dark.similarity(&inverted)
+        );
+        assert_eq!(dark.similarity(&inverted), inverted.similarity(&dark));
+
+        // sizes don't matter, only the spread
+        let doubled = scheme(&[0x1d2021, 0x1d2021, 0xcc241d, 0xcc241d]);
+        let single = scheme(&[0x1d2021, 0xcc241d]);
+        assert!((doubled.similarity(&single) - 1.0).abs() < 0.0001);
+        let half = scheme(&[0x1d2021, 0x458588]);
+        assert!((half.similarity(&single) - 0.5).abs() < 0.0001);
+
+        let empty = Scheme::new("empty");
+        assert_eq!(empty.similarity(&empty), 1.0);
+        assert_eq!(empty.similarity(&dark), 0.0);
+    }
+}
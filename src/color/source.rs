@@ -0,0 +1,25 @@
+// where in a scheme file an entry was read from, for reports that point the
+// user at the line to fix. the text readers set it on every entry they read,
+// entries built in code, from binary palettes or from JSON have none
+use std::fmt::Display;
+use std::path::PathBuf;
+
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct SourceLocation {
+    // None for text read without a path, see reader::parse_from_str
+    pub path: Option<PathBuf>,
+    // 1-based
+    pub line: usize,
+    // 1-based, in chars, where the color is written on the line
+    pub column: usize,
+}
+
+// path:line:column as compilers print it, line:column without a path
+impl Display for SourceLocation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(path) = &self.path {
+            write!(f, "{}:", path.display())?;
+        }
+        write!(f, "{}:{}", self.line, self.column)
+    }
+}
@@ -0,0 +1,236 @@
+use super::component::clamp_unit;
+use super::gamut::{in_gamut, map_linear};
+use super::linear::{decode_srgb, encode_srgb};
+use super::matrix::ColorMatrix;
+use super::xyz::{SRGB_TO_XYZ, XYZ_TO_SRGB};
+use super::{Canonical, RGB, RGBA};
+use crate::errors::ColorError;
+
+// rgb color spaces sharing the D65 white point, matrices from CSS Color 4
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum ColorSpace {
+    Srgb,
+    DisplayP3,
+    Rec2020,
+}
+
+// what to do with colors that can not be represented in sRGB. PreserveHue
+// reduces chroma in OKLCH until the color fits, Clamp clamps every channel
+// on its own, which can shift the hue
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Default)]
+pub enum GamutPolicy {
+    #[default]
+    PreserveHue,
+    Clamp,
+    Error,
+}
+
+const P3_TO_XYZ: ColorMatrix = ColorMatrix([
+    [0.486_570_95, 0.265_667_7, 0.198_217_28],
+    [0.228_974_56, 0.691_738_5, 0.079_286_91],
+    [0.0, 0.045_113_38, 1.043_944_4],
+]);
+
+const XYZ_TO_P3: ColorMatrix = ColorMatrix([
+    [2.493_497, -0.931_383_6, -0.402_710_8],
+    [-0.829_489, 1.762_664, 0.023_624_686],
+    [0.035_845_83, -0.076_172_39, 0.956_884_5],
+]);
+
+const REC2020_TO_XYZ: ColorMatrix = ColorMatrix([
+    [0.636_958, 0.144_616_9, 0.168_880_98],
+    [0.262_700_2, 0.677_998_1, 0.059_301_72],
+    [0.0, 0.028_072_693, 1.060_985_1],
+]);
+
+const XYZ_TO_REC2020: ColorMatrix = ColorMatrix([
+    [1.716_651_2, -0.355_670_8, -0.253_366_3],
+    [-0.666_684_4, 1.616_481_2, 0.015_768_546],
+    [0.017_639_857, -0.042_770_613, 0.942_103_1],
+]);
+
+// BT.2020 transfer function constants
+const REC2020_ALPHA: f32 = 1.099_296_8;
+const REC2020_BETA: f32 = 0.018_053_97;
+
+impl ColorSpace {
+    fn decode(&self, encoded: f32) -> f32 {
+        match self {
+            ColorSpace::Srgb | ColorSpace::DisplayP3 => decode_srgb(encoded),
+            ColorSpace::Rec2020 => {
+                if encoded < REC2020_BETA * 4.5 {
+                    encoded / 4.5
+                } else {
+                    ((encoded + REC2020_ALPHA - 1.0) / REC2020_ALPHA).powf(1.0 / 0.45)
+                }
+            }
+        }
+    }
+
+    fn encode(&self, linear: f32) -> f32 {
+        match self {
+            ColorSpace::Srgb | ColorSpace::DisplayP3 => encode_srgb(linear),
+            ColorSpace::Rec2020 => {
+                if linear < REC2020_BETA {
+                    linear * 4.5
+                } else {
+                    REC2020_ALPHA * linear.powf(0.45) - (REC2020_ALPHA - 1.0)
+                }
+            }
+        }
+    }
+
+    fn rgb_to_xyz(self) -> &'static ColorMatrix {
+        match self {
+            ColorSpace::Srgb => &SRGB_TO_XYZ,
+            ColorSpace::DisplayP3 => &P3_TO_XYZ,
+            ColorSpace::Rec2020 => &REC2020_TO_XYZ,
+        }
+    }
+
+    fn xyz_to_rgb(self) -> &'static ColorMatrix {
+        match self {
+            ColorSpace::Srgb => &XYZ_TO_SRGB,
+            ColorSpace::DisplayP3 => &XYZ_TO_P3,
+            ColorSpace::Rec2020 => &XYZ_TO_REC2020,
+        }
+    }
+}
+
+impl Canonical {
+    // gamma encoded components of this color in `space`, within [0, 1]. sRGB
+    // lies within P3 and Rec2020, so there is no gamut to leave here
+    pub fn to_space(&self, space: ColorSpace) -> RGBA<f32> {
+        let xyz = SRGB_TO_XYZ.apply(self.to_linear());
+        space
+            .xyz_to_rgb()
+            .apply(xyz)
+            .map(|c| space.encode(c))
+            .into_rgba(self.a as f32 / 255.0)
+    }
+
+    // converts gamma encoded components of `space` into sRGB, colors outside
+    // of the sRGB gamut lose chroma until they fit. the components themselves
+    // are clamped to [0, 1] first, NaN to 0
+    pub fn from_space(space: ColorSpace, rgba: RGBA<f32>) -> Canonical {
+        let (linear, alpha) = Self::linear_srgb_from_space(space, rgba);
+        Canonical::from_linear(map_linear(linear), alpha)
+    }
+
+    pub fn from_space_with(
+        space: ColorSpace,
+        rgba: RGBA<f32>,
+        policy: GamutPolicy,
+    ) -> Result<Canonical, ColorError> {
+        let (linear, alpha) = Self::linear_srgb_from_space(space, rgba);
+        let linear = match policy {
+            GamutPolicy::PreserveHue => map_linear(linear),
+            GamutPolicy::Clamp => linear,
+            GamutPolicy::Error if in_gamut(linear) => linear,
+            GamutPolicy::Error => {
+                return Err(ColorError::OutOfGamut {
+                    color: format!("{:?} color ({}, {}, {})", space, rgba.r, rgba.g, rgba.b),
+                })
+            }
+        };
+        Ok(Canonical::from_linear(linear, alpha))
+    }
+
+    fn linear_srgb_from_space(space: ColorSpace, rgba: RGBA<f32>) -> (RGB<f32>, u8) {
+        let alpha = (clamp_unit(rgba.a) * 255.0).round() as u8;
+        let linear = RGB::from(rgba).map(|c| space.decode(clamp_unit(c)));
+        let xyz = space.rgb_to_xyz().apply(linear);
+        (XYZ_TO_SRGB.apply(xyz), alpha)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::Oklch;
+    use crate::errors::ErrorKind;
+
+    fn assert_roughly_equal(a: RGBA<f32>, b: RGBA<f32>) {
+        let diff = f32::abs(a.r - b.r) + f32::abs(a.g - b.g) + f32::abs(a.b - b.b);
+        assert!(diff < 0.003, "{:?} is not roughly {:?}", a, b)
+    }
+
+    #[test]
+    fn test_to_space() {
+        let red = Canonical::new(255, 0, 0, 255);
+
+        assert_roughly_equal(
+            red.to_space(ColorSpace::Srgb),
+            RGBA::new(1.0, 0.0, 0.0, 1.0),
+        );
+        assert_roughly_equal(
+            red.to_space(ColorSpace::DisplayP3),
+            RGBA::new(0.9175, 0.2003, 0.1386, 1.0),
+        );
+        assert_roughly_equal(
+            red.to_space(ColorSpace::Rec2020),
+            RGBA::new(0.7919, 0.2309, 0.0738, 1.0),
+        );
+    }
+
+    #[test]
+    fn test_from_space_roundtrip() {
+        let color = Canonical::new(12, 200, 99, 128);
+        for space in [ColorSpace::Srgb, ColorSpace::DisplayP3, ColorSpace::Rec2020] {
+            assert_eq!(Canonical::from_space(space, color.to_space(space)), color);
+        }
+    }
+
+    #[test]
+    fn test_from_space_p3_primaries_clamp() {
+        let p3_red = RGBA::new(1.0, 0.0, 0.0, 1.0);
+        let p3_green = RGBA::new(0.0, 1.0, 0.0, 1.0);
+        let clamp = |rgba| {
+            Canonical::from_space_with(ColorSpace::DisplayP3, rgba, GamutPolicy::Clamp).unwrap()
+        };
+
+        assert_eq!(
+            Canonical::from_space(ColorSpace::DisplayP3, p3_red),
+            Canonical::new(255, 52, 41, 255)
+        );
+        assert_eq!(
+            Canonical::from_space(ColorSpace::DisplayP3, p3_green),
+            Canonical::new(0, 247, 79, 255)
+        );
+        assert_eq!(clamp(p3_red), Canonical::new(255, 0, 0, 255));
+        assert_eq!(clamp(p3_green), Canonical::new(0, 255, 0, 255));
+        assert_eq!(
+            Canonical::from_space_with(ColorSpace::DisplayP3, p3_red, GamutPolicy::Error)
+                .unwrap_err()
+                .kind(),
+            ErrorKind::OutOfGamut
+        );
+        assert_eq!(
+            Canonical::from_space_with(
+                ColorSpace::DisplayP3,
+                RGBA::new(0.9175, 0.2003, 0.1386, 1.0),
+                GamutPolicy::Error
+            )
+            .unwrap(),
+            Canonical::new(255, 0, 0, 255)
+        );
+    }
+
+    #[test]
+    fn test_from_space_preserves_hue() {
+        // P3 green is well outside sRGB, clamping channels turns it yellower
+        let p3_green = RGBA::new(0.0, 1.0, 0.0, 1.0);
+        let (linear, _) = Canonical::linear_srgb_from_space(ColorSpace::DisplayP3, p3_green);
+        let hue = Oklch::from_linear(linear).h;
+        let with = |policy| {
+            Canonical::from_space_with(ColorSpace::DisplayP3, p3_green, policy)
+                .unwrap()
+                .to_oklch()
+                .h
+        };
+        let preserved = with(GamutPolicy::PreserveHue);
+        let clamped = with(GamutPolicy::Clamp);
+        assert!(preserved.distance_to(hue) < 0.5, "{} and {}", preserved, hue);
+        assert!(clamped.distance_to(hue) > 2.5, "{} and {}", clamped, hue);
+    }
+}
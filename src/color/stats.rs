@@ -0,0 +1,225 @@
+use std::collections::HashMap;
+
+use super::linear::AverageSpace;
+use super::{Canonical, Scheme, RGB};
+
+pub const HUE_BUCKETS: usize = 12;
+
+#[derive(PartialEq, Debug, Clone)]
+pub struct SchemeStats {
+    pub min_luminance: f32,
+    pub max_luminance: f32,
+    pub mean_luminance: f32,
+    pub mean_saturation: f32,
+    // 30° wide buckets starting at red. achromatic colors have no hue and
+    // are not counted
+    pub hue_histogram: [usize; HUE_BUCKETS],
+}
+
+// alpha is averaged in the stored values either way
+pub(crate) fn mean(colors: &[Canonical], space: AverageSpace) -> Option<Canonical> {
+    if colors.is_empty() {
+        return None;
+    }
+    let len = colors.len() as u32;
+    if space == AverageSpace::Linear {
+        let sum = colors.iter().fold(RGB::new(0.0, 0.0, 0.0), |acc, c| {
+            let linear = c.to_linear();
+            RGB::new(acc.r + linear.r, acc.g + linear.g, acc.b + linear.b)
+        });
+        let alpha = colors.iter().map(|c| c.a as u32).sum::<u32>();
+        let alpha = ((alpha + len / 2) / len) as u8;
+        return Some(Canonical::from_linear(sum.map(|c| c / len as f32), alpha));
+    }
+    let sum = colors.iter().fold([0u32; 4], |acc, c| {
+        [
+            acc[0] + c.r as u32,
+            acc[1] + c.g as u32,
+            acc[2] + c.b as u32,
+            acc[3] + c.a as u32,
+        ]
+    });
+    let avg = |s: u32| ((s + len / 2) / len) as u8;
+    Some(Canonical::new(
+        avg(sum[0]),
+        avg(sum[1]),
+        avg(sum[2]),
+        avg(sum[3]),
+    ))
+}
+
+impl Scheme {
+    // per channel mean in linear light, alpha included
+    pub fn average_color(&self) -> Option<Canonical> {
+        self.average_color_with(AverageSpace::Linear)
+    }
+
+    pub fn average_color_with(&self, space: AverageSpace) -> Option<Canonical> {
+        let colors: Vec<_> = self.colors().copied().collect();
+        mean(&colors, space)
+    }
+
+    // the most frequent color. with `bucket_bits`, colors only count as equal
+    // when their channels agree in the given number of most significant bits,
+    // and the first scheme color of the most populated bucket is returned.
+    // ties are resolved in favor of the color appearing first
+    pub fn dominant_color(&self, bucket_bits: Option<u8>) -> Option<Canonical> {
+        let shift = 8 - bucket_bits.unwrap_or(8).clamp(1, 8);
+        let bucket = |c: &Canonical| (c.r >> shift, c.g >> shift, c.b >> shift, c.a >> shift);
+
+        let mut counts = HashMap::new();
+        for c in self.colors() {
+            *counts.entry(bucket(c)).or_insert(0usize) += 1;
+        }
+
+        let mut dominant: Option<(&Canonical, usize)> = None;
+        for c in self.colors() {
+            let count = counts[&bucket(c)];
+            match dominant {
+                Some((_, max)) if max >= count => {}
+                _ => dominant = Some((c, count)),
+            }
+        }
+        dominant.map(|(c, _)| *c)
+    }
+
+    pub fn stats(&self) -> Option<SchemeStats> {
+        if self.is_empty() {
+            return None;
+        }
+
+        let mut min_luminance = f32::MAX;
+        let mut max_luminance = f32::MIN;
+        let mut luminance_sum = 0.0;
+        let mut saturation_sum = 0.0;
+        let mut hue_histogram = [0; HUE_BUCKETS];
+
+        for c in self.colors() {
+            let luminance = c.luminance();
+            min_luminance = min_luminance.min(luminance);
+            max_luminance = max_luminance.max(luminance);
+            luminance_sum += luminance;
+
+            let hsl = c.to_hsl();
+            saturation_sum += hsl.s;
+            if c.chroma() > 0.0 {
+                let bucket = (hsl.h.degrees() / (360.0 / HUE_BUCKETS as f32)) as usize;
+                hue_histogram[bucket.min(HUE_BUCKETS - 1)] += 1;
+            }
+        }
+
+        let len = self.len() as f32;
+        Some(SchemeStats {
+            min_luminance,
+            max_luminance,
+            mean_luminance: luminance_sum / len,
+            mean_saturation: saturation_sum / len,
+            hue_histogram,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::Entry;
+
+    fn assert_roughly_equal(a: f32, b: f32) {
+        let diff = f32::abs(a - b);
+        assert!(diff < 0.001, "Diff {} was not smaller than 0.001", diff)
+    }
+
+    fn scheme() -> Scheme {
+        Scheme::from_colors(
+            "small",
+            vec![
+                Canonical::new(0, 0, 0, 255),
+                Canonical::new(255, 0, 0, 255),
+                Canonical::new(255, 0, 0, 255),
+                Canonical::new(0, 0, 255, 255),
+                Canonical::new(255, 255, 255, 255),
+            ],
+        )
+    }
+
+    #[test]
+    fn test_average_color() {
+        // r: 765 / 5 = 153, g: 255 / 5 = 51, b: 510 / 5 = 102
+        assert_eq!(
+            scheme().average_color_with(AverageSpace::Gamma),
+            Some(Canonical::new(153, 51, 102, 255))
+        );
+        // three full red channels of five are 60% of the light, #cb
+        assert_eq!(
+            scheme().average_color(),
+            Some(Canonical::new(203, 124, 170, 255))
+        );
+    }
+
+    #[test]
+    fn test_average_red_green() {
+        let scheme = Scheme::from_colors(
+            "rg",
+            vec![
+                Canonical::new(255, 0, 0, 255),
+                Canonical::new(0, 255, 0, 255),
+            ],
+        );
+        assert_eq!(
+            scheme.average_color(),
+            Some(Canonical::new(0xbc, 0xbc, 0, 255))
+        );
+        assert_eq!(
+            scheme.average_color_with(AverageSpace::Gamma),
+            Some(Canonical::new(0x80, 0x80, 0, 255))
+        );
+    }
+
+    #[test]
+    fn test_dominant_color() {
+        let mut scheme = scheme();
+        assert_eq!(
+            scheme.dominant_color(None),
+            Some(Canonical::new(255, 0, 0, 255))
+        );
+
+        // with two bits per channel, the three blues share a bucket
+        scheme
+            .entries
+            .push(Entry::new(Canonical::new(10, 0, 250, 255)));
+        scheme
+            .entries
+            .push(Entry::new(Canonical::new(0, 20, 230, 255)));
+        assert_eq!(
+            scheme.dominant_color(None),
+            Some(Canonical::new(255, 0, 0, 255))
+        );
+        assert_eq!(
+            scheme.dominant_color(Some(2)),
+            Some(Canonical::new(0, 0, 255, 255))
+        );
+    }
+
+    #[test]
+    fn test_stats() {
+        let stats = scheme().stats().unwrap();
+
+        assert_roughly_equal(stats.min_luminance, 0.0);
+        assert_roughly_equal(stats.max_luminance, 1.0);
+        // (0 + 0.2126 * 2 + 0.0722 + 1) / 5
+        assert_roughly_equal(stats.mean_luminance, 0.29948);
+        // black and white are unsaturated, the rest fully saturated
+        assert_roughly_equal(stats.mean_saturation, 0.6);
+        assert_eq!(stats.hue_histogram, [2, 0, 0, 0, 0, 0, 0, 0, 1, 0, 0, 0]);
+    }
+
+    #[test]
+    fn test_empty_scheme() {
+        let empty = Scheme::new("empty");
+
+        assert_eq!(empty.average_color(), None);
+        assert_eq!(empty.dominant_color(None), None);
+        assert_eq!(empty.dominant_color(Some(3)), None);
+        assert_eq!(empty.stats(), None);
+    }
+}
@@ -0,0 +1,200 @@
+// a scheme as an aligned text table, one row per entry:
+//
+//   gruvbox
+//   #  name        hex        rgb          hsl
+//   0  background  #282828    40, 40, 40   0, 0%, 16%
+//   1  red         #cc241d80  204, 36, 29  2, 75%, 46%
+//
+// hex has the alpha of translucent colors, the other columns never do
+use std::fmt::{Display, Write};
+
+use super::diff::hex;
+use super::{Entry, Scheme};
+
+// how Scheme::to_table lays out the table
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub struct TableOptions {
+    // a column of swatches in 24 bit ANSI colors, for terminals
+    pub color: bool,
+    // longer schemes show this many entries and a note about the rest, None
+    // shows all
+    pub max_rows: Option<usize>,
+}
+
+impl Default for TableOptions {
+    fn default() -> Self {
+        TableOptions {
+            color: false,
+            max_rows: Some(100),
+        }
+    }
+}
+
+const HEADER: [&str; 5] = ["#", "name", "hex", "rgb", "hsl"];
+
+const SWATCH_WIDTH: usize = 4;
+
+fn cells(index: usize, entry: &Entry) -> [String; 5] {
+    let c = entry.color;
+    let hsl = c.to_hsl();
+    [
+        index.to_string(),
+        entry.name.clone().unwrap_or_default(),
+        hex(&c),
+        format!("{}, {}, {}", c.r, c.g, c.b),
+        format!("{:.0}, {:.0}%, {:.0}%", hsl.h.degrees(), hsl.s * 100.0, hsl.l * 100.0),
+    ]
+}
+
+impl Scheme {
+    pub fn to_table(&self, options: &TableOptions) -> String {
+        let shown = options.max_rows.unwrap_or(usize::MAX).min(self.len());
+        let rows: Vec<[String; 5]> = self.entries[..shown]
+            .iter()
+            .enumerate()
+            .map(|(i, entry)| cells(i, entry))
+            .collect();
+        let mut widths = HEADER.map(|h| h.chars().count());
+        for row in &rows {
+            for (width, cell) in widths.iter_mut().zip(row) {
+                *width = (*width).max(cell.chars().count());
+            }
+        }
+
+        let mut table = String::new();
+        let mut line = |swatch: &str, row: &[String]| {
+            let mut line = swatch.to_string();
+            for (i, (cell, width)) in row.iter().zip(widths).enumerate() {
+                let gap = width - cell.chars().count();
+                // the index is right aligned, the rest left
+                if i == 0 {
+                    line.push_str(&" ".repeat(gap));
+                    line.push_str(cell);
+                } else {
+                    line.push_str(cell);
+                    line.push_str(&" ".repeat(gap));
+                }
+                line.push_str("  ");
+            }
+            table.push_str(line.trim_end());
+            table.push('\n');
+        };
+
+        let blank = match options.color {
+            true => " ".repeat(SWATCH_WIDTH + 2),
+            false => String::new(),
+        };
+        line(&blank, &HEADER.map(String::from));
+        for (row, entry) in rows.iter().zip(&self.entries) {
+            let swatch = match options.color {
+                true => {
+                    let c = entry.color;
+                    format!(
+                        "\x1b[48;2;{};{};{}m{}\x1b[0m  ",
+                        c.r,
+                        c.g,
+                        c.b,
+                        " ".repeat(SWATCH_WIDTH)
+                    )
+                }
+                false => String::new(),
+            };
+            line(&swatch, row);
+        }
+        if shown < self.len() {
+            let _ = writeln!(table, "{}…", blank);
+            let _ = writeln!(
+                table,
+                "{} more entries, {} in all",
+                self.len() - shown,
+                self.len()
+            );
+        }
+        format!("{}\n{}", self.name, table)
+    }
+}
+
+// to_table with the default options, no ANSI colors
+impl Display for Scheme {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.to_table(&TableOptions::default()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::Canonical;
+
+    fn gruvbox() -> Scheme {
+        let mut scheme = Scheme::new("gruvbox");
+        scheme
+            .push_named("background", Canonical::new(0x28, 0x28, 0x28, 255))
+            .unwrap();
+        scheme
+            .push_named("red", Canonical::new(0xcc, 0x24, 0x1d, 128))
+            .unwrap();
+        scheme.push(Canonical::new(0x45, 0x85, 0x88, 255));
+        scheme
+    }
+
+    #[test]
+    fn test_to_table() {
+        assert_eq!(
+            gruvbox().to_string(),
+            "gruvbox
+#  name        hex        rgb           hsl
+0  background  #282828    40, 40, 40    0, 0%, 16%
+1  red         #cc241d80  204, 36, 29   2, 75%, 46%
+2              #458588    69, 133, 136  183, 33%, 40%
+"
+        );
+        assert_eq!(
+            Scheme::new("empty").to_string(),
+            "empty\n#  name  hex  rgb  hsl\n"
+        );
+    }
+
+    #[test]
+    fn test_to_table_long() {
+        let colors = (0..=255).map(|i| Canonical::new(i, i, i, 255)).collect();
+        let scheme = Scheme::from_colors("grays", colors);
+        let options = TableOptions {
+            max_rows: Some(3),
+            ..Default::default()
+        };
+        assert_eq!(
+            scheme.to_table(&options),
+            "grays
+#  name  hex      rgb      hsl
+0        #000000  0, 0, 0  0, 0%, 0%
+1        #010101  1, 1, 1  0, 0%, 0%
+2        #020202  2, 2, 2  0, 0%, 1%
+…
+253 more entries, 256 in all
+"
+        );
+        // the index column is as wide as the widest index shown
+        let all = scheme.to_table(&TableOptions {
+            max_rows: None,
+            ..Default::default()
+        });
+        assert_eq!(all.lines().count(), 258);
+        assert!(all.lines().nth(2).unwrap().starts_with("  0  "));
+        assert!(all.lines().last().unwrap().starts_with("255  "));
+    }
+
+    #[test]
+    fn test_to_table_color() {
+        let table = gruvbox().to_table(&TableOptions {
+            color: true,
+            ..Default::default()
+        });
+        let lines: Vec<_> = table.lines().collect();
+        assert_eq!(lines[1], "      #  name        hex        rgb           hsl");
+        assert_eq!(
+            lines[2],
+            "\x1b[48;2;40;40;40m    \x1b[0m  0  background  #282828    40, 40, 40    0, 0%, 16%"
+        );
+    }
+}
@@ -0,0 +1,142 @@
+use super::Canonical;
+
+const MIN_KELVIN: f32 = 1000.0;
+const MAX_KELVIN: f32 = 40000.0;
+
+// below this chroma a color is too washed out to call it warm or cool
+const NEUTRAL_CHROMA: f32 = 0.1;
+
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum Warmth {
+    Warm,
+    Neutral,
+    Cool,
+}
+
+impl Canonical {
+    /// Approximates the color of a black body radiator at `temp` kelvin, using
+    /// Neil Bartlett's refinement of Tanner Helland's curve fit. Temperatures
+    /// outside 1000K–40000K are clamped and NaN is taken as 1000K. This is a
+    /// visual approximation for tinting, not colorimetry.
+    pub fn from_kelvin(temp: f32) -> Canonical {
+        let temp = if temp.is_nan() { MIN_KELVIN } else { temp };
+        let t = temp.clamp(MIN_KELVIN, MAX_KELVIN) / 100.0;
+
+        let r = if t < 66.0 {
+            255.0
+        } else {
+            let x = t - 55.0;
+            351.976_9 + 0.114_206_45 * x - 40.253_66 * x.ln()
+        };
+
+        let g = if t < 66.0 {
+            let x = t - 2.0;
+            -155.254_86 - 0.445_969_5 * x + 104.492_16 * x.ln()
+        } else {
+            let x = t - 50.0;
+            325.449_4 + 0.079_434_57 * x - 28.085_296 * x.ln()
+        };
+
+        let b = if t >= 66.0 {
+            255.0
+        } else if t <= 20.0 {
+            0.0
+        } else {
+            let x = t - 10.0;
+            -254.769_35 + 0.827_409_6 * x + 115.679_94 * x.ln()
+        };
+
+        Canonical::new(
+            r.clamp(0.0, 255.0).round() as u8,
+            g.clamp(0.0, 255.0).round() as u8,
+            b.clamp(0.0, 255.0).round() as u8,
+            u8::MAX,
+        )
+    }
+
+    // reds, oranges and yellows are warm; greens, blues and violets are cool.
+    // grays and near-whites are neutral regardless of their hue
+    pub fn temperature_class(&self) -> Warmth {
+        if self.chroma() < NEUTRAL_CHROMA {
+            return Warmth::Neutral;
+        }
+        let hue = self.to_hsl().h.degrees();
+        if !(75.0..330.0).contains(&hue) {
+            Warmth::Warm
+        } else {
+            Warmth::Cool
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_roughly_equal(a: Canonical, b: Canonical) {
+        let diff = |x: u8, y: u8| (x as i16 - y as i16).abs();
+        assert!(
+            diff(a.r, b.r) <= 3 && diff(a.g, b.g) <= 3 && diff(a.b, b.b) <= 3,
+            "{:?} is not roughly {:?}",
+            a,
+            b
+        )
+    }
+
+    #[test]
+    fn test_from_kelvin_reference_points() {
+        // daylight is (almost) white
+        assert_roughly_equal(
+            Canonical::from_kelvin(6600.0),
+            Canonical::new(255, 249, 255, 255),
+        );
+        // incandescent bulb is a warm orange
+        assert_roughly_equal(
+            Canonical::from_kelvin(2700.0),
+            Canonical::new(255, 170, 87, 255),
+        );
+        // clear blue sky
+        assert_roughly_equal(
+            Canonical::from_kelvin(10000.0),
+            Canonical::new(204, 220, 255, 255),
+        );
+    }
+
+    #[test]
+    fn test_from_kelvin_clamps() {
+        assert_eq!(Canonical::from_kelvin(10.0), Canonical::from_kelvin(1000.0));
+        assert_eq!(
+            Canonical::from_kelvin(99_999.0),
+            Canonical::from_kelvin(40000.0)
+        );
+        assert_eq!(Canonical::from_kelvin(1000.0).b, 0);
+    }
+
+    #[test]
+    fn test_temperature_class() {
+        assert_eq!(
+            Canonical::from_kelvin(2700.0).temperature_class(),
+            Warmth::Warm
+        );
+        assert_eq!(
+            Canonical::from_kelvin(6600.0).temperature_class(),
+            Warmth::Neutral
+        );
+        assert_eq!(
+            Canonical::from_kelvin(10000.0).temperature_class(),
+            Warmth::Cool
+        );
+        assert_eq!(
+            Canonical::new(255, 0, 0, 255).temperature_class(),
+            Warmth::Warm
+        );
+        assert_eq!(
+            Canonical::new(0, 128, 0, 255).temperature_class(),
+            Warmth::Cool
+        );
+        assert_eq!(
+            Canonical::new(128, 128, 128, 255).temperature_class(),
+            Warmth::Neutral
+        );
+    }
+}
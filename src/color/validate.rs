@@ -0,0 +1,408 @@
+use std::fmt::Display;
+
+use super::{Scheme, SourceLocation};
+
+// the entries named by `foreground` and `background` must both exist and
+// have a contrast ratio of at least `min_ratio`
+#[derive(PartialEq, Debug, Clone)]
+pub struct ContrastRule {
+    pub foreground: String,
+    pub background: String,
+    pub min_ratio: f32,
+}
+
+// every rule is off by default
+#[derive(PartialEq, Debug, Clone, Default)]
+pub struct ValidationRules {
+    pub min_colors: Option<usize>,
+    pub exact_colors: Option<usize>,
+    pub required_names: Vec<String>,
+    pub unique_names: bool,
+    pub opaque: bool,
+    pub contrast: Vec<ContrastRule>,
+}
+
+#[derive(PartialEq, Debug, Clone)]
+pub enum Violation {
+    TooFewColors {
+        minimum: usize,
+        found: usize,
+    },
+    WrongColorCount {
+        expected: usize,
+        found: usize,
+    },
+    MissingName(String),
+    DuplicateName(String),
+    // index of the entry and its alpha
+    NotOpaque {
+        index: usize,
+        alpha: u8,
+    },
+    LowContrast {
+        foreground: String,
+        background: String,
+        ratio: f32,
+        min_ratio: f32,
+    },
+}
+
+#[derive(PartialEq, Debug, Clone)]
+pub struct ValidationReport {
+    pub scheme: String,
+    pub violations: Vec<Violation>,
+    // one per violation, where the entry it is about was read from. None
+    // for violations about the whole scheme and for entries built in code
+    pub sources: Vec<Option<SourceLocation>>,
+}
+
+impl ValidationReport {
+    pub fn is_ok(&self) -> bool {
+        self.violations.is_empty()
+    }
+
+    // one line per violation under a header, meant to be printed as is
+    // before exiting
+    pub fn summary(&self) -> String {
+        self.to_string()
+    }
+}
+
+impl Scheme {
+    pub fn validate(&self, rules: &ValidationRules) -> ValidationReport {
+        let mut violations = Vec::new();
+
+        if let Some(minimum) = rules.min_colors {
+            if self.len() < minimum {
+                violations.push(Violation::TooFewColors {
+                    minimum,
+                    found: self.len(),
+                });
+            }
+        }
+
+        if let Some(expected) = rules.exact_colors {
+            if self.len() != expected {
+                violations.push(Violation::WrongColorCount {
+                    expected,
+                    found: self.len(),
+                });
+            }
+        }
+
+        let missing = |name: &str, violations: &mut Vec<Violation>| {
+            let violation = Violation::MissingName(name.to_string());
+            if self.get(name).is_none() && !violations.contains(&violation) {
+                violations.push(violation);
+            }
+        };
+        for name in &rules.required_names {
+            missing(name, &mut violations);
+        }
+
+        if rules.unique_names {
+            let mut seen: Vec<&str> = Vec::new();
+            let mut reported: Vec<&str> = Vec::new();
+            for name in self.entries.iter().filter_map(|e| e.name.as_deref()) {
+                if seen.contains(&name) {
+                    if !reported.contains(&name) {
+                        violations.push(Violation::DuplicateName(name.to_string()));
+                        reported.push(name);
+                    }
+                } else {
+                    seen.push(name);
+                }
+            }
+        }
+
+        if rules.opaque {
+            for (index, color) in self.colors().enumerate() {
+                if color.a != u8::MAX {
+                    violations.push(Violation::NotOpaque {
+                        index,
+                        alpha: color.a,
+                    });
+                }
+            }
+        }
+
+        for rule in &rules.contrast {
+            match (self.get(&rule.foreground), self.get(&rule.background)) {
+                (Some(foreground), Some(background)) => {
+                    let ratio = foreground.contrast_ratio(background);
+                    if ratio < rule.min_ratio {
+                        violations.push(Violation::LowContrast {
+                            foreground: rule.foreground.clone(),
+                            background: rule.background.clone(),
+                            ratio,
+                            min_ratio: rule.min_ratio,
+                        });
+                    }
+                }
+                _ => {
+                    missing(&rule.foreground, &mut violations);
+                    missing(&rule.background, &mut violations);
+                }
+            }
+        }
+
+        ValidationReport {
+            scheme: self.name.clone(),
+            sources: violations.iter().map(|v| self.source_of(v)).collect(),
+            violations,
+        }
+    }
+
+    // the entry to fix: the second of a duplicate name, the foreground of a
+    // low contrast
+    fn source_of(&self, violation: &Violation) -> Option<SourceLocation> {
+        let mut named = self.entries.iter().filter(|e| match violation {
+            Violation::DuplicateName(name) => e.name.as_ref() == Some(name),
+            Violation::LowContrast { foreground, .. } => e.name.as_ref() == Some(foreground),
+            _ => false,
+        });
+        let entry = match violation {
+            Violation::DuplicateName(_) => named.nth(1),
+            Violation::NotOpaque { index, .. } => self.entries.get(*index),
+            Violation::LowContrast { .. } => named.next(),
+            _ => None,
+        };
+        entry.and_then(|e| e.source.clone())
+    }
+}
+
+impl Display for Violation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match *self {
+            Violation::TooFewColors { minimum, found } => {
+                write!(f, "expected at least {} colors, found {}", minimum, found)
+            }
+            Violation::WrongColorCount { expected, found } => {
+                write!(f, "expected exactly {} colors, found {}", expected, found)
+            }
+            Violation::MissingName(ref name) => write!(f, "missing entry named {}", name),
+            Violation::DuplicateName(ref name) => {
+                write!(f, "more than one entry named {}", name)
+            }
+            Violation::NotOpaque { index, alpha } => {
+                write!(f, "color {} is not opaque (alpha {})", index, alpha)
+            }
+            Violation::LowContrast {
+                ref foreground,
+                ref background,
+                ratio,
+                min_ratio,
+            } => write!(
+                f,
+                "contrast between {} and {} is {:.2}:1, required {:.2}:1",
+                foreground, background, ratio, min_ratio
+            ),
+        }
+    }
+}
+
+impl Display for ValidationReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.is_ok() {
+            return write!(f, "{}: ok", self.scheme);
+        }
+        write!(
+            f,
+            "{}: {} violation{}",
+            self.scheme,
+            self.violations.len(),
+            if self.violations.len() == 1 { "" } else { "s" }
+        )?;
+        for (i, violation) in self.violations.iter().enumerate() {
+            write!(f, "\n  - {}", violation)?;
+            if let Some(Some(source)) = self.sources.get(i) {
+                write!(f, " at {}", source)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::{Canonical, Entry};
+
+    fn scheme() -> Scheme {
+        Scheme::from_entries(
+            "test".into(),
+            None,
+            vec![
+                Entry::named("background", Canonical::new(20, 20, 20, 255)),
+                Entry::named("foreground", Canonical::new(60, 60, 60, 255)),
+                Entry::named("accent", Canonical::new(200, 0, 0, 128)),
+                Entry::named("accent", Canonical::new(0, 200, 0, 255)),
+            ],
+        )
+    }
+
+    #[test]
+    fn test_no_rules() {
+        let report = scheme().validate(&ValidationRules::default());
+        assert!(report.is_ok());
+        assert_eq!(report.summary(), "test: ok");
+    }
+
+    #[test]
+    fn test_color_count() {
+        let rules = ValidationRules {
+            min_colors: Some(5),
+            exact_colors: Some(4),
+            ..Default::default()
+        };
+        assert_eq!(
+            scheme().validate(&rules).violations,
+            vec![Violation::TooFewColors {
+                minimum: 5,
+                found: 4
+            }]
+        );
+
+        let rules = ValidationRules {
+            min_colors: Some(4),
+            exact_colors: Some(3),
+            ..Default::default()
+        };
+        assert_eq!(
+            scheme().validate(&rules).violations,
+            vec![Violation::WrongColorCount {
+                expected: 3,
+                found: 4
+            }]
+        );
+    }
+
+    #[test]
+    fn test_required_names() {
+        let rules = ValidationRules {
+            required_names: vec!["background".into(), "cursor".into()],
+            ..Default::default()
+        };
+        assert_eq!(
+            scheme().validate(&rules).violations,
+            vec![Violation::MissingName("cursor".into())]
+        );
+    }
+
+    #[test]
+    fn test_unique_names() {
+        let rules = ValidationRules {
+            unique_names: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            scheme().validate(&rules).violations,
+            vec![Violation::DuplicateName("accent".into())]
+        );
+    }
+
+    #[test]
+    fn test_opaque() {
+        let rules = ValidationRules {
+            opaque: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            scheme().validate(&rules).violations,
+            vec![Violation::NotOpaque {
+                index: 2,
+                alpha: 128
+            }]
+        );
+    }
+
+    #[test]
+    fn test_contrast() {
+        let rule = |foreground: &str, min_ratio| ContrastRule {
+            foreground: foreground.into(),
+            background: "background".into(),
+            min_ratio,
+        };
+
+        let rules = ValidationRules {
+            contrast: vec![rule("foreground", 1.5)],
+            ..Default::default()
+        };
+        assert!(scheme().validate(&rules).is_ok());
+
+        let rules = ValidationRules {
+            contrast: vec![rule("foreground", 4.5), rule("cursor", 4.5)],
+            ..Default::default()
+        };
+        let report = scheme().validate(&rules);
+        assert!(matches!(
+            report.violations[0],
+            Violation::LowContrast { ratio, .. } if ratio < 4.5
+        ));
+        assert_eq!(
+            report.violations[1],
+            Violation::MissingName("cursor".into())
+        );
+        assert_eq!(report.violations.len(), 2);
+    }
+
+    #[test]
+    fn test_summary() {
+        let rules = ValidationRules {
+            required_names: vec!["cursor".into()],
+            opaque: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            scheme().validate(&rules).summary(),
+            "test: 2 violations\n  - missing entry named cursor\n  - color 2 is not opaque (alpha 128)"
+        );
+    }
+
+    #[test]
+    fn test_sources() {
+        let dir = crate::temp::TempDir::new("validate_sources");
+        let path = dir.join("cool_rs_validate_sources.txt");
+        std::fs::write(
+            &path,
+            "sources\nbackground: #141414\n// dim\nforeground:   #3c3c3c\n  accent: #c8000080\n",
+        )
+        .unwrap();
+        let scheme = crate::reader::parse(path.to_str().unwrap()).unwrap();
+        let rules = ValidationRules {
+            required_names: vec!["cursor".into()],
+            opaque: true,
+            contrast: vec![ContrastRule {
+                foreground: "foreground".into(),
+                background: "background".into(),
+                min_ratio: 4.5,
+            }],
+            ..Default::default()
+        };
+        let report = scheme.validate(&rules);
+        let at = |line, column| {
+            Some(SourceLocation {
+                path: Some(path.clone()),
+                line,
+                column,
+            })
+        };
+        assert_eq!(report.sources, [None, at(5, 11), at(4, 15)]);
+        let summary = report.summary();
+        let lines: Vec<_> = summary.lines().collect();
+        assert_eq!(lines[1], "  - missing entry named cursor");
+        assert_eq!(
+            lines[2],
+            format!(
+                "  - color 2 is not opaque (alpha 128) at {}:5:11",
+                path.display()
+            )
+        );
+        assert!(lines[3].ends_with(&format!(" at {}:4:15", path.display())));
+
+        // built in code, nothing to point at
+        let report = self::scheme().validate(&rules);
+        assert!(report.sources.iter().all(Option::is_none));
+        assert!(!report.summary().contains(" at "));
+    }
+}
@@ -0,0 +1,182 @@
+use super::linear::decode_srgb_f64;
+use super::matrix::ColorMatrix;
+use super::{Canonical, RGB};
+
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub struct XYZ {
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+}
+
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Default)]
+pub enum WhitePoint {
+    // print and ICC profile connection space
+    D50,
+    // sRGB and most displays
+    #[default]
+    D65,
+}
+
+pub(crate) const SRGB_TO_XYZ: ColorMatrix = ColorMatrix([
+    [0.412_390_8, 0.357_584_33, 0.180_480_8],
+    [0.212_639, 0.715_168_7, 0.072_192_32],
+    [0.019_330_82, 0.119_194_78, 0.950_532_15],
+]);
+
+pub(crate) const XYZ_TO_SRGB: ColorMatrix = ColorMatrix([
+    [3.240_97, -1.537_383_2, -0.498_610_76],
+    [-0.969_243_65, 1.875_967_5, 0.041_555_06],
+    [0.055_630_08, -0.203_976_96, 1.056_971_5],
+]);
+
+const BRADFORD: ColorMatrix = ColorMatrix([
+    [0.895_1, 0.266_4, -0.161_4],
+    [-0.750_2, 1.713_5, 0.036_7],
+    [0.038_9, -0.068_5, 1.029_6],
+]);
+
+const BRADFORD_INVERSE: ColorMatrix = ColorMatrix([
+    [0.986_992_9, -0.147_054_3, 0.159_962_7],
+    [0.432_305_3, 0.518_360_3, 0.049_291_2],
+    [-0.008_528_7, 0.040_042_8, 0.968_486_7],
+]);
+
+// the same in f64. the sRGB matrix is the exact rational one of CSS Color 4
+// and the Bradford inverse is exact for the rounded Bradford matrix
+type Matrix64 = [[f64; 3]; 3];
+
+const SRGB_TO_XYZ_F64: Matrix64 = [
+    [0.4123907992659595, 0.35758433938387796, 0.1804807884018343],
+    [0.21263900587151036, 0.7151686787677559, 0.07219231536073371],
+    [0.01933081871559185, 0.11919477979462599, 0.9505321522496606],
+];
+
+const BRADFORD_F64: Matrix64 = [
+    [0.8951, 0.2664, -0.1614],
+    [-0.7502, 1.7135, 0.0367],
+    [0.0389, -0.0685, 1.0296],
+];
+
+const BRADFORD_INVERSE_F64: Matrix64 = [
+    [0.9869929054667121, -0.1470542564209901, 0.15996265166373125],
+    [0.4323052697233945, 0.5183602715367776, 0.049291228212855615],
+    [-0.008528664575177331, 0.04004282165408486, 0.96848669578755],
+];
+
+fn apply_f64(matrix: &Matrix64, v: [f64; 3]) -> [f64; 3] {
+    matrix.map(|row| row[0] * v[0] + row[1] * v[1] + row[2] * v[2])
+}
+
+impl XYZ {
+    pub fn new(x: f32, y: f32, z: f32) -> XYZ {
+        XYZ { x, y, z }
+    }
+
+    pub(crate) fn as_rgb(&self) -> RGB<f32> {
+        RGB::new(self.x, self.y, self.z)
+    }
+
+    fn from_rgb(rgb: RGB<f32>) -> XYZ {
+        XYZ::new(rgb.r, rgb.g, rgb.b)
+    }
+}
+
+impl WhitePoint {
+    // tristimulus values of the white point, normalized to Y = 1
+    pub fn xyz(self) -> XYZ {
+        match self {
+            WhitePoint::D50 => XYZ::new(0.964_22, 1.0, 0.825_21),
+            WhitePoint::D65 => XYZ::new(0.950_47, 1.0, 1.088_83),
+        }
+    }
+
+    pub(crate) fn xyz_f64(self) -> [f64; 3] {
+        match self {
+            WhitePoint::D50 => [0.96422, 1.0, 0.82521],
+            WhitePoint::D65 => [0.95047, 1.0, 1.08883],
+        }
+    }
+}
+
+// Bradford chromatic adaptation of `xyz` seen under `from` to how it appears
+// under `to`
+pub fn adapt(xyz: XYZ, from: WhitePoint, to: WhitePoint) -> XYZ {
+    if from == to {
+        return xyz;
+    }
+    let source = BRADFORD.apply(from.xyz().as_rgb());
+    let destination = BRADFORD.apply(to.xyz().as_rgb());
+    let cone = BRADFORD.apply(xyz.as_rgb());
+    let scaled = RGB::new(
+        cone.r * destination.r / source.r,
+        cone.g * destination.g / source.g,
+        cone.b * destination.b / source.b,
+    );
+    XYZ::from_rgb(BRADFORD_INVERSE.apply(scaled))
+}
+
+pub(crate) fn adapt_f64(xyz: [f64; 3], from: WhitePoint, to: WhitePoint) -> [f64; 3] {
+    if from == to {
+        return xyz;
+    }
+    let source = apply_f64(&BRADFORD_F64, from.xyz_f64());
+    let destination = apply_f64(&BRADFORD_F64, to.xyz_f64());
+    let cone = apply_f64(&BRADFORD_F64, xyz);
+    let scaled = [0, 1, 2].map(|i| cone[i] * destination[i] / source[i]);
+    apply_f64(&BRADFORD_INVERSE_F64, scaled)
+}
+
+impl Canonical {
+    // relative to the D65 white point of sRGB
+    pub fn to_xyz(&self) -> XYZ {
+        XYZ::from_rgb(SRGB_TO_XYZ.apply(self.to_linear()))
+    }
+
+    pub(crate) fn to_xyz_f64(self) -> [f64; 3] {
+        let linear = [self.r, self.g, self.b].map(|c| decode_srgb_f64(c as f64 / 255.0));
+        apply_f64(&SRGB_TO_XYZ_F64, linear)
+    }
+
+    pub fn from_xyz(xyz: XYZ, alpha: u8) -> Canonical {
+        Canonical::from_linear(XYZ_TO_SRGB.apply(xyz.as_rgb()), alpha)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_xyz_roughly_equal(a: XYZ, b: XYZ) {
+        let diff = f32::abs(a.x - b.x) + f32::abs(a.y - b.y) + f32::abs(a.z - b.z);
+        assert!(diff < 0.0005, "{:?} is not roughly {:?}", a, b)
+    }
+
+    #[test]
+    fn test_adapt_white_points() {
+        let d50 = WhitePoint::D50.xyz();
+        let d65 = WhitePoint::D65.xyz();
+
+        assert_xyz_roughly_equal(adapt(d65, WhitePoint::D65, WhitePoint::D50), d50);
+        assert_xyz_roughly_equal(adapt(d50, WhitePoint::D50, WhitePoint::D65), d65);
+    }
+
+    #[test]
+    fn test_adapt_roundtrip() {
+        let xyz = Canonical::new(200, 30, 120, 255).to_xyz();
+        let there = adapt(xyz, WhitePoint::D65, WhitePoint::D50);
+        let back = adapt(there, WhitePoint::D50, WhitePoint::D65);
+
+        assert_xyz_roughly_equal(back, xyz);
+        assert_eq!(adapt(xyz, WhitePoint::D65, WhitePoint::D65), xyz);
+    }
+
+    #[test]
+    fn test_canonical_xyz() {
+        let white = Canonical::new(255, 255, 255, 255);
+        let color = Canonical::new(12, 200, 99, 128);
+
+        assert_xyz_roughly_equal(white.to_xyz(), WhitePoint::D65.xyz());
+        assert_eq!(Canonical::from_xyz(color.to_xyz(), 128), color);
+    }
+}
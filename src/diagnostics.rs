@@ -0,0 +1,273 @@
+// miette::Diagnostic for the parse errors, so a CLI rendering them through
+// miette gets error codes, help and the offending part of a line underlined
+use std::fmt::Display;
+
+use miette::{
+    Diagnostic, LabeledSpan, MietteError, MietteSpanContents, SourceCode, SourceSpan, SpanContents,
+};
+
+use crate::errors::{
+    DocumentEditError, ErrorKind, ParseFormatError, SchemeBuildError, SchemeReaderError,
+    UnknownFormatError,
+};
+use crate::formats::ColorFormats;
+
+// longer lines are reported without their source
+pub const MAX_SOURCE_LEN: usize = 1024;
+
+const FORMATS_HELP: &str = "colors are written as #rrggbb, #rrggbbaa, rgb(r, g, b), \
+     rgba(r, g, b, a), color(space r g b), hsl(h s% l%), hwb(h w% b%) or transparent";
+
+// cool_rs::parse_color for ErrorKind::ParseColor
+fn code(kind: ErrorKind) -> Box<dyn Display> {
+    let mut code = String::from("cool_rs::");
+    for (i, c) in format!("{:?}", kind).chars().enumerate() {
+        if c.is_ascii_uppercase() && i > 0 {
+            code.push('_');
+        }
+        code.push(c.to_ascii_lowercase());
+    }
+    Box::new(code)
+}
+
+// the part of the input a format error is about
+fn offending(e: &ParseFormatError) -> Option<&str> {
+    match *e {
+        ParseFormatError::NoMatch { ref input, .. }
+        | ParseFormatError::InvalidNumber { ref input, .. }
+        | ParseFormatError::OutOfRange { ref input, .. }
+        | ParseFormatError::NoAllowedFormat { ref input, .. }
+        | ParseFormatError::Unrepresentable { ref input, .. }
+        | ParseFormatError::InvalidDumpEntry { ref input, .. }
+        | ParseFormatError::NonAscii { ref input, .. }
+        | ParseFormatError::AmbiguousInput { ref input, .. }
+        | ParseFormatError::AmbiguousDecimal { ref input } => Some(input),
+        ParseFormatError::UnsupportedColorSpace { ref space } => Some(space),
+        ParseFormatError::UnknownUnit { ref unit, .. } => Some(unit),
+        _ => None,
+    }
+}
+
+fn label(e: &ParseFormatError) -> &'static str {
+    match *e {
+        ParseFormatError::NoMatch { .. } => "not a color",
+        ParseFormatError::InvalidNumber { .. } => "not a number",
+        ParseFormatError::OutOfRange { .. } => "out of range",
+        ParseFormatError::UnsupportedColorSpace { .. } => "unsupported color space",
+        ParseFormatError::NoAllowedFormat { .. } => "format not allowed",
+        ParseFormatError::Unrepresentable { .. } => "not representable",
+        ParseFormatError::InvalidDumpEntry { .. } => "not 0xRRGGBBAA",
+        ParseFormatError::NonAscii { .. } => "not ASCII",
+        ParseFormatError::UnknownUnit { .. } => "unknown unit",
+        ParseFormatError::AmbiguousDecimal { .. } | ParseFormatError::AmbiguousInput { .. } => {
+            "ambiguous"
+        }
+        _ => "invalid",
+    }
+}
+
+impl Diagnostic for ParseFormatError {
+    fn code<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+        Some(code(self.kind()))
+    }
+
+    fn help<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+        match *self {
+            ParseFormatError::NoMatch { .. } => Some(Box::new(FORMATS_HELP)),
+            ParseFormatError::AmbiguousDecimal { .. } => Some(Box::new(
+                "separate the components with ; to use , as the decimal point",
+            )),
+            ParseFormatError::AmbiguousInput { .. } => Some(Box::new(
+                "write floats with a decimal point, rgb(1.0, 1.0, 1.0) is white",
+            )),
+            ParseFormatError::UnknownUnit { .. } => {
+                Some(Box::new("angles are written in deg, grad, rad or turn"))
+            }
+            ParseFormatError::UnsupportedColorSpace { .. } => Some(Box::new(
+                "supported color spaces are srgb, display-p3 and rec2020",
+            )),
+            _ => None,
+        }
+    }
+}
+
+impl Diagnostic for UnknownFormatError {
+    fn code<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+        Some(code(self.kind()))
+    }
+
+    fn help<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+        let names: Vec<_> = ColorFormats::ALL.iter().map(|f| f.name()).collect();
+        Some(Box::new(format!("known formats are {}", names.join(", "))))
+    }
+}
+
+impl Diagnostic for SchemeBuildError {
+    fn code<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+        Some(code(self.kind()))
+    }
+
+    fn help<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+        match *self {
+            SchemeBuildError::DuplicateName { .. } => Some(Box::new(
+                "rename one of them or pick a DuplicateNamePolicy that allows overrides",
+            )),
+            _ => None,
+        }
+    }
+}
+
+impl Diagnostic for DocumentEditError {
+    fn code<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+        Some(code(self.kind()))
+    }
+
+    fn help<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+        match *self {
+            DocumentEditError::NotInDocument { .. } => {
+                Some(Box::new("change it in the included file instead"))
+            }
+            _ => None,
+        }
+    }
+}
+
+impl Diagnostic for SchemeReaderError {
+    fn code<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+        Some(code(self.kind()))
+    }
+
+    fn help<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+        match *self {
+            SchemeReaderError::ParseColorError { ref source, .. } => source.help(),
+            SchemeReaderError::DuplicateNameError { .. } => Some(Box::new(
+                "rename one of them or pick a DuplicateNamePolicy that allows overrides",
+            )),
+            SchemeReaderError::UnresolvedReference { .. } => {
+                Some(Box::new("references can only point at entries above them"))
+            }
+            SchemeReaderError::LimitExceeded { .. } => {
+                Some(Box::new("raise the limit in ReaderOptions::limits"))
+            }
+            SchemeReaderError::InvalidToken { ref source, .. } => source.help(),
+            SchemeReaderError::UnresolvedAlias { .. } => Some(Box::new(
+                "aliases resolve one level, point it at a token with a color",
+            )),
+            SchemeReaderError::InvalidJsonScheme { .. } => {
+                Some(Box::new("Scheme::json_schema describes what is expected"))
+            }
+            SchemeReaderError::TruncatedPng { .. } | SchemeReaderError::PngChecksum { .. } => {
+                Some(Box::new("the file is damaged, export the image again"))
+            }
+            SchemeReaderError::NotResolved { .. } => Some(Box::new(
+                "set COOL_SCHEME to a scheme file or the name of a builtin scheme",
+            )),
+            SchemeReaderError::LossyTranscode { .. } => Some(Box::new(
+                "without strict, and with another AlphaPolicy, the transcode reports the losses",
+            )),
+            _ => None,
+        }
+    }
+
+    fn source_code(&self) -> Option<&dyn SourceCode> {
+        match *self {
+            SchemeReaderError::ParseColorError { ref text, .. } if text.len() <= MAX_SOURCE_LEN => {
+                Some(self)
+            }
+            _ => None,
+        }
+    }
+
+    fn labels(&self) -> Option<Box<dyn Iterator<Item = LabeledSpan> + '_>> {
+        let SchemeReaderError::ParseColorError {
+            ref text,
+            ref source,
+            ..
+        } = *self
+        else {
+            return None;
+        };
+        let span = match offending(source).and_then(|input| text.find(input).map(|at| (at, input)))
+        {
+            Some((at, input)) => SourceSpan::from((at, input.len())),
+            None => SourceSpan::from((0, text.len())),
+        };
+        Some(Box::new(std::iter::once(LabeledSpan::new_with_span(
+            Some(label(source).into()),
+            span,
+        ))))
+    }
+}
+
+// the source of a ParseColorError is its offending line, placed at its line
+// number so reports point at the right line of the file
+impl SourceCode for SchemeReaderError {
+    fn read_span<'a>(
+        &'a self,
+        span: &SourceSpan,
+        context_lines_before: usize,
+        context_lines_after: usize,
+    ) -> Result<Box<dyn SpanContents<'a> + 'a>, MietteError> {
+        let SchemeReaderError::ParseColorError { line, ref text, .. } = *self else {
+            return Err(MietteError::OutOfBounds);
+        };
+        let contents = text.read_span(span, context_lines_before, context_lines_after)?;
+        Ok(Box::new(MietteSpanContents::new(
+            contents.data(),
+            *contents.span(),
+            line - 1,
+            contents.column(),
+            contents.line_count(),
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::reader::{parse_from_str, NameSource};
+    use miette::{GraphicalReportHandler, GraphicalTheme};
+
+    fn render(e: &dyn Diagnostic) -> String {
+        let mut out = String::new();
+        GraphicalReportHandler::new_themed(GraphicalTheme::unicode_nocolor())
+            .with_width(80)
+            .render_report(&mut out, e)
+            .unwrap();
+        out
+    }
+
+    #[test]
+    fn test_codes() {
+        let e = parse_from_str(NameSource::FirstLine, "").unwrap_err();
+        assert_eq!(e.code().unwrap().to_string(), "cool_rs::no_lines");
+        let e = crate::formats::parse_any("red").unwrap_err();
+        assert_eq!(e.code().unwrap().to_string(), "cool_rs::no_match");
+    }
+
+    #[test]
+    fn test_report() {
+        let e = parse_from_str(
+            NameSource::FirstLine,
+            "mono\nbackground: #000000\nforeground: rgb(300, 0, 0)\n",
+        )
+        .unwrap_err();
+        assert_eq!(
+            render(&e),
+            "cool_rs::parse_color
+
+  × Failed to parse color on line 3
+  ╰─▶ 300 is out of range for a RGBu8 component
+   ╭────
+ 3 │ foreground: rgb(300, 0, 0)
+   ·                 ─┬─
+   ·                  ╰── out of range
+   ╰────
+"
+        );
+
+        let long = format!("mono\n{}: #zz0000\n", "a".repeat(MAX_SOURCE_LEN));
+        let e = parse_from_str(NameSource::FirstLine, &long).unwrap_err();
+        assert!(e.source_code().is_none());
+    }
+}
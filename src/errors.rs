@@ -3,72 +3,1099 @@ use std::{
     fmt::Display,
     io,
     num::{ParseFloatError, ParseIntError},
+    path::{Path, PathBuf},
 };
 
-use crate::formats;
+use crate::color::diff::hex;
+use crate::color::{Canonical, EntryKey, ValidationReport};
+use crate::formats::ColorFormats;
+use crate::reader::{Limit, Lossy, SchemeFileFormat};
+
+// wrapping variants keep the wrapped error as `source` and leave it out of
+// their own message, walk source() for the full story
+
+// what went wrong without the context, for comparing errors. every failure
+// has its own kind, shared by the error types that can report it: a name
+// given twice is DuplicateName and a failed validation Validation whether a
+// reader or a builder refused the scheme, and a color a format can't write
+// is Unrepresentable for a document edit and for emitting alike
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+#[non_exhaustive]
+pub enum ErrorKind {
+    // ColorError
+    ParseHex,
+    ParseInt,
+    OutOfGamut,
+    AlphaLost,
+    // SchemeReaderError
+    Io,
+    NoLines,
+    InvalidUtf8,
+    ParseColor,
+    DuplicateName,
+    UnresolvedReference,
+    IncludeWithoutPath,
+    FilenameWithoutPath,
+    IncludeCycle,
+    IncludeTooDeep,
+    LimitExceeded,
+    StdinIsTerminal,
+    Http,
+    Validation,
+    InvalidJson,
+    InvalidToken,
+    UnresolvedAlias,
+    InvalidJsonScheme,
+    MalformedPalette,
+    TruncatedPng,
+    PngChecksum,
+    NotResolved,
+    UndefinedVariable,
+    VariableCycle,
+    LossyTranscode,
+    // ParseFormatError
+    NoMatch,
+    InvalidNumber,
+    OutOfRange,
+    UnsupportedColorSpace,
+    InvalidHex,
+    UnknownUnit,
+    AmbiguousDecimal,
+    NoAllowedFormat,
+    InvalidPrecision,
+    InvalidDumpEntry,
+    NonAscii,
+    AmbiguousInput,
+    // UnknownFormatError
+    UnknownFormat,
+    // NoCounterpart
+    NoCounterpart,
+    // ResampleError
+    NoSamples,
+    NoStops,
+    // NotEnoughColors
+    NotEnoughColors,
+    // ComponentError
+    ComponentOutOfRange,
+    NotFinite,
+    // DocumentEditError
+    UnknownEntry,
+    NotInDocument,
+    Unrepresentable,
+}
 
 #[derive(Debug)]
+#[non_exhaustive]
 pub enum ColorError {
-    ParseHexError(String),
-    ParseToIntError(ParseIntError, String),
+    // the hex string has neither 6 nor 8 digits
+    ParseHexError { input: String },
+    // `component` is the channel the digits are for, like "red"
+    ParseToIntError {
+        input: String,
+        component: &'static str,
+        source: ParseIntError,
+    },
+    OutOfGamut { color: String },
+    // AlphaPolicy::Error, the entry is translucent and the format isn't
+    AlphaLost { entry: EntryKey, alpha: u8 },
+}
+
+impl ColorError {
+    pub fn kind(&self) -> ErrorKind {
+        match *self {
+            ColorError::ParseHexError { .. } => ErrorKind::ParseHex,
+            ColorError::ParseToIntError { .. } => ErrorKind::ParseInt,
+            ColorError::OutOfGamut { .. } => ErrorKind::OutOfGamut,
+            ColorError::AlphaLost { .. } => ErrorKind::AlphaLost,
+        }
+    }
 }
 
 impl Display for ColorError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match *self {
-            ColorError::ParseHexError(ref e) => write!(f, "Failed to parse hex {}", e),
-            ColorError::ParseToIntError(ref e, ref input) => {
-                write!(f, "Failed to parse string {} into Int. {}", input, e)
+            ColorError::ParseHexError { ref input } => write!(
+                f,
+                "Failed to parse hex {}, expected 6 or 8 hex digits",
+                input
+            ),
+            ColorError::ParseToIntError {
+                ref input,
+                component,
+                ..
+            } => write!(
+                f,
+                "Failed to parse string \"{}\" (component {}) into Int",
+                input, component
+            ),
+            ColorError::OutOfGamut { ref color } => {
+                write!(f, "{} is outside of the sRGB gamut", color)
             }
+            ColorError::AlphaLost { ref entry, alpha } => write!(
+                f,
+                "{} is translucent, alpha {}, and the format has no alpha",
+                entry, alpha
+            ),
         }
     }
 }
 
-impl From<ParseIntError> for ColorError {
-    fn from(e: ParseIntError) -> Self {
-        ColorError::ParseToIntError(e, "".into())
+impl StdError for ColorError {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        match *self {
+            ColorError::ParseToIntError { ref source, .. } => Some(source),
+            _ => None,
+        }
     }
 }
 
-impl StdError for ColorError {}
-
 // READER
 
+// lines are 1-based
 #[derive(Debug)]
+#[non_exhaustive]
 pub enum SchemeReaderError {
-    IOError(io::Error, String),
+    // path is None when reading from something other than a file
+    IOError {
+        path: Option<PathBuf>,
+        source: io::Error,
+    },
     NoLinesError,
+    // offset is the byte offset into the file of the first invalid sequence
+    InvalidUtf8 {
+        line: usize,
+        offset: usize,
+    },
+    // text is the offending line as read
+    ParseColorError {
+        line: usize,
+        text: String,
+        source: ParseFormatError,
+    },
+    DuplicateNameError {
+        name: String,
+        first_line: usize,
+        line: usize,
+    },
+    // an `@name` that no earlier entry defines
+    UnresolvedReference {
+        name: String,
+        line: usize,
+    },
+    // `@include` while reading without a file path
+    IncludeWithoutPath {
+        line: usize,
+    },
+    // NameSource::Filename while reading without a file path
+    FilenameWithoutPath,
+    // the chain of files from the root to the repeated one
+    IncludeCycle {
+        chain: Vec<PathBuf>,
+    },
+    // the chain of files up to the first one past the depth limit
+    IncludeTooDeep {
+        chain: Vec<PathBuf>,
+    },
+    LimitExceeded {
+        limit: Limit,
+        line: usize,
+    },
+    StdinIsTerminal,
+    // status is None if the server never answered
+    HttpError {
+        url: String,
+        status: Option<u16>,
+        message: String,
+    },
+    ValidationError {
+        report: ValidationReport,
+    },
+    // design tokens, see reader::tokens
+    InvalidJson {
+        line: usize,
+        column: usize,
+        message: &'static str,
+    },
+    InvalidToken {
+        token: String,
+        source: ParseFormatError,
+    },
+    // the alias names no token, or one that is an alias itself
+    UnresolvedAlias {
+        token: String,
+        alias: String,
+    },
+    // reader::json_scheme, `at` is a JSON pointer to the offending value
+    InvalidJsonScheme {
+        at: String,
+        expected: &'static str,
+    },
+    // reader::binary, ase and aseprite, a file that isn't the palette its
+    // extension says
+    MalformedPalette {
+        format: &'static str,
+        reason: &'static str,
+    },
+    // reader::png_palette, the file ends inside the chunk starting at
+    // `offset`, or at `offset` before the IEND chunk
+    TruncatedPng {
+        offset: usize,
+    },
+    // a chunk whose data doesn't match its CRC
+    PngChecksum {
+        chunk: String,
+        stored: u32,
+        computed: u32,
+    },
+    // every step of reader::resolve_default failed, why each did in order
+    NotResolved {
+        tried: Vec<String>,
+    },
+    // reader::i3, a `$variable` that no `set` defines
+    UndefinedVariable {
+        name: String,
+        line: usize,
+    },
+    // the variables from the first to the repeated one
+    VariableCycle {
+        chain: Vec<String>,
+    },
+    // reader::transcode with TranscodeOptions asking not to lose anything
+    LossyTranscode {
+        format: SchemeFileFormat,
+        lossy: Vec<Lossy>,
+    },
+}
+
+impl SchemeReaderError {
+    pub fn kind(&self) -> ErrorKind {
+        match *self {
+            SchemeReaderError::IOError { .. } => ErrorKind::Io,
+            SchemeReaderError::NoLinesError => ErrorKind::NoLines,
+            SchemeReaderError::InvalidUtf8 { .. } => ErrorKind::InvalidUtf8,
+            SchemeReaderError::ParseColorError { .. } => ErrorKind::ParseColor,
+            SchemeReaderError::DuplicateNameError { .. } => ErrorKind::DuplicateName,
+            SchemeReaderError::UnresolvedReference { .. } => ErrorKind::UnresolvedReference,
+            SchemeReaderError::IncludeWithoutPath { .. } => ErrorKind::IncludeWithoutPath,
+            SchemeReaderError::FilenameWithoutPath => ErrorKind::FilenameWithoutPath,
+            SchemeReaderError::IncludeCycle { .. } => ErrorKind::IncludeCycle,
+            SchemeReaderError::IncludeTooDeep { .. } => ErrorKind::IncludeTooDeep,
+            SchemeReaderError::LimitExceeded { .. } => ErrorKind::LimitExceeded,
+            SchemeReaderError::StdinIsTerminal => ErrorKind::StdinIsTerminal,
+            SchemeReaderError::HttpError { .. } => ErrorKind::Http,
+            SchemeReaderError::ValidationError { .. } => ErrorKind::Validation,
+            SchemeReaderError::InvalidJson { .. } => ErrorKind::InvalidJson,
+            SchemeReaderError::InvalidToken { .. } => ErrorKind::InvalidToken,
+            SchemeReaderError::UnresolvedAlias { .. } => ErrorKind::UnresolvedAlias,
+            SchemeReaderError::InvalidJsonScheme { .. } => ErrorKind::InvalidJsonScheme,
+            SchemeReaderError::MalformedPalette { .. } => ErrorKind::MalformedPalette,
+            SchemeReaderError::TruncatedPng { .. } => ErrorKind::TruncatedPng,
+            SchemeReaderError::PngChecksum { .. } => ErrorKind::PngChecksum,
+            SchemeReaderError::NotResolved { .. } => ErrorKind::NotResolved,
+            SchemeReaderError::UndefinedVariable { .. } => ErrorKind::UndefinedVariable,
+            SchemeReaderError::VariableCycle { .. } => ErrorKind::VariableCycle,
+            SchemeReaderError::LossyTranscode { .. } => ErrorKind::LossyTranscode,
+        }
+    }
+
+    pub(crate) fn io(source: io::Error, path: Option<&Path>) -> Self {
+        SchemeReaderError::IOError {
+            path: path.map(Path::to_path_buf),
+            source,
+        }
+    }
 }
 
 impl Display for SchemeReaderError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match *self {
-            SchemeReaderError::IOError(ref io_err, ref message) => {
-                write!(f, "io::Error occurred: {}. {}", message, io_err)
-            }
+            SchemeReaderError::IOError { ref path, .. } => match path {
+                Some(path) => write!(f, "Failed to read {}", path.display()),
+                None => write!(f, "Failed to read scheme"),
+            },
             SchemeReaderError::NoLinesError => write!(f, "The file read appears to be empty"),
+            SchemeReaderError::InvalidUtf8 { line, offset } => write!(
+                f,
+                "Invalid UTF-8 on line {} at byte offset {}",
+                line, offset
+            ),
+            SchemeReaderError::ParseColorError { line, .. } => {
+                write!(f, "Failed to parse color on line {}", line)
+            }
+            SchemeReaderError::DuplicateNameError {
+                ref name,
+                first_line,
+                line,
+            } => write!(
+                f,
+                "{} is defined on line {} and again on line {}",
+                name, first_line, line
+            ),
+            SchemeReaderError::UnresolvedReference { ref name, line } => write!(
+                f,
+                "@{} on line {} does not refer to an earlier entry",
+                name, line
+            ),
+            SchemeReaderError::IncludeWithoutPath { line } => write!(
+                f,
+                "@include on line {} can't be resolved without a file path",
+                line
+            ),
+            SchemeReaderError::FilenameWithoutPath => write!(
+                f,
+                "The scheme name can't be taken from the filename without a file path"
+            ),
+            SchemeReaderError::IncludeCycle { ref chain } => {
+                write!(f, "Include cycle: {}", display_chain(chain))
+            }
+            SchemeReaderError::IncludeTooDeep { ref chain } => {
+                write!(f, "Includes nested too deeply: {}", display_chain(chain))
+            }
+            SchemeReaderError::LimitExceeded { limit, line } => {
+                write!(f, "Exceeded the {} on line {}", limit, line)
+            }
+            SchemeReaderError::StdinIsTerminal => {
+                write!(f, "Standard input is a terminal, pipe a scheme into it instead")
+            }
+            SchemeReaderError::HttpError {
+                ref url,
+                ref message,
+                ..
+            } => write!(f, "Failed to fetch {}: {}", url, message),
+            SchemeReaderError::ValidationError { ref report } => write!(f, "{}", report),
+            SchemeReaderError::InvalidJson {
+                line,
+                column,
+                message,
+            } => write!(f, "Invalid JSON on line {}, column {}: {}", line, column, message),
+            SchemeReaderError::InvalidToken { ref token, .. } => {
+                write!(f, "Token {} is not a color", token)
+            }
+            SchemeReaderError::UnresolvedAlias {
+                ref token,
+                ref alias,
+            } => write!(f, "Token {} refers to {{{}}}, which isn't a token", token, alias),
+            SchemeReaderError::InvalidJsonScheme { ref at, expected } => {
+                write!(f, "Invalid JSON scheme at {}: expected {}", at, expected)
+            }
+            SchemeReaderError::MalformedPalette { format, reason } => {
+                write!(f, "Malformed {} palette: {}", format, reason)
+            }
+            SchemeReaderError::TruncatedPng { offset } => {
+                write!(f, "PNG is cut off at byte {}", offset)
+            }
+            SchemeReaderError::PngChecksum { ref chunk, stored, computed } => write!(
+                f,
+                "PNG {} chunk is damaged, its CRC is {:08x} but its data gives {:08x}",
+                chunk, stored, computed
+            ),
+            SchemeReaderError::NotResolved { ref tried } => {
+                write!(f, "No scheme found: {}", tried.join("; "))
+            }
+            SchemeReaderError::UndefinedVariable { ref name, line } => {
+                write!(f, "${} on line {} is not set", name, line)
+            }
+            SchemeReaderError::VariableCycle { ref chain } => {
+                let chain: Vec<String> = chain.iter().map(|name| format!("${}", name)).collect();
+                write!(f, "Variable cycle: {}", chain.join(" -> "))
+            }
+            SchemeReaderError::LossyTranscode { format, ref lossy } => {
+                let lossy: Vec<String> = lossy.iter().map(ToString::to_string).collect();
+                write!(f, "Transcoding to {} loses: {}", format, lossy.join(", "))
+            }
         }
     }
 }
 
+fn display_chain(chain: &[PathBuf]) -> String {
+    chain
+        .iter()
+        .map(|path| path.display().to_string())
+        .collect::<Vec<_>>()
+        .join(" -> ")
+}
+
+impl StdError for SchemeReaderError {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        match *self {
+            SchemeReaderError::IOError { ref source, .. } => Some(source),
+            SchemeReaderError::ParseColorError { ref source, .. } => Some(source),
+            SchemeReaderError::InvalidToken { ref source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+// for `?` where no file is involved, like reading stdin. with a file at
+// hand, SchemeReaderError::io keeps its path
 impl From<io::Error> for SchemeReaderError {
-    fn from(err: io::Error) -> Self {
-        SchemeReaderError::IOError(err, "".into())
+    fn from(source: io::Error) -> Self {
+        SchemeReaderError::io(source, None)
+    }
+}
+
+// SCHEMES
+
+// why SchemeBuilder::build refused a scheme
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum SchemeBuildError {
+    // indices of the entries as they were added
+    DuplicateName {
+        name: String,
+        first: usize,
+        index: usize,
+    },
+    Validation {
+        report: ValidationReport,
+    },
+}
+
+impl SchemeBuildError {
+    pub fn kind(&self) -> ErrorKind {
+        match *self {
+            SchemeBuildError::DuplicateName { .. } => ErrorKind::DuplicateName,
+            SchemeBuildError::Validation { .. } => ErrorKind::Validation,
+        }
+    }
+}
+
+impl Display for SchemeBuildError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match *self {
+            SchemeBuildError::DuplicateName {
+                ref name,
+                first,
+                index,
+            } => write!(f, "{} names entry {} and again entry {}", name, first, index),
+            SchemeBuildError::Validation { ref report } => write!(f, "{}", report),
+        }
+    }
+}
+
+impl StdError for SchemeBuildError {}
+
+// a name Scheme::push_named or Scheme::rename would have given twice
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct DuplicateName {
+    pub name: String,
+    // the entry that already has it
+    pub index: usize,
+}
+
+impl DuplicateName {
+    pub fn kind(&self) -> ErrorKind {
+        ErrorKind::DuplicateName
+    }
+}
+
+impl Display for DuplicateName {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} already names entry {}", self.name, self.index)
+    }
+}
+
+impl StdError for DuplicateName {}
+
+// a float channel the checked constructors refuse
+#[derive(PartialEq, Debug, Clone, Copy)]
+#[non_exhaustive]
+pub enum ComponentError {
+    OutOfRange { component: &'static str, value: f32 },
+    // NaN or infinite
+    NotFinite { component: &'static str, value: f32 },
+}
+
+impl ComponentError {
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            ComponentError::OutOfRange { .. } => ErrorKind::ComponentOutOfRange,
+            ComponentError::NotFinite { .. } => ErrorKind::NotFinite,
+        }
+    }
+
+    pub fn component(&self) -> &'static str {
+        match self {
+            ComponentError::OutOfRange { component, .. }
+            | ComponentError::NotFinite { component, .. } => component,
+        }
+    }
+}
+
+impl Display for ComponentError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ComponentError::OutOfRange { component, value } => {
+                write!(f, "{} component {} is outside [0, 1]", component, value)
+            }
+            ComponentError::NotFinite { component, value } => {
+                write!(f, "{} component is {}", component, value)
+            }
+        }
+    }
+}
+
+impl StdError for ComponentError {}
+
+// an entry Scheme::recolor_like_with found nothing in the hue source for
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct NoCounterpart {
+    pub index: usize,
+    pub name: Option<String>,
+}
+
+impl NoCounterpart {
+    pub fn kind(&self) -> ErrorKind {
+        ErrorKind::NoCounterpart
+    }
+}
+
+impl Display for NoCounterpart {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.name {
+            Some(ref name) => write!(f, "Entry {} has no counterpart in the hue source", name),
+            None => write!(f, "Entry {} has no counterpart in the hue source", self.index),
+        }
+    }
+}
+
+impl StdError for NoCounterpart {}
+
+// why Scheme::resampled or Scheme::decimated returned nothing
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+#[non_exhaustive]
+pub enum ResampleError {
+    // asked for 0 colors
+    NoSamples,
+    // an empty scheme has no gradient to sample
+    NoStops,
+}
+
+impl ResampleError {
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            ResampleError::NoSamples => ErrorKind::NoSamples,
+            ResampleError::NoStops => ErrorKind::NoStops,
+        }
+    }
+}
+
+impl Display for ResampleError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ResampleError::NoSamples => write!(f, "Can't resample a scheme to 0 colors"),
+            ResampleError::NoStops => write!(f, "Can't resample a scheme without colors"),
+        }
+    }
+}
+
+impl StdError for ResampleError {}
+
+// a scheme too short for a fixed size table, like the 16 ansi colors
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub struct NotEnoughColors {
+    pub needed: usize,
+    pub found: usize,
+}
+
+impl NotEnoughColors {
+    pub fn kind(&self) -> ErrorKind {
+        ErrorKind::NotEnoughColors
+    }
+}
+
+impl Display for NotEnoughColors {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Needed {} colors, the scheme has {}", self.needed, self.found)
     }
 }
 
-impl StdError for SchemeReaderError {}
+impl StdError for NotEnoughColors {}
+
+// why SchemeDocument::set_color left the document as it was. `key` is the
+// entry as it was asked for
+#[derive(PartialEq, Eq, Debug, Clone)]
+#[non_exhaustive]
+pub enum DocumentEditError {
+    UnknownEntry { key: String },
+    // an unnamed entry from an include, there is no line to change and no
+    // name to override it by
+    NotInDocument { key: String },
+    // the color can't be written so it reads back the same, e.g. alpha on
+    // a GIMP row or hex with the options not allowing it
+    Unrepresentable { key: String },
+}
+
+impl DocumentEditError {
+    pub fn kind(&self) -> ErrorKind {
+        match *self {
+            DocumentEditError::UnknownEntry { .. } => ErrorKind::UnknownEntry,
+            DocumentEditError::NotInDocument { .. } => ErrorKind::NotInDocument,
+            DocumentEditError::Unrepresentable { .. } => ErrorKind::Unrepresentable,
+        }
+    }
+}
+
+impl Display for DocumentEditError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match *self {
+            DocumentEditError::UnknownEntry { ref key } => write!(f, "No entry {}", key),
+            DocumentEditError::NotInDocument { ref key } => {
+                write!(f, "Entry {} is from an include and has no name", key)
+            }
+            DocumentEditError::Unrepresentable { ref key } => {
+                write!(f, "Entry {} can't be written with this color", key)
+            }
+        }
+    }
+}
+
+impl StdError for DocumentEditError {}
 
 // FORMATS
 
 #[derive(Debug)]
-pub struct ParseFormatError(pub formats::ColorFormats, pub String);
-
-impl From<ParseFloatError> for ParseFormatError {
-    fn from(orig: ParseFloatError) -> Self {
-        ParseFormatError(
-            formats::ColorFormats::RGBf,
-            format!("unable to parse captured string to float: {}", orig),
-        )
+#[non_exhaustive]
+pub enum ParseFormatError {
+    // the input is not written in this format at all
+    NoMatch {
+        format: ColorFormats,
+        input: String,
+    },
+    // `input` is the offending component
+    InvalidNumber {
+        format: ColorFormats,
+        input: String,
+        source: ParseFloatError,
+    },
+    OutOfRange {
+        format: ColorFormats,
+        input: String,
+    },
+    UnsupportedColorSpace {
+        space: String,
+    },
+    InvalidHex {
+        source: ColorError,
+    },
+    // an angle unit other than deg, grad, rad or turn
+    UnknownUnit {
+        format: ColorFormats,
+        unit: String,
+    },
+    // the input parses both with `,` as a separator and as a decimal point
+    AmbiguousDecimal {
+        input: String,
+    },
+    // nothing in `allowed` matched the input
+    NoAllowedFormat {
+        input: String,
+        allowed: Vec<ColorFormats>,
+    },
+    // the input parsed, but `format` can't write the color, see formats::emit
+    Unrepresentable {
+        format: ColorFormats,
+        input: String,
+    },
+    // EmitOptions::float_precision too low to write `format`
+    InvalidPrecision {
+        format: ColorFormats,
+        precision: usize,
+    },
+    // an entry of a color::parse_hex_dump list, counted from 0
+    InvalidDumpEntry {
+        index: usize,
+        input: String,
+    },
+    // formats::parse_os input that isn't UTF-8, with a byte outside ASCII
+    // within the color. `input` is converted lossily, `offset` counts bytes
+    NonAscii {
+        input: String,
+        offset: usize,
+    },
+    // AmbiguityPolicy::Strict, the input reads as each of `candidates` and
+    // they differ. the first is the one parse_any would pick
+    AmbiguousInput {
+        input: String,
+        candidates: Vec<(ColorFormats, Canonical)>,
+    },
+}
+
+impl ParseFormatError {
+    pub fn kind(&self) -> ErrorKind {
+        match *self {
+            ParseFormatError::NoMatch { .. } => ErrorKind::NoMatch,
+            ParseFormatError::InvalidNumber { .. } => ErrorKind::InvalidNumber,
+            ParseFormatError::OutOfRange { .. } => ErrorKind::OutOfRange,
+            ParseFormatError::UnsupportedColorSpace { .. } => ErrorKind::UnsupportedColorSpace,
+            ParseFormatError::InvalidHex { .. } => ErrorKind::InvalidHex,
+            ParseFormatError::UnknownUnit { .. } => ErrorKind::UnknownUnit,
+            ParseFormatError::AmbiguousDecimal { .. } => ErrorKind::AmbiguousDecimal,
+            ParseFormatError::NoAllowedFormat { .. } => ErrorKind::NoAllowedFormat,
+            ParseFormatError::Unrepresentable { .. } => ErrorKind::Unrepresentable,
+            ParseFormatError::InvalidPrecision { .. } => ErrorKind::InvalidPrecision,
+            ParseFormatError::InvalidDumpEntry { .. } => ErrorKind::InvalidDumpEntry,
+            ParseFormatError::NonAscii { .. } => ErrorKind::NonAscii,
+            ParseFormatError::AmbiguousInput { .. } => ErrorKind::AmbiguousInput,
+        }
+    }
+
+    // the format the input was parsed as, None if no format was picked
+    pub fn format(&self) -> Option<ColorFormats> {
+        match *self {
+            ParseFormatError::NoMatch { format, .. }
+            | ParseFormatError::InvalidNumber { format, .. }
+            | ParseFormatError::OutOfRange { format, .. }
+            | ParseFormatError::UnknownUnit { format, .. }
+            | ParseFormatError::Unrepresentable { format, .. }
+            | ParseFormatError::InvalidPrecision { format, .. } => Some(format),
+            ParseFormatError::UnsupportedColorSpace { .. } => Some(ColorFormats::ColorFunction),
+            ParseFormatError::InvalidHex { .. } => Some(ColorFormats::Hex),
+            ParseFormatError::AmbiguousDecimal { .. }
+            | ParseFormatError::NoAllowedFormat { .. }
+            | ParseFormatError::InvalidDumpEntry { .. }
+            | ParseFormatError::NonAscii { .. }
+            | ParseFormatError::AmbiguousInput { .. } => None,
+        }
+    }
+}
+
+impl Display for ParseFormatError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match *self {
+            ParseFormatError::NoMatch { format, ref input } => {
+                write!(f, "{} is not a {:?} color", input, format)
+            }
+            ParseFormatError::InvalidNumber {
+                format, ref input, ..
+            } => write!(f, "Failed to parse {} as a {:?} component", input, format),
+            ParseFormatError::OutOfRange { format, ref input } => {
+                write!(f, "{} is out of range for a {:?} component", input, format)
+            }
+            ParseFormatError::UnsupportedColorSpace { ref space } => {
+                write!(f, "Unsupported color space {}", space)
+            }
+            ParseFormatError::InvalidHex { .. } => write!(f, "Invalid hex color"),
+            ParseFormatError::UnknownUnit { format, ref unit } => {
+                write!(f, "Unknown angle unit {} in a {:?} color", unit, format)
+            }
+            ParseFormatError::AmbiguousDecimal { ref input } => write!(
+                f,
+                "{} reads differently with , as a decimal point than as a separator",
+                input
+            ),
+            ParseFormatError::NoAllowedFormat {
+                ref input,
+                ref allowed,
+            } => write!(
+                f,
+                "{} is not written in an allowed format ({})",
+                input,
+                allowed
+                    .iter()
+                    .map(ColorFormats::name)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            ParseFormatError::Unrepresentable { format, ref input } => {
+                write!(f, "{} can not be written as a {:?} color", input, format)
+            }
+            ParseFormatError::InvalidPrecision { format, precision } => {
+                write!(f, "{:?} colors can not be written with {} decimals", format, precision)
+            }
+            ParseFormatError::InvalidDumpEntry { index, ref input } => {
+                write!(f, "Entry {} of the hex dump, {}, is not 0xRRGGBBAA", index, input)
+            }
+            ParseFormatError::NonAscii { ref input, offset } => {
+                write!(f, "{} is not UTF-8 and byte {} is not ASCII", input, offset)
+            }
+            ParseFormatError::AmbiguousInput {
+                ref input,
+                ref candidates,
+            } => write!(
+                f,
+                "{} is ambiguous, it reads as {}",
+                input,
+                candidates
+                    .iter()
+                    .map(|(format, color)| format!("{} {}", format, hex(color)))
+                    .collect::<Vec<_>>()
+                    .join(" or ")
+            ),
+        }
+    }
+}
+
+impl StdError for ParseFormatError {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        match *self {
+            ParseFormatError::InvalidNumber { ref source, .. } => Some(source),
+            ParseFormatError::InvalidHex { ref source } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+// the ColorError carries the input and the channel that failed
+impl From<ColorError> for ParseFormatError {
+    fn from(source: ColorError) -> Self {
+        ParseFormatError::InvalidHex { source }
+    }
+}
+
+// a format name ColorFormats::from_str doesn't know
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct UnknownFormatError {
+    pub name: String,
+}
+
+impl UnknownFormatError {
+    pub fn kind(&self) -> ErrorKind {
+        ErrorKind::UnknownFormat
+    }
+}
+
+impl Display for UnknownFormatError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Unknown color format {}", self.name)
+    }
+}
+
+impl StdError for UnknownFormatError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::Canonical;
+    use crate::formats::parse_any;
+    use crate::reader::{parse_from_str, NameSource};
+
+    // every message in the source chain, outermost first
+    fn chain(e: &dyn StdError) -> Vec<String> {
+        let mut messages = vec![e.to_string()];
+        let mut source = e.source();
+        while let Some(e) = source {
+            messages.push(e.to_string());
+            source = e.source();
+        }
+        messages
+    }
+
+    #[test]
+    fn test_color_errors() {
+        let e = Canonical::parse_from_hex("#12345").unwrap_err();
+        assert_eq!(
+            chain(&e),
+            vec!["Failed to parse hex #12345, expected 6 or 8 hex digits"]
+        );
+
+        let e = Canonical::parse_from_hex("#zz0000").unwrap_err();
+        assert_eq!(e.kind(), ErrorKind::ParseInt);
+        assert!(matches!(e, ColorError::ParseToIntError { ref input, .. } if input == "zz"));
+        assert_eq!(
+            chain(&e),
+            vec![
+                "Failed to parse string \"zz\" (component red) into Int",
+                "invalid digit found in string"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_format_errors() {
+        let cases = [
+            ("cmyk(0, 0, 0, 1)", vec!["cmyk(0, 0, 0, 1) is not a Hex color"]),
+            (
+                "rgb(300, 0, 0)",
+                vec!["300 is out of range for a RGBu8 component"],
+            ),
+            (
+                "color(xyz 1 0 0)",
+                vec!["Unsupported color space xyz"],
+            ),
+            ("#gg0000", vec!["#gg0000 is not a Hex color"]),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(chain(&parse_any(input).unwrap_err()), expected, "{}", input);
+        }
+        let e = parse_any("rgb(300, 0, 0)").unwrap_err();
+        assert_eq!(e.kind(), ErrorKind::OutOfRange);
+        assert_eq!(e.format(), Some(ColorFormats::RGBu8));
+    }
+
+    #[test]
+    fn test_reader_errors() {
+        let e = parse_from_str(NameSource::FirstLine, "mono\n#000000\nrgb(300, 0, 0)").unwrap_err();
+        assert!(matches!(
+            e,
+            SchemeReaderError::ParseColorError {
+                line: 3,
+                ref text,
+                source: ParseFormatError::OutOfRange { .. }
+            } if text == "rgb(300, 0, 0)"
+        ));
+        assert_eq!(
+            chain(&e),
+            vec![
+                "Failed to parse color on line 3",
+                "300 is out of range for a RGBu8 component"
+            ]
+        );
+
+        let e = parse_from_str(NameSource::FirstLine, "mono\na: #000000\na: #000000").unwrap_err();
+        assert_eq!(chain(&e), vec!["a is defined on line 2 and again on line 3"]);
+
+        let e = parse_from_str(NameSource::FirstLine, "mono\na: @b").unwrap_err();
+        assert_eq!(
+            chain(&e),
+            vec!["@b on line 2 does not refer to an earlier entry"]
+        );
+
+        let e = parse_from_str(NameSource::FirstLine, "mono\n@include \"other\"").unwrap_err();
+        assert_eq!(
+            chain(&e),
+            vec!["@include on line 2 can't be resolved without a file path"]
+        );
+
+        let e = parse_from_str(NameSource::Filename, "#000000").unwrap_err();
+        assert_eq!(
+            chain(&e),
+            vec!["The scheme name can't be taken from the filename without a file path"]
+        );
+
+        let e = crate::reader::parse("/nonexistent/cool_rs/scheme.txt").unwrap_err();
+        assert_eq!(e.kind(), ErrorKind::Io);
+        assert!(matches!(
+            e,
+            SchemeReaderError::IOError { path: Some(ref path), .. }
+                if path == Path::new("/nonexistent/cool_rs/scheme.txt")
+        ));
+        assert_eq!(chain(&e).len(), 2);
+        assert_eq!(chain(&e)[0], "Failed to read /nonexistent/cool_rs/scheme.txt");
+    }
+
+    #[test]
+    fn test_from_conversions() {
+        let hex = || -> Result<Canonical, ParseFormatError> {
+            Ok(Canonical::parse_from_hex("#12zz56")?)
+        };
+        let e = hex().unwrap_err();
+        assert!(matches!(
+            e,
+            ParseFormatError::InvalidHex {
+                source: ColorError::ParseToIntError { ref input, component: "green", .. }
+            } if input == "zz"
+        ));
+        assert_eq!(
+            chain(&e),
+            vec![
+                "Invalid hex color",
+                "Failed to parse string \"zz\" (component green) into Int",
+                "invalid digit found in string"
+            ]
+        );
+
+        let read = || -> Result<Vec<u8>, SchemeReaderError> {
+            Ok(std::fs::read("/nonexistent/cool_rs/scheme.txt")?)
+        };
+        let e = read().unwrap_err();
+        assert!(matches!(
+            e,
+            SchemeReaderError::IOError { path: None, ref source }
+                if source.kind() == io::ErrorKind::NotFound
+        ));
+        assert_eq!(chain(&e).len(), 2);
+        assert_eq!(chain(&e)[0], "Failed to read scheme");
+    }
+
+    #[test]
+    fn test_shared_kinds() {
+        let report = ValidationReport {
+            scheme: "mono".into(),
+            violations: vec![],
+            sources: vec![],
+        };
+        let duplicates = [
+            SchemeReaderError::DuplicateNameError {
+                name: "a".into(),
+                first_line: 2,
+                line: 3,
+            }
+            .kind(),
+            SchemeBuildError::DuplicateName {
+                name: "a".into(),
+                first: 0,
+                index: 1,
+            }
+            .kind(),
+            DuplicateName {
+                name: "a".into(),
+                index: 0,
+            }
+            .kind(),
+        ];
+        assert_eq!(duplicates, [ErrorKind::DuplicateName; 3]);
+        let validation = [
+            SchemeReaderError::ValidationError {
+                report: report.clone(),
+            }
+            .kind(),
+            SchemeBuildError::Validation { report }.kind(),
+        ];
+        assert_eq!(validation, [ErrorKind::Validation; 2]);
+        let unrepresentable = [
+            DocumentEditError::Unrepresentable { key: "a".into() }.kind(),
+            ParseFormatError::Unrepresentable {
+                format: ColorFormats::Hex,
+                input: "#00000080".into(),
+            }
+            .kind(),
+        ];
+        assert_eq!(unrepresentable, [ErrorKind::Unrepresentable; 2]);
+    }
+
+    #[test]
+    fn test_reader_error_display() {
+        let cases = [
+            (SchemeReaderError::NoLinesError, "The file read appears to be empty"),
+            (
+                SchemeReaderError::InvalidUtf8 { line: 2, offset: 9 },
+                "Invalid UTF-8 on line 2 at byte offset 9",
+            ),
+            (
+                SchemeReaderError::IncludeCycle {
+                    chain: vec!["a".into(), "b".into(), "a".into()],
+                },
+                "Include cycle: a -> b -> a",
+            ),
+            (
+                SchemeReaderError::IncludeTooDeep {
+                    chain: vec!["a".into(), "b".into()],
+                },
+                "Includes nested too deeply: a -> b",
+            ),
+            (
+                SchemeReaderError::LimitExceeded {
+                    limit: Limit::LineLength,
+                    line: 1,
+                },
+                "Exceeded the maximum line length on line 1",
+            ),
+            (
+                SchemeReaderError::StdinIsTerminal,
+                "Standard input is a terminal, pipe a scheme into it instead",
+            ),
+            (
+                SchemeReaderError::HttpError {
+                    url: "http://localhost/theme".into(),
+                    status: Some(404),
+                    message: "status 404".into(),
+                },
+                "Failed to fetch http://localhost/theme: status 404",
+            ),
+        ];
+        for (e, expected) in cases {
+            assert_eq!(chain(&e), vec![expected]);
+        }
     }
 }
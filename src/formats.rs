@@ -1,58 +1,194 @@
-use regex::{Match, Regex};
+use std::{borrow::Cow, ffi::OsStr, fmt::Display, str::FromStr};
 
-use crate::{color::Canonical, errors::ParseFormatError};
+use regex::{Captures, Regex};
 
-#[derive(Debug)]
+use crate::{
+    color::{diff::hex, palettes::XTERM_256, Canonical, ColorSpace, Packed, HSL, RGB, RGBA},
+    errors::{ParseFormatError, UnknownFormatError},
+    reader::Limits,
+};
+
+mod ambiguity;
+pub use ambiguity::{parse_with_ambiguities, AmbiguityPolicy};
+mod lint;
+pub use lint::{lint, lint_fix, LintFinding, LintRule, LintRules};
+mod replace;
+pub use replace::{replace, ReplaceOptions};
+mod scan;
+pub use scan::{guess, guess_all, scan, scan_with_opts, ColorMatch};
+
+// not every format has a parser yet, see parse_any for the ones that do
+#[derive(PartialEq, Eq, Hash, Debug, Clone, Copy)]
 pub enum ColorFormats {
     RGBu8,
     RGBf,
     Hex,
+    ColorFunction,
+    HSL,
+    HWB,
+    Named,
+    Lab,
+    Oklch,
+    Percent,
+    SpaceSeparated,
+    Integer,
+    XtermIndex,
+}
+
+impl ColorFormats {
+    pub const ALL: [ColorFormats; 13] = [
+        ColorFormats::RGBu8,
+        ColorFormats::RGBf,
+        ColorFormats::Hex,
+        ColorFormats::ColorFunction,
+        ColorFormats::HSL,
+        ColorFormats::HWB,
+        ColorFormats::Named,
+        ColorFormats::Lab,
+        ColorFormats::Oklch,
+        ColorFormats::Percent,
+        ColorFormats::SpaceSeparated,
+        ColorFormats::Integer,
+        ColorFormats::XtermIndex,
+    ];
+
+    // stable, meant for command line flags and config files
+    pub fn name(&self) -> &'static str {
+        match *self {
+            ColorFormats::RGBu8 => "rgb-u8",
+            ColorFormats::RGBf => "rgb-f",
+            ColorFormats::Hex => "hex",
+            ColorFormats::ColorFunction => "color",
+            ColorFormats::HSL => "hsl",
+            ColorFormats::HWB => "hwb",
+            ColorFormats::Named => "named",
+            ColorFormats::Lab => "lab",
+            ColorFormats::Oklch => "oklch",
+            ColorFormats::Percent => "percent",
+            ColorFormats::SpaceSeparated => "space-separated",
+            ColorFormats::Integer => "integer",
+            ColorFormats::XtermIndex => "xterm-index",
+        }
+    }
+}
+
+impl Display for ColorFormats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+// the inverse of Display, ignoring case
+impl FromStr for ColorFormats {
+    type Err = UnknownFormatError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let name = s.trim().to_ascii_lowercase();
+        ColorFormats::ALL
+            .into_iter()
+            .find(|format| format.name() == name)
+            .ok_or_else(|| UnknownFormatError { name: s.into() })
+    }
 }
 
 pub trait ColorFormat {
     fn matches(colr: &str) -> bool;
     fn parse(colr: &str) -> Result<Canonical, ParseFormatError>;
+    // the color written so that parse reads it back the same. only
+    // meaningful if can_emit
+    fn emit_with(color: &Canonical, options: &EmitOptions) -> String;
+
+    fn emit(color: &Canonical) -> String {
+        Self::emit_with(color, &EmitOptions::default())
+    }
+
+    // false for colors the format has no way to write
+    fn can_emit(_color: &Canonical) -> bool {
+        true
+    }
 }
 
 lazy_static! {
+    // any unsigned float literal, the range is checked when parsing
     static ref RGBA_F_REGEX: Regex = Regex::new(
         r"(?x)
-    [rR][gG][bB][aA]?
+    ^[rR][gG][bB][aA]?
     \(
-        \s*(?P<r>[01]\.\d+)\s*,
-        \s*(?P<g>[01]\.\d+)\s*,
-        \s*(?P<b>[01]\.\d+)\s*
+        \s*(?P<r>(?:\d+\.?\d*|\.\d+)(?:[eE][+-]?\d+)?)\s*,
+        \s*(?P<g>(?:\d+\.?\d*|\.\d+)(?:[eE][+-]?\d+)?)\s*,
+        \s*(?P<b>(?:\d+\.?\d*|\.\d+)(?:[eE][+-]?\d+)?)\s*
         (,
-            \s*(?P<a>[01]\.\d+)
+            \s*(?P<a>(?:\d+\.?\d*|\.\d+)(?:[eE][+-]?\d+)?%?|(?i:none))
         \s*)?
-    \)"
+    \)$"
     )
     .unwrap();
-    static ref RGBA_U8_REGEX: Regex = Regex::new(
+    // the space separated syntax of CSS Color 4, `rgb(1 0.5 0 / 50%)`, where
+    // `none` may stand in any slot. the legacy one only has it as the alpha
+    static ref RGB_F_SPACE_REGEX: Regex = Regex::new(
         r"(?x)
-    [rR][gG][bB][aA]?
+    ^[rR][gG][bB][aA]?
     \(
-        \s*(?P<r>[0-9]{1,3})\s*,
-        \s*(?P<g>[0-9]{1,3})\s*,
-        \s*(?P<b>[0-9]{1,3})\s*
-        (,
-            \s*(?P<a>[0-9]{1,3})
-        \s*)?
-    \)"
+        \s*(?P<r>(?:\d+\.?\d*|\.\d+)(?:[eE][+-]?\d+)?|(?i:none))
+        \s+(?P<g>(?:\d+\.?\d*|\.\d+)(?:[eE][+-]?\d+)?|(?i:none))
+        \s+(?P<b>(?:\d+\.?\d*|\.\d+)(?:[eE][+-]?\d+)?|(?i:none))
+        (?:\s*/\s*
+            (?P<a>(?:\d+\.?\d*|\.\d+)(?:[eE][+-]?\d+)?%?|(?i:none))
+        )?
+    \s*\)$"
+    )
+    .unwrap();
+    static ref COLOR_FUNCTION_REGEX: Regex = Regex::new(
+        r"(?x)
+    ^[cC][oO][lL][oO][rR]
+    \(
+        \s*(?P<space>[a-zA-Z0-9-]+)
+        \s+(?P<r>[+-]?(?:\d+\.?\d*|\.\d+)(?:[eE][+-]?\d+)?%?|(?i:none))
+        \s+(?P<g>[+-]?(?:\d+\.?\d*|\.\d+)(?:[eE][+-]?\d+)?%?|(?i:none))
+        \s+(?P<b>[+-]?(?:\d+\.?\d*|\.\d+)(?:[eE][+-]?\d+)?%?|(?i:none))
+        (\s*/\s*
+            (?P<a>[+-]?(?:\d+\.?\d*|\.\d+)(?:[eE][+-]?\d+)?%?|(?i:none))
+        )?
+    \s*\)$"
+    )
+    .unwrap();
+    // legacy comma separated and modern space separated syntax. the hue may
+    // carry any unit, unknown ones are rejected when parsing
+    static ref HSL_REGEX: Regex = Regex::new(
+        r"(?x)
+    ^[hH][sS][lL][aA]?
+    \(
+        \s*(?P<h>[+-]?(?:\d+\.?\d*|\.\d+)(?:[eE][+-]?\d+)?[a-zA-Z]*|(?i:none))
+        (?:\s*,\s*|\s+)(?P<s>(?:\d+\.?\d*|\.\d+)(?:[eE][+-]?\d+)?%|(?i:none))
+        (?:\s*,\s*|\s+)(?P<l>(?:\d+\.?\d*|\.\d+)(?:[eE][+-]?\d+)?%|(?i:none))
+        (?:\s*[,/]\s*
+            (?P<a>(?:\d+\.?\d*|\.\d+)(?:[eE][+-]?\d+)?%?|(?i:none))
+        )?
+    \s*\)$"
+    )
+    .unwrap();
+    static ref HWB_REGEX: Regex = Regex::new(
+        r"(?x)
+    ^[hH][wW][bB]
+    \(
+        \s*(?P<h>[+-]?(?:\d+\.?\d*|\.\d+)(?:[eE][+-]?\d+)?[a-zA-Z]*|(?i:none))
+        \s+(?P<w>(?:\d+\.?\d*|\.\d+)(?:[eE][+-]?\d+)?%|(?i:none))
+        \s+(?P<b>(?:\d+\.?\d*|\.\d+)(?:[eE][+-]?\d+)?%|(?i:none))
+        (?:\s*/\s*
+            (?P<a>(?:\d+\.?\d*|\.\d+)(?:[eE][+-]?\d+)?%?|(?i:none))
+        )?
+    \s*\)$"
     )
     .unwrap();
-    static ref HEX_REGEX: Regex = Regex::new(
-        r"/(?x)
-    \#
-      (?P<r>[0-9a-fA-F]{2})
-      (?P<g>[0-9a-fA-F]{2})
-      (?P<b>[0-9a-fA-F]{2})
-      (?P<a>[0-9a-fA-F]{2})?"
+    static ref SPACE_SEPARATED_REGEX: Regex = Regex::new(
+        r"^(?P<r>\d+)[ \t]+(?P<g>\d+)[ \t]+(?P<b>\d+)(?:[ \t]+(?P<name>.*))?$"
     )
     .unwrap();
+    static ref INTEGER_REGEX: Regex = Regex::new(r"^(?:0[xX](?P<hex>[0-9a-fA-F]+)|(?P<dec>\d+))$").unwrap();
+    static ref XTERM_INDEX_REGEX: Regex = Regex::new(r"^(?i:colou?r)(?P<index>\d+)$").unwrap();
     static ref RGBA_GENERIC_REGEX: Regex = Regex::new(
         r"(?x)
-        \(
+        ^\(
             \s*
             (
               (?:[01]\.\d+)|
@@ -68,13 +204,12 @@ lazy_static! {
               (?:[01]\.\d+)|
               (?:\d{1,3})
             )
-            (
-              (?:\s*,\s*)?
+            (?:\s*,\s*(
               (?:[01]\.\d+)|
               (?:\d{1,3})
-            )?
+            ))?
             \s*
-        \)"
+        \)$"
     )
     .unwrap();
 }
@@ -82,128 +217,2298 @@ lazy_static! {
 pub struct RGBFloatFormat {}
 pub struct RGBu8Format {}
 
+// the components of an rgb color without the function name, `(r, g, b)` or
+// `(r, g, b, a)`. u8s or floats like RGBu8Format and RGBFloatFormat read
+// them, the alpha either way
 pub struct RGBAFormat {}
 
+// `#rrggbb` or `#rrggbbaa`
+pub struct HexFormat {}
+
+// `hsl(h s% l% / a)` or `hsla(h, s%, l%, a)`
+pub struct HSLFormat {}
+
+// `hwb(h w% b% / a)`
+pub struct HWBFormat {}
+
+// a GIMP palette row, `r g b name` with any run of spaces or tabs between
+// the columns and an optional name. not tried by parse_any, a lone number
+// triple is too easy to mistake for something else
+pub struct SpaceSeparatedFormat {}
+
+// a packed color as a decimal or `0x` prefixed hex integer, `16711680` or
+// `0xFF0000`. only tried by parse_any_with and ParseOptions when allowed,
+// any number would match otherwise
+pub struct IntegerFormat {}
+
+// a slot of the xterm 256 color table as tmux writes it, `colour123` or
+// `color123`. only tried when allowed like integers, `color1` is a name as
+// often as a color
+pub struct XtermIndexFormat {}
+
+// the layout of the bytes in a packed integer, most significant first
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Default)]
+pub enum ByteOrder {
+    // 0xRRGGBB with alpha 255
+    #[default]
+    Rgb,
+    // 0xBBGGRR with alpha 255, the Windows COLORREF layout
+    Bgr,
+    // 0xRRGGBBAA, as Canonical::pack
+    Rgba,
+    // 0xAARRGGBB
+    Argb,
+}
+
+// color keywords, for now only `transparent`
+pub struct NamedFormat {}
+
+// CSS Color 4 `color(<space> r g b / a)` for the predefined rgb spaces.
+// colors outside of the sRGB gamut are clamped
+pub struct ColorFunctionFormat {}
+
 impl ColorFormat for RGBAFormat {
     fn matches(colr: &str) -> bool {
-        RGBA_GENERIC_REGEX.is_match(colr.trim())
+        RGBA_GENERIC_REGEX.is_match(strip(colr))
     }
 
     fn parse(colr: &str) -> Result<Canonical, ParseFormatError> {
-        let caps = RGBA_GENERIC_REGEX.captures(colr);
-        let caps = caps.ok_or(ParseFormatError(ColorFormats::RGBf, colr.into()))?;
+        let caps = RGBA_GENERIC_REGEX
+            .captures(strip(colr))
+            .ok_or_else(|| ParseFormatError::NoMatch {
+                format: ColorFormats::RGBu8,
+                input: colr.into(),
+            })?;
+        let rgb = format!("rgb({}, {}, {})", &caps[1], &caps[2], &caps[3]);
+        let color = match RGBFloatFormat::matches(&rgb) {
+            true => RGBFloatFormat::parse(&rgb)?,
+            false => RGBu8Format::parse(&rgb)?,
+        };
+        let a = match caps.get(4).map(|a| a.as_str()) {
+            Some(a) if unit_alpha(a) => {
+                (extract_alpha(a, ColorFormats::RGBf)? * 255.0).round() as u8
+            }
+            Some(a) => a.parse::<u8>().map_err(|_| ParseFormatError::OutOfRange {
+                format: ColorFormats::RGBu8,
+                input: a.into(),
+            })?,
+            None => u8::MAX,
+        };
+        Ok(Canonical::new(color.r, color.g, color.b, a))
+    }
+
+    // as u8s, what RGBu8Format writes without the function name
+    fn emit_with(color: &Canonical, options: &EmitOptions) -> String {
+        let rgb = RGBu8Format::emit_with(color, options);
+        rgb[rgb.find('(').unwrap_or_default()..].to_string()
+    }
+}
+
+impl ColorFormat for ColorFunctionFormat {
+    fn matches(colr: &str) -> bool {
+        COLOR_FUNCTION_REGEX.is_match(strip(colr))
+    }
 
-        let r = caps
-            .get(1)
-            .ok_or(ParseFormatError(ColorFormats::RGBf, colr.into()))?;
+    fn parse(colr: &str) -> Result<Canonical, ParseFormatError> {
+        let caps = COLOR_FUNCTION_REGEX
+            .captures(strip(colr))
+            .ok_or_else(|| ParseFormatError::NoMatch {
+                format: ColorFormats::ColorFunction,
+                input: colr.into(),
+            })?;
 
-        let g = caps
-            .get(1)
-            .ok_or(ParseFormatError(ColorFormats::RGBf, colr.into()))?
-            .as_str();
+        let space = match caps["space"].to_ascii_lowercase().as_str() {
+            "srgb" => ColorSpace::Srgb,
+            "display-p3" => ColorSpace::DisplayP3,
+            "rec2020" => ColorSpace::Rec2020,
+            other => {
+                return Err(ParseFormatError::UnsupportedColorSpace {
+                    space: other.into(),
+                })
+            }
+        };
 
-        let b = caps
-            .get(1)
-            .ok_or(ParseFormatError(ColorFormats::RGBf, colr.into()))?
-            .as_str();
+        let r = extract_number_or_percentage(&caps["r"])?;
+        let g = extract_number_or_percentage(&caps["g"])?;
+        let b = extract_number_or_percentage(&caps["b"])?;
+        let a = match caps.name("a") {
+            Some(a) => extract_number_or_percentage(a.as_str())?,
+            None => 1.0,
+        };
+        Ok(Canonical::from_space(space, RGBA::new(r, g, b, a)))
+    }
 
-        todo!()
+    // always as srgb
+    fn emit_with(color: &Canonical, options: &EmitOptions) -> String {
+        let srgb = color.to_space(ColorSpace::Srgb);
+        let rgb = format!(
+            "srgb {} {} {}",
+            options.decimal(srgb.r),
+            options.decimal(srgb.g),
+            options.decimal(srgb.b)
+        );
+        match options.alpha(color) {
+            None => format!("color({})", rgb),
+            Some(a) => format!("color({}{}{})", rgb, options.spacing.slash(), options.decimal(a)),
+        }
     }
 }
 
-fn try_parse_color(colr: &str) -> Result<Canonical, ParseFormatError> {
-    todo!()
+// how emit_with writes colors
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub struct EmitOptions {
+    // decimals of every float written, trailing zeros are left out. 0 can't
+    // be used for the float rgb and color() formats, their components would
+    // only be 0 or 1
+    pub float_precision: usize,
+    pub uppercase_hex: bool,
+    // alpha for opaque colors too, in the formats that have it
+    pub always_alpha: bool,
+    pub spacing: Spacing,
 }
 
-impl ColorFormat for RGBFloatFormat {
-    fn matches(color_str: &str) -> bool {
-        RGBA_F_REGEX.is_match(color_str.trim())
+impl Default for EmitOptions {
+    fn default() -> Self {
+        EmitOptions {
+            float_precision: 3,
+            uppercase_hex: false,
+            always_alpha: false,
+            spacing: Spacing::default(),
+        }
     }
+}
 
-    fn parse(color_str: &str) -> Result<Canonical, ParseFormatError> {
-        let caps = RGBA_F_REGEX.captures(color_str);
-        let caps = match caps {
-            Some(captures) => captures,
-            None => return Err(ParseFormatError(ColorFormats::RGBf, color_str.into())),
-        };
-        let r = extract_float_in_range(caps.name("r"))?;
-        let g = extract_float_in_range(caps.name("g"))?;
-        let b = extract_float_in_range(caps.name("b"))?;
-        let a = match caps.name("a") {
-            opt @ Some(_) => extract_float_in_range(opt)?,
-            None => 1.0,
+impl EmitOptions {
+    // `value` with float_precision decimals at most
+    fn decimal(&self, value: f32) -> String {
+        let s = format!("{:.*}", self.float_precision, value);
+        let s = match s.contains('.') {
+            true => s.trim_end_matches('0').trim_end_matches('.'),
+            false => &s,
         };
-        Ok(Canonical::from_f(r, g, b, a))
+        match s {
+            "-0" => "0".into(),
+            s => s.into(),
+        }
     }
-}
 
-fn extract_float_in_range(match_opt: Option<Match>) -> Result<f32, ParseFormatError> {
-    match match_opt {
-        Some(mat) => {
-            let f = mat.as_str().parse::<f32>()?;
-            if 0.0 <= f && f <= 1.0 {
-                Ok(f)
-            } else {
-                Err(ParseFormatError(
-                    ColorFormats::RGBf,
-                    format!("parsed float {} is not within valid range (0, 1)", f),
-                ))
+    // alpha in [0, 1] if it is to be written
+    fn alpha(&self, color: &Canonical) -> Option<f32> {
+        (self.always_alpha || color.a != u8::MAX).then(|| color.a as f32 / 255.0)
+    }
+
+    fn hex(&self, digits: String) -> String {
+        match self.uppercase_hex {
+            true => digits.to_ascii_uppercase(),
+            false => digits,
+        }
+    }
+
+    // InvalidPrecision if the options can't write `format` at all
+    pub fn check(&self, format: ColorFormats) -> Result<(), ParseFormatError> {
+        match format {
+            ColorFormats::RGBf | ColorFormats::ColorFunction if self.float_precision == 0 => {
+                Err(ParseFormatError::InvalidPrecision {
+                    format,
+                    precision: self.float_precision,
+                })
             }
+            _ => Ok(()),
         }
-        None => Err(ParseFormatError(
-            ColorFormats::RGBf,
-            "required float color component is missing".into(),
-        )),
     }
 }
 
-#[cfg(test)]
-mod tests_rgb_float_format {
-    use super::*;
+// the separators of the functional notations
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Default)]
+pub enum Spacing {
+    // `rgb(255,128,0)` and `hsl(30 100% 50%/0.2)`
+    #[default]
+    Compact,
+    // `rgb(255, 128, 0)` and `hsl(30 100% 50% / 0.2)`
+    Spaced,
+}
 
-    #[test]
-    fn test_color_format_matches() {
-        let ok_candidates = vec![
-            "rgb(0.0, 0.0, 0.0)",         // - rgb variations
-            "rgb(0.0, 0.0, 0.0, 0.0)",    // |
-            "rgba(0.0, 0.0, 0.0)",        // |
-            "rgba(0.0, 0.0, 0.0, 0.0)",   // |
-            "RGBA(0.0, 0.0, 0.0)",        // - casing
-            "RgBa(0.0, 0.0, 0.0)",        // |
-            "rGb(0.0, 0.0, 0.0)",         // |
-            " rgb( 0.5  ,  1.0 ,0.25 ) ", // - strange spacings
-            "rgb(0.111111111111111111, 0.2, 0.12345, 0.696969)",
-            "rgba(0.00, 0.0000, 0.00000, 0.0)",
-            // invalid but matches
-            "rgb(1.5, 0.91, 1.99999)", // - starts with `1` (but > 1)
-        ];
+impl Spacing {
+    fn comma(self) -> &'static str {
+        match self {
+            Spacing::Compact => ",",
+            Spacing::Spaced => ", ",
+        }
+    }
 
-        for cand in ok_candidates {
-            assert!(RGBFloatFormat::matches(cand))
+    fn slash(self) -> &'static str {
+        match self {
+            Spacing::Compact => "/",
+            Spacing::Spaced => " / ",
         }
+    }
+}
 
-        let ko_candidates = vec![
-            "rgb(0, 0, 0)",
-            "rgba(0, 0, 0)",
-            "rgb(-1.0, 1.0, 0.0)",
-            "rgba(1.0.0, 1.0, 0.1001)",
-        ];
+// hsl() and hwb() share the layout, `name(h a% b%)` or `name(h a% b% / alpha)`
+fn emit_hue_function(
+    name: &str,
+    [hue, first, second]: [f32; 3],
+    color: &Canonical,
+    options: &EmitOptions,
+) -> String {
+    let args = format!(
+        "{} {}% {}%",
+        options.decimal(hue),
+        options.decimal(first * 100.0),
+        options.decimal(second * 100.0)
+    );
+    match options.alpha(color) {
+        None => format!("{}({})", name, args),
+        Some(a) => format!("{}({}{}{})", name, args, options.spacing.slash(), options.decimal(a)),
+    }
+}
 
-        for cand in ko_candidates {
-            assert!(!RGBFloatFormat::matches(cand))
+// writes `color` in `format`. None for the formats without a parser (Lab,
+// Oklch and Percent) and for colors the format can't write, like anything
+// but transparent as Named or a translucent color as SpaceSeparated
+pub fn emit(color: &Canonical, format: ColorFormats) -> Option<String> {
+    emit_with(color, format, &EmitOptions::default())
+}
+
+// None as well for options the format can't be written with, see
+// EmitOptions::check
+pub fn emit_with(color: &Canonical, format: ColorFormats, options: &EmitOptions) -> Option<String> {
+    fn checked<F: ColorFormat>(color: &Canonical, options: &EmitOptions) -> Option<String> {
+        F::can_emit(color).then(|| F::emit_with(color, options))
+    }
+    options.check(format).ok()?;
+    match format {
+        ColorFormats::RGBu8 => checked::<RGBu8Format>(color, options),
+        ColorFormats::RGBf => checked::<RGBFloatFormat>(color, options),
+        ColorFormats::Hex => checked::<HexFormat>(color, options),
+        ColorFormats::ColorFunction => checked::<ColorFunctionFormat>(color, options),
+        ColorFormats::HSL => checked::<HSLFormat>(color, options),
+        ColorFormats::HWB => checked::<HWBFormat>(color, options),
+        ColorFormats::Named => checked::<NamedFormat>(color, options),
+        ColorFormats::SpaceSeparated => checked::<SpaceSeparatedFormat>(color, options),
+        ColorFormats::Integer => checked::<IntegerFormat>(color, options),
+        ColorFormats::XtermIndex => checked::<XtermIndexFormat>(color, options),
+        ColorFormats::Lab | ColorFormats::Oklch | ColorFormats::Percent => None,
+    }
+}
+
+// parses `colr` like parse_any and writes the color in `to`
+pub fn convert(colr: &str, to: ColorFormats) -> Result<String, ParseFormatError> {
+    convert_with(colr, to, &EmitOptions::default())
+}
+
+pub fn convert_with(
+    colr: &str,
+    to: ColorFormats,
+    options: &EmitOptions,
+) -> Result<String, ParseFormatError> {
+    options.check(to)?;
+    let color = parse_any(colr)?;
+    emit_with(&color, to, options).ok_or_else(|| ParseFormatError::Unrepresentable {
+        format: to,
+        input: colr.into(),
+    })
+}
+
+// `50%` is read as 0.5
+fn extract_number_or_percentage(s: &str) -> Result<f32, ParseFormatError> {
+    if is_none(s) {
+        return Ok(0.0);
+    }
+    let (number, scale) = match s.strip_suffix('%') {
+        Some(number) => (number, 100.0),
+        None => (s, 1.0),
+    };
+    number
+        .parse::<f32>()
+        .map(|f| f / scale)
+        .map_err(|source| ParseFormatError::InvalidNumber {
+            format: ColorFormats::ColorFunction,
+            input: s.into(),
+            source,
+        })
+}
+
+// CSS Color 4 `none` marks a missing component. it's read as 0 and the
+// missing-ness isn't kept, so anything computed from the color afterwards
+// (mixing, gradients, conversions) sees a plain zero
+fn is_none(component: &str) -> bool {
+    component.eq_ignore_ascii_case("none")
+}
+
+fn strip_bytes(colr: &[u8]) -> &[u8] {
+    let colr = colr.trim_ascii();
+    colr.strip_suffix(b";").unwrap_or(colr).trim_ascii_end()
+}
+
+// the scanners only pass on ASCII
+fn ascii(s: &[u8]) -> &str {
+    std::str::from_utf8(s).expect("scanned components are ASCII")
+}
+
+// `#rrggbb` or `#rrggbbaa`
+fn scan_hex(colr: &[u8]) -> bool {
+    match strip_bytes(colr) {
+        [b'#', digits @ ..] => {
+            matches!(digits.len(), 6 | 8) && digits.iter().all(u8::is_ascii_hexdigit)
         }
+        _ => false,
     }
+}
 
-    #[test]
-    fn test_color_format_parse() {
-        assert_eq!(
-            RGBFloatFormat::parse("rgb(0.0, 0.0, 0.0").unwrap(),
-            Canonical::from_f(0.0, 0.0, 0.0, 1.0)
+// r, g, b and the alpha if there is one
+type RGBComponents<'a> = ([&'a [u8]; 3], Option<&'a [u8]>);
+
+// the components of `rgb(r, g, b)`, `rgba(r, g, b, a)` or `rgb(r g b / a)`
+// with channels of at most three digits. the alpha may also be a decimal, a
+// percentage or `none`, the space separated channels `none` as well
+fn scan_rgb_u8(colr: &[u8]) -> Option<RGBComponents<'_>> {
+    let colr = strip_bytes(colr);
+    if colr.len() < 3 || !colr[..3].eq_ignore_ascii_case(b"rgb") {
+        return None;
+    }
+    let rest = match &colr[3..] {
+        [b'a' | b'A', rest @ ..] => rest,
+        rest => rest,
+    };
+    let inner = rest.strip_prefix(b"(")?.strip_suffix(b")")?;
+    // `r, g, b, a` or `r g b / a`, where `none` may stand in any slot
+    let (channels, slash) = match inner.iter().position(|&b| b == b'/') {
+        Some(at) => (&inner[..at], Some(inner[at + 1..].trim_ascii())),
+        None => (inner, None),
+    };
+    let comma = channels.contains(&b',');
+    if comma && slash.is_some() {
+        return None;
+    }
+    let mut parts = channels
+        .split(|&b| match comma {
+            true => b == b',',
+            false => b.is_ascii_whitespace(),
+        })
+        .map(<[u8]>::trim_ascii)
+        .filter(|part| comma || !part.is_empty());
+    let mut channel = || {
+        parts.next().filter(|c| {
+            (1..=3).contains(&c.len()) && c.iter().all(u8::is_ascii_digit)
+                || !comma && c.eq_ignore_ascii_case(b"none")
+        })
+    };
+    let rgb = [channel()?, channel()?, channel()?];
+    let a = if comma { parts.next() } else { slash };
+    let a = match a {
+        Some(a) if channel_or_percent(a) || decimal(a) || a.eq_ignore_ascii_case(b"none") => {
+            Some(a)
+        }
+        Some(_) => return None,
+        None => None,
+    };
+    match parts.next() {
+        Some(_) => None,
+        None => Some((rgb, a)),
+    }
+}
+
+// up to three digits or an unsigned decimal percentage
+fn channel_or_percent(s: &[u8]) -> bool {
+    if (1..=3).contains(&s.len()) && s.iter().all(u8::is_ascii_digit) {
+        return true;
+    }
+    match s.strip_suffix(b"%") {
+        Some(number) => decimal(number),
+        None => false,
+    }
+}
+
+// an unsigned number, with or without a decimal point
+fn decimal(s: &[u8]) -> bool {
+    let (int, frac) = match s.iter().position(|&b| b == b'.') {
+        Some(dot) => (&s[..dot], &s[dot + 1..]),
+        None => (s, &[][..]),
+    };
+    int.len() + frac.len() > 0 && int.iter().chain(frac).all(u8::is_ascii_digit)
+}
+
+// an alpha on the 0..1 scale of CSS rather than a u8: a percentage, one
+// with a decimal point, or a bare `0` or `1`, so `rgba(0, 0, 0, 1)` is
+// opaque. the two mean the same either way for `0`
+fn unit_alpha(a: &str) -> bool {
+    a.ends_with('%') || a.contains('.') || a == "0" || a == "1"
+}
+
+// surrounding whitespace and a single trailing semicolon, as left over from
+// copying a value out of CSS. every format strips these before matching
+fn strip(colr: &str) -> &str {
+    let colr = colr.trim();
+    colr.strip_suffix(';').unwrap_or(colr).trim_end()
+}
+
+// drops whitespace between a function name and its parenthesis like Qt
+// writes `rgb (1, 2, 3)`, the formats strip the rest
+fn tidy(colr: &str) -> Cow<'_, str> {
+    let colr = colr.trim();
+    match colr.split_once('(') {
+        Some((name, rest))
+            if name.len() != name.trim_end().len()
+                && name.trim_end().chars().all(|c| c.is_ascii_alphabetic()) =>
+        {
+            Cow::Owned(format!("{}({}", name.trim_end(), rest))
+        }
+        _ => Cow::Borrowed(colr),
+    }
+}
+
+// tries float rgb, u8 rgb, color(), hsl(), hwb() and keywords in that order
+// and falls back
+// to hex, so input that matches none of them is reported as malformed hex.
+// the input is tidied first, see parse_exact for a strict variant
+pub fn parse_any(colr: &str) -> Result<Canonical, ParseFormatError> {
+    parse_first(&tidy(colr))
+}
+
+// parse_any for arguments as the OS passes them. input that isn't UTF-8 is
+// still parsed if the color itself is ASCII: bytes outside ASCII before and
+// after it are skipped like whitespace, as the Latin-1 no-break space 0xa0
+// pasted into a terminal in a legacy locale. the color is only ever ASCII,
+// so nothing is lost
+pub fn parse_os(input: &OsStr) -> Result<Canonical, ParseFormatError> {
+    if let Some(input) = input.to_str() {
+        return parse_any(input);
+    }
+    let bytes = input.as_encoded_bytes();
+    let start = bytes.iter().position(u8::is_ascii).unwrap_or(bytes.len());
+    let end = bytes.iter().rposition(u8::is_ascii).map_or(start, |i| i + 1);
+    match bytes[start..end].iter().position(|b| !b.is_ascii()) {
+        Some(i) => Err(ParseFormatError::NonAscii {
+            input: input.to_string_lossy().into_owned(),
+            offset: start + i,
+        }),
+        None if start == end => Err(ParseFormatError::NonAscii {
+            input: input.to_string_lossy().into_owned(),
+            offset: 0,
+        }),
+        None => parse_any(ascii(&bytes[start..end])),
+    }
+}
+
+// parse_any for every color, failing with the error of the first one that
+// doesn't parse
+pub fn parse_colors<S: AsRef<str>>(colrs: &[S]) -> Result<Vec<Canonical>, ParseFormatError> {
+    colrs.iter().map(|colr| parse_any(colr.as_ref())).collect()
+}
+
+// parse_colors in parallel, with the same result
+#[cfg(feature = "parallel")]
+pub fn parse_colors_par<S: AsRef<str> + Sync>(
+    colrs: &[S],
+) -> Result<Vec<Canonical>, ParseFormatError> {
+    crate::parallel::par_map(colrs, |colr| parse_any(colr.as_ref()))
+        .into_iter()
+        .collect()
+}
+
+// parse_any for input that is exactly one color, without surrounding
+// whitespace, semicolon or a space before the parenthesis
+pub fn parse_exact(colr: &str) -> Result<Canonical, ParseFormatError> {
+    if strip(colr) != colr || tidy(colr) != colr {
+        return Err(ParseFormatError::NoMatch {
+            format: ColorFormats::Hex,
+            input: colr.into(),
+        });
+    }
+    parse_first(colr)
+}
+
+fn parse_first(colr: &str) -> Result<Canonical, ParseFormatError> {
+    if RGBFloatFormat::matches(colr) {
+        RGBFloatFormat::parse(colr)
+    } else if RGBu8Format::matches(colr) {
+        RGBu8Format::parse(colr)
+    } else if ColorFunctionFormat::matches(colr) {
+        ColorFunctionFormat::parse(colr)
+    } else if HSLFormat::matches(colr) {
+        HSLFormat::parse(colr)
+    } else if HWBFormat::matches(colr) {
+        HWBFormat::parse(colr)
+    } else if NamedFormat::matches(colr) {
+        NamedFormat::parse(colr)
+    } else {
+        HexFormat::parse(colr)
+    }
+}
+
+// the formats parse_any tries, in its order
+const PARSE_ANY_ORDER: [ColorFormats; 7] = [
+    ColorFormats::RGBf,
+    ColorFormats::RGBu8,
+    ColorFormats::ColorFunction,
+    ColorFormats::HSL,
+    ColorFormats::HWB,
+    ColorFormats::Named,
+    ColorFormats::Hex,
+];
+
+// the order parse_any_with tries formats in, parse_any's with xterm
+// indices and integers before hex
+const ALLOWED_ORDER: [ColorFormats; 9] = [
+    ColorFormats::RGBf,
+    ColorFormats::RGBu8,
+    ColorFormats::ColorFunction,
+    ColorFormats::HSL,
+    ColorFormats::HWB,
+    ColorFormats::Named,
+    ColorFormats::XtermIndex,
+    ColorFormats::Integer,
+    ColorFormats::Hex,
+];
+
+// like parse_any but only tries the formats in `allowed`, formats without a
+// parser are ignored. this is the only way to have integers parsed, in the
+// default byte order. there is no fallback, input matching none of the
+// allowed formats is reported together with the allowed set
+pub fn parse_any_with(colr: &str, allowed: &[ColorFormats]) -> Result<Canonical, ParseFormatError> {
+    parse_allowed(colr, allowed, ByteOrder::default())
+}
+
+fn parse_allowed(
+    colr: &str,
+    allowed: &[ColorFormats],
+    byte_order: ByteOrder,
+) -> Result<Canonical, ParseFormatError> {
+    let colr: &str = &tidy(colr);
+    for format in ALLOWED_ORDER.into_iter().filter(|format| allowed.contains(format)) {
+        return match format {
+            ColorFormats::RGBf if RGBFloatFormat::matches(colr) => RGBFloatFormat::parse(colr),
+            ColorFormats::RGBu8 if RGBu8Format::matches(colr) => RGBu8Format::parse(colr),
+            ColorFormats::ColorFunction if ColorFunctionFormat::matches(colr) => {
+                ColorFunctionFormat::parse(colr)
+            }
+            ColorFormats::HSL if HSLFormat::matches(colr) => HSLFormat::parse(colr),
+            ColorFormats::HWB if HWBFormat::matches(colr) => HWBFormat::parse(colr),
+            ColorFormats::Named if NamedFormat::matches(colr) => NamedFormat::parse(colr),
+            ColorFormats::XtermIndex if XtermIndexFormat::matches(colr) => {
+                XtermIndexFormat::parse(colr)
+            }
+            ColorFormats::Integer if IntegerFormat::matches(colr) => {
+                IntegerFormat::parse_with(colr, byte_order)
+            }
+            ColorFormats::Hex if HexFormat::matches(colr) => HexFormat::parse(colr),
+            _ => continue,
+        };
+    }
+    Err(ParseFormatError::NoAllowedFormat {
+        input: colr.into(),
+        allowed: allowed.to_vec(),
+    })
+}
+
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Default)]
+pub enum OutOfRangePolicy {
+    // a ParseFormatError::OutOfRange, as the parsers have always done
+    #[default]
+    Error,
+    // components past their range are taken as the nearest end of it,
+    // `rgb(300, 0, -5%)` as `rgb(255, 0, 0%)`. integers past their bytes
+    // stay an error, there is no nearest color to them
+    Clamp,
+}
+
+// how colors are read, by parse_any_with_opts, scan_with_opts and the
+// reader's parse_with_opts alike. the defaults parse as parse_any does:
+//
+//   let options = ParseOptions::new().allow(&[ColorFormats::Hex]).lenient();
+#[derive(PartialEq, Eq, Debug, Clone, Default)]
+pub struct ParseOptions {
+    // only these formats are tried, None tries every format like parse_any
+    pub formats: Option<Vec<ColorFormats>>,
+    // also read `,` as a decimal point, see delocalize. never on by default
+    // since it's ambiguous with the usual syntax
+    pub locale_tolerant: bool,
+    // how IntegerFormat reads its value when it is allowed
+    pub byte_order: ByteOrder,
+    // what happens to input that reads as more than one color
+    pub ambiguity: AmbiguityPolicy,
+    pub out_of_range: OutOfRangePolicy,
+    // the reader skips lines that fail to parse, even with
+    // ReaderOptions::strict. single colors parse the same either way
+    pub lenient: bool,
+    // the reader's limits, in place of ReaderOptions::limits when set
+    pub limits: Option<Limits>,
+}
+
+impl ParseOptions {
+    pub fn new() -> Self {
+        ParseOptions::default()
+    }
+
+    pub fn allow(self, formats: &[ColorFormats]) -> Self {
+        ParseOptions {
+            formats: Some(formats.to_vec()),
+            ..self
+        }
+    }
+
+    pub fn locale_tolerant(self) -> Self {
+        ParseOptions {
+            locale_tolerant: true,
+            ..self
+        }
+    }
+
+    pub fn byte_order(self, byte_order: ByteOrder) -> Self {
+        ParseOptions { byte_order, ..self }
+    }
+
+    pub fn ambiguity(self, ambiguity: AmbiguityPolicy) -> Self {
+        ParseOptions { ambiguity, ..self }
+    }
+
+    pub fn clamp_out_of_range(self) -> Self {
+        ParseOptions {
+            out_of_range: OutOfRangePolicy::Clamp,
+            ..self
+        }
+    }
+
+    pub fn lenient(self) -> Self {
+        ParseOptions {
+            lenient: true,
+            ..self
+        }
+    }
+
+    pub fn limits(self, limits: Limits) -> Self {
+        ParseOptions {
+            limits: Some(limits),
+            ..self
+        }
+    }
+
+    pub fn warn_ambiguous() -> Self {
+        ParseOptions {
+            ambiguity: AmbiguityPolicy::Warn,
+            ..Default::default()
+        }
+    }
+
+    pub fn strict_ambiguous() -> Self {
+        ParseOptions {
+            ambiguity: AmbiguityPolicy::Strict,
+            ..Default::default()
+        }
+    }
+}
+
+// AmbiguityPolicy::Warn parses like Priority here, see parse_with_ambiguities
+// for the other readings
+pub fn parse_any_with_opts(
+    colr: &str,
+    options: &ParseOptions,
+) -> Result<Canonical, ParseFormatError> {
+    parse_with_ambiguities(colr, options).map(|(color, _)| color)
+}
+
+// the name parse_any_with_opts had first
+pub fn parse_with_options(colr: &str, options: &ParseOptions) -> Result<Canonical, ParseFormatError> {
+    parse_any_with_opts(colr, options)
+}
+
+fn parse_unchecked(colr: &str, options: &ParseOptions) -> Result<Canonical, ParseFormatError> {
+    let parse_once = |colr: &str| match options.formats {
+        Some(ref formats) => parse_allowed(colr, formats, options.byte_order),
+        None => parse_any(colr),
+    };
+    // every component past its range is clamped in turn, there are at most
+    // four of them
+    let parse = |colr: &str| {
+        let mut colr = Cow::Borrowed(colr);
+        loop {
+            match parse_once(&colr) {
+                Err(ParseFormatError::OutOfRange { format, ref input })
+                    if options.out_of_range == OutOfRangePolicy::Clamp =>
+                {
+                    match clamp_component(&colr, format, input) {
+                        Some(clamped) => colr = Cow::Owned(clamped),
+                        None => return parse_once(&colr),
+                    }
+                }
+                result => return result,
+            }
+        }
+    };
+    if !options.locale_tolerant {
+        return parse(colr);
+    }
+    match delocalize(colr) {
+        None => parse(colr),
+        Some((delocalized, false)) => parse(&delocalized),
+        Some((delocalized, true)) => match (parse(colr), parse(&delocalized)) {
+            (Ok(usual), Ok(localized)) if usual != localized => {
+                Err(ParseFormatError::AmbiguousDecimal { input: colr.into() })
+            }
+            (Ok(color), _) | (Err(_), Ok(color)) => Ok(color),
+            (Err(e), Err(_)) => Err(e),
+        },
+    }
+}
+
+// the input with `component` replaced by the nearest value in range. None
+// if there is none or it is already in range, which ends the clamping
+fn clamp_component(colr: &str, format: ColorFormats, component: &str) -> Option<String> {
+    let (number, percent) = match component.strip_suffix('%') {
+        Some(number) => (number, true),
+        None => (component, false),
+    };
+    let value: f32 = number.parse().ok()?;
+    let clamped = match (percent, format) {
+        (true, _) => format!("{}%", value.clamp(0.0, 100.0)),
+        (false, ColorFormats::Integer) => return None,
+        (false, ColorFormats::RGBu8 | ColorFormats::SpaceSeparated) => {
+            value.clamp(0.0, 255.0).round().to_string()
+        }
+        (false, _) => value.clamp(0.0, 1.0).to_string(),
+    };
+    if clamped == component {
+        return None;
+    }
+    // the component on its own, not a part of a longer number
+    let part_of_number = |c: char| c.is_ascii_alphanumeric() || matches!(c, '.' | '%' | '-' | '+');
+    let (start, _) = colr.match_indices(component).find(|&(start, _)| {
+        let end = start + component.len();
+        !colr[..start].ends_with(part_of_number) && !colr[end..].starts_with(part_of_number)
+    })?;
+    Some(format!(
+        "{}{}{}",
+        &colr[..start],
+        clamped,
+        &colr[start + component.len()..]
+    ))
+}
+
+// rewrites a functional color written with `,` as the decimal point to the
+// usual syntax, along with whether that reading is a guess:
+//   - if the arguments contain a `;` it separates them and every `,` is a
+//     decimal point, `rgb(0,5; 0,25; 1)`. this is certain
+//   - otherwise a `,` between two digits is a decimal point and any other
+//     `,` separates, `rgb(0,5, 0,25, 1)`. this is a guess, which the caller
+//     only takes when the input doesn't parse the usual way as well
+// None if there is nothing to rewrite
+fn delocalize(colr: &str) -> Option<(String, bool)> {
+    let colr = colr.trim().strip_suffix(';').unwrap_or(colr.trim());
+    let (name, args) = colr.split_once('(')?;
+    let args = args.strip_suffix(')')?;
+    if args.contains(';') {
+        let args: Vec<_> = args.split(';').map(|c| c.trim().replace(',', ".")).collect();
+        return Some((format!("{}({})", name, args.join(", ")), false));
+    }
+    let chars: Vec<char> = args.chars().collect();
+    let decimal = |i: usize| {
+        i > 0
+            && chars[i - 1].is_ascii_digit()
+            && chars.get(i + 1).is_some_and(char::is_ascii_digit)
+    };
+    if !(0..chars.len()).any(|i| chars[i] == ',' && decimal(i)) {
+        return None;
+    }
+    let args: String = chars
+        .iter()
+        .enumerate()
+        .map(|(i, &c)| if c == ',' && decimal(i) { '.' } else { c })
+        .collect();
+    Some((format!("{}({})", name, args), true))
+}
+
+impl ColorFormat for HSLFormat {
+    fn matches(colr: &str) -> bool {
+        HSL_REGEX.is_match(strip(colr))
+    }
+
+    fn parse(colr: &str) -> Result<Canonical, ParseFormatError> {
+        let format = ColorFormats::HSL;
+        let caps = HSL_REGEX
+            .captures(strip(colr))
+            .ok_or_else(|| ParseFormatError::NoMatch {
+                format,
+                input: colr.into(),
+            })?;
+        let hsl = HSL::new(
+            extract_hue(&caps["h"], format)?,
+            extract_percentage(&caps["s"], format)?,
+            extract_percentage(&caps["l"], format)?,
         );
+        let a = match caps.name("a") {
+            Some(a) => extract_alpha(a.as_str(), format)?,
+            None => 1.0,
+        };
+        Ok(Canonical::from_hsl(hsl, a))
+    }
 
-        assert_eq!(
-            RGBFloatFormat::parse("rgba(0.5, 0.123, 0.1010, 0.90)").unwrap(),
-            Canonical::from_f(0.5, 0.123, 0.1010, 0.90)
+    fn emit_with(color: &Canonical, options: &EmitOptions) -> String {
+        let hsl = color.to_hsl();
+        emit_hue_function("hsl", [hsl.h.degrees(), hsl.s, hsl.l], color, options)
+    }
+}
+
+impl ColorFormat for HWBFormat {
+    fn matches(colr: &str) -> bool {
+        HWB_REGEX.is_match(strip(colr))
+    }
+
+    fn parse(colr: &str) -> Result<Canonical, ParseFormatError> {
+        let format = ColorFormats::HWB;
+        let caps = HWB_REGEX
+            .captures(strip(colr))
+            .ok_or_else(|| ParseFormatError::NoMatch {
+                format,
+                input: colr.into(),
+            })?;
+        let a = match caps.name("a") {
+            Some(a) => extract_alpha(a.as_str(), format)?,
+            None => 1.0,
+        };
+        Ok(Canonical::from_hwb(
+            extract_hue(&caps["h"], format)?,
+            extract_percentage(&caps["w"], format)?,
+            extract_percentage(&caps["b"], format)?,
+            a,
+        ))
+    }
+
+    fn emit_with(color: &Canonical, options: &EmitOptions) -> String {
+        let max = color.r.max(color.g).max(color.b) as f32 / 255.0;
+        let min = color.r.min(color.g).min(color.b) as f32 / 255.0;
+        emit_hue_function("hwb", [color.to_hsl().h.degrees(), min, 1.0 - max], color, options)
+    }
+}
+
+fn parse_number(s: &str, format: ColorFormats) -> Result<f32, ParseFormatError> {
+    s.parse::<f32>()
+        .map_err(|source| ParseFormatError::InvalidNumber {
+            format,
+            input: s.into(),
+            source,
+        })
+}
+
+// in degrees, within [0, 360). a bare number is in degrees as well
+fn extract_hue(s: &str, format: ColorFormats) -> Result<f32, ParseFormatError> {
+    if is_none(s) {
+        return Ok(0.0);
+    }
+    let number = s.trim_end_matches(|c: char| c.is_ascii_alphabetic());
+    let value = parse_number(number, format)?;
+    let unit = &s[number.len()..];
+    let degrees = match unit.to_ascii_lowercase().as_str() {
+        "" | "deg" => value,
+        "grad" => value * 0.9,
+        "rad" => value.to_degrees(),
+        "turn" => value * 360.0,
+        _ => {
+            return Err(ParseFormatError::UnknownUnit {
+                format,
+                unit: unit.into(),
+            })
+        }
+    };
+    Ok(degrees.rem_euclid(360.0))
+}
+
+// `50%` as 0.5, limited to [0%, 100%]
+fn extract_percentage(s: &str, format: ColorFormats) -> Result<f32, ParseFormatError> {
+    if is_none(s) {
+        return Ok(0.0);
+    }
+    let number = s.strip_suffix('%').unwrap_or(s);
+    let value = parse_number(number, format)? / 100.0;
+    if (0.0..=1.0).contains(&value) {
+        Ok(value)
+    } else {
+        Err(ParseFormatError::OutOfRange {
+            format,
+            input: s.into(),
+        })
+    }
+}
+
+// a number in [0, 1] or a percentage, read into [0, 1]
+fn extract_alpha(s: &str, format: ColorFormats) -> Result<f32, ParseFormatError> {
+    if is_none(s) {
+        return Ok(0.0);
+    }
+    if s.ends_with('%') {
+        return extract_percentage(s, format);
+    }
+    let value = parse_number(s, format)?;
+    if (0.0..=1.0).contains(&value) {
+        Ok(value)
+    } else {
+        Err(ParseFormatError::OutOfRange {
+            format,
+            input: s.into(),
+        })
+    }
+}
+
+impl SpaceSeparatedFormat {
+    pub fn parse_named(colr: &str) -> Result<(Canonical, Option<&str>), ParseFormatError> {
+        let format = ColorFormats::SpaceSeparated;
+        let caps = SPACE_SEPARATED_REGEX
+            .captures(strip(colr))
+            .ok_or_else(|| ParseFormatError::NoMatch {
+                format,
+                input: colr.into(),
+            })?;
+        let channel = |name: &str| -> Result<u8, ParseFormatError> {
+            let s = caps.name(name).unwrap().as_str();
+            s.parse::<u8>().map_err(|_| ParseFormatError::OutOfRange {
+                format,
+                input: s.into(),
+            })
+        };
+        let color = Canonical::new(channel("r")?, channel("g")?, channel("b")?, u8::MAX);
+        Ok((color, caps.name("name").map(|name| name.as_str())))
+    }
+}
+
+impl ColorFormat for SpaceSeparatedFormat {
+    fn matches(colr: &str) -> bool {
+        SPACE_SEPARATED_REGEX.is_match(strip(colr))
+    }
+
+    fn parse(colr: &str) -> Result<Canonical, ParseFormatError> {
+        SpaceSeparatedFormat::parse_named(colr).map(|(color, _)| color)
+    }
+
+    fn emit_with(color: &Canonical, _options: &EmitOptions) -> String {
+        format!("{} {} {}", color.r, color.g, color.b)
+    }
+
+    fn can_emit(color: &Canonical) -> bool {
+        color.a == u8::MAX
+    }
+}
+
+impl IntegerFormat {
+    // values past 24 bits are out of range for the orders without alpha
+    pub fn parse_with(colr: &str, order: ByteOrder) -> Result<Canonical, ParseFormatError> {
+        let format = ColorFormats::Integer;
+        let caps = INTEGER_REGEX
+            .captures(strip(colr))
+            .ok_or_else(|| ParseFormatError::NoMatch {
+                format,
+                input: colr.into(),
+            })?;
+        let value = match (caps.name("hex"), caps.name("dec")) {
+            (Some(hex), _) => u32::from_str_radix(hex.as_str(), 16),
+            (_, Some(dec)) => dec.as_str().parse::<u32>(),
+            _ => unreachable!(),
+        };
+        let out_of_range = || ParseFormatError::OutOfRange {
+            format,
+            input: strip(colr).into(),
+        };
+        let value = value.map_err(|_| out_of_range())?;
+        let rgba = match order {
+            ByteOrder::Rgb | ByteOrder::Bgr if value > 0xFF_FFFF => return Err(out_of_range()),
+            ByteOrder::Rgb => value << 8 | 0xFF,
+            ByteOrder::Bgr => value.swap_bytes() | 0xFF,
+            ByteOrder::Rgba => value,
+            ByteOrder::Argb => value.rotate_left(8),
+        };
+        Ok(Canonical::unpack(Packed(rgba)))
+    }
+}
+
+impl ColorFormat for IntegerFormat {
+    fn matches(colr: &str) -> bool {
+        INTEGER_REGEX.is_match(strip(colr))
+    }
+
+    fn parse(colr: &str) -> Result<Canonical, ParseFormatError> {
+        IntegerFormat::parse_with(colr, ByteOrder::default())
+    }
+
+    // in the default byte order, which has no alpha
+    fn emit_with(color: &Canonical, options: &EmitOptions) -> String {
+        format!("0x{}", options.hex(format!("{:06x}", RGB::from(*color).pack_rgb())))
+    }
+
+    fn can_emit(color: &Canonical) -> bool {
+        color.a == u8::MAX
+    }
+}
+
+impl ColorFormat for XtermIndexFormat {
+    fn matches(colr: &str) -> bool {
+        XTERM_INDEX_REGEX.is_match(strip(colr))
+    }
+
+    fn parse(colr: &str) -> Result<Canonical, ParseFormatError> {
+        let format = ColorFormats::XtermIndex;
+        let caps = XTERM_INDEX_REGEX
+            .captures(strip(colr))
+            .ok_or_else(|| ParseFormatError::NoMatch {
+                format,
+                input: colr.into(),
+            })?;
+        let index: u8 = caps["index"]
+            .parse()
+            .map_err(|_| ParseFormatError::OutOfRange {
+                format,
+                input: strip(colr).into(),
+            })?;
+        Ok(XTERM_256[index as usize])
+    }
+
+    // the first slot with the color, tmux's spelling
+    fn emit_with(color: &Canonical, _options: &EmitOptions) -> String {
+        let index = XTERM_256.iter().position(|c| c == color).unwrap_or_default();
+        format!("colour{}", index)
+    }
+
+    fn can_emit(color: &Canonical) -> bool {
+        XTERM_256.contains(color)
+    }
+}
+
+impl ColorFormat for NamedFormat {
+    fn matches(colr: &str) -> bool {
+        strip(colr).eq_ignore_ascii_case("transparent")
+    }
+
+    fn parse(colr: &str) -> Result<Canonical, ParseFormatError> {
+        if !NamedFormat::matches(colr) {
+            return Err(ParseFormatError::NoMatch {
+                format: ColorFormats::Named,
+                input: colr.into(),
+            });
+        }
+        Ok(Canonical::new(0, 0, 0, 0))
+    }
+
+    fn emit_with(_color: &Canonical, _options: &EmitOptions) -> String {
+        "transparent".into()
+    }
+
+    fn can_emit(color: &Canonical) -> bool {
+        *color == Canonical::new(0, 0, 0, 0)
+    }
+}
+
+// hex and u8 rgb are ASCII, so they can be parsed straight from bytes that
+// aren't valid UTF-8 as a whole, like a slice of a memory mapped file.
+// neither allocates unless parsing fails
+impl HexFormat {
+    pub fn parse_bytes(colr: &[u8]) -> Result<Canonical, ParseFormatError> {
+        if !scan_hex(colr) {
+            return Err(ParseFormatError::NoMatch {
+                format: ColorFormats::Hex,
+                input: String::from_utf8_lossy(colr).into_owned(),
+            });
+        }
+        Ok(Canonical::parse_from_hex_bytes(strip_bytes(colr))?)
+    }
+}
+
+impl RGBu8Format {
+    pub fn parse_bytes(colr: &[u8]) -> Result<Canonical, ParseFormatError> {
+        let (rgb, a) = scan_rgb_u8(colr).ok_or_else(|| ParseFormatError::NoMatch {
+            format: ColorFormats::RGBu8,
+            input: String::from_utf8_lossy(colr).into_owned(),
+        })?;
+        let channel = |s: &str| -> Result<u8, ParseFormatError> {
+            if is_none(s) {
+                return Ok(0);
+            }
+            s.parse::<u8>().map_err(|_| ParseFormatError::OutOfRange {
+                format: ColorFormats::RGBu8,
+                input: s.into(),
+            })
+        };
+        let a = match a.map(ascii) {
+            Some(a) if unit_alpha(a) => {
+                (extract_alpha(a, ColorFormats::RGBu8)? * 255.0).round() as u8
+            }
+            Some(a) => channel(a)?,
+            None => u8::MAX,
+        };
+        let [r, g, b] = rgb.map(ascii);
+        Ok(Canonical::new(channel(r)?, channel(g)?, channel(b)?, a))
+    }
+}
+
+impl ColorFormat for HexFormat {
+    fn matches(colr: &str) -> bool {
+        scan_hex(colr.as_bytes())
+    }
+
+    fn parse(colr: &str) -> Result<Canonical, ParseFormatError> {
+        HexFormat::parse_bytes(colr.as_bytes())
+    }
+
+    // `#rrggbb`, or `#rrggbbaa` if translucent
+    fn emit_with(color: &Canonical, options: &EmitOptions) -> String {
+        match options.always_alpha {
+            true => options.hex(color.to_hex_string()),
+            false => options.hex(hex(color)),
+        }
+    }
+}
+
+impl ColorFormat for RGBu8Format {
+    fn matches(colr: &str) -> bool {
+        scan_rgb_u8(colr.as_bytes()).is_some()
+    }
+
+    fn parse(colr: &str) -> Result<Canonical, ParseFormatError> {
+        RGBu8Format::parse_bytes(colr.as_bytes())
+    }
+
+    fn emit_with(color: &Canonical, options: &EmitOptions) -> String {
+        let comma = options.spacing.comma();
+        let rgb = [color.r, color.g, color.b].map(|c| c.to_string()).join(comma);
+        match options.alpha(color) {
+            None => format!("rgb({})", rgb),
+            // a bare 1 reads back as opaque, 1/255 needs the 0..1 scale
+            Some(_) if color.a == 1 => format!("rgba({}{}0.004)", rgb, comma),
+            Some(_) => format!("rgba({}{}{})", rgb, comma, color.a),
+        }
+    }
+}
+
+// components may be any float literal including bare `0` and `1`, but at
+// least one of r, g and b has to be written as a float. colors of only
+// integer channels are RGBu8 whatever the alpha, `rgba(255, 0, 0, 0.5)`
+impl ColorFormat for RGBFloatFormat {
+    fn matches(color_str: &str) -> bool {
+        match float_captures(strip(color_str)) {
+            Some(caps) => ["r", "g", "b"]
+                .iter()
+                .filter_map(|name| caps.name(name))
+                .map(|c| c.as_str())
+                .any(|c| !is_none(c) && !c.ends_with('%') && c.contains(['.', 'e', 'E'])),
+            None => false,
+        }
+    }
+
+    fn parse(color_str: &str) -> Result<Canonical, ParseFormatError> {
+        let caps = float_captures(strip(color_str));
+        let caps = match caps {
+            Some(captures) => captures,
+            None => {
+                return Err(ParseFormatError::NoMatch {
+                    format: ColorFormats::RGBf,
+                    input: color_str.into(),
+                })
+            }
+        };
+        let channel = |c: &str| match is_none(c) {
+            true => Ok(0.0),
+            false => extract_float_in_range(c),
+        };
+        let r = channel(&caps["r"])?;
+        let g = channel(&caps["g"])?;
+        let b = channel(&caps["b"])?;
+        let a = match caps.name("a") {
+            Some(a) => extract_alpha(a.as_str(), ColorFormats::RGBf)?,
+            None => 1.0,
+        };
+        Ok(Canonical::from_f(r, g, b, a))
+    }
+
+    // every component with a decimal point, so it isn't read as RGBu8
+    fn emit_with(color: &Canonical, options: &EmitOptions) -> String {
+        let float = |c: f32| {
+            let s = options.decimal(c);
+            if s.contains('.') {
+                s
+            } else {
+                s + ".0"
+            }
+        };
+        let comma = options.spacing.comma();
+        let rgb = [color.r, color.g, color.b]
+            .map(|c| float(c as f32 / 255.0))
+            .join(comma);
+        match options.alpha(color) {
+            None => format!("rgb({})", rgb),
+            Some(a) => format!("rgba({}{}{})", rgb, comma, float(a)),
+        }
+    }
+}
+
+// the legacy comma separated syntax or the space separated one
+fn float_captures(colr: &str) -> Option<Captures<'_>> {
+    RGBA_F_REGEX
+        .captures(colr)
+        .or_else(|| RGB_F_SPACE_REGEX.captures(colr))
+}
+
+fn extract_float_in_range(s: &str) -> Result<f32, ParseFormatError> {
+    let f = s
+        .parse::<f32>()
+        .map_err(|source| ParseFormatError::InvalidNumber {
+            format: ColorFormats::RGBf,
+            input: s.into(),
+            source,
+        })?;
+    if (0.0..=1.0).contains(&f) {
+        Ok(f)
+    } else {
+        Err(ParseFormatError::OutOfRange {
+            format: ColorFormats::RGBf,
+            input: s.into(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests_rgb_float_format {
+    use super::*;
+    use crate::errors::ErrorKind;
+
+    #[test]
+    fn test_color_format_matches() {
+        let ok_candidates = vec![
+            "rgb(0.0, 0.0, 0.0)",         // - rgb variations
+            "rgb(0.0, 0.0, 0.0, 0.0)",    // |
+            "rgba(0.0, 0.0, 0.0)",        // |
+            "rgba(0.0, 0.0, 0.0, 0.0)",   // |
+            "RGBA(0.0, 0.0, 0.0)",        // - casing
+            "RgBa(0.0, 0.0, 0.0)",        // |
+            "rGb(0.0, 0.0, 0.0)",         // |
+            " rgb( 0.5  ,  1.0 ,0.25 ) ", // - strange spacings
+            "rgb(0.111111111111111111, 0.2, 0.12345, 0.696969)",
+            "rgba(0.00, 0.0000, 0.00000, 0.0)",
+            "rgb(.5, 1, 0)",          // - other float literals
+            "rgba(1e-1, 0.5e0, 1E0)", // |
+            "rgb(1., 0, 0)",          // |
+            // invalid but matches
+            "rgb(1.5, 0.91, 1.99999)", // - starts with `1` (but > 1)
+        ];
+
+        for cand in ok_candidates {
+            assert!(RGBFloatFormat::matches(cand))
+        }
+
+        let ko_candidates = vec![
+            "rgb(0, 0, 0)",
+            "rgba(0, 0, 0)",
+            "rgba(0, 0, 1, 255)",
+            "rgba(0, 0, 0, 0.5)", // the alpha alone doesn't make it float
+            "rgb(., 0.5, 0.5)",
+            "rgb(1e, 0.5, 0.5)",
+            "rgb(-1.0, 1.0, 0.0)",
+            "rgba(1.0.0, 1.0, 0.1001)",
+        ];
+
+        for cand in ko_candidates {
+            assert!(!RGBFloatFormat::matches(cand))
+        }
+    }
+
+    #[test]
+    fn test_color_format_parse() {
+        assert_eq!(
+            RGBFloatFormat::parse("rgb(0.0, 0.0, 0.0)").unwrap(),
+            Canonical::from_f(0.0, 0.0, 0.0, 1.0)
+        );
+
+        assert_eq!(
+            RGBFloatFormat::parse("rgba(0.5, 0.123, 0.1010, 0.90)").unwrap(),
+            Canonical::from_f(0.5, 0.123, 0.1010, 0.90)
+        );
+
+        for (colr, expected) in [
+            ("rgb(.5, 0, 1)", Canonical::from_f(0.5, 0.0, 1.0, 1.0)),
+            ("rgb(1e-1, 0.5e0, 1.)", Canonical::from_f(0.1, 0.5, 1.0, 1.0)),
+            ("rgba(0, 0, 0.25E+0, 1)", Canonical::from_f(0.0, 0.0, 0.25, 1.0)),
+            ("rgba(0.2, 0.4, 0, 1)", Canonical::from_f(0.2, 0.4, 0.0, 1.0)),
+        ] {
+            assert_eq!(RGBFloatFormat::parse(colr).unwrap(), expected, "{}", colr);
+        }
+
+        for colr in ["rgb(1e1, 0.5, 0.5)", "rgb(0.5, 2e0, 0)", "rgba(0.5, 0, 0, 15e-1)"] {
+            assert_eq!(
+                RGBFloatFormat::parse(colr).unwrap_err().kind(),
+                ErrorKind::OutOfRange,
+                "{}",
+                colr
+            );
+        }
+    }
+
+    // integer channels with a CSS alpha are u8 colors, the alpha on 0..1
+    #[test]
+    fn test_u8_channels_with_unit_alpha() {
+        for (colr, expected) in [
+            ("rgba(255, 0, 0, 0.5)", Canonical::new(255, 0, 0, 128)),
+            ("rgba(0, 0, 0, 1)", Canonical::new(0, 0, 0, 255)),
+            ("rgba(0, 0, 0, 0)", Canonical::new(0, 0, 0, 0)),
+            ("rgba(10, 20, 30, .25)", Canonical::new(10, 20, 30, 64)),
+            ("rgba(10, 20, 30, 2)", Canonical::new(10, 20, 30, 2)),
+        ] {
+            assert_eq!(parse_any(colr).unwrap(), expected, "{}", colr);
+        }
+        assert_eq!(
+            RGBAFormat::parse("(255, 0, 0, 1)").unwrap(),
+            Canonical::new(255, 0, 0, 255)
+        );
+        assert_eq!(
+            parse_any("rgba(255, 0, 0, 1.5)").unwrap_err().kind(),
+            ErrorKind::OutOfRange
+        );
+
+        // an alpha of 1/255 is written so it reads back
+        let faint = Canonical::new(1, 2, 3, 1);
+        let emitted = RGBu8Format::emit(&faint);
+        assert_eq!(emitted, "rgba(1,2,3,0.004)");
+        assert_eq!(parse_any(&emitted).unwrap(), faint);
+    }
+}
+
+#[cfg(test)]
+mod tests_rgba_format {
+    use super::*;
+    use crate::errors::ErrorKind;
+
+    #[test]
+    fn test_rgba_format() {
+        assert!(RGBAFormat::matches(" (1, 2, 3); "));
+        assert!(!RGBAFormat::matches("rgb(1, 2, 3)"));
+        assert!(!RGBAFormat::matches("(1, 2)"));
+        let parse = |colr: &str| RGBAFormat::parse(colr).unwrap();
+        assert_eq!(parse("(254, 128, 25)"), Canonical::new(254, 128, 25, 255));
+        assert_eq!(parse("(1, 2, 3, 128)"), Canonical::new(1, 2, 3, 128));
+        assert_eq!(parse("(1, 2, 3, 0.5)"), Canonical::new(1, 2, 3, 128));
+        assert_eq!(parse("(1.0, 0.5, 0.0)"), Canonical::new(255, 128, 0, 255));
+        assert_eq!(parse("(1.0,0.5,0.0,1.0)"), Canonical::new(255, 128, 0, 255));
+
+        let e = RGBAFormat::parse("(256, 0, 0)").unwrap_err();
+        assert_eq!(e.kind(), ErrorKind::OutOfRange);
+        let e = RGBAFormat::parse("(1.5, 0.5, 0.5)").unwrap_err();
+        assert_eq!(e.kind(), ErrorKind::OutOfRange);
+        let e = RGBAFormat::parse("rgb(1, 2, 3)").unwrap_err();
+        assert_eq!(e.kind(), ErrorKind::NoMatch);
+
+        for color in [Canonical::new(1, 2, 3, 255), Canonical::new(1, 2, 3, 4)] {
+            assert_eq!(parse(&RGBAFormat::emit(&color)), color);
+        }
+        assert_eq!(RGBAFormat::emit(&Canonical::new(1, 2, 3, 4)), "(1,2,3,4)");
+    }
+}
+
+#[cfg(test)]
+mod tests_parse_any {
+    use super::*;
+    use crate::errors::ErrorKind;
+
+    #[test]
+    fn test_parse_colors() {
+        let colrs: Vec<String> = (0..200).map(|i| format!("rgb({}, 0, 0)", i)).collect();
+        let parsed = parse_colors(&colrs).unwrap();
+        assert_eq!(parsed[199], Canonical::new(199, 0, 0, 255));
+        #[cfg(feature = "parallel")]
+        assert_eq!(parse_colors_par(&colrs).unwrap(), parsed);
+
+        let bad = ["#000000", "rgb(300, 0, 0)", "#zz", "rgb(256, 0, 0)"];
+        let e = parse_colors(&bad).unwrap_err();
+        assert_eq!(e.to_string(), "300 is out of range for a RGBu8 component");
+        #[cfg(feature = "parallel")]
+        assert_eq!(parse_colors_par(&bad).unwrap_err().to_string(), e.to_string());
+    }
+
+    #[test]
+    fn test_parse_any() {
+        let expected = Canonical::new(255, 0, 51, 255);
+        for colr in [
+            "#ff0033",
+            " #FF0033ff ",
+            "rgb(1.0, 0.0, 0.2)",
+            "rgb(255, 0, 51)",
+            "rgba(255, 0, 51, 255)",
+            "color(srgb 1 0 0.2)",
+        ] {
+            assert_eq!(parse_any(colr).unwrap(), expected, "{}", colr);
+        }
+
+        for (colr, kind, format) in [
+            ("rgb(256, 0, 0)", ErrorKind::OutOfRange, ColorFormats::RGBu8),
+            ("#ff00", ErrorKind::NoMatch, ColorFormats::Hex),
+            ("red", ErrorKind::NoMatch, ColorFormats::Hex),
+        ] {
+            let e = parse_any(colr).unwrap_err();
+            assert_eq!((e.kind(), e.format()), (kind, Some(format)), "{}", colr);
+        }
+    }
+
+    #[test]
+    fn test_none_and_transparent() {
+        for (colr, expected) in [
+            ("transparent", Canonical::new(0, 0, 0, 0)),
+            (" Transparent;", Canonical::new(0, 0, 0, 0)),
+            ("rgba(255, 0, 0, none)", Canonical::new(255, 0, 0, 0)),
+            ("rgba(1.0, 0.5, 0, NONE)", Canonical::from_f(1.0, 0.5, 0.0, 0.0)),
+            ("color(srgb none 1 1)", Canonical::new(0, 255, 255, 255)),
+            ("color(srgb 1 none 1)", Canonical::new(255, 0, 255, 255)),
+            ("color(srgb 1 1 none)", Canonical::new(255, 255, 0, 255)),
+            ("color(srgb 1 1 1 / none)", Canonical::new(255, 255, 255, 0)),
+            ("rgb(255 0 0)", Canonical::new(255, 0, 0, 255)),
+            ("rgb(255 0 0 / none)", Canonical::new(255, 0, 0, 0)),
+            ("rgb(none 255 255)", Canonical::new(0, 255, 255, 255)),
+            ("rgb(255 none 255 / 50%)", Canonical::new(255, 0, 255, 128)),
+            ("rgba( 255  255 NONE/0.5 )", Canonical::new(255, 255, 0, 128)),
+            ("rgb(none 0.5 1)", Canonical::from_f(0.0, 0.5, 1.0, 1.0)),
+            ("rgb(1.0 none 1 / 0.5)", Canonical::from_f(1.0, 0.0, 1.0, 0.5)),
+            ("rgb(1 0.5 none / none)", Canonical::from_f(1.0, 0.5, 0.0, 0.0)),
+        ] {
+            assert_eq!(parse_any(colr).unwrap(), expected, "{}", colr);
+        }
+        // the legacy syntax only has `none` as the alpha, and the two don't mix
+        assert!(parse_any("rgb(none, 0, 0)").is_err());
+        assert!(parse_any("rgb(0.5, none, 0)").is_err());
+        assert!(parse_any("rgb(255, 0, 0 / 1)").is_err());
+        assert!(parse_any("rgb(255 0 0, 1)").is_err());
+        assert!(parse_any("rgb(255 0 0 1)").is_err());
+        assert!(parse_any("rgb(255 0 0 /)").is_err());
+        assert!(parse_any("color(srgb nothing 1 1)").is_err());
+        assert!(parse_any_with("transparent", &[ColorFormats::Hex]).is_err());
+        assert_eq!(
+            parse_any_with("transparent", &[ColorFormats::Named]).unwrap(),
+            Canonical::new(0, 0, 0, 0)
+        );
+    }
+
+    #[test]
+    fn test_messy_input() {
+        for (colr, expected) in [
+            ("#aabbcc;", Canonical::new(170, 187, 204, 255)),
+            ("\t#aabbcc ;\r\n", Canonical::new(170, 187, 204, 255)),
+            ("rgb(1, 2, 3);\n", Canonical::new(1, 2, 3, 255)),
+            ("  rgb (1,2,3)  ", Canonical::new(1, 2, 3, 255)),
+            ("RGBA\t(1, 2, 3, 4);", Canonical::new(1, 2, 3, 4)),
+            ("rgb (1.0, 0.0, 0.0);", Canonical::new(255, 0, 0, 255)),
+            ("color(srgb 1 0 0);\n", Canonical::new(255, 0, 0, 255)),
+            ("color (srgb 0 0 1)", Canonical::new(0, 0, 255, 255)),
+        ] {
+            assert_eq!(parse_any(colr).unwrap(), expected, "{:?}", colr);
+            assert!(parse_exact(colr).is_err(), "{:?}", colr);
+        }
+        assert_eq!(
+            HexFormat::parse("#aabbcc;\n").unwrap(),
+            Canonical::new(170, 187, 204, 255)
+        );
+        assert!(RGBu8Format::matches(" rgb(1, 2, 3); "));
+        assert!(!RGBu8Format::matches("rgb (1, 2, 3)"));
+
+        assert!(parse_any("#aabbcc;;").is_err());
+        assert!(parse_any("rgb(1, 2, 3)\n;\nrgb(1, 2, 3)").is_err());
+        assert_eq!(
+            parse_exact("#aabbcc").unwrap(),
+            Canonical::new(170, 187, 204, 255)
+        );
+    }
+
+    #[test]
+    fn test_parse_any_with() {
+        let colr = "rgb(255, 0, 51)";
+        let expected = Canonical::new(255, 0, 51, 255);
+        assert_eq!(parse_any_with(colr, &ColorFormats::ALL).unwrap(), expected);
+        assert_eq!(
+            parse_any_with(colr, &[ColorFormats::Hex, ColorFormats::RGBu8]).unwrap(),
+            expected
+        );
+        assert_eq!(
+            parse_any_with("#ff0033", &[ColorFormats::Hex]).unwrap(),
+            expected
+        );
+
+        let e = parse_any_with(colr, &[ColorFormats::Hex]).unwrap_err();
+        assert_eq!(e.kind(), ErrorKind::NoAllowedFormat);
+        assert_eq!(e.format(), None);
+        assert_eq!(
+            e.to_string(),
+            "rgb(255, 0, 51) is not written in an allowed format (hex)"
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests_color_function_format {
+    use super::*;
+    use crate::errors::ErrorKind;
+
+    #[test]
+    fn test_color_format_matches() {
+        let ok_candidates = vec![
+            "color(srgb 1 0 0)",
+            "color(display-p3 1 0 0)",
+            "color(display-p3 0.5 .25 1e-1 / 0.5)",
+            "COLOR(rec2020 100% 0% 50% / 50%)",
+            " color( srgb 0.1   0.2 0.3 )  ",
+        ];
+
+        for cand in ok_candidates {
+            assert!(ColorFunctionFormat::matches(cand), "{}", cand)
+        }
+
+        let ko_candidates = vec![
+            "color(srgb 1 0)",
+            "color(srgb, 1, 0, 0)",
+            "color(1 0 0)",
+            "rgb(1 0 0)",
+            "color(srgb 1 0 0",
+        ];
+
+        for cand in ko_candidates {
+            assert!(!ColorFunctionFormat::matches(cand), "{}", cand)
+        }
+    }
+
+    #[test]
+    fn test_color_format_parse() {
+        assert_eq!(
+            ColorFunctionFormat::parse("color(srgb 1 0.5 0 / 50%)").unwrap(),
+            Canonical::new(255, 128, 0, 128)
+        );
+        // out of gamut p3 red keeps its hue, losing chroma until it fits
+        assert_eq!(
+            ColorFunctionFormat::parse("color(display-p3 1 0 0)").unwrap(),
+            Canonical::new(255, 52, 41, 255)
+        );
+        assert_eq!(
+            ColorFunctionFormat::parse("color(display-p3 0.9175 0.2003 0.1386)").unwrap(),
+            Canonical::new(255, 0, 0, 255)
+        );
+        assert_eq!(
+            ColorFunctionFormat::parse("color(rec2020 100% 100% 100%)").unwrap(),
+            Canonical::new(255, 255, 255, 255)
+        );
+        assert_eq!(
+            ColorFunctionFormat::parse("color(a98-rgb 1 0 0)")
+                .unwrap_err()
+                .kind(),
+            ErrorKind::UnsupportedColorSpace
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests_color_formats {
+    use super::*;
+
+    #[test]
+    fn test_name_round_trip() {
+        for format in ColorFormats::ALL {
+            assert_eq!(format.to_string().parse::<ColorFormats>(), Ok(format));
+        }
+        assert_eq!(ColorFormats::RGBu8.to_string(), "rgb-u8");
+        assert_eq!(" HSL ".parse::<ColorFormats>(), Ok(ColorFormats::HSL));
+        assert_eq!(
+            "cmyk".parse::<ColorFormats>(),
+            Err(UnknownFormatError {
+                name: "cmyk".into()
+            })
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests_hsl_format {
+    use super::*;
+    use crate::errors::ErrorKind;
+
+    #[test]
+    fn test_angle_units() {
+        let expected = Canonical::new(64, 191, 191, 255);
+        for colr in [
+            "hsl(180 50% 50%)",
+            "hsl(180deg 50% 50%)",
+            "hsl(200grad 50% 50%)",
+            "hsl(3.14159265rad 50% 50%)",
+            "hsl(0.5turn 50% 50%)",
+            "hsl(-0.5TURN 50% 50%)",
+            "hsla(540, 50%, 50%)",
+        ] {
+            assert_eq!(parse_any(colr).unwrap(), expected, "{}", colr);
+        }
+
+        let expected = Canonical::new(51, 204, 204, 255);
+        for colr in [
+            "hwb(180 20% 20%)",
+            "hwb(200grad 20% 20%)",
+            "hwb(3.14159265rad 20% 20%)",
+            "hwb(.5turn 20% 20%)",
+        ] {
+            assert_eq!(parse_any(colr).unwrap(), expected, "{}", colr);
+        }
+    }
+
+    #[test]
+    fn test_unknown_unit() {
+        match parse_any("hsl(1.5foo 50% 50%)") {
+            Err(ParseFormatError::UnknownUnit {
+                format: ColorFormats::HSL,
+                unit,
+            }) => assert_eq!(unit, "foo"),
+            other => panic!("unexpected result {:?}", other),
+        }
+        assert_eq!(
+            parse_any("hwb(2e 0% 0%)").unwrap_err().to_string(),
+            "Unknown angle unit e in a HWB color"
+        );
+    }
+
+    #[test]
+    fn test_percentage_alpha() {
+        for (colr, expected) in [
+            ("rgba(255, 0, 0, 50%)", Canonical::new(255, 0, 0, 128)),
+            ("rgba(1.0, 0.0, 0.0, 50%)", Canonical::new(255, 0, 0, 128)),
+            ("rgba(255, 0, 0, 12.5%)", Canonical::new(255, 0, 0, 32)),
+            ("hsl(0 100% 50% / 50%)", Canonical::new(255, 0, 0, 128)),
+            ("hsla(0, 100%, 50%, 0.5)", Canonical::new(255, 0, 0, 128)),
+            ("hwb(0 0% 0% / 50%)", Canonical::new(255, 0, 0, 128)),
+            ("hsl(none 0% 50% / none)", Canonical::new(128, 128, 128, 0)),
+        ] {
+            assert_eq!(parse_any(colr).unwrap(), expected, "{}", colr);
+        }
+        for colr in [
+            "rgba(255, 0, 0, 150%)",
+            "hsl(0 100% 50% / 1.5)",
+            "hsl(0 120% 50%)",
+        ] {
+            assert_eq!(
+                parse_any(colr).unwrap_err().kind(),
+                ErrorKind::OutOfRange,
+                "{}",
+                colr
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests_parse_options {
+    use super::*;
+    use crate::errors::ErrorKind;
+
+    fn tolerant() -> ParseOptions {
+        ParseOptions {
+            locale_tolerant: true,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_locale_tolerant() {
+        let expected = Canonical::from_f(0.5, 0.25, 1.0, 1.0);
+        for colr in [
+            "rgb(0,5, 0,25, 1,0)",
+            "rgb(0,5; 0,25; 1)",
+            "rgb(0,5;0,25;1,0);",
+            "color(srgb 0,5 0,25 1)",
+        ] {
+            assert!(parse_any(colr).is_err(), "{}", colr);
+            assert!(
+                parse_with_options(colr, &ParseOptions::default()).is_err(),
+                "{}",
+                colr
+            );
+            assert_eq!(
+                parse_with_options(colr, &tolerant()).unwrap(),
+                expected,
+                "{}",
+                colr
+            );
+        }
+        assert_eq!(
+            parse_with_options("rgba(0,5; 0,25; 1; 0,5)", &tolerant()).unwrap(),
+            Canonical::from_f(0.5, 0.25, 1.0, 0.5)
+        );
+
+        // the usual syntax is unaffected
+        for colr in ["rgb(255, 0, 51)", "rgb(255,0,51)", "hsl(180,50%,50%)", "#ff0033"] {
+            assert_eq!(
+                parse_with_options(colr, &tolerant()).unwrap(),
+                parse_any(colr).unwrap(),
+                "{}",
+                colr
+            );
+        }
+    }
+
+    #[test]
+    fn test_locale_ambiguous() {
+        // rgba(1, 0, 0, 0) or rgb(1.0, 0, 0)
+        let e = parse_with_options("rgb(1,0, 0, 0)", &tolerant()).unwrap_err();
+        assert_eq!(e.kind(), ErrorKind::AmbiguousDecimal);
+        assert_eq!(parse_any("rgb(1,0, 0, 0)").unwrap(), Canonical::new(1, 0, 0, 0));
+
+        assert!(parse_with_options("rgb(0,5,0,25,1,0)", &tolerant()).is_err());
+    }
+
+    #[test]
+    fn test_options_formats() {
+        let options = ParseOptions {
+            formats: Some(vec![ColorFormats::Hex]),
+            locale_tolerant: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            parse_with_options("rgb(0,5; 0,25; 1)", &options)
+                .unwrap_err()
+                .kind(),
+            ErrorKind::NoAllowedFormat
+        );
+    }
+
+    #[test]
+    fn test_options_builder() {
+        assert_eq!(ParseOptions::new(), ParseOptions::default());
+        for colr in ["#ff0033", "rgb(1, 1, 1)", "rgb(300, 0, 0)", "0xff0033", "orange"] {
+            assert_eq!(
+                parse_any_with_opts(colr, &ParseOptions::new()).map_err(|e| e.to_string()),
+                parse_any(colr).map_err(|e| e.to_string()),
+                "{}",
+                colr
+            );
+        }
+
+        let options = ParseOptions::new()
+            .allow(&[ColorFormats::Hex])
+            .locale_tolerant()
+            .byte_order(ByteOrder::Argb)
+            .ambiguity(AmbiguityPolicy::Strict)
+            .clamp_out_of_range()
+            .lenient();
+        assert_eq!(
+            options,
+            ParseOptions {
+                formats: Some(vec![ColorFormats::Hex]),
+                locale_tolerant: true,
+                byte_order: ByteOrder::Argb,
+                ambiguity: AmbiguityPolicy::Strict,
+                out_of_range: OutOfRangePolicy::Clamp,
+                lenient: true,
+                limits: None,
+            }
+        );
+        let e = parse_any_with_opts("rgb(1, 2, 3)", &options).unwrap_err();
+        assert_eq!(e.kind(), ErrorKind::NoAllowedFormat);
+    }
+
+    #[test]
+    fn test_clamp_out_of_range() {
+        let clamp = ParseOptions::new().clamp_out_of_range();
+        for (colr, expected) in [
+            ("rgb(300, 0, 256)", Canonical::new(255, 0, 255, 255)),
+            ("rgb(1.5, 0.5, 0)", Canonical::from_f(1.0, 0.5, 0.0, 1.0)),
+            ("rgba(255, 0, 0, 150%)", Canonical::new(255, 0, 0, 255)),
+            ("hsl(0, 120%, 50%)", Canonical::new(255, 0, 0, 255)),
+            ("hsla(0deg 100% 50% / 2)", Canonical::new(255, 0, 0, 255)),
+            // only the component past its range changes, not the 30 in 300
+            ("rgb(300, 30, 0)", Canonical::new(255, 30, 0, 255)),
+        ] {
+            let e = parse_any(colr).unwrap_err();
+            assert_eq!(e.kind(), ErrorKind::OutOfRange, "{}", colr);
+            assert_eq!(parse_any_with_opts(colr, &clamp).unwrap(), expected, "{}", colr);
+        }
+        let integers = clamp.allow(&[ColorFormats::Integer]);
+        let e = parse_any_with_opts("0x1ff0033", &integers).unwrap_err();
+        assert_eq!(e.kind(), ErrorKind::OutOfRange);
+    }
+}
+
+#[cfg(test)]
+mod tests_space_separated_format {
+    use super::*;
+    use crate::errors::ErrorKind;
+
+    #[test]
+    fn test_parse_named() {
+        let expected = Canonical::new(235, 219, 178, 255);
+        for (colr, name) in [
+            ("235 219 178  fg", Some("fg")),
+            ("235\t219\t178\tfg", Some("fg")),
+            (" 235   219 \t 178     light yellow ", Some("light yellow")),
+            ("235 219 178", None),
+            ("235\t219\t178\t", None),
+        ] {
+            assert_eq!(
+                SpaceSeparatedFormat::parse_named(colr).unwrap(),
+                (expected, name),
+                "{:?}",
+                colr
+            );
+            assert_eq!(SpaceSeparatedFormat::parse(colr).unwrap(), expected);
+        }
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(
+            SpaceSeparatedFormat::parse("256 0 0 red").unwrap_err().kind(),
+            ErrorKind::OutOfRange
+        );
+        for colr in ["235 219", "235,219,178", "#ebdbb2", "fg 235 219 178"] {
+            assert!(!SpaceSeparatedFormat::matches(colr), "{}", colr);
+        }
+        // a row is not a color to parse_any
+        assert!(parse_any("235 219 178").is_err());
+    }
+}
+
+#[cfg(test)]
+mod tests_integer_format {
+    use super::*;
+    use crate::errors::ErrorKind;
+
+    #[test]
+    fn test_parse() {
+        let red = Canonical::new(255, 0, 0, 255);
+        assert_eq!(IntegerFormat::parse("16711680").unwrap(), red);
+        assert_eq!(IntegerFormat::parse("0xFF0000").unwrap(), red);
+        assert_eq!(IntegerFormat::parse(" 0xff0000; ").unwrap(), red);
+        assert_eq!(IntegerFormat::parse("0").unwrap(), Canonical::new(0, 0, 0, 255));
+        for colr in ["-1", "0x", "ff0000", "1.5", "0xff0000 1"] {
+            assert!(!IntegerFormat::matches(colr), "{}", colr);
+        }
+    }
+
+    #[test]
+    fn test_byte_orders() {
+        let colr = Canonical::new(0x11, 0x22, 0x33, 0xFF);
+        let cases = [
+            ("0x112233", ByteOrder::Rgb, colr),
+            ("0x332211", ByteOrder::Bgr, colr),
+            ("3351057", ByteOrder::Bgr, colr),
+            ("0x11223344", ByteOrder::Rgba, Canonical::new(0x11, 0x22, 0x33, 0x44)),
+            ("0x44112233", ByteOrder::Argb, Canonical::new(0x11, 0x22, 0x33, 0x44)),
+        ];
+        for (input, order, expected) in cases {
+            assert_eq!(IntegerFormat::parse_with(input, order).unwrap(), expected, "{}", input);
+        }
+    }
+
+    #[test]
+    fn test_out_of_range() {
+        for (input, order) in [
+            ("0x1000000", ByteOrder::Rgb),
+            ("16777216", ByteOrder::Bgr),
+            ("4294967296", ByteOrder::Rgba),
+            ("0x100000000", ByteOrder::Argb),
+        ] {
+            let e = IntegerFormat::parse_with(input, order).unwrap_err();
+            assert_eq!(e.kind(), ErrorKind::OutOfRange, "{}", input);
+        }
+        assert!(IntegerFormat::parse_with("4294967295", ByteOrder::Rgba).is_ok());
+    }
+
+    #[test]
+    fn test_only_when_allowed() {
+        assert!(parse_any("16711680").is_err());
+        let red = Canonical::new(255, 0, 0, 255);
+        assert_eq!(parse_any_with("16711680", &[ColorFormats::Integer]).unwrap(), red);
+        let options = ParseOptions {
+            formats: Some(vec![ColorFormats::Integer, ColorFormats::Hex]),
+            byte_order: ByteOrder::Bgr,
+            ..Default::default()
+        };
+        assert_eq!(parse_with_options("0x0000FF", &options).unwrap(), red);
+        assert_eq!(parse_with_options("#ff0000", &options).unwrap(), red);
+    }
+}
+
+#[cfg(test)]
+mod tests_xterm_index_format {
+    use super::*;
+    use crate::errors::ErrorKind;
+
+    #[test]
+    fn test_parse() {
+        let cube = Canonical::new(0x87, 0xaf, 0xaf, 255);
+        assert_eq!(XtermIndexFormat::parse("colour109").unwrap(), cube);
+        assert_eq!(XtermIndexFormat::parse("color109").unwrap(), cube);
+        assert_eq!(XtermIndexFormat::parse(" Colour109; ").unwrap(), cube);
+        assert_eq!(XtermIndexFormat::parse("colour0").unwrap(), XTERM_256[0]);
+        let last = Canonical::new(238, 238, 238, 255);
+        assert_eq!(XtermIndexFormat::parse("color255").unwrap(), last);
+        for colr in ["colour", "colour-1", "colours1", "colour 1", "colr1", "0x1"] {
+            assert!(!XtermIndexFormat::matches(colr), "{}", colr);
+        }
+    }
+
+    #[test]
+    fn test_out_of_range() {
+        for colr in ["colour256", "color1000", "colour99999999999"] {
+            let e = XtermIndexFormat::parse(colr).unwrap_err();
+            assert_eq!(e.kind(), ErrorKind::OutOfRange, "{}", colr);
+            assert_eq!(e.format(), Some(ColorFormats::XtermIndex));
+        }
+    }
+
+    #[test]
+    fn test_only_when_allowed() {
+        assert!(parse_any("colour1").is_err());
+        let allowed = [ColorFormats::XtermIndex, ColorFormats::Hex];
+        assert_eq!(parse_any_with("colour1", &allowed).unwrap(), XTERM_256[1]);
+        assert_eq!(
+            parse_any_with("#1d2021", &allowed).unwrap(),
+            Canonical::new(0x1d, 0x20, 0x21, 255)
+        );
+        let e = parse_any_with("colour256", &allowed).unwrap_err();
+        assert_eq!(e.kind(), ErrorKind::OutOfRange);
+        assert_eq!(guess_all("colour12"), [ColorFormats::XtermIndex]);
+    }
+
+    #[test]
+    fn test_emit() {
+        // the first slot wins, black is 0 and not 16
+        assert_eq!(emit(&XTERM_256[16], ColorFormats::XtermIndex).unwrap(), "colour0");
+        assert_eq!(emit(&XTERM_256[109], ColorFormats::XtermIndex).unwrap(), "colour109");
+        assert_eq!(emit(&Canonical::new(1, 2, 3, 255), ColorFormats::XtermIndex), None);
+    }
+}
+
+#[cfg(test)]
+mod tests_parse_bytes {
+    use super::*;
+    use crate::errors::ErrorKind;
+
+    #[test]
+    fn test_parse_bytes() {
+        // a color between bytes that aren't UTF-8
+        let buffer = b"\xc3(\xff bg: #ff0000; fg: rgba(0, 255, 0, 50%) \xe2\x28";
+        assert_eq!(
+            HexFormat::parse_bytes(&buffer[8..17]).unwrap(),
+            Canonical::new(255, 0, 0, 255)
+        );
+        assert_eq!(
+            RGBu8Format::parse_bytes(&buffer[21..41]).unwrap(),
+            Canonical::new(0, 255, 0, 128)
+        );
+
+        let e = RGBu8Format::parse_bytes(b"rgb(300, 0, 0)").unwrap_err();
+        assert_eq!(e.kind(), ErrorKind::OutOfRange);
+        let e = HexFormat::parse_bytes(b"#ff00\xff").unwrap_err();
+        assert_eq!(e.to_string(), "#ff00\u{fffd} is not a Hex color");
+    }
+
+    #[test]
+    fn test_same_as_str() {
+        for colr in [
+            "rgb(1,2,3)",
+            "RGBA( 1 , 2 , 3 , 4 )",
+            "rgba(1, 2, 3, none)",
+            "rgba(1, 2, 3, .5%)",
+            "rgba(1, 2, 3, 5.%)",
+            "rgba(1, 2, 3, .%)",
+            "rgba(1, 2, 3, 1.5)",
+            "rgb(1, 2, 3,)",
+            "rgb(1, 2)",
+            "rgb(1, 2, 3, 4, 5)",
+            "rgb(1 2 3)",
+            "rgb(1 2 none / 40%)",
+            "rgb(1 2 3 4)",
+            "rgb(1, 2 3)",
+            "rgb(1000, 2, 3)",
+            "rgbx(1, 2, 3)",
+            " #aabbcc; ",
+            "#aabbccd",
+            "##aabbcc",
+        ] {
+            assert_eq!(
+                RGBu8Format::parse(colr).ok(),
+                RGBu8Format::parse_bytes(colr.as_bytes()).ok(),
+                "{}",
+                colr
+            );
+            assert_eq!(
+                HexFormat::parse(colr).ok(),
+                HexFormat::parse_bytes(colr.as_bytes()).ok(),
+                "{}",
+                colr
+            );
+        }
+        assert!(RGBu8Format::matches("rgba(1, 2, 3, .5%)"));
+        assert!(!RGBu8Format::matches("rgba(1, 2, 3, .%)"));
+        assert!(!RGBu8Format::matches("rgb(1, 2, 3,)"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_parse_os() {
+        use std::os::unix::ffi::OsStrExt;
+
+        let os = |bytes: &[u8]| OsStr::from_bytes(bytes).to_owned();
+        let orange = Canonical::new(255, 128, 0, 255);
+        assert_eq!(parse_os(&os(b"#ff8000")).unwrap(), orange);
+        assert_eq!(parse_os(&os(b"hsl(30, 100%, 50%)")).unwrap(), orange);
+        // Latin-1 no-break spaces around the color
+        assert_eq!(parse_os(&os(b"\xa0#ff8000\xa0")).unwrap(), orange);
+        assert_eq!(parse_os(&os(b"\xff\xfe rgb(255, 128, 0) \xc3")).unwrap(), orange);
+
+        let e = parse_os(&os(b"\xa0#ff\xa080\xff00")).unwrap_err();
+        assert_eq!(e.kind(), ErrorKind::NonAscii);
+        assert_eq!(
+            e.to_string(),
+            "\u{fffd}#ff\u{fffd}80\u{fffd}00 is not UTF-8 and byte 4 is not ASCII"
+        );
+        let e = parse_os(&os(b"\xa0\xa0")).unwrap_err();
+        assert_eq!(e.kind(), ErrorKind::NonAscii);
+        // an ASCII part that isn't a color fails as it would anyway
+        let e = parse_os(&os(b"\xa0#ff80\xa0")).unwrap_err();
+        assert_eq!(e.kind(), ErrorKind::NoMatch);
+    }
+}
+
+#[cfg(test)]
+mod tests_emit {
+    use super::*;
+    use crate::errors::ErrorKind;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    fn parse_as(format: ColorFormats, colr: &str) -> Result<Canonical, ParseFormatError> {
+        match format {
+            ColorFormats::RGBu8 => RGBu8Format::parse(colr),
+            ColorFormats::RGBf => RGBFloatFormat::parse(colr),
+            ColorFormats::Hex => HexFormat::parse(colr),
+            ColorFormats::ColorFunction => ColorFunctionFormat::parse(colr),
+            ColorFormats::HSL => HSLFormat::parse(colr),
+            ColorFormats::HWB => HWBFormat::parse(colr),
+            ColorFormats::Named => NamedFormat::parse(colr),
+            ColorFormats::SpaceSeparated => SpaceSeparatedFormat::parse(colr),
+            ColorFormats::Integer => IntegerFormat::parse(colr),
+            ColorFormats::XtermIndex => XtermIndexFormat::parse(colr),
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn test_emit() {
+        let opaque = Canonical::new(255, 128, 0, 255);
+        let translucent = Canonical::new(255, 128, 0, 51);
+        let cases = [
+            (ColorFormats::RGBu8, "rgb(255,128,0)", "rgba(255,128,0,51)"),
+            (
+                ColorFormats::RGBf,
+                "rgb(1.0,0.502,0.0)",
+                "rgba(1.0,0.502,0.0,0.2)",
+            ),
+            (ColorFormats::Hex, "#ff8000", "#ff800033"),
+            (
+                ColorFormats::ColorFunction,
+                "color(srgb 1 0.502 0)",
+                "color(srgb 1 0.502 0/0.2)",
+            ),
+            (
+                ColorFormats::HSL,
+                "hsl(30.118 100% 50%)",
+                "hsl(30.118 100% 50%/0.2)",
+            ),
+            (
+                ColorFormats::HWB,
+                "hwb(30.118 0% 0%)",
+                "hwb(30.118 0% 0%/0.2)",
+            ),
+        ];
+        for (format, expected_opaque, expected_translucent) in cases {
+            assert_eq!(emit(&opaque, format).unwrap(), expected_opaque);
+            assert_eq!(emit(&translucent, format).unwrap(), expected_translucent);
+        }
+
+        assert_eq!(
+            emit(&opaque, ColorFormats::SpaceSeparated).unwrap(),
+            "255 128 0"
+        );
+        assert_eq!(emit(&opaque, ColorFormats::Integer).unwrap(), "0xff8000");
+        assert_eq!(emit(&translucent, ColorFormats::SpaceSeparated), None);
+        assert_eq!(emit(&translucent, ColorFormats::Integer), None);
+        assert_eq!(emit(&opaque, ColorFormats::Named), None);
+        assert_eq!(
+            emit(&Canonical::new(0, 0, 0, 0), ColorFormats::Named).unwrap(),
+            "transparent"
+        );
+        assert_eq!(emit(&opaque, ColorFormats::Lab), None);
+    }
+
+    #[test]
+    fn test_emit_options() {
+        let color = Canonical::new(128, 31, 26, 230);
+        let opaque = Canonical::new(171, 205, 239, 255);
+        let spaced = EmitOptions {
+            spacing: Spacing::Spaced,
+            ..Default::default()
+        };
+        let precise = EmitOptions {
+            float_precision: 6,
+            uppercase_hex: true,
+            always_alpha: true,
+            spacing: Spacing::Spaced,
+        };
+        let coarse = EmitOptions {
+            float_precision: 1,
+            always_alpha: true,
+            ..Default::default()
+        };
+        let snapshot = |options: &EmitOptions| {
+            [
+                ColorFormats::RGBu8,
+                ColorFormats::RGBf,
+                ColorFormats::Hex,
+                ColorFormats::ColorFunction,
+                ColorFormats::HSL,
+                ColorFormats::Integer,
+            ]
+            .into_iter()
+            .map(|format| {
+                let color = match format {
+                    ColorFormats::Hex | ColorFormats::Integer => opaque,
+                    _ => color,
+                };
+                emit_with(&color, format, options).unwrap_or_default()
+            })
+            .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            snapshot(&spaced),
+            [
+                "rgba(128, 31, 26, 230)",
+                "rgba(0.502, 0.122, 0.102, 0.902)",
+                "#abcdef",
+                "color(srgb 0.502 0.122 0.102 / 0.902)",
+                "hsl(2.941 66.234% 30.196% / 0.902)",
+                "0xabcdef",
+            ]
+        );
+        assert_eq!(
+            snapshot(&precise),
+            [
+                "rgba(128, 31, 26, 230)",
+                "rgba(0.501961, 0.121569, 0.101961, 0.901961)",
+                "#ABCDEFFF",
+                "color(srgb 0.501961 0.121569 0.101961 / 0.901961)",
+                "hsl(2.941176 66.233772% 30.196079% / 0.901961)",
+                "0xABCDEF",
+            ]
+        );
+        assert_eq!(
+            snapshot(&coarse),
+            [
+                "rgba(128,31,26,230)",
+                "rgba(0.5,0.1,0.1,0.9)",
+                "#abcdefff",
+                "color(srgb 0.5 0.1 0.1/0.9)",
+                "hsl(2.9 66.2% 30.2%/0.9)",
+                "0xabcdef",
+            ]
+        );
+        assert_eq!(
+            emit_with(&opaque, ColorFormats::RGBu8, &coarse).unwrap(),
+            "rgba(171,205,239,255)"
+        );
+
+        // every option combination parses back, if not to the same color
+        for precision in 1..=6 {
+            for always_alpha in [false, true] {
+                for spacing in [Spacing::Compact, Spacing::Spaced] {
+                    let options = EmitOptions {
+                        float_precision: precision,
+                        uppercase_hex: precision % 2 == 0,
+                        always_alpha,
+                        spacing,
+                    };
+                    for format in ColorFormats::ALL {
+                        if let Some(emitted) = emit_with(&color, format, &options) {
+                            assert!(parse_as(format, &emitted).is_ok(), "{}", emitted);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_zero_precision() {
+        let zero = EmitOptions {
+            float_precision: 0,
+            ..Default::default()
+        };
+        let color = Canonical::new(128, 31, 26, 230);
+        assert_eq!(emit_with(&color, ColorFormats::RGBf, &zero), None);
+        assert_eq!(emit_with(&color, ColorFormats::ColorFunction, &zero), None);
+        assert_eq!(
+            emit_with(&color, ColorFormats::HSL, &zero).unwrap(),
+            "hsl(3 66% 30%/1)"
+        );
+
+        let e = convert_with("#801f1a", ColorFormats::RGBf, &zero).unwrap_err();
+        assert_eq!(e.kind(), ErrorKind::InvalidPrecision);
+        assert_eq!(
+            e.to_string(),
+            "RGBf colors can not be written with 0 decimals"
+        );
+        assert!(zero.check(ColorFormats::Hex).is_ok());
+        assert_eq!(
+            convert_with("#801f1a", ColorFormats::Hex, &zero).unwrap(),
+            "#801f1a"
+        );
+    }
+
+    #[test]
+    fn test_emit_roundtrip() {
+        let mut rng = StdRng::seed_from_u64(0x00c0_ffee);
+        let mut colors: Vec<_> = (0..=255).map(|v| Canonical::new(v, v, v, v)).collect();
+        colors.extend(
+            (0..10_000).map(|_| Canonical::new(rng.gen(), rng.gen(), rng.gen(), rng.gen())),
+        );
+        colors.push(Canonical::new(0, 0, 0, 0));
+
+        for format in ColorFormats::ALL {
+            for color in &colors {
+                let Some(emitted) = emit(color, format) else {
+                    continue;
+                };
+                assert_eq!(parse_as(format, &emitted).unwrap(), *color, "{}", emitted);
+            }
+        }
+    }
+
+    #[test]
+    fn test_convert() {
+        assert_eq!(
+            convert("rgb(255, 128, 0)", ColorFormats::Hex).unwrap(),
+            "#ff8000"
+        );
+        assert_eq!(
+            convert("#ff800033", ColorFormats::RGBu8).unwrap(),
+            "rgba(255,128,0,51)"
+        );
+        assert_eq!(
+            convert("transparent", ColorFormats::Hex).unwrap(),
+            "#00000000"
+        );
+        assert_eq!(
+            convert(" hsl(120 100% 25%); ", ColorFormats::Hex).unwrap(),
+            "#008000"
+        );
+
+        let e = convert("#ff800033", ColorFormats::Integer).unwrap_err();
+        assert_eq!(e.kind(), ErrorKind::Unrepresentable);
+        assert_eq!(e.format(), Some(ColorFormats::Integer));
+        assert_eq!(
+            e.to_string(),
+            "#ff800033 can not be written as a Integer color"
+        );
+        assert_eq!(
+            convert("#ff8000", ColorFormats::Oklch).unwrap_err().kind(),
+            ErrorKind::Unrepresentable
+        );
+        assert_eq!(
+            convert("#gg", ColorFormats::Hex).unwrap_err().kind(),
+            ErrorKind::NoMatch
         );
     }
 }
@@ -0,0 +1,165 @@
+// input that reads as more than one color. `rgb(1, 1, 1)` is an RGBu8
+// color, nearly black, but the RGBf parser takes it as white, which is what
+// someone used to GLSL floats means. parse_any goes by the order of its
+// formats and takes the first that matches, the other readings here are
+// every format whose parser takes the input, whether its matches would or not
+use super::scan::format_matches;
+use super::{
+    parse_unchecked, tidy, ColorFormat, ColorFormats, ColorFunctionFormat, HSLFormat, HWBFormat,
+    HexFormat, IntegerFormat, NamedFormat, ParseOptions, RGBFloatFormat, RGBu8Format,
+    XtermIndexFormat, ALLOWED_ORDER, PARSE_ANY_ORDER,
+};
+use crate::color::Canonical;
+use crate::errors::ParseFormatError;
+
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Default)]
+pub enum AmbiguityPolicy {
+    // the first format that matches wins and nothing else is tried, as
+    // parse_any has always done
+    #[default]
+    Priority,
+    // the first wins, parse_with_ambiguities lists the other readings
+    Warn,
+    // other readings are a ParseFormatError::AmbiguousInput
+    Strict,
+}
+
+fn read_as(format: ColorFormats, colr: &str, options: &ParseOptions) -> Option<Canonical> {
+    match format {
+        ColorFormats::RGBf => RGBFloatFormat::parse(colr).ok(),
+        ColorFormats::RGBu8 => RGBu8Format::parse(colr).ok(),
+        ColorFormats::ColorFunction => ColorFunctionFormat::parse(colr).ok(),
+        ColorFormats::HSL => HSLFormat::parse(colr).ok(),
+        ColorFormats::HWB => HWBFormat::parse(colr).ok(),
+        ColorFormats::Named => NamedFormat::parse(colr).ok(),
+        ColorFormats::Integer => IntegerFormat::parse_with(colr, options.byte_order).ok(),
+        ColorFormats::XtermIndex => XtermIndexFormat::parse(colr).ok(),
+        ColorFormats::Hex => HexFormat::parse(colr).ok(),
+        _ => None,
+    }
+}
+
+// the color parse_with_options gives, and with AmbiguityPolicy::Warn the
+// formats that read the input as a different color with those colors, in
+// the order the formats are tried. only the allowed formats count. always
+// empty with Priority, which doesn't look
+pub fn parse_with_ambiguities(
+    colr: &str,
+    options: &ParseOptions,
+) -> Result<(Canonical, Vec<(ColorFormats, Canonical)>), ParseFormatError> {
+    let color = parse_unchecked(colr, options)?;
+    if options.ambiguity == AmbiguityPolicy::Priority {
+        return Ok((color, Vec::new()));
+    }
+    let tidied = tidy(colr);
+    let formats: Vec<ColorFormats> = match options.formats {
+        Some(ref allowed) => ALLOWED_ORDER
+            .into_iter()
+            .filter(|format| allowed.contains(format))
+            .collect(),
+        None => PARSE_ANY_ORDER.to_vec(),
+    };
+    // None for input that only parsed after delocalizing, which isn't
+    // looked into further
+    let Some(picked) = formats
+        .iter()
+        .copied()
+        .find(|&format| format_matches(format, &tidied))
+    else {
+        return Ok((color, Vec::new()));
+    };
+
+    let mut others: Vec<(ColorFormats, Canonical)> = Vec::new();
+    for format in formats.into_iter().filter(|&format| format != picked) {
+        match read_as(format, &tidied, options) {
+            Some(other) if other != color && !others.iter().any(|&(_, c)| c == other) => {
+                others.push((format, other))
+            }
+            _ => {}
+        }
+    }
+    if options.ambiguity == AmbiguityPolicy::Strict && !others.is_empty() {
+        others.insert(0, (picked, color));
+        return Err(ParseFormatError::AmbiguousInput {
+            input: colr.into(),
+            candidates: others,
+        });
+    }
+    Ok((color, others))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::errors::ErrorKind;
+    use crate::formats::{parse_any, parse_with_options};
+
+    #[test]
+    fn test_ambiguity_policies() {
+        let black = Canonical::new(1, 1, 1, 255);
+        let white = Canonical::new(255, 255, 255, 255);
+        let input = "rgb(1,1,1)";
+
+        // the documented priority, u8 before anything else
+        assert_eq!(parse_any(input).unwrap(), black);
+        let priority = ParseOptions::default();
+        assert_eq!(
+            parse_with_ambiguities(input, &priority).unwrap(),
+            (black, vec![])
+        );
+
+        let warn = ParseOptions::warn_ambiguous();
+        assert_eq!(parse_with_options(input, &warn).unwrap(), black);
+        assert_eq!(
+            parse_with_ambiguities(input, &warn).unwrap(),
+            (black, vec![(ColorFormats::RGBf, white)])
+        );
+
+        let strict = ParseOptions::strict_ambiguous();
+        let e = parse_with_options(input, &strict).unwrap_err();
+        assert_eq!(e.kind(), ErrorKind::AmbiguousInput);
+        assert_eq!(
+            e.to_string(),
+            "rgb(1,1,1) is ambiguous, it reads as rgb-u8 #010101 or rgb-f #ffffff"
+        );
+        match e {
+            ParseFormatError::AmbiguousInput { candidates, .. } => assert_eq!(
+                candidates,
+                [(ColorFormats::RGBu8, black), (ColorFormats::RGBf, white)]
+            ),
+            e => panic!("{:?}", e),
+        }
+    }
+
+    #[test]
+    fn test_unambiguous() {
+        let strict = ParseOptions::strict_ambiguous();
+        for (input, expected) in [
+            // the same color either way
+            ("rgb(0, 0, 0)", Canonical::new(0, 0, 0, 255)),
+            ("rgb(0.5, 0.5, 0.5)", Canonical::new(128, 128, 128, 255)),
+            ("rgb(2, 1, 1)", Canonical::new(2, 1, 1, 255)),
+            ("#ff8000", Canonical::new(255, 128, 0, 255)),
+            ("hsl(30 100% 50%)", Canonical::new(255, 128, 0, 255)),
+        ] {
+            assert_eq!(
+                parse_with_options(input, &strict).unwrap(),
+                expected,
+                "{}",
+                input
+            );
+        }
+        // only the allowed formats count
+        let options = ParseOptions {
+            formats: Some(vec![ColorFormats::RGBu8]),
+            ..ParseOptions::strict_ambiguous()
+        };
+        assert_eq!(
+            parse_with_options("rgb(1, 0, 0)", &options).unwrap(),
+            Canonical::new(1, 0, 0, 255)
+        );
+        // errors are the errors of the priority parse
+        let e = parse_with_options("rgb(300, 0, 0)", &strict).unwrap_err();
+        assert_eq!(e.kind(), ErrorKind::OutOfRange);
+    }
+}
@@ -0,0 +1,328 @@
+// style rules for the colors in a text, config files or source. lint finds
+// the colors scan finds plus two kinds scan leaves out because parse_any
+// doesn't read them: 3 and 4 digit shorthand hex, and hex without its `#`
+// as the value of a `key: ` or `key=`. a bare number of six or eight digits
+// after those counts too, config values rarely are one
+use std::ops::Range;
+
+use regex::Regex;
+
+use super::{emit_with, parse_any, scan, ColorFormats, EmitOptions, Spacing};
+
+// every rule is off by default
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Default)]
+pub struct LintRules {
+    pub lowercase_hex: bool,
+    pub leading_hash: bool,
+    pub no_shorthand: bool,
+    // `#rrggbbaa` rather than `#rrggbb`, and an alpha component in the
+    // functions that have one
+    pub explicit_alpha: bool,
+    // float rgb and color(srgb) components that are whole steps of 1/255,
+    // so the value survives a round trip through 8 bits
+    pub exact_u8: bool,
+}
+
+impl LintRules {
+    pub fn all() -> Self {
+        LintRules {
+            lowercase_hex: true,
+            leading_hash: true,
+            no_shorthand: true,
+            explicit_alpha: true,
+            exact_u8: true,
+        }
+    }
+}
+
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum LintRule {
+    LowercaseHex,
+    LeadingHash,
+    NoShorthand,
+    ExplicitAlpha,
+    ExactU8,
+}
+
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct LintFinding {
+    // byte range of the color in the linted text
+    pub span: Range<usize>,
+    pub rule: LintRule,
+    pub message: String,
+    // the color with every finding on it fixed, the same for all findings of
+    // one span
+    pub replacement: String,
+}
+
+lazy_static! {
+    static ref SHORTHAND_REGEX: Regex = Regex::new(r"#(?:[0-9a-fA-F]{3,4})\b").unwrap();
+    static ref BARE_HEX_REGEX: Regex =
+        Regex::new(r#"[:=][ \t]*["']?\b([0-9a-fA-F]{8}|[0-9a-fA-F]{6})\b"#).unwrap();
+    static ref NUMBER_REGEX: Regex =
+        Regex::new(r"[-+]?(?:\d+\.?\d*|\.\d+)(?:[eE][-+]?\d+)?%?").unwrap();
+}
+
+fn hex_findings(literal: &str, rules: &LintRules) -> (Vec<(LintRule, String)>, String) {
+    let mut findings = Vec::new();
+    let mut digits = literal.trim_start_matches('#').to_string();
+    let hash = literal.starts_with('#');
+    if rules.no_shorthand && digits.len() <= 4 {
+        findings.push((
+            LintRule::NoShorthand,
+            format!("{} is shorthand, write all digits", literal),
+        ));
+        digits = digits.chars().flat_map(|c| [c, c]).collect();
+    }
+    if rules.leading_hash && !hash {
+        findings.push((
+            LintRule::LeadingHash,
+            format!("{} has no leading #", literal),
+        ));
+    }
+    if rules.lowercase_hex && digits.chars().any(|c| c.is_ascii_uppercase()) {
+        findings.push((
+            LintRule::LowercaseHex,
+            format!("{} isn't lowercase", literal),
+        ));
+        digits.make_ascii_lowercase();
+    }
+    if rules.explicit_alpha && digits.len() == 6 {
+        findings.push((LintRule::ExplicitAlpha, format!("{} has no alpha", literal)));
+        let upper = digits.chars().any(|c| c.is_ascii_uppercase())
+            && !digits.chars().any(|c| c.is_ascii_lowercase());
+        digits.push_str(if upper { "FF" } else { "ff" });
+    }
+    let hash = if hash || rules.leading_hash { "#" } else { "" };
+    (findings, format!("{}{}", hash, digits))
+}
+
+// the numbers between the parentheses, without the space of color()
+fn components(literal: &str) -> Vec<&str> {
+    let inner = &literal[literal.find('(').map_or(0, |i| i + 1)..];
+    let inner = match literal[..3].eq_ignore_ascii_case("col") {
+        true => inner
+            .trim_start()
+            .split_once(char::is_whitespace)
+            .map_or("", |(_, rest)| rest),
+        false => inner,
+    };
+    NUMBER_REGEX.find_iter(inner).map(|m| m.as_str()).collect()
+}
+
+// whether a function literal has an alpha component
+pub(super) fn has_alpha(literal: &str) -> bool {
+    components(literal).len() > 3 || literal.contains('/')
+}
+
+// Compact if the literal has commas without spaces after them
+pub(super) fn spacing(literal: &str) -> Spacing {
+    match literal.contains(',') && !literal.contains(", ") {
+        true => Spacing::Compact,
+        false => Spacing::Spaced,
+    }
+}
+
+fn is_exact(component: &str) -> bool {
+    let value: f32 = match component.strip_suffix('%') {
+        Some(percent) => percent.parse::<f32>().unwrap_or(0.0) / 100.0,
+        None => component.parse().unwrap_or(0.0),
+    };
+    let steps = value * 255.0;
+    (steps - steps.round()).abs() < 1e-3
+}
+
+fn function_findings(
+    literal: &str,
+    format: ColorFormats,
+    rules: &LintRules,
+) -> Option<(Vec<(LintRule, String)>, String)> {
+    let color = parse_any(literal).ok()?;
+    let components = components(literal);
+    let has_alpha = has_alpha(literal);
+    let srgb = match format {
+        ColorFormats::RGBf => true,
+        ColorFormats::ColorFunction => literal.to_ascii_lowercase().contains("(srgb "),
+        _ => false,
+    };
+    let spacing = spacing(literal);
+
+    let mut findings = Vec::new();
+    let mut fixed = literal.to_string();
+    if rules.exact_u8 && srgb && !components.iter().take(3).all(|c| is_exact(c)) {
+        let options = EmitOptions {
+            always_alpha: has_alpha || rules.explicit_alpha,
+            spacing,
+            ..Default::default()
+        };
+        fixed = emit_with(&color, ColorFormats::RGBu8, &options)?;
+        findings.push((
+            LintRule::ExactU8,
+            format!("{} isn't exact in rgb-u8, it rounds to {}", literal, fixed),
+        ));
+    }
+    if rules.explicit_alpha && !has_alpha && format != ColorFormats::Named {
+        findings.push((LintRule::ExplicitAlpha, format!("{} has no alpha", literal)));
+        if findings.len() == 1 {
+            let opaque = match format {
+                ColorFormats::RGBu8 => "255",
+                _ => "1",
+            };
+            let separator = match (literal.contains(','), spacing) {
+                (true, Spacing::Compact) => ",",
+                (true, Spacing::Spaced) => ", ",
+                (false, _) => " / ",
+            };
+            let close = fixed.trim_end_matches(')').trim_end().len();
+            fixed = format!("{}{}{})", &fixed[..close], separator, opaque);
+        }
+    }
+    Some((findings, fixed))
+}
+
+// in order of their spans
+pub fn lint(text: &str, rules: LintRules) -> Vec<LintFinding> {
+    let mut literals: Vec<(Range<usize>, Option<ColorFormats>)> = scan(text)
+        .into_iter()
+        .map(|m| (m.span, Some(m.format)))
+        .collect();
+    literals.extend(SHORTHAND_REGEX.find_iter(text).map(|m| (m.range(), None)));
+    literals.extend(
+        BARE_HEX_REGEX
+            .captures_iter(text)
+            .map(|caps| (caps.get(1).unwrap().range(), None)),
+    );
+    literals.sort_by_key(|(span, _)| span.start);
+
+    let mut findings = Vec::new();
+    for (span, format) in literals {
+        let literal = &text[span.clone()];
+        let checked = match format {
+            None | Some(ColorFormats::Hex) => Some(hex_findings(literal, &rules)),
+            Some(format) => function_findings(literal, format, &rules),
+        };
+        let Some((found, replacement)) = checked else {
+            continue;
+        };
+        findings.extend(found.into_iter().map(|(rule, message)| LintFinding {
+            span: span.clone(),
+            rule,
+            message,
+            replacement: replacement.clone(),
+        }));
+    }
+    findings
+}
+
+// the text with the replacement of every finding, all else unchanged
+pub fn lint_fix(text: &str, rules: LintRules) -> String {
+    let mut fixed = String::with_capacity(text.len());
+    let mut end = 0;
+    for finding in lint(text, rules) {
+        if finding.span.start < end {
+            continue;
+        }
+        fixed.push_str(&text[end..finding.span.start]);
+        fixed.push_str(&finding.replacement);
+        end = finding.span.end;
+    }
+    fixed.push_str(&text[end..]);
+    fixed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // a violation of each rule, in this order, and a line without any
+    const FIXTURE: &str = "\
+a { color: #FF8000ff; }
+background = 282828ff
+border: #fa0f;
+fill: #ff8000;
+shadow: rgb(0.5, 0.25, 1, 1);
+ok: #458588ff, rgba(1, 2, 3, 255)
+";
+
+    #[test]
+    fn test_lint() {
+        let findings = lint(FIXTURE, LintRules::all());
+        let found: Vec<_> = findings
+            .iter()
+            .map(|f| (&FIXTURE[f.span.clone()], f.rule, f.replacement.as_str()))
+            .collect();
+        assert_eq!(
+            found,
+            [
+                ("#FF8000ff", LintRule::LowercaseHex, "#ff8000ff"),
+                ("282828ff", LintRule::LeadingHash, "#282828ff"),
+                ("#fa0f", LintRule::NoShorthand, "#ffaa00ff"),
+                ("#ff8000", LintRule::ExplicitAlpha, "#ff8000ff"),
+                (
+                    "rgb(0.5, 0.25, 1, 1)",
+                    LintRule::ExactU8,
+                    "rgba(128, 64, 255, 255)"
+                ),
+            ]
+        );
+        assert_eq!(findings[0].span, 11..20);
+        assert_eq!(findings[1].span, 37..45);
+        assert_eq!(findings[1].message, "282828ff has no leading #");
+        assert_eq!(
+            findings[4].message,
+            "rgb(0.5, 0.25, 1, 1) isn't exact in rgb-u8, it rounds to rgba(128, 64, 255, 255)"
+        );
+    }
+
+    #[test]
+    fn test_lint_fix() {
+        assert_eq!(
+            lint_fix(FIXTURE, LintRules::all()),
+            "\
+a { color: #ff8000ff; }
+background = #282828ff
+border: #ffaa00ff;
+fill: #ff8000ff;
+shadow: rgba(128, 64, 255, 255);
+ok: #458588ff, rgba(1, 2, 3, 255)
+"
+        );
+        // no rules, no changes
+        assert!(lint(FIXTURE, LintRules::default()).is_empty());
+        assert_eq!(lint_fix(FIXTURE, LintRules::default()), FIXTURE);
+    }
+
+    #[test]
+    fn test_rules_combine() {
+        let only = |rules: LintRules, text: &str| lint_fix(text, rules);
+        let alpha = LintRules {
+            explicit_alpha: true,
+            ..Default::default()
+        };
+        assert_eq!(only(alpha, "x: #ABCDEF"), "x: #ABCDEFFF");
+        assert_eq!(only(alpha, "x: rgb(1,2,3)"), "x: rgb(1,2,3,255)");
+        assert_eq!(
+            only(alpha, "x: hsl(30 100% 50%)"),
+            "x: hsl(30 100% 50% / 1)"
+        );
+        assert_eq!(only(alpha, "x: rgb(1.0, 0.5, 0)"), "x: rgb(1.0, 0.5, 0, 1)");
+        assert_eq!(only(alpha, "x: transparent"), "x: transparent");
+        // shorthand, case and hash on one color make one replacement
+        let findings = lint("x: #ABC", LintRules::all());
+        assert_eq!(findings.len(), 3);
+        assert!(findings.iter().all(|f| f.replacement == "#aabbccff"));
+        let exact = LintRules {
+            exact_u8: true,
+            ..Default::default()
+        };
+        assert_eq!(only(exact, "rgb(0.2, 0.4, 1)"), "rgb(0.2, 0.4, 1)");
+        assert_eq!(only(exact, "color(srgb 50% 0 0)"), "rgb(128, 0, 0)");
+        // only srgb values round to rgb-u8
+        assert_eq!(
+            only(exact, "color(display-p3 0.5 0 0)"),
+            "color(display-p3 0.5 0 0)"
+        );
+        // #define isn't a color, neither are other numbers
+        assert!(lint("#define X 1\nsize = 12\n", LintRules::all()).is_empty());
+    }
+}
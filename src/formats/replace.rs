@@ -0,0 +1,163 @@
+// re-skinning a text: every color scan finds that the map has a new color
+// for is written over in the syntax it was in. hex keeps its case and its
+// width, unless the new color needs alpha the old one didn't have, the
+// functions keep their spacing and their alpha component. everything else
+// in the text stays byte for byte as it was
+use std::collections::HashMap;
+
+use super::lint::{has_alpha, spacing};
+use super::{emit_with, parse_any, scan, ColorFormats, EmitOptions};
+use crate::color::diff::hex;
+use crate::color::{Canonical, DistanceMetric, Metric};
+
+#[derive(PartialEq, Debug, Clone, Copy, Default)]
+pub struct ReplaceOptions {
+    // colors this close to a key of the map count as that key, the closest
+    // key wins. None only replaces exact matches
+    pub tolerance: Option<f32>,
+    pub metric: Metric,
+}
+
+fn lookup(
+    map: &HashMap<Canonical, Canonical>,
+    color: &Canonical,
+    options: &ReplaceOptions,
+) -> Option<Canonical> {
+    if let Some(&new) = map.get(color) {
+        return Some(new);
+    }
+    let tolerance = options.tolerance?;
+    map.iter()
+        .map(|(old, new)| (options.metric.distance(old, color), new))
+        .filter(|&(distance, _)| distance <= tolerance)
+        .min_by(|a, b| a.0.total_cmp(&b.0))
+        .map(|(_, &new)| new)
+}
+
+fn rewrite_hex(literal: &str, color: &Canonical) -> String {
+    let digits = &literal[1..];
+    let mut new = match digits.len() {
+        8 => color.to_hex_string(),
+        _ => hex(color),
+    };
+    let upper = digits.chars().any(|c| c.is_ascii_uppercase())
+        && !digits.chars().any(|c| c.is_ascii_lowercase());
+    if upper {
+        new.make_ascii_uppercase();
+    }
+    new
+}
+
+// `literal` as `format`, written again with `color`
+fn rewrite(literal: &str, format: ColorFormats, color: &Canonical) -> String {
+    if format == ColorFormats::Hex {
+        return rewrite_hex(literal, color);
+    }
+    let options = EmitOptions {
+        always_alpha: has_alpha(literal),
+        spacing: spacing(literal),
+        ..Default::default()
+    };
+    // transparent can only be transparent
+    emit_with(color, format, &options).unwrap_or_else(|| hex(color))
+}
+
+// the new text and how many colors were replaced
+pub fn replace(
+    text: &str,
+    map: &HashMap<Canonical, Canonical>,
+    options: &ReplaceOptions,
+) -> (String, usize) {
+    let mut replaced = String::with_capacity(text.len());
+    let mut count = 0;
+    let mut end = 0;
+    for found in scan(text) {
+        let literal = &text[found.span.clone()];
+        let Ok(color) = parse_any(literal) else {
+            continue;
+        };
+        let Some(new) = lookup(map, &color, options) else {
+            continue;
+        };
+        replaced.push_str(&text[end..found.span.start]);
+        replaced.push_str(&rewrite(literal, found.format, &new));
+        end = found.span.end;
+        count += 1;
+    }
+    replaced.push_str(&text[end..]);
+    (replaced, count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CSS: &str = "\
+#define ACCENT
+.a { color: #CC241D; border: 1px solid #cc241dff; }
+.b { background: rgb(40, 40, 40); fill: rgba(40,40,40,255); }
+.c { stroke: hsl(0 0% 16%); color: #458588; }
+";
+
+    fn map() -> HashMap<Canonical, Canonical> {
+        HashMap::from([
+            (
+                Canonical::new(204, 36, 29, 255),
+                Canonical::new(191, 97, 106, 255),
+            ),
+            (
+                Canonical::new(40, 40, 40, 255),
+                Canonical::new(46, 52, 64, 255),
+            ),
+        ])
+    }
+
+    #[test]
+    fn test_replace() {
+        let (replaced, count) = replace(CSS, &map(), &ReplaceOptions::default());
+        assert_eq!(count, 4);
+        assert_eq!(
+            replaced,
+            "\
+#define ACCENT
+.a { color: #BF616A; border: 1px solid #bf616aff; }
+.b { background: rgb(46, 52, 64); fill: rgba(46,52,64,255); }
+.c { stroke: hsl(0 0% 16%); color: #458588; }
+"
+        );
+        // nothing to replace, nothing changed
+        let (same, count) = replace(CSS, &HashMap::new(), &ReplaceOptions::default());
+        assert_eq!((same.as_str(), count), (CSS, 0));
+    }
+
+    #[test]
+    fn test_replace_tolerance() {
+        let options = ReplaceOptions {
+            tolerance: Some(3.0),
+            ..Default::default()
+        };
+        // hsl(0 0% 16%) is #292929, a step from #282828
+        let (replaced, count) = replace(CSS, &map(), &options);
+        assert_eq!(count, 5);
+        assert!(
+            replaced.contains("stroke: hsl(220 16.364% 21.569%);"),
+            "{}",
+            replaced
+        );
+        assert!(replaced.contains("color: #458588;"));
+    }
+
+    #[test]
+    fn test_replace_alpha() {
+        let to_translucent = HashMap::from([(
+            Canonical::new(69, 133, 136, 255),
+            Canonical::new(1, 2, 3, 128),
+        )]);
+        let (replaced, _) = replace(
+            "#458588 rgb(69, 133, 136) hsl(183 33% 40%)",
+            &to_translucent,
+            &ReplaceOptions::default(),
+        );
+        assert_eq!(replaced, "#01020380 rgba(1, 2, 3, 128) hsl(183 33% 40%)");
+    }
+}
@@ -0,0 +1,238 @@
+// what kind of color a string is, without parsing it. the shape of every
+// format is in one RegexSet, so a guess is a single pass over the input
+// plus the matches of the few formats whose shape fits. scan finds colors
+// in longer text, config files or source, with their byte ranges
+use std::ops::Range;
+
+use regex::{Regex, RegexSet};
+
+use super::{
+    parse_any_with_opts, strip, tidy, ColorFormat, ColorFormats, ColorFunctionFormat, HSLFormat,
+    HWBFormat, HexFormat, IntegerFormat, NamedFormat, ParseOptions, RGBFloatFormat, RGBu8Format,
+    SpaceSeparatedFormat, XtermIndexFormat, ALLOWED_ORDER, PARSE_ANY_ORDER,
+};
+
+// loose on purpose, each format's matches has the last word
+const SHAPES: [(&str, &[ColorFormats]); 9] = [
+    (r"(?i)^rgba?\(", &[ColorFormats::RGBf, ColorFormats::RGBu8]),
+    (r"(?i)^color\(", &[ColorFormats::ColorFunction]),
+    (r"(?i)^hsla?\(", &[ColorFormats::HSL]),
+    (r"(?i)^hwb\(", &[ColorFormats::HWB]),
+    (r"(?i)^transparent$", &[ColorFormats::Named]),
+    (r"^#[0-9a-fA-F]+$", &[ColorFormats::Hex]),
+    (r"^\d+[ \t]+\d+[ \t]+\d+", &[ColorFormats::SpaceSeparated]),
+    (r"^(?:0[xX][0-9a-fA-F]+|\d+)$", &[ColorFormats::Integer]),
+    (r"(?i)^colou?r\d+$", &[ColorFormats::XtermIndex]),
+];
+
+lazy_static! {
+    static ref SHAPE_SET: RegexSet = RegexSet::new(SHAPES.map(|(shape, _)| shape)).unwrap();
+    // hex up to a word boundary, so `#define` is no color, and the functions
+    // and keywords of parse_any
+    static ref CANDIDATE_REGEX: Regex = Regex::new(
+        r"(?x)
+        \#[0-9a-fA-F]+\b
+        | (?i:\b(?:rgba?|hsla?|hwb|color))\s*\([^()]*\)
+        | (?i:\btransparent\b)"
+    )
+    .unwrap();
+}
+
+// the format's own matches, false for formats without a parser
+pub(super) fn format_matches(format: ColorFormats, colr: &str) -> bool {
+    match format {
+        ColorFormats::RGBf => RGBFloatFormat::matches(colr),
+        ColorFormats::RGBu8 => RGBu8Format::matches(colr),
+        ColorFormats::ColorFunction => ColorFunctionFormat::matches(colr),
+        ColorFormats::HSL => HSLFormat::matches(colr),
+        ColorFormats::HWB => HWBFormat::matches(colr),
+        ColorFormats::Named => NamedFormat::matches(colr),
+        ColorFormats::Hex => HexFormat::matches(colr),
+        ColorFormats::SpaceSeparated => SpaceSeparatedFormat::matches(colr),
+        ColorFormats::Integer => IntegerFormat::matches(colr),
+        ColorFormats::XtermIndex => XtermIndexFormat::matches(colr),
+        // no parser, nothing to match
+        ColorFormats::Lab | ColorFormats::Oklch | ColorFormats::Percent => false,
+    }
+}
+
+// every format the input matches, including the ones parse_any doesn't try:
+// `255` is an integer, `0 128 255` a GIMP row. a match isn't a parse, the
+// values may still be out of range
+pub fn guess_all(input: &str) -> Vec<ColorFormats> {
+    let colr = tidy(input);
+    SHAPE_SET
+        .matches(strip(&colr))
+        .into_iter()
+        .flat_map(|shape| SHAPES[shape].1)
+        .copied()
+        .filter(|&format| format_matches(format, &colr))
+        .collect()
+}
+
+// the format parse_any would parse the input with, None if it would fail
+// with no format matching
+pub fn guess(input: &str) -> Option<ColorFormats> {
+    let all = guess_all(input);
+    PARSE_ANY_ORDER
+        .into_iter()
+        .find(|format| all.contains(format))
+}
+
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct ColorMatch {
+    // byte range into the scanned text
+    pub span: Range<usize>,
+    // what guess says of the text in span
+    pub format: ColorFormats,
+}
+
+// every color in `text` that parse_any would take a format for, in order.
+// integers and GIMP rows are left out, there would be no telling them from
+// any other number
+pub fn scan(text: &str) -> Vec<ColorMatch> {
+    CANDIDATE_REGEX
+        .find_iter(text)
+        .filter_map(|m| {
+            guess(m.as_str()).map(|format| ColorMatch {
+                span: m.range(),
+                format,
+            })
+        })
+        .collect()
+}
+
+// the colors of scan that parse with the options, rather than only looking
+// like they would. with allowed formats a match has the first of them its
+// text fits
+pub fn scan_with_opts(text: &str, options: &ParseOptions) -> Vec<ColorMatch> {
+    CANDIDATE_REGEX
+        .find_iter(text)
+        .filter_map(|m| {
+            let format = match options.formats {
+                Some(ref allowed) => {
+                    let all = guess_all(m.as_str());
+                    ALLOWED_ORDER
+                        .into_iter()
+                        .find(|format| allowed.contains(format) && all.contains(format))
+                }
+                None => guess(m.as_str()),
+            }?;
+            parse_any_with_opts(m.as_str(), options).ok()?;
+            Some(ColorMatch {
+                span: m.range(),
+                format,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::formats::{parse_any, parse_any_with};
+
+    #[test]
+    fn test_guess() {
+        for (input, expected) in [
+            ("#ff8000", Some(ColorFormats::Hex)),
+            (" #FF8000aa; ", Some(ColorFormats::Hex)),
+            ("rgb(255, 128, 0)", Some(ColorFormats::RGBu8)),
+            ("rgb (1.0, 0.5, 0)", Some(ColorFormats::RGBf)),
+            (
+                "color(display-p3 1 0.5 0)",
+                Some(ColorFormats::ColorFunction),
+            ),
+            ("hsla(30deg, 100%, 50%, 0.5)", Some(ColorFormats::HSL)),
+            ("hwb(30 0% 0%)", Some(ColorFormats::HWB)),
+            ("Transparent", Some(ColorFormats::Named)),
+            // out of range still is rgb
+            ("rgb(300, 0, 0)", Some(ColorFormats::RGBu8)),
+            ("#ff80", None),
+            ("16744448", None),
+            ("orange", None),
+            ("", None),
+        ] {
+            assert_eq!(guess(input), expected, "{:?}", input);
+        }
+        assert_eq!(guess_all("16744448"), [ColorFormats::Integer]);
+        assert_eq!(guess_all("255 128 0"), [ColorFormats::SpaceSeparated]);
+        assert_eq!(guess_all("rgb(1.0, 0.5, 0)"), [ColorFormats::RGBf]);
+        assert!(guess_all("#zz0000").is_empty());
+    }
+
+    // every string literal in the tests of formats.rs
+    fn corpus() -> Vec<&'static str> {
+        let source = include_str!("../formats.rs");
+        let tests = &source[source.find("#[cfg(test)]").unwrap()..];
+        let literal = Regex::new(r#""((?:[^"\\\n]|\\.)*)""#).unwrap();
+        literal
+            .captures_iter(tests)
+            .map(|caps| caps.get(1).unwrap().as_str())
+            .filter(|s| !s.contains('\\'))
+            .collect()
+    }
+
+    #[test]
+    fn test_guess_agrees_with_parse_any() {
+        let corpus = corpus();
+        assert!(corpus.len() > 200, "{}", corpus.len());
+        for input in corpus {
+            let parsed = parse_any(input).map_err(|e| e.to_string());
+            match guess(input) {
+                Some(format) => {
+                    let with = parse_any_with(input, &[format]).map_err(|e| e.to_string());
+                    assert_eq!(parsed, with, "{:?} guessed as {}", input, format);
+                }
+                None => assert!(parsed.is_err(), "{:?} parses but isn't guessed", input),
+            }
+        }
+    }
+
+    #[test]
+    fn test_scan() {
+        let css = "#define ACCENT\n.a { color: #FF8000; background: rgb (1, 2, 3) }\n\
+                   .b { border: 1px solid hsl(30 100% 50%); fill: transparent; }\n\
+                   .c { color: #ff80; stroke: rgb(1, 2); }";
+        let found: Vec<_> = scan(css)
+            .into_iter()
+            .map(|m| (&css[m.span], m.format))
+            .collect();
+        assert_eq!(
+            found,
+            [
+                ("#FF8000", ColorFormats::Hex),
+                ("rgb (1, 2, 3)", ColorFormats::RGBu8),
+                ("hsl(30 100% 50%)", ColorFormats::HSL),
+                ("transparent", ColorFormats::Named),
+            ]
+        );
+        assert!(scan("no colors here, #1 and #define").is_empty());
+    }
+
+    #[test]
+    fn test_scan_with_opts() {
+        let css = ".a { color: #FF8000; background: rgb(300, 0, 0); fill: hsl(30 100% 50%) }";
+        let found = |options: &ParseOptions| -> Vec<_> {
+            scan_with_opts(css, options)
+                .into_iter()
+                .map(|m| (&css[m.span], m.format))
+                .collect()
+        };
+        // scan takes the out of range rgb for a color, the default options
+        // don't
+        assert_eq!(scan(css).len(), 3);
+        assert_eq!(
+            found(&ParseOptions::new()),
+            [
+                ("#FF8000", ColorFormats::Hex),
+                ("hsl(30 100% 50%)", ColorFormats::HSL),
+            ]
+        );
+        assert_eq!(found(&ParseOptions::new().clamp_out_of_range()).len(), 3);
+        assert_eq!(
+            found(&ParseOptions::new().allow(&[ColorFormats::HSL])),
+            [("hsl(30 100% 50%)", ColorFormats::HSL)]
+        );
+    }
+}
@@ -0,0 +1,566 @@
+// just enough json for the design token files: a parser keeping the order of
+// object members, and a writer indenting by two spaces
+use std::fmt::Write;
+
+// deeper documents are rejected rather than risking the stack
+const MAX_DEPTH: usize = 128;
+
+#[derive(PartialEq, Debug, Clone)]
+pub(crate) enum Value {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<Value>),
+    // members in document order. a repeated key is kept twice, get finds the
+    // first
+    Object(Vec<(String, Value)>),
+}
+
+impl Value {
+    pub(crate) fn get(&self, key: &str) -> Option<&Value> {
+        match self {
+            Value::Object(members) => members.iter().find(|(k, _)| k == key).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn as_str(&self) -> Option<&str> {
+        match self {
+            Value::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn as_f64(&self) -> Option<f64> {
+        match self {
+            Value::Number(n) => Some(*n),
+            _ => None,
+        }
+    }
+}
+
+// a Value goes through serde as the JSON it stands for, integral numbers as
+// integers. it is also a Deserializer itself, which lets Deserialize impls
+// run on a document without a serde format crate
+#[cfg(feature = "serde")]
+mod serde_impls {
+    use serde::de::value::{Error, MapDeserializer, SeqDeserializer};
+    use serde::de::{IntoDeserializer, MapAccess, SeqAccess, Visitor};
+    use serde::ser::{SerializeMap, SerializeSeq};
+    use serde::{forward_to_deserialize_any, Deserialize, Deserializer, Serialize, Serializer};
+
+    use super::Value;
+
+    // within ±2^53 every integer is exact in an f64
+    const EXACT: f64 = 9007199254740992.0;
+
+    impl Serialize for Value {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            match self {
+                Value::Null => serializer.serialize_unit(),
+                &Value::Bool(b) => serializer.serialize_bool(b),
+                &Value::Number(n) if n.fract() == 0.0 && (0.0..=EXACT).contains(&n) => {
+                    serializer.serialize_u64(n as u64)
+                }
+                &Value::Number(n) if n.fract() == 0.0 && (-EXACT..0.0).contains(&n) => {
+                    serializer.serialize_i64(n as i64)
+                }
+                &Value::Number(n) => serializer.serialize_f64(n),
+                Value::String(s) => serializer.serialize_str(s),
+                Value::Array(items) => {
+                    let mut seq = serializer.serialize_seq(Some(items.len()))?;
+                    for item in items {
+                        seq.serialize_element(item)?;
+                    }
+                    seq.end()
+                }
+                Value::Object(members) => {
+                    let mut map = serializer.serialize_map(Some(members.len()))?;
+                    for (key, value) in members {
+                        map.serialize_entry(key, value)?;
+                    }
+                    map.end()
+                }
+            }
+        }
+    }
+
+    struct ValueVisitor;
+
+    impl<'de> Visitor<'de> for ValueVisitor {
+        type Value = Value;
+
+        fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+            f.write_str("a JSON value")
+        }
+
+        fn visit_unit<E>(self) -> Result<Value, E> {
+            Ok(Value::Null)
+        }
+
+        fn visit_none<E>(self) -> Result<Value, E> {
+            Ok(Value::Null)
+        }
+
+        fn visit_some<D: Deserializer<'de>>(self, deserializer: D) -> Result<Value, D::Error> {
+            Value::deserialize(deserializer)
+        }
+
+        fn visit_bool<E>(self, b: bool) -> Result<Value, E> {
+            Ok(Value::Bool(b))
+        }
+
+        fn visit_i64<E>(self, n: i64) -> Result<Value, E> {
+            Ok(Value::Number(n as f64))
+        }
+
+        fn visit_u64<E>(self, n: u64) -> Result<Value, E> {
+            Ok(Value::Number(n as f64))
+        }
+
+        fn visit_f64<E>(self, n: f64) -> Result<Value, E> {
+            Ok(Value::Number(n))
+        }
+
+        fn visit_str<E>(self, s: &str) -> Result<Value, E> {
+            Ok(Value::String(s.into()))
+        }
+
+        fn visit_string<E>(self, s: String) -> Result<Value, E> {
+            Ok(Value::String(s))
+        }
+
+        fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Value, A::Error> {
+            let mut items = Vec::new();
+            while let Some(item) = seq.next_element()? {
+                items.push(item);
+            }
+            Ok(Value::Array(items))
+        }
+
+        // repeated keys are kept, as the parser keeps them
+        fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Value, A::Error> {
+            let mut members = Vec::new();
+            while let Some(member) = map.next_entry()? {
+                members.push(member);
+            }
+            Ok(Value::Object(members))
+        }
+    }
+
+    impl<'de> Deserialize<'de> for Value {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Value, D::Error> {
+            deserializer.deserialize_any(ValueVisitor)
+        }
+    }
+
+    impl<'de> Deserializer<'de> for Value {
+        type Error = Error;
+
+        fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+            match self {
+                Value::Null => visitor.visit_unit(),
+                Value::Bool(b) => visitor.visit_bool(b),
+                Value::Number(n) if n.fract() == 0.0 && (0.0..=EXACT).contains(&n) => {
+                    visitor.visit_u64(n as u64)
+                }
+                Value::Number(n) if n.fract() == 0.0 && (-EXACT..0.0).contains(&n) => {
+                    visitor.visit_i64(n as i64)
+                }
+                Value::Number(n) => visitor.visit_f64(n),
+                Value::String(s) => visitor.visit_string(s),
+                Value::Array(items) => visitor.visit_seq(SeqDeserializer::new(items.into_iter())),
+                Value::Object(members) => {
+                    visitor.visit_map(MapDeserializer::new(members.into_iter()))
+                }
+            }
+        }
+
+        fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+            match self {
+                Value::Null => visitor.visit_none(),
+                value => visitor.visit_some(value),
+            }
+        }
+
+        forward_to_deserialize_any! {
+            bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+            bytes byte_buf unit unit_struct newtype_struct seq tuple
+            tuple_struct map struct enum identifier ignored_any
+        }
+    }
+
+    impl IntoDeserializer<'_, Error> for Value {
+        type Deserializer = Value;
+
+        fn into_deserializer(self) -> Value {
+            self
+        }
+    }
+}
+
+// lines and columns are 1-based, columns count chars
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub(crate) struct JsonError {
+    pub line: usize,
+    pub column: usize,
+    pub message: &'static str,
+}
+
+pub(crate) fn parse(s: &str) -> Result<Value, JsonError> {
+    let mut parser = Parser {
+        chars: s.strip_prefix('\u{feff}').unwrap_or(s).chars().peekable(),
+        line: 1,
+        column: 1,
+    };
+    let value = parser.value(0)?;
+    parser.whitespace();
+    match parser.chars.peek() {
+        None => Ok(value),
+        Some(_) => Err(parser.error("trailing characters")),
+    }
+}
+
+struct Parser<'s> {
+    chars: std::iter::Peekable<std::str::Chars<'s>>,
+    line: usize,
+    column: usize,
+}
+
+impl Parser<'_> {
+    fn error(&self, message: &'static str) -> JsonError {
+        JsonError {
+            line: self.line,
+            column: self.column,
+            message,
+        }
+    }
+
+    fn next(&mut self) -> Option<char> {
+        let c = self.chars.next()?;
+        if c == '\n' {
+            self.line += 1;
+            self.column = 1;
+        } else {
+            self.column += 1;
+        }
+        Some(c)
+    }
+
+    fn whitespace(&mut self) {
+        while matches!(self.chars.peek(), Some(' ' | '\t' | '\n' | '\r')) {
+            self.next();
+        }
+    }
+
+    fn expect(&mut self, expected: char, message: &'static str) -> Result<(), JsonError> {
+        self.whitespace();
+        match self.chars.peek() {
+            Some(&c) if c == expected => {
+                self.next();
+                Ok(())
+            }
+            _ => Err(self.error(message)),
+        }
+    }
+
+    fn value(&mut self, depth: usize) -> Result<Value, JsonError> {
+        if depth > MAX_DEPTH {
+            return Err(self.error("nested too deeply"));
+        }
+        self.whitespace();
+        match self.chars.peek() {
+            Some('{') => self.object(depth),
+            Some('[') => self.array(depth),
+            Some('"') => self.string().map(Value::String),
+            Some('-' | '0'..='9') => self.number(),
+            Some('t') => self.literal("true", Value::Bool(true)),
+            Some('f') => self.literal("false", Value::Bool(false)),
+            Some('n') => self.literal("null", Value::Null),
+            Some(_) => Err(self.error("expected a value")),
+            None => Err(self.error("unexpected end of input")),
+        }
+    }
+
+    fn literal(&mut self, word: &str, value: Value) -> Result<Value, JsonError> {
+        for expected in word.chars() {
+            if self.chars.peek() != Some(&expected) {
+                return Err(self.error("expected a value"));
+            }
+            self.next();
+        }
+        Ok(value)
+    }
+
+    fn object(&mut self, depth: usize) -> Result<Value, JsonError> {
+        self.next();
+        let mut members = Vec::new();
+        self.whitespace();
+        if self.chars.peek() == Some(&'}') {
+            self.next();
+            return Ok(Value::Object(members));
+        }
+        loop {
+            self.whitespace();
+            if self.chars.peek() != Some(&'"') {
+                return Err(self.error("expected a member name"));
+            }
+            let key = self.string()?;
+            self.expect(':', "expected `:` after a member name")?;
+            members.push((key, self.value(depth + 1)?));
+            self.whitespace();
+            match self.next() {
+                Some(',') => continue,
+                Some('}') => return Ok(Value::Object(members)),
+                _ => return Err(self.error("expected `,` or `}`")),
+            }
+        }
+    }
+
+    fn array(&mut self, depth: usize) -> Result<Value, JsonError> {
+        self.next();
+        let mut items = Vec::new();
+        self.whitespace();
+        if self.chars.peek() == Some(&']') {
+            self.next();
+            return Ok(Value::Array(items));
+        }
+        loop {
+            items.push(self.value(depth + 1)?);
+            self.whitespace();
+            match self.next() {
+                Some(',') => continue,
+                Some(']') => return Ok(Value::Array(items)),
+                _ => return Err(self.error("expected `,` or `]`")),
+            }
+        }
+    }
+
+    fn string(&mut self) -> Result<String, JsonError> {
+        self.next();
+        let mut s = String::new();
+        loop {
+            match self.next() {
+                Some('"') => return Ok(s),
+                Some('\\') => s.push(self.escape()?),
+                Some(c) if (c as u32) < 0x20 => {
+                    return Err(self.error("control character in a string"))
+                }
+                Some(c) => s.push(c),
+                None => return Err(self.error("unterminated string")),
+            }
+        }
+    }
+
+    fn escape(&mut self) -> Result<char, JsonError> {
+        Ok(match self.next() {
+            Some('"') => '"',
+            Some('\\') => '\\',
+            Some('/') => '/',
+            Some('b') => '\u{8}',
+            Some('f') => '\u{c}',
+            Some('n') => '\n',
+            Some('r') => '\r',
+            Some('t') => '\t',
+            Some('u') => {
+                let high = self.hex4()?;
+                if !(0xd800..0xdc00).contains(&high) {
+                    return char::from_u32(high).ok_or_else(|| self.error("invalid \\u escape"));
+                }
+                // a surrogate pair
+                if self.next() != Some('\\') || self.next() != Some('u') {
+                    return Err(self.error("unpaired surrogate"));
+                }
+                let low = self.hex4()?;
+                if !(0xdc00..0xe000).contains(&low) {
+                    return Err(self.error("unpaired surrogate"));
+                }
+                let c = 0x10000 + ((high - 0xd800) << 10) + (low - 0xdc00);
+                char::from_u32(c).ok_or_else(|| self.error("invalid \\u escape"))?
+            }
+            _ => return Err(self.error("invalid escape")),
+        })
+    }
+
+    fn hex4(&mut self) -> Result<u32, JsonError> {
+        let mut value = 0;
+        for _ in 0..4 {
+            let digit = self.next().and_then(|c| c.to_digit(16));
+            value = value * 16 + digit.ok_or_else(|| self.error("invalid \\u escape"))?;
+        }
+        Ok(value)
+    }
+
+    fn number(&mut self) -> Result<Value, JsonError> {
+        let mut s = String::new();
+        while let Some(&c) = self.chars.peek() {
+            if !matches!(c, '-' | '+' | '.' | 'e' | 'E' | '0'..='9') {
+                break;
+            }
+            s.push(c);
+            self.next();
+        }
+        // str::parse is laxer than json about leading zeros and dots
+        let digits = s.strip_prefix('-').unwrap_or(&s);
+        let leading_zero =
+            digits.starts_with('0') && digits[1..].starts_with(|c: char| c.is_ascii_digit());
+        let valid = digits.starts_with(|c: char| c.is_ascii_digit())
+            && !leading_zero
+            && !digits.contains(".e")
+            && !digits.contains(".E")
+            && !digits.ends_with('.');
+        match s.parse::<f64>() {
+            Ok(n) if valid => Ok(Value::Number(n)),
+            _ => Err(self.error("invalid number")),
+        }
+    }
+}
+
+// two space indents, a member per line
+pub(crate) fn to_pretty_string(value: &Value) -> String {
+    let mut out = String::new();
+    write_value(&mut out, value, 0);
+    out.push('\n');
+    out
+}
+
+fn write_value(out: &mut String, value: &Value, indent: usize) {
+    let pad = |out: &mut String, indent: usize| out.extend(std::iter::repeat_n("  ", indent));
+    match value {
+        Value::Null => out.push_str("null"),
+        Value::Bool(b) => write!(out, "{}", b).unwrap(),
+        Value::Number(n) => write!(out, "{}", n).unwrap(),
+        Value::String(s) => write_string(out, s),
+        Value::Array(items) if items.is_empty() => out.push_str("[]"),
+        Value::Object(members) if members.is_empty() => out.push_str("{}"),
+        Value::Array(items) => {
+            out.push_str("[\n");
+            for (i, item) in items.iter().enumerate() {
+                pad(out, indent + 1);
+                write_value(out, item, indent + 1);
+                out.push_str(if i + 1 < items.len() { ",\n" } else { "\n" });
+            }
+            pad(out, indent);
+            out.push(']');
+        }
+        Value::Object(members) => {
+            out.push_str("{\n");
+            for (i, (key, value)) in members.iter().enumerate() {
+                pad(out, indent + 1);
+                write_string(out, key);
+                out.push_str(": ");
+                write_value(out, value, indent + 1);
+                out.push_str(if i + 1 < members.len() { ",\n" } else { "\n" });
+            }
+            pad(out, indent);
+            out.push('}');
+        }
+    }
+}
+
+fn write_string(out: &mut String, s: &str) {
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => write!(out, "\\u{:04x}", c as u32).unwrap(),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let value = parse(concat!(
+            "\u{feff} {\"a\": [1, -2.5e3, true, false, null], ",
+            "\"b\": {\"c\": \"x\\n\\u00e9\\ud83c\\udfa8\"}, \"a\": {}}",
+        ))
+        .unwrap();
+        assert_eq!(
+            value.get("a"),
+            Some(&Value::Array(vec![
+                Value::Number(1.0),
+                Value::Number(-2500.0),
+                Value::Bool(true),
+                Value::Bool(false),
+                Value::Null
+            ]))
+        );
+        assert_eq!(
+            value
+                .get("b")
+                .and_then(|b| b.get("c"))
+                .and_then(Value::as_str),
+            Some("x\né🎨")
+        );
+        assert_eq!(value.get("missing"), None);
+    }
+
+    #[test]
+    fn test_errors() {
+        let error = |s: &str| parse(s).unwrap_err();
+        assert_eq!(
+            error("{\n  \"a\": 1,\n  \"b\" 2\n}"),
+            JsonError {
+                line: 3,
+                column: 7,
+                message: "expected `:` after a member name"
+            }
+        );
+        assert_eq!(error("[1, 2").message, "expected `,` or `]`");
+        assert_eq!(error("\"abc").message, "unterminated string");
+        assert_eq!(error("{} {}").message, "trailing characters");
+        assert_eq!(error("{'a': 1}").message, "expected a member name");
+        for number in ["01", "1.", "-", ".5", "1.e3", "+1"] {
+            assert!(parse(number).is_err(), "{}", number);
+        }
+        assert_eq!(error("\"\\ud800\"").message, "unpaired surrogate");
+        assert_eq!(error(&"[".repeat(200)).message, "nested too deeply");
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        let text = r##"{
+  "color": {
+    "accent": {
+      "$type": "color",
+      "$value": "#ff8800"
+    },
+    "quote \" and \\": [],
+    "empty": {}
+  },
+  "n": [
+    1,
+    0.5
+  ]
+}
+"##;
+        let value = parse(text).unwrap();
+        assert_eq!(to_pretty_string(&value), text);
+    }
+
+    // a Value read back through its own Deserializer is the same Value
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_roundtrip() {
+        use serde::Deserialize;
+
+        let text = r#"{"a": [null, true, -3, 0.5, 4294967295, "s"], "a": {}, "b": []}"#;
+        let value = parse(text).unwrap();
+        assert_eq!(Value::deserialize(value.clone()).unwrap(), value);
+        let Value::Object(members) = Value::deserialize(value).unwrap() else {
+            panic!("not an object")
+        };
+        assert_eq!(members.len(), 3);
+    }
+}
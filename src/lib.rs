@@ -1,10 +1,92 @@
+//! ```
+//! use cool_rs::prelude::*;
+//!
+//! # fn main() -> Result<(), ParseFormatError> {
+//! let c = parse_color("#fe8019")?;
+//! assert_eq!(c, Canonical::new(254, 128, 25, 255));
+//! # Ok(())
+//! # }
+//! ```
+
 #[macro_use]
 extern crate lazy_static;
 
+// debug events through the `log` facade with the log feature, target
+// "cool_rs". `log` has no spans, so a parse is bracketed by two events
+// instead: "reading" with the `path` and `format` fields, and "read" with
+// the same and `colors`, the number of entries. lenient parsing adds a
+// "skipping line" event with `line` and `error`. parse_auto says which
+// reader it picks with `path`, `format` and `detected_by`, and each fallback
+// it takes with `path`, `from` and `to`. without the feature the arguments
+// are still type checked but nothing is formatted or emitted
+#[cfg(feature = "log")]
+macro_rules! debug_event {
+    ($($arg:tt)*) => { log::debug!(target: "cool_rs", $($arg)*) };
+}
+#[cfg(not(feature = "log"))]
+macro_rules! debug_event {
+    ($($key:ident $(:$capture:tt)? = $value:expr),+; $($arg:tt)+) => {
+        if false {
+            $(let _ = &$value;)+
+            let _ = format_args!($($arg)+);
+        }
+    };
+    ($($arg:tt)*) => {
+        if false {
+            let _ = format_args!($($arg)*);
+        }
+    };
+}
+
+/// A [`Canonical`](color::Canonical) from a hex literal, checked at compile
+/// time. See `Canonical::from_hex_const` for the accepted forms.
+///
+/// ```
+/// use cool_rs::{color::Canonical, hex};
+///
+/// const ACCENT: Canonical = hex!("#fe8019");
+/// assert_eq!(ACCENT, Canonical::new(254, 128, 25, 255));
+/// ```
+///
+/// ```compile_fail
+/// let c = cool_rs::hex!("#fe80zz");
+/// ```
+#[macro_export]
+macro_rules! hex {
+    ($hex:expr) => {{
+        // through a const so bad literals fail the build in any context
+        const COLOR: $crate::color::Canonical = $crate::color::Canonical::from_hex_const($hex);
+        COLOR
+    }};
+}
+
 pub mod color;
 pub mod errors;
 pub mod reader;
 pub mod formats;
+pub mod registry;
+#[cfg(feature = "parallel")]
+mod parallel;
+#[cfg(feature = "json")]
+mod json;
+#[cfg(feature = "digest")]
+mod sha256;
+#[cfg(feature = "diagnostics")]
+pub mod diagnostics;
+#[cfg(feature = "builtin-schemes")]
+pub mod schemes;
+#[cfg(test)]
+#[path = "../tests/common/temp.rs"]
+mod temp;
+
 
+// the single entry point for parsing one color, see formats::parse_any
+pub use formats::parse_any as parse_color;
 
-pub mod prelude {}
\ No newline at end of file
+// glob import this for the common types, traits and functions
+pub mod prelude {
+    pub use crate::color::{Canonical, Scheme, RGB, RGBA};
+    pub use crate::errors::{ColorError, ErrorKind, ParseFormatError, SchemeReaderError};
+    pub use crate::formats::{parse_any, ColorFormat};
+    pub use crate::parse_color;
+}
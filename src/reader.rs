@@ -1,21 +1,1495 @@
-use std::io::{BufRead, BufReader};
-use std::{fs::File, path::Path};
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::io::{self, BufRead, BufReader, IsTerminal, Read};
+use std::ops::Range;
+use std::{
+    fs::{self, File},
+    path::{Path, PathBuf},
+};
 
-use crate::color::Scheme;
+use crate::color::{EntryRef, Scheme, SchemeRef, SourceLocation, ValidationRules};
 use crate::errors::*;
+use crate::formats::{
+    parse_with_options, ColorFormat, ColorFormats, ParseOptions, SpaceSeparatedFormat,
+};
 
+#[cfg(feature = "async")]
+mod nonblocking;
+#[cfg(feature = "async")]
+pub use nonblocking::{parse_async, parse_async_with, parse_from_async_reader};
+#[cfg(feature = "watch")]
+mod watcher;
+#[cfg(feature = "watch")]
+pub use watcher::{watch, watch_with, WatchHandle, DEBOUNCE};
+#[cfg(feature = "http")]
+mod remote;
+#[cfg(feature = "http")]
+pub use remote::{parse_url, parse_url_with, HTTP_TIMEOUT};
+#[cfg(feature = "mmap")]
+mod mapped;
+#[cfg(feature = "mmap")]
+pub use mapped::{parse_mmap, parse_mmap_with};
+#[cfg(feature = "json")]
+pub mod tokens;
+#[cfg(feature = "json")]
+pub mod json_scheme;
+#[cfg(feature = "resolve")]
+mod resolve;
+#[cfg(feature = "resolve")]
+pub use resolve::{
+    config_dir, resolve_default, resolve_with, ResolveOptions, ResolveStep, SCHEME_VAR,
+};
+mod audit;
+pub use audit::{audit_dir, AuditProblem, DirAudit, InvalidScheme, NearDuplicate};
+pub mod ase;
+pub mod binary;
+pub mod png_palette;
+pub mod vim;
+pub mod emacs;
+pub mod i3;
+pub mod tmux;
+pub mod base16;
+#[cfg(feature = "aseprite")]
+pub mod aseprite;
+#[cfg(feature = "tmtheme")]
+pub mod tmtheme;
+mod document;
+pub use document::{parse_document, parse_document_with, SchemeDocument};
+mod export;
+mod writer;
+pub use writer::{write, write_to_string};
+mod transcode;
+pub use transcode::{
+    parse_auto, transcode, transcode_scheme, Lossy, SchemeFileFormat, TranscodeOptions,
+    TranscodeReport,
+};
+
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Default)]
+pub enum DuplicateNamePolicy {
+    #[default]
+    Error,
+    FirstWins,
+    // the later color replaces the earlier one, keeping its position
+    LastWins,
+}
+
+// resource limits for untrusted input. line and file limits apply to every
+// included file separately, max_colors to the merged scheme
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub struct Limits {
+    // in bytes, without the line terminator
+    pub max_line_len: usize,
+    pub max_colors: usize,
+    pub max_file_bytes: usize,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Limits {
+            max_line_len: 64 * 1024,
+            max_colors: 64 * 1024,
+            max_file_bytes: 64 * 1024 * 1024,
+        }
+    }
+}
+
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum Limit {
+    LineLength,
+    Colors,
+    FileBytes,
+}
+
+impl Display for Limit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match *self {
+            Limit::LineLength => write!(f, "maximum line length"),
+            Limit::Colors => write!(f, "maximum number of colors"),
+            Limit::FileBytes => write!(f, "maximum file size"),
+        }
+    }
+}
+
+#[derive(PartialEq, Eq, Debug, Clone, Default)]
+pub enum NameSource {
+    // the first line of the file is the scheme name
+    #[default]
+    FirstLine,
+    // the file stem, the first line is read like any other
+    Filename,
+    Explicit(String),
+}
+
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct ReaderOptions {
+    // lenient reading skips lines that fail to parse instead of failing
+    pub strict: bool,
+    pub duplicate_names: DuplicateNamePolicy,
+    // an `@name:` line overrides the name unless it is Explicit
+    pub name_source: NameSource,
+    // how many levels of `@include` may be nested below the file being read
+    pub max_include_depth: usize,
+    pub limits: Limits,
+    // how color values are parsed, which formats are accepted among others
+    pub parse: ParseOptions,
+}
+
+impl ReaderOptions {
+    pub fn strict() -> Self {
+        ReaderOptions {
+            strict: true,
+            duplicate_names: DuplicateNamePolicy::Error,
+            name_source: NameSource::FirstLine,
+            max_include_depth: 8,
+            limits: Limits::default(),
+            parse: ParseOptions::default(),
+        }
+    }
+
+    pub fn lenient() -> Self {
+        ReaderOptions {
+            strict: false,
+            duplicate_names: DuplicateNamePolicy::LastWins,
+            name_source: NameSource::FirstLine,
+            max_include_depth: 8,
+            limits: Limits::default(),
+            parse: ParseOptions::default(),
+        }
+    }
+}
+
+impl Default for ReaderOptions {
+    fn default() -> Self {
+        ReaderOptions::strict()
+    }
+}
+
+// the first line is the scheme name, every following non-empty line is a
+// color in any supported format or an `@name` reference, optionally
+// prefixed with `name:`, or a GIMP palette row `r g b name`.
+// `@include "other"` merges the entries of another scheme file, relative to
+// this one, at that point. `@name:` and `@author:` lines set metadata and
+// lines starting with `//` are comments. comment lines right above an entry
+// and a `//` comment after it become the entry's comment, see
+// write_to_string for the way back
 pub fn parse(path: &str) -> Result<Scheme, SchemeReaderError> {
-    let path = Path::new(path);
+    parse_with(path, &ReaderOptions::default())
+}
+
+pub fn parse_with(path: &str, options: &ReaderOptions) -> Result<Scheme, SchemeReaderError> {
+    read_file(Path::new(path), &[], options)
+}
+
+// the default reader with the colors read as `options` says, which may also
+// make it lenient or set its limits
+pub fn parse_with_opts(path: &str, options: &ParseOptions) -> Result<Scheme, SchemeReaderError> {
+    let options = ReaderOptions {
+        parse: options.clone(),
+        ..Default::default()
+    };
+    parse_with(path, &options)
+}
+
+// without a path to resolve them against, `@include` lines are an error
+pub fn parse_from_reader<R: BufRead>(
+    reader: R,
+    options: &ReaderOptions,
+) -> Result<Scheme, SchemeReaderError> {
+    read(reader, None, &[], options)
+}
+
+// NameSource::Filename always fails here, there is no file
+pub fn parse_from_str(name_source: NameSource, s: &str) -> Result<Scheme, SchemeReaderError> {
+    let options = ReaderOptions {
+        name_source,
+        ..Default::default()
+    };
+    parse_borrowed_with(s, &options).map(SchemeRef::into_owned)
+}
+
+// like parse_from_str, but the names in the scheme borrow from `s` instead
+// of being copied out line by line
+pub fn parse_borrowed(s: &str) -> Result<SchemeRef<'_>, SchemeReaderError> {
+    parse_borrowed_with(s, &ReaderOptions::default())
+}
+
+pub fn parse_borrowed_with<'s>(
+    s: &'s str,
+    options: &ReaderOptions,
+) -> Result<SchemeRef<'s>, SchemeReaderError> {
+    read_slice(s.as_bytes(), None, &[], options, read_file)
+}
+
+// reads standard input until it is closed, so it is meant for pipes. when
+// stdin is an interactive terminal it fails right away instead of waiting
+// for an end of file that might never come
+pub fn parse_stdin() -> Result<Scheme, SchemeReaderError> {
+    let stdin = io::stdin();
+    if stdin.is_terminal() {
+        return Err(SchemeReaderError::StdinIsTerminal);
+    }
+    parse_from_reader(stdin.lock(), &ReaderOptions::default())
+}
+
+// `chain` holds the files that (transitively) included this one
+fn read_file(
+    path: &Path,
+    chain: &[PathBuf],
+    options: &ReaderOptions,
+) -> Result<Scheme, SchemeReaderError> {
+    let io_error = |e| SchemeReaderError::io(e, Some(path));
+    debug_event!(path:% = path.display(), format = "native"; "reading {}", path.display());
+    let file = File::open(path).map_err(io_error)?;
+    let mut chain = chain.to_vec();
+    chain.push(path.canonicalize().map_err(io_error)?);
+    let scheme = read(BufReader::new(file), Some(path), &chain, options)?;
+    debug_event!(
+        path:% = path.display(), format = "native", colors = scheme.len();
+        "read {}: {} colors", path.display(), scheme.len()
+    );
+    Ok(scheme)
+}
+
+// `path` is None when reading without filesystem context, `chain` then is empty
+fn read<R: BufRead>(
+    mut reader: R,
+    path: Option<&Path>,
+    chain: &[PathBuf],
+    options: &ReaderOptions,
+) -> Result<Scheme, SchemeReaderError> {
+    let mut parser = LineParser::new(path, chain, options)?;
+    let mut buf = Vec::new();
+    loop {
+        buf.clear();
+        let limit = parser.read_limit();
+        let read = (&mut reader)
+            .take(limit)
+            .read_until(b'\n', &mut buf)
+            .map_err(|e| SchemeReaderError::io(e, path))?;
+        if read == 0 {
+            break;
+        }
+        if let Some(include) = parser.feed(&buf)? {
+            let canonical = include
+                .path
+                .canonicalize()
+                .map_err(|e| include.io_error(e))?;
+            parser.check_include(&canonical)?;
+            let included = read_file(&canonical, chain, options)?;
+            parser.merge(included, include.line)?;
+        }
+    }
+    parser.finish()
+}
+
+// read over input that is in memory as a whole. the lines are cut exactly
+// like the buffered reader cuts them, at a \n or after read_limit() bytes,
+// but names borrow from `bytes`. includes are read with `read_include`
+fn read_slice<'s>(
+    bytes: &'s [u8],
+    path: Option<&Path>,
+    chain: &[PathBuf],
+    options: &ReaderOptions,
+    read_include: fn(&Path, &[PathBuf], &ReaderOptions) -> Result<Scheme, SchemeReaderError>,
+) -> Result<SchemeRef<'s>, SchemeReaderError> {
+    let mut parser = LineParser::new(path, chain, options)?;
+    feed_slice(&mut parser, bytes, chain, options, read_include)?;
+    parser.finish_borrowed()
+}
+
+fn feed_slice<'s>(
+    parser: &mut LineParser<'_, 's>,
+    mut bytes: &'s [u8],
+    chain: &[PathBuf],
+    options: &ReaderOptions,
+    read_include: fn(&Path, &[PathBuf], &ReaderOptions) -> Result<Scheme, SchemeReaderError>,
+) -> Result<(), SchemeReaderError> {
+    while !bytes.is_empty() {
+        let limit = usize::try_from(parser.read_limit()).unwrap_or(usize::MAX);
+        let window = &bytes[..bytes.len().min(limit)];
+        let end = window
+            .iter()
+            .position(|&b| b == b'\n')
+            .map_or(window.len(), |newline| newline + 1);
+        let (line, rest) = bytes.split_at(end);
+        bytes = rest;
+        if let Some(include) = parser.feed_borrowed(line)? {
+            let canonical = include
+                .path
+                .canonicalize()
+                .map_err(|e| include.io_error(e))?;
+            parser.check_include(&canonical)?;
+            let included = read_include(&canonical, chain, options)?;
+            parser.merge(included, include.line)?;
+        }
+    }
+    Ok(())
+}
+
+const BOM: &[u8] = "\u{feff}".as_bytes();
+
+// an `@include` the frontend has to read and hand back to LineParser::merge
+pub(crate) struct PendingInclude {
+    // relative to the working directory, not yet canonical
+    pub path: PathBuf,
+    pub line: usize,
+}
+
+impl PendingInclude {
+    pub fn io_error(&self, e: io::Error) -> SchemeReaderError {
+        SchemeReaderError::io(e, Some(&self.path))
+    }
+}
+
+// where the color of an entry is written, as a byte range into the input
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub(crate) struct ValueSpan {
+    pub range: Range<usize>,
+    // `r g b name` rather than `name: value`
+    pub gimp_row: bool,
+}
+
+// the reader state shared by the sync and async frontends. they read raw
+// lines of at most read_limit() bytes and feed them in one by one, resolving
+// the includes that feed hands back. names are copied out of the lines, or
+// borrowed from them with feed_borrowed when the input outlives the parser
+pub(crate) struct LineParser<'a, 's> {
+    path: Option<&'a Path>,
+    chain: &'a [PathBuf],
+    options: &'a ReaderOptions,
+    scheme: SchemeRef<'s>,
+    name: Option<Cow<'s, str>>,
+    // entry index and line of every name seen so far, and whether it came
+    // from an include. included names may be overridden regardless of policy
+    names: HashMap<Cow<'s, str>, (usize, usize, bool)>,
+    // per entry, only kept for SchemeDocument. None for colors that come
+    // from an include
+    spans: Option<Vec<Option<ValueSpan>>>,
+    // the comment lines right above the current line
+    comment: Option<Cow<'s, str>>,
+    line: usize,
+    offset: usize,
+}
+
+impl<'a, 's> LineParser<'a, 's> {
+    pub fn new(
+        path: Option<&'a Path>,
+        chain: &'a [PathBuf],
+        options: &'a ReaderOptions,
+    ) -> Result<Self, SchemeReaderError> {
+        let name = match options.name_source {
+            NameSource::FirstLine => None,
+            NameSource::Filename => Some(
+                path.and_then(Path::file_stem)
+                    .ok_or(SchemeReaderError::FilenameWithoutPath)?
+                    .to_string_lossy()
+                    .trim()
+                    .to_string(),
+            ),
+            NameSource::Explicit(ref name) => Some(name.trim().to_string()),
+        };
+        Ok(LineParser {
+            path,
+            chain,
+            options,
+            scheme: SchemeRef::new(Cow::Borrowed("")),
+            name: name.map(Cow::Owned),
+            names: HashMap::new(),
+            spans: None,
+            comment: None,
+            line: 0,
+            offset: 0,
+        })
+    }
+
+    // ParseOptions::limits wins over the reader's own
+    fn limits(&self) -> Limits {
+        self.options.parse.limits.unwrap_or(self.options.limits)
+    }
+
+    pub fn track_spans(&mut self) {
+        self.spans = Some(Vec::new());
+    }
+
+    pub fn take_spans(&mut self) -> Vec<Option<ValueSpan>> {
+        self.spans.take().unwrap_or_default()
+    }
+
+    // room for the longest allowed line, a \r\n terminator and one byte past
+    // the file limit, so neither limit ever needs more to be buffered
+    pub fn read_limit(&self) -> u64 {
+        let limits = self.limits();
+        let remaining = limits.max_file_bytes.saturating_sub(self.offset) + 1;
+        remaining.min(limits.max_line_len.saturating_add(2)) as u64
+    }
+
+    // `raw` is the next line including its terminator, if any
+    pub fn feed(&mut self, raw: &[u8]) -> Result<Option<PendingInclude>, SchemeReaderError> {
+        self.feed_with(raw, |s| Cow::Owned(s.into()))
+    }
+
+    pub fn feed_borrowed(
+        &mut self,
+        raw: &'s [u8],
+    ) -> Result<Option<PendingInclude>, SchemeReaderError> {
+        self.feed_with(raw, Cow::Borrowed)
+    }
+
+    // `keep` turns the parts of a line that end up in the scheme into names
+    fn feed_with<'l>(
+        &mut self,
+        raw: &'l [u8],
+        keep: impl Fn(&'l str) -> Cow<'s, str>,
+    ) -> Result<Option<PendingInclude>, SchemeReaderError> {
+        let decoded = self.decode(raw)?;
+        let line_number = self.line;
+        if line_number == 1 && self.options.name_source == NameSource::FirstLine {
+            self.name = Some(keep(decoded.trim()));
+            return Ok(None);
+        }
+
+        let line = decoded.trim();
+        if let Some(comment) = line.strip_prefix("//") {
+            let comment = comment.trim();
+            self.comment = Some(match self.comment.take() {
+                Some(above) => Cow::Owned(format!("{}\n{}", above, comment)),
+                None => keep(comment),
+            });
+            return Ok(None);
+        }
+        // only comments right above an entry are its own
+        let above = self.comment.take();
+        if line.is_empty() {
+            return Ok(None);
+        }
+
+        if let Some(name) = line.strip_prefix("@name:") {
+            if !matches!(self.options.name_source, NameSource::Explicit(_)) {
+                self.name = Some(keep(name.trim()));
+            }
+            return Ok(None);
+        }
+        if let Some(author) = line.strip_prefix("@author:") {
+            self.scheme.author = Some(keep(author.trim()));
+            return Ok(None);
+        }
+
+        if let Some(target) = include_target(line) {
+            let current = match self.chain.last() {
+                Some(current) if self.path.is_some() => current,
+                _ => return Err(SchemeReaderError::IncludeWithoutPath { line: line_number }),
+            };
+            return Ok(Some(PendingInclude {
+                path: current
+                    .parent()
+                    .unwrap_or_else(|| Path::new(""))
+                    .join(target),
+                line: line_number,
+            }));
+        }
+
+        // a trailing comment goes after the comments above
+        let (line, trailing) = match line.split_once("//") {
+            Some((line, comment)) => (line.trim_end(), Some(comment.trim())),
+            None => (line, None),
+        };
+        let comment = match (above, trailing.filter(|c| !c.is_empty())) {
+            (Some(above), Some(trailing)) => Some(Cow::Owned(format!("{}\n{}", above, trailing))),
+            (above, trailing) => above.or_else(|| trailing.map(&keep)),
+        };
+
+        let (mut entry, value, gimp_row) =
+            match parse_entry(line, line_number, &self.scheme, self.options, &keep) {
+                Ok(parsed) => parsed,
+                Err(e) if !self.options.strict || self.options.parse.lenient => {
+                    debug_event!(
+                        line = line_number, error:% = e;
+                        "skipping line {}: {}", line_number, e
+                    );
+                    return Ok(None);
+                }
+                Err(e) => return Err(e),
+            };
+        // value is a part of raw, and raw starts where decode left off
+        let start = self.offset - raw.len() + (value.as_ptr() as usize - raw.as_ptr() as usize);
+        let span = ValueSpan {
+            range: start..start + value.len(),
+            gimp_row,
+        };
+        entry.comment = comment;
+        let before = value.as_ptr() as usize - decoded.as_ptr() as usize;
+        entry.source = Some(SourceLocation {
+            path: self.path.map(Path::to_path_buf),
+            line: line_number,
+            column: decoded[..before].chars().count() + 1,
+        });
+
+        if let Some(name) = &entry.name {
+            if let Some(&(existing, first_line, included)) = self.names.get(&name[..]) {
+                match self.options.duplicate_names {
+                    _ if included => {
+                        self.scheme.entries[existing].color = entry.color;
+                        self.scheme.entries[existing].source = entry.source;
+                        self.set_span(existing, Some(span));
+                        self.names
+                            .insert(name.clone(), (existing, line_number, false));
+                    }
+                    DuplicateNamePolicy::Error => {
+                        return Err(SchemeReaderError::DuplicateNameError {
+                            name: name.to_string(),
+                            first_line,
+                            line: line_number,
+                        })
+                    }
+                    DuplicateNamePolicy::FirstWins => {}
+                    DuplicateNamePolicy::LastWins => {
+                        self.scheme.entries[existing].color = entry.color;
+                        self.scheme.entries[existing].source = entry.source;
+                        self.set_span(existing, Some(span));
+                    }
+                }
+                return Ok(None);
+            }
+            self.names.insert(
+                name.clone(),
+                (self.scheme.entries.len(), line_number, false),
+            );
+        }
+        self.push(entry, line_number, Some(span))?;
+        Ok(None)
+    }
+
+    // strips a leading byte order mark and the line terminator, and enforces
+    // the line and file limits
+    fn decode<'l>(&mut self, raw: &'l [u8]) -> Result<&'l str, SchemeReaderError> {
+        let start = self.offset;
+        self.offset += raw.len();
+        self.line += 1;
+
+        let limits = self.limits();
+        if self.offset > limits.max_file_bytes {
+            return Err(SchemeReaderError::LimitExceeded {
+                limit: Limit::FileBytes,
+                line: self.line,
+            });
+        }
+
+        let mut bytes = raw;
+        let mut skipped = 0;
+        if start == 0 && bytes.starts_with(BOM) {
+            bytes = &bytes[BOM.len()..];
+            skipped = BOM.len();
+        }
+        bytes = bytes.strip_suffix(b"\n").unwrap_or(bytes);
+        bytes = bytes.strip_suffix(b"\r").unwrap_or(bytes);
+        if bytes.len() > limits.max_line_len {
+            return Err(SchemeReaderError::LimitExceeded {
+                limit: Limit::LineLength,
+                line: self.line,
+            });
+        }
+
+        std::str::from_utf8(bytes).map_err(|e| SchemeReaderError::InvalidUtf8 {
+            line: self.line,
+            offset: start + skipped + e.valid_up_to(),
+        })
+    }
+
+    // fails if including the canonical path would close a cycle or nest too deep
+    pub fn check_include(&self, canonical: &Path) -> Result<(), SchemeReaderError> {
+        let mut included_chain = self.chain.to_vec();
+        included_chain.push(canonical.to_path_buf());
+        if self.chain.iter().any(|path| path == canonical) {
+            return Err(SchemeReaderError::IncludeCycle {
+                chain: included_chain,
+            });
+        }
+        if self.chain.len() > self.options.max_include_depth {
+            return Err(SchemeReaderError::IncludeTooDeep {
+                chain: included_chain,
+            });
+        }
+        Ok(())
+    }
+
+    pub fn merge(&mut self, included: Scheme, line: usize) -> Result<(), SchemeReaderError> {
+        for entry in included.entries.into_iter().map(EntryRef::from) {
+            match entry
+                .name
+                .as_ref()
+                .and_then(|name| self.names.get(&name[..]))
+            {
+                Some(&(existing, _, _)) => {
+                    self.scheme.entries[existing].color = entry.color;
+                    self.scheme.entries[existing].source = entry.source;
+                    self.set_span(existing, None);
+                }
+                None => {
+                    if let Some(name) = &entry.name {
+                        self.names
+                            .insert(name.clone(), (self.scheme.entries.len(), line, true));
+                    }
+                    self.push(entry, line, None)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn push(
+        &mut self,
+        entry: EntryRef<'s>,
+        line: usize,
+        span: Option<ValueSpan>,
+    ) -> Result<(), SchemeReaderError> {
+        if self.scheme.len() >= self.limits().max_colors {
+            return Err(SchemeReaderError::LimitExceeded {
+                limit: Limit::Colors,
+                line,
+            });
+        }
+        self.scheme.entries.push(entry);
+        if let Some(spans) = &mut self.spans {
+            spans.push(span);
+        }
+        Ok(())
+    }
+
+    fn set_span(&mut self, index: usize, span: Option<ValueSpan>) {
+        if let Some(spans) = &mut self.spans {
+            spans[index] = span;
+        }
+    }
+
+    pub fn finish(self) -> Result<Scheme, SchemeReaderError> {
+        self.finish_borrowed().map(SchemeRef::into_owned)
+    }
+
+    pub fn finish_borrowed(mut self) -> Result<SchemeRef<'s>, SchemeReaderError> {
+        if self.line == 0 {
+            return Err(SchemeReaderError::NoLinesError);
+        }
+        self.scheme.name = self.name.unwrap_or_default();
+        Ok(self.scheme)
+    }
+}
+
+// `@include "path"`, quotes optional
+fn include_target(line: &str) -> Option<&str> {
+    let rest = line.strip_prefix("@include")?;
+    if !rest.starts_with(char::is_whitespace) {
+        return None;
+    }
+    Some(rest.trim().trim_matches('"'))
+}
+
+// every file directly in `path`, ordered by file name. reading stops at the
+// first file that fails to parse
+pub fn parse_dir(path: &str) -> Result<Vec<Scheme>, SchemeReaderError> {
+    dir_files(Path::new(path))?
+        .iter()
+        .map(|file| read_file(file, &[], &ReaderOptions::default()))
+        .collect()
+}
+
+// parse_dir with the files read in parallel. every file is read, but the
+// result is the same as parse_dir's
+#[cfg(feature = "parallel")]
+pub fn parse_dir_parallel(path: &str) -> Result<Vec<Scheme>, SchemeReaderError> {
+    let files = dir_files(Path::new(path))?;
+    crate::parallel::par_map(&files, |file| {
+        read_file(file, &[], &ReaderOptions::default())
+    })
+    .into_iter()
+    .collect()
+}
+
+fn dir_files(dir: &Path) -> Result<Vec<PathBuf>, SchemeReaderError> {
+    let io_error = |e| SchemeReaderError::io(e, Some(dir));
+    let mut files = Vec::new();
+    for entry in fs::read_dir(dir).map_err(io_error)? {
+        let entry = entry.map_err(io_error)?;
+        if entry.file_type().map_err(io_error)?.is_file() {
+            files.push(entry.path());
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+// parses the file and fails with the full report if any rule is violated
+pub fn parse_validated(path: &str, rules: &ValidationRules) -> Result<Scheme, SchemeReaderError> {
+    let scheme = parse(path)?;
+    let report = scheme.validate(rules);
+    if report.is_ok() {
+        Ok(scheme)
+    } else {
+        Err(SchemeReaderError::ValidationError { report })
+    }
+}
+
+// a color value of `@name` reuses the color of an earlier entry, so forward
+// and cyclic references can't resolve. besides the entry, gives the part of
+// `line` the color was read from and whether the line is a GIMP row
+fn parse_entry<'l, 's>(
+    line: &'l str,
+    line_number: usize,
+    scheme: &SchemeRef<'s>,
+    options: &ReaderOptions,
+    keep: impl Fn(&'l str) -> Cow<'s, str>,
+) -> Result<(EntryRef<'s>, &'l str, bool), SchemeReaderError> {
+    let parse_error = |source| SchemeReaderError::ParseColorError {
+        line: line_number,
+        text: line.into(),
+        source,
+    };
+    let gimp_rows = match options.parse.formats {
+        Some(ref formats) => formats.contains(&ColorFormats::SpaceSeparated),
+        None => true,
+    };
+    if gimp_rows && SpaceSeparatedFormat::matches(line) {
+        let (color, name) = SpaceSeparatedFormat::parse_named(line).map_err(parse_error)?;
+        let value = match name {
+            Some(name) => line[..name.as_ptr() as usize - line.as_ptr() as usize].trim_end(),
+            None => line,
+        };
+        return Ok((EntryRef::new(name.map(keep), color), value, true));
+    }
+
+    let (name, value) = match line.split_once(':') {
+        Some((name, value)) => (Some(name.trim()), value.trim()),
+        None => (None, line),
+    };
+    let color =
+        match value.strip_prefix('@') {
+            Some(reference) => *scheme.get(reference.trim()).ok_or_else(|| {
+                SchemeReaderError::UnresolvedReference {
+                    name: reference.trim().into(),
+                    line: line_number,
+                }
+            })?,
+            None => parse_with_options(value, &options.parse).map_err(parse_error)?,
+        };
+    // a trailing ; is not part of the color
+    let value = value.strip_suffix(';').unwrap_or(value).trim_end();
+    Ok((EntryRef::new(name.map(keep), color), value, false))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+    use crate::color::{Canonical, Entry, Violation};
+    use crate::errors::ErrorKind;
+    use crate::temp::TempDir;
+
+    #[test]
+    fn test_parse() {
+        let dir = TempDir::new("reader_parse");
+        let path = dir.write(
+            "cool_rs_reader_parse.txt",
+            "mono\nbackground: #000000\n\n  rgb(255, 255, 255)\nfg : rgb(0.5, 0.5, 0.5)\n",
+        );
+        let scheme = parse(&path).unwrap();
+
+        assert_eq!(scheme.name, "mono");
+        assert_eq!(
+            scheme.entries,
+            vec![
+                Entry::named("background", Canonical::new(0, 0, 0, 255)),
+                Entry::new(Canonical::new(255, 255, 255, 255)),
+                Entry::named("fg", Canonical::new(128, 128, 128, 255)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let dir = TempDir::new("reader_parse_errors");
+        let empty = dir.write("cool_rs_reader_empty.txt", "");
+        assert_eq!(parse(&empty).unwrap_err().kind(), ErrorKind::NoLines);
+
+        let bad = dir.write("cool_rs_reader_bad.txt", "bad\n#000000\n\n#zz0000\n");
+        assert!(matches!(
+            parse(&bad),
+            Err(SchemeReaderError::ParseColorError { line: 4, .. })
+        ));
+    }
+
+    #[test]
+    fn test_duplicate_names() {
+        let dir = TempDir::new("reader_duplicate_names");
+        let path = dir.write(
+            "cool_rs_reader_duplicates.txt",
+            "dupes\nbackground: #000000\nforeground: #ffffff\nbackground: #111111\n",
+        );
+        let with_policy = |duplicate_names| {
+            parse_with(
+                &path,
+                &ReaderOptions {
+                    duplicate_names,
+                    ..Default::default()
+                },
+            )
+        };
+
+        match with_policy(DuplicateNamePolicy::Error) {
+            Err(SchemeReaderError::DuplicateNameError {
+                name,
+                first_line: 2,
+                line: 4,
+            }) => {
+                assert_eq!(name, "background")
+            }
+            other => panic!("unexpected result {:?}", other),
+        }
+
+        let first = with_policy(DuplicateNamePolicy::FirstWins).unwrap();
+        assert_eq!(first.len(), 2);
+        assert_eq!(first.get("background"), Some(&Canonical::new(0, 0, 0, 255)));
+
+        let last = with_policy(DuplicateNamePolicy::LastWins).unwrap();
+        assert_eq!(last.len(), 2);
+        assert_eq!(
+            last.entries[0],
+            Entry::named("background", Canonical::new(17, 17, 17, 255))
+        );
+
+        assert!(parse(&path).is_err());
+        assert_eq!(parse_with(&path, &ReaderOptions::lenient()).unwrap(), last);
+    }
+
+    #[test]
+    fn test_references() {
+        let dir = TempDir::new("reader_references");
+        let path = dir.write(
+            "cool_rs_reader_references.txt",
+            "refs\naccent: #fe8019\ncursor: @accent\n@cursor\n",
+        );
+        let scheme = parse(&path).unwrap();
+        let accent = Canonical::new(0xfe, 0x80, 0x19, 255);
+        assert_eq!(
+            scheme.entries,
+            vec![
+                Entry::named("accent", accent),
+                Entry::named("cursor", accent),
+                Entry::new(accent),
+            ]
+        );
+
+        let unknown = dir.write(
+            "cool_rs_reader_unknown_reference.txt",
+            "refs\naccent: #fe8019\ncursor: @acent\n",
+        );
+        match parse(&unknown) {
+            Err(SchemeReaderError::UnresolvedReference { name, line: 3 }) => {
+                assert_eq!(name, "acent")
+            }
+            other => panic!("unexpected result {:?}", other),
+        }
+
+        let forward = dir.write(
+            "cool_rs_reader_forward_reference.txt",
+            "refs\ncursor: @accent\naccent: #fe8019\n",
+        );
+        match parse(&forward) {
+            Err(SchemeReaderError::UnresolvedReference { name, line: 2 }) => {
+                assert_eq!(name, "accent")
+            }
+            other => panic!("unexpected result {:?}", other),
+        }
+    }
+
+    fn include_dir(name: &str) -> TempDir {
+        let dir = TempDir::new(name);
+        fs::create_dir(dir.join("base")).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_include() {
+        let dir = include_dir("reader_include");
+        fs::write(
+            dir.join("base/palette.txt"),
+            "palette\nblack: #000000\nred: #ff0000\n",
+        )
+        .unwrap();
+        fs::write(
+            dir.join("base/dark.txt"),
+            "dark\n@include \"palette.txt\"\nbackground: @black\n",
+        )
+        .unwrap();
+        fs::write(
+            dir.join("theme.txt"),
+            "theme\n@include \"base/dark.txt\"\nred: #cc0000\naccent: @red\n",
+        )
+        .unwrap();
+
+        let scheme = parse(dir.join("theme.txt").to_str().unwrap()).unwrap();
+        let black = Canonical::new(0, 0, 0, 255);
+        let red = Canonical::new(0xcc, 0, 0, 255);
+        assert_eq!(scheme.name, "theme");
+        assert_eq!(
+            scheme.entries,
+            vec![
+                Entry::named("black", black),
+                Entry::named("red", red),
+                Entry::named("background", black),
+                Entry::named("accent", red),
+            ]
+        );
+
+        let options = ReaderOptions {
+            max_include_depth: 1,
+            ..Default::default()
+        };
+        assert!(matches!(
+            parse_with(dir.join("theme.txt").to_str().unwrap(), &options),
+            Err(SchemeReaderError::IncludeTooDeep { chain }) if chain.len() == 3
+        ));
+    }
+
+    #[test]
+    fn test_parse_with_opts() {
+        let dir = TempDir::new("reader_parse_with_opts");
+        let path = dir.write(
+            "cool_rs_reader_parse_with_opts.txt",
+            "opts\nbg: #000000\nfg: rgb(300, 255, 255)\nbroken\n",
+        );
+        let e = parse_with_opts(&path, &ParseOptions::new()).unwrap_err();
+        assert_eq!(e.kind(), ErrorKind::ParseColor);
+
+        let lenient = parse_with_opts(&path, &ParseOptions::new().lenient()).unwrap();
+        assert_eq!(lenient.len(), 1);
+        let clamped = ParseOptions::new().clamp_out_of_range().lenient();
+        let scheme = parse_with_opts(&path, &clamped).unwrap();
+        assert_eq!(scheme.get("fg"), Some(&Canonical::new(255, 255, 255, 255)));
+
+        let hex_only = ParseOptions::new().allow(&[ColorFormats::Hex]).lenient();
+        assert_eq!(parse_with_opts(&path, &hex_only).unwrap().len(), 1);
+
+        let limits = Limits {
+            max_colors: 1,
+            ..Default::default()
+        };
+        let limited = clamped.limits(limits);
+        match parse_with_opts(&path, &limited) {
+            Err(SchemeReaderError::LimitExceeded {
+                limit: Limit::Colors,
+                line: 3,
+            }) => {}
+            other => panic!("unexpected result {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_entry_sources() {
+        let dir = include_dir("reader_sources");
+        fs::write(
+            dir.join("base/palette.txt"),
+            "palette\nblack: #000000\nred: #ff0000\n",
+        )
+        .unwrap();
+        fs::write(
+            dir.join("theme.txt"),
+            "theme\n@include \"base/palette.txt\"\nred: #cc0000\n\taccent:  @red\n",
+        )
+        .unwrap();
+        let theme = dir.join("theme.txt");
+        let scheme = parse(theme.to_str().unwrap()).unwrap();
+        let sources: Vec<_> = scheme
+            .entries
+            .iter()
+            .map(|e| e.source.clone().unwrap())
+            .collect();
+        // included entries point into their own file, an override at itself
+        let palette = dir.join("base/palette.txt").canonicalize().unwrap();
+        assert_eq!(sources[0].path.as_ref(), Some(&palette));
+        assert_eq!((sources[0].line, sources[0].column), (2, 8));
+        assert_eq!(sources[1].to_string(), format!("{}:3:6", theme.display()));
+        assert_eq!(sources[2].to_string(), format!("{}:4:11", theme.display()));
+
+        let scheme = parse_from_str(NameSource::FirstLine, "mono\n\n  white: #ffffff\n").unwrap();
+        let source = scheme.entries[0].source.as_ref().unwrap();
+        assert_eq!(source.to_string(), "3:10");
+        // the source doesn't take part in comparisons
+        let white = Canonical::new(255, 255, 255, 255);
+        assert_eq!(scheme.entries[0], Entry::named("white", white));
+    }
+
+    #[test]
+    fn test_include_cycle() {
+        let dir = include_dir("reader_include_cycle");
+        fs::write(dir.join("a.txt"), "a\n@include b.txt\n").unwrap();
+        fs::write(dir.join("b.txt"), "b\n#000000\n@include \"a.txt\"\n").unwrap();
+
+        match parse(dir.join("a.txt").to_str().unwrap()) {
+            Err(SchemeReaderError::IncludeCycle { chain }) => {
+                let names: Vec<_> = chain
+                    .iter()
+                    .map(|p| p.file_name().unwrap().to_str().unwrap())
+                    .collect();
+                assert_eq!(names, vec!["a.txt", "b.txt", "a.txt"]);
+            }
+            other => panic!("unexpected result {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_parse_from_reader() {
+        let input = "mono\nbackground: #000000\n";
+        assert_eq!(
+            parse_from_reader(input.as_bytes(), &ReaderOptions::default())
+                .unwrap()
+                .get("background"),
+            Some(&Canonical::new(0, 0, 0, 255))
+        );
+
+        let input = "mono\n#000000\n@include \"base.txt\"\n";
+        assert!(matches!(
+            parse_from_reader(input.as_bytes(), &ReaderOptions::default()),
+            Err(SchemeReaderError::IncludeWithoutPath { line: 3 })
+        ));
+    }
+
+    #[test]
+    fn test_name_source() {
+        let dir = TempDir::new("reader_name_source");
+        let path = dir.write("cool_rs_reader_name.txt", "// mono\n#000000\n#ffffff\n");
+        let with_source = |name_source| {
+            parse_with(
+                &path,
+                &ReaderOptions {
+                    name_source,
+                    ..Default::default()
+                },
+            )
+            .unwrap()
+        };
+
+        let first_line = with_source(NameSource::FirstLine);
+        assert_eq!(first_line.name, "// mono");
+        assert_eq!(first_line.len(), 2);
+
+        let filename = with_source(NameSource::Filename);
+        assert_eq!(filename.name, "cool_rs_reader_name");
+        assert_eq!(filename.len(), 2);
+
+        let explicit = with_source(NameSource::Explicit("given".into()));
+        assert_eq!(explicit.name, "given");
+        assert_eq!(explicit.len(), 2);
+
+        let options = ReaderOptions {
+            name_source: NameSource::Filename,
+            ..Default::default()
+        };
+        assert_eq!(
+            parse_from_reader("#000000\n".as_bytes(), &options)
+                .unwrap_err()
+                .kind(),
+            ErrorKind::FilenameWithoutPath
+        );
+    }
+
+    #[test]
+    fn test_header_lines() {
+        let dir = TempDir::new("reader_header_lines");
+        let input = "@name: Night Owl\n@author: someone\nbackground: #011627\n";
+        let options = ReaderOptions {
+            name_source: NameSource::Filename,
+            ..Default::default()
+        };
+        let path = dir.write("cool_rs_reader_headers.txt", input);
+        let scheme = parse_with(&path, &options).unwrap();
+        assert_eq!(scheme.name, "Night Owl");
+        assert_eq!(scheme.author.as_deref(), Some("someone"));
+        assert_eq!(scheme.len(), 1);
+
+        // the first line is still the name, @name: overrides it
+        let scheme = parse_from_reader(
+            format!("night-owl\n{}", input).as_bytes(),
+            &ReaderOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(scheme.name, "Night Owl");
+
+        let options = ReaderOptions {
+            name_source: NameSource::Explicit("owl".into()),
+            ..Default::default()
+        };
+        let scheme = parse_from_reader(input.as_bytes(), &options).unwrap();
+        assert_eq!(scheme.name, "owl");
+        assert_eq!(scheme.author.as_deref(), Some("someone"));
+    }
+
+    #[test]
+    fn test_bom_and_crlf() {
+        let dir = TempDir::new("reader_bom_and_crlf");
+        let clean = "mono\nbackground: #000000\n@author: someone\n#ffffff";
+        let windows = format!("\u{feff}{}\r\n", clean.replace('\n', "\r\n"));
+
+        let clean = parse(&dir.write("cool_rs_reader_clean.txt", clean)).unwrap();
+        let windows = parse(&dir.write("cool_rs_reader_windows.txt", &windows)).unwrap();
+        assert_eq!(windows, clean);
+        assert_eq!(windows.name, "mono");
+
+        let options = ReaderOptions {
+            name_source: NameSource::Explicit("mono".into()),
+            ..Default::default()
+        };
+        let bom_color = parse_from_reader("\u{feff}#000000\r\n".as_bytes(), &options).unwrap();
+        assert_eq!(bom_color.len(), 1);
+    }
+
+    #[test]
+    fn test_invalid_utf8() {
+        let mut input = b"mono\r\n#000000\r\nbad: #ff".to_vec();
+        input.extend_from_slice(&[0xff, 0xfe]);
+        input.extend_from_slice(b"00\n");
+
+        assert!(matches!(
+            parse_from_reader(&input[..], &ReaderOptions::default()),
+            Err(SchemeReaderError::InvalidUtf8 {
+                line: 3,
+                offset: 23
+            })
+        ));
+    }
+
+    #[test]
+    fn test_limits() {
+        let input = "mono\n#000000\n#111111\n#222222\n";
+        let with_limits = |limits| {
+            parse_from_reader(
+                input.as_bytes(),
+                &ReaderOptions {
+                    limits,
+                    ..Default::default()
+                },
+            )
+        };
+        assert_eq!(with_limits(Limits::default()).unwrap().len(), 3);
+
+        let line = Limits {
+            max_line_len: 6,
+            ..Default::default()
+        };
+        assert!(matches!(
+            with_limits(line),
+            Err(SchemeReaderError::LimitExceeded {
+                limit: Limit::LineLength,
+                line: 2
+            })
+        ));
+        let line = Limits {
+            max_line_len: 7,
+            ..Default::default()
+        };
+        assert!(with_limits(line).is_ok());
+
+        let colors = Limits {
+            max_colors: 2,
+            ..Default::default()
+        };
+        assert!(matches!(
+            with_limits(colors),
+            Err(SchemeReaderError::LimitExceeded {
+                limit: Limit::Colors,
+                line: 4
+            })
+        ));
+
+        let file = Limits {
+            max_file_bytes: input.len() - 1,
+            ..Default::default()
+        };
+        assert!(matches!(
+            with_limits(file),
+            Err(SchemeReaderError::LimitExceeded {
+                limit: Limit::FileBytes,
+                line: 4
+            })
+        ));
+        let file = Limits {
+            max_file_bytes: input.len(),
+            ..Default::default()
+        };
+        assert!(with_limits(file).is_ok());
+    }
+
+    #[test]
+    fn test_line_limit_reads_bounded() {
+        // an endless line must fail without reading all of it
+        let endless = std::io::repeat(b'a');
+        let options = ReaderOptions {
+            limits: Limits {
+                max_line_len: 1024,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        assert!(matches!(
+            parse_from_reader(BufReader::new(endless), &options),
+            Err(SchemeReaderError::LimitExceeded {
+                limit: Limit::LineLength,
+                line: 1
+            })
+        ));
+    }
+
+    #[test]
+    fn test_allowed_formats() {
+        let input = "mono\n#000000\nrgb(255, 255, 255)\n";
+        assert_eq!(
+            parse_from_reader(input.as_bytes(), &ReaderOptions::default())
+                .unwrap()
+                .len(),
+            2
+        );
+
+        let hex_only = ReaderOptions {
+            parse: ParseOptions {
+                formats: Some(vec![ColorFormats::Hex]),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        match parse_from_reader(input.as_bytes(), &hex_only) {
+            Err(SchemeReaderError::ParseColorError {
+                line: 3, source, ..
+            }) => {
+                assert_eq!(source.kind(), ErrorKind::NoAllowedFormat)
+            }
+            other => panic!("unexpected result {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_locale_tolerant() {
+        let input = "export\nbackground: rgb(0,5; 0,5; 0,5)\nforeground: #ffffff\n";
+        assert!(parse_from_reader(input.as_bytes(), &ReaderOptions::default()).is_err());
+
+        let options = ReaderOptions {
+            parse: ParseOptions {
+                locale_tolerant: true,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let scheme = parse_from_reader(input.as_bytes(), &options).unwrap();
+        assert_eq!(
+            scheme.get("background"),
+            Some(&Canonical::new(128, 128, 128, 255))
+        );
+    }
+
+    #[test]
+    fn test_gimp_rows() {
+        let input = "pasted\n235 219 178\tfg\n40  40  40\nbg: #1d2021\n";
+        let scheme = parse_from_str(NameSource::FirstLine, input).unwrap();
+        assert_eq!(
+            scheme.entries,
+            vec![
+                Entry::named("fg", Canonical::new(235, 219, 178, 255)),
+                Entry::new(Canonical::new(40, 40, 40, 255)),
+                Entry::named("bg", Canonical::new(29, 32, 33, 255)),
+            ]
+        );
+
+        assert!(matches!(
+            parse_from_str(NameSource::FirstLine, "pasted\n300 0 0 red\n"),
+            Err(SchemeReaderError::ParseColorError { line: 2, .. })
+        ));
+
+        let hex_only = ReaderOptions {
+            parse: ParseOptions {
+                formats: Some(vec![ColorFormats::Hex]),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        assert!(parse_from_reader("pasted\n235 219 178 fg\n".as_bytes(), &hex_only).is_err());
+    }
+
+    #[test]
+    fn test_parse_from_str() {
+        let scheme = parse_from_str(NameSource::FirstLine, "mono\nbackground: #000000\n").unwrap();
+        assert_eq!(scheme.name, "mono");
+        assert_eq!(scheme.len(), 1);
+
+        let scheme = parse_from_str(NameSource::Explicit("given".into()), "#000000").unwrap();
+        assert_eq!(scheme.name, "given");
+        assert_eq!(scheme.len(), 1);
+
+        assert_eq!(
+            parse_from_str(NameSource::Filename, "#000000")
+                .unwrap_err()
+                .kind(),
+            ErrorKind::FilenameWithoutPath
+        );
+        assert_eq!(
+            parse_from_str(NameSource::FirstLine, "")
+                .unwrap_err()
+                .kind(),
+            ErrorKind::NoLines
+        );
+    }
+
+    #[test]
+    fn test_parse_borrowed() {
+        let input = "\u{feff}mono\r\n@author: someone\nbg: #000000\nfg: @bg\n235 219 178 light\n";
+        let scheme = parse_borrowed(input).unwrap();
+        assert!(matches!(scheme.name, Cow::Borrowed("mono")));
+        assert!(matches!(scheme.author, Some(Cow::Borrowed("someone"))));
+        assert!(scheme
+            .entries
+            .iter()
+            .all(|e| matches!(e.name, Some(Cow::Borrowed(_)))));
+
+        let lenient = ReaderOptions::lenient();
+        let long_lines = ReaderOptions {
+            limits: Limits {
+                max_line_len: 16,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        for (input, options) in [
+            (input, &ReaderOptions::default()),
+            (
+                "mono\nbg: #000000\nbg: #ffffff\n",
+                &ReaderOptions::default(),
+            ),
+            ("mono\nbg: #000000\nbg: #ffffff\n", &lenient),
+            ("mono\nbg: #zz0000\nfg: #ffffff", &lenient),
+            ("mono\nbg: #zz0000\n", &ReaderOptions::default()),
+            ("mono\nbackground: #000000\n", &long_lines),
+            ("", &ReaderOptions::default()),
+        ] {
+            let owned = parse_from_reader(input.as_bytes(), options);
+            match (owned, parse_borrowed_with(input, options)) {
+                (Ok(owned), Ok(borrowed)) => assert_eq!(borrowed.into_owned(), owned),
+                (Err(owned), Err(borrowed)) => {
+                    assert_eq!(borrowed.to_string(), owned.to_string())
+                }
+                (owned, borrowed) => panic!("{:?} but {:?}", owned, borrowed),
+            }
+        }
+    }
+
+    #[test]
+    fn test_lenient() {
+        let dir = TempDir::new("reader_lenient");
+        let path = dir.write("cool_rs_reader_lenient.txt", "bad\n#000000\nnot a color\n");
+        assert!(parse(&path).is_err());
+        assert_eq!(
+            parse_with(&path, &ReaderOptions::lenient()).unwrap().len(),
+            1
+        );
+    }
+
+    fn scheme_dir(name: &str, files: usize) -> TempDir {
+        let dir = TempDir::new(name);
+        for i in 0..files {
+            let contents = format!("scheme {}\nc: rgb({}, 0, 0)\n", i, i);
+            fs::write(dir.join(format!("{:02}.txt", i)), contents).unwrap();
+        }
+        dir
+    }
+
+    #[test]
+    fn test_parse_comments() {
+        let text = "commented
+// the usual
+@author: someone
+// above red
+red: #cc241d // and after it
+// first
+// second
+green: #98971a
+
+// far from blue
+
+blue: #458588
+#ffffff //
+// skipped with the bad line
+not a color
+#000000
+";
+        let comments = |scheme: &Scheme| -> Vec<Option<String>> {
+            scheme.entries.iter().map(|e| e.comment.clone()).collect()
+        };
+        let strict = text.replace("not a color\n", "");
+        let scheme = parse_from_str(NameSource::FirstLine, &strict).unwrap();
+        assert_eq!(scheme.author.as_deref(), Some("someone"));
+        assert_eq!(
+            comments(&scheme),
+            [
+                Some("above red\nand after it".into()),
+                Some("first\nsecond".into()),
+                None,
+                None,
+                Some("skipped with the bad line".into())
+            ]
+        );
+        assert_eq!(scheme.get("red"), Some(&Canonical::new(204, 36, 29, 255)));
+
+        let options = ReaderOptions::lenient();
+        let lenient = parse_borrowed_with(text, &options).unwrap();
+        assert_eq!(lenient.entries.len(), 5);
+        assert_eq!(lenient.entries[4].comment, None);
+    }
+
+    #[test]
+    fn test_parse_dir() {
+        let dir = scheme_dir("reader_dir", 50);
+        let path = dir.path().to_str().unwrap();
+        let schemes = parse_dir(path).unwrap();
+        let names: Vec<_> = schemes.iter().map(|s| s.name.clone()).collect();
+        let expected: Vec<_> = (0..50).map(|i| format!("scheme {}", i)).collect();
+        assert_eq!(names, expected);
+        #[cfg(feature = "parallel")]
+        assert_eq!(parse_dir_parallel(path).unwrap(), schemes);
 
-    let file = File::open(&path)?;
-    let reader = BufReader::new(file);
-    let mut lines = reader.lines();
-    let scheme_name = match lines.next() {
-        Some(header) => header.map_err(|err| SchemeReaderError::IOError(err, "".into())),
-        None => Err(SchemeReaderError::NoLinesError),
-    }?;
+        fs::write(dir.join("10.txt"), "bad\nc: #zz\n").unwrap();
+        fs::write(dir.join("30.txt"), "").unwrap();
+        let e = parse_dir(path).unwrap_err();
+        assert_eq!(e.kind(), ErrorKind::ParseColor);
+        #[cfg(feature = "parallel")]
+        assert_eq!(
+            parse_dir_parallel(path).unwrap_err().to_string(),
+            e.to_string()
+        );
+    }
 
-    println!("Scheme name read as {}", &scheme_name);
+    #[test]
+    fn test_parse_validated() {
+        let dir = TempDir::new("reader_parse_validated");
+        let path = dir.write(
+            "cool_rs_reader_validated.txt",
+            "duo\nbackground: #000000\nforeground: #ffffff\n",
+        );
+        let rules = ValidationRules {
+            required_names: vec!["background".into(), "foreground".into()],
+            ..Default::default()
+        };
+        assert_eq!(parse_validated(&path, &rules).unwrap().len(), 2);
 
-    todo!()
+        let rules = ValidationRules {
+            min_colors: Some(3),
+            ..Default::default()
+        };
+        match parse_validated(&path, &rules) {
+            Err(SchemeReaderError::ValidationError { report }) => assert_eq!(
+                report.violations,
+                vec![Violation::TooFewColors {
+                    minimum: 3,
+                    found: 2
+                }]
+            ),
+            other => panic!("unexpected result {:?}", other),
+        }
+    }
 }
@@ -0,0 +1,204 @@
+// Adobe Swatch Exchange files (.ase), the swatches Photoshop, Illustrator
+// and InDesign export. every number is big endian: the signature `ASEF`, a
+// version and a block count, then blocks of a type, a length and data. a
+// color block (0x0001) holds a UTF-16 name ending in a zero, a color model,
+// its values as f32 and a color type. groups (0xc001, 0xc002) only add
+// structure and are skipped like blocks of any other type
+//
+// RGB values and the single Gray value are in 0..1. LAB has L in 0..1 for
+// 0..100 and a and b as they are, relative to D50 like the ICC connection
+// space Adobe works in, so they are adapted to the D65 of sRGB with
+// Bradford. CMYK is converted naively, the file carries no print profile
+use std::fs;
+use std::path::Path;
+
+use crate::color::{Canonical, Entry, Lab, Scheme, WhitePoint};
+use crate::errors::SchemeReaderError;
+
+const SIGNATURE: &[u8] = b"ASEF";
+const COLOR: u16 = 0x0001;
+
+fn malformed(reason: &'static str) -> SchemeReaderError {
+    SchemeReaderError::MalformedPalette {
+        format: "ase",
+        reason,
+    }
+}
+
+// a cursor over the file that fails with "truncated" past its end
+struct Bytes<'a>(&'a [u8]);
+
+impl<'a> Bytes<'a> {
+    fn take(&mut self, n: usize) -> Result<&'a [u8], SchemeReaderError> {
+        if self.0.len() < n {
+            return Err(malformed("truncated"));
+        }
+        let (taken, rest) = self.0.split_at(n);
+        self.0 = rest;
+        Ok(taken)
+    }
+
+    fn u16(&mut self) -> Result<u16, SchemeReaderError> {
+        self.take(2).map(|b| u16::from_be_bytes([b[0], b[1]]))
+    }
+
+    fn u32(&mut self) -> Result<u32, SchemeReaderError> {
+        self.take(4)
+            .map(|b| u32::from_be_bytes([b[0], b[1], b[2], b[3]]))
+    }
+
+    fn f32(&mut self) -> Result<f32, SchemeReaderError> {
+        self.u32().map(f32::from_bits)
+    }
+}
+
+// the values of a color block after its model
+fn color(model: &[u8], values: &mut Bytes) -> Result<Canonical, SchemeReaderError> {
+    let color = match model {
+        b"RGB " => {
+            let [r, g, b] = [values.f32()?, values.f32()?, values.f32()?];
+            Canonical::from_f(r, g, b, 1.0)
+        }
+        b"Gray" => {
+            let v = values.f32()?;
+            Canonical::from_f(v, v, v, 1.0)
+        }
+        b"LAB " => {
+            let [l, a, b] = [values.f32()?, values.f32()?, values.f32()?];
+            Canonical::from_lab_with(Lab::new(l * 100.0, a, b), u8::MAX, WhitePoint::D50)
+        }
+        b"CMYK" => {
+            let [c, m, y, k] = [values.f32()?, values.f32()?, values.f32()?, values.f32()?];
+            let ink = |v: f32| (1.0 - v) * (1.0 - k);
+            Canonical::from_f(ink(c), ink(m), ink(y), 1.0)
+        }
+        _ => return Err(malformed("unknown color model")),
+    };
+    Ok(color)
+}
+
+// the color blocks in file order, named ones with roles inferred from the
+// names
+pub fn ase_entries(bytes: &[u8]) -> Result<Vec<Entry>, SchemeReaderError> {
+    let mut file = Bytes(bytes);
+    if file.take(4)? != SIGNATURE {
+        return Err(malformed("not an ASE file"));
+    }
+    file.take(4)?;
+    let count = file.u32()?;
+
+    let mut entries = Vec::new();
+    for _ in 0..count {
+        let kind = file.u16()?;
+        let len = file.u32()? as usize;
+        let mut block = Bytes(file.take(len)?);
+        if kind != COLOR {
+            continue;
+        }
+        let units = block.u16()? as usize;
+        let name: Vec<u16> = block
+            .take(units * 2)?
+            .chunks_exact(2)
+            .map(|unit| u16::from_be_bytes([unit[0], unit[1]]))
+            .take_while(|&unit| unit != 0)
+            .collect();
+        let name = String::from_utf16(&name).map_err(|_| malformed("a name is not UTF-16"))?;
+        let model = block.take(4)?;
+        let color = color(model, &mut block)?;
+        entries.push(match name.trim() {
+            "" => Entry::new(color),
+            name => Entry::named(name, color),
+        });
+    }
+    Ok(entries)
+}
+
+// a scheme named after the file
+pub fn parse(path: &str) -> Result<Scheme, SchemeReaderError> {
+    let path = Path::new(path);
+    let bytes = fs::read(path).map_err(|e| SchemeReaderError::io(e, Some(path)))?;
+    let name = path.file_stem().unwrap_or_default().to_string_lossy();
+    Ok(Scheme::from_entries(
+        name.into_owned(),
+        None,
+        ase_entries(&bytes)?,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::temp::TempDir;
+
+    // a group of four swatches as the spec lays them out, written byte by
+    // byte. the LAB values are sRGB red and a mid gray under D50
+    #[rustfmt::skip]
+    const SWATCHES: &[u8] = &[
+        // `ASEF`, version 1.0, 6 blocks
+        0x41, 0x53, 0x45, 0x46, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x06,
+        // group start 0xc001 "Brand"
+        0xc0, 0x01, 0x00, 0x00, 0x00, 0x0e, 0x00, 0x06, 0x00, 0x42, 0x00, 0x72,
+        0x00, 0x61, 0x00, 0x6e, 0x00, 0x64, 0x00, 0x00,
+        // color 0x0001 "red", LAB 54.29 80.8 69.89, normal
+        0x00, 0x01, 0x00, 0x00, 0x00, 0x1c, 0x00, 0x04, 0x00, 0x72, 0x00, 0x65,
+        0x00, 0x64, 0x00, 0x00, 0x4c, 0x41, 0x42, 0x20, 0x3f, 0x0a, 0xfb, 0x7f,
+        0x42, 0xa1, 0x99, 0x9a, 0x42, 0x8b, 0xc7, 0xae, 0x00, 0x02,
+        // color 0x0001 "gray", LAB 50 0 0, normal
+        0x00, 0x01, 0x00, 0x00, 0x00, 0x1e, 0x00, 0x05, 0x00, 0x67, 0x00, 0x72,
+        0x00, 0x61, 0x00, 0x79, 0x00, 0x00, 0x4c, 0x41, 0x42, 0x20, 0x3f, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x02,
+        // color 0x0001 "teal", RGB 0 0.5 0.5, normal
+        0x00, 0x01, 0x00, 0x00, 0x00, 0x1e, 0x00, 0x05, 0x00, 0x74, 0x00, 0x65,
+        0x00, 0x61, 0x00, 0x6c, 0x00, 0x00, 0x52, 0x47, 0x42, 0x20, 0x00, 0x00,
+        0x00, 0x00, 0x3f, 0x00, 0x00, 0x00, 0x3f, 0x00, 0x00, 0x00, 0x00, 0x02,
+        // color 0x0001, Gray 0.25, no name, normal
+        0x00, 0x01, 0x00, 0x00, 0x00, 0x0e, 0x00, 0x01, 0x00, 0x00, 0x47, 0x72,
+        0x61, 0x79, 0x3e, 0x80, 0x00, 0x00, 0x00, 0x02,
+        // group end 0xc002
+        0xc0, 0x02, 0x00, 0x00, 0x00, 0x00,
+    ];
+
+    #[test]
+    fn test_parse() {
+        let dir = TempDir::new("ase_parse");
+        let path = dir.write("cool_rs_swatches.ase", SWATCHES);
+        let scheme = parse(&path).unwrap();
+        assert_eq!(scheme.name, "cool_rs_swatches");
+        assert_eq!(
+            scheme.entries,
+            [
+                Entry::named("red", Canonical::new(255, 0, 0, 255)),
+                Entry::named("gray", Canonical::new(119, 119, 119, 255)),
+                Entry::named("teal", Canonical::new(0, 128, 128, 255)),
+                Entry::new(Canonical::new(64, 64, 64, 255)),
+            ]
+        );
+    }
+
+    // the LAB path is the D50 one: read as D65 without adapting, red comes
+    // out visibly off
+    #[test]
+    fn test_lab_is_d50() {
+        let red = Lab::new(54.29, 80.8, 69.89);
+        let read = ase_entries(SWATCHES).unwrap()[0].color;
+        assert_eq!(read, Canonical::from_lab_with(red, 255, WhitePoint::D50));
+        assert_eq!(read.to_lab_with(WhitePoint::D50).l.round(), 54.0);
+        assert_ne!(Canonical::from_lab(red, 255), read);
+    }
+
+    #[test]
+    fn test_malformed() {
+        let reason = |bytes: &[u8]| match ase_entries(bytes).unwrap_err() {
+            SchemeReaderError::MalformedPalette { reason, .. } => reason,
+            e => panic!("{:?}", e),
+        };
+        for len in [0, 3, 11, 15, 40, SWATCHES.len() - 1] {
+            assert_eq!(reason(&SWATCHES[..len]), "truncated", "{}", len);
+        }
+        assert_eq!(reason(b"RIFF\0\x01\0\0\0\0\0\0"), "not an ASE file");
+
+        let mut unknown = SWATCHES.to_vec();
+        unknown[48..52].copy_from_slice(b"HSB ");
+        assert_eq!(reason(&unknown), "unknown color model");
+    }
+}
@@ -0,0 +1,336 @@
+// the palette of an Aseprite file, from the palette chunk (0x2019) of its
+// first frame, with the names and alphas of its entries. only the chunk
+// headers of the frame are walked, cels are skipped unread, so compressed
+// image data never needs inflating. files of Aseprite before 1.1 only have
+// the old palette chunks (0x0004, 0x0011), which aren't read
+//
+// every number is little endian. the file header is 128 bytes, with the
+// magic 0xa5e0 at 4. a frame header is 16 bytes, the magic 0xf1fa at 4, the
+// chunk count at 12 or, in older files where that is 0, at 6. a chunk is
+// its size including the 6 byte header, its type and its data
+use std::fs;
+use std::path::Path;
+
+use crate::color::{Canonical, Entry, Scheme};
+use crate::errors::SchemeReaderError;
+
+const FILE_MAGIC: u16 = 0xa5e0;
+const FRAME_MAGIC: u16 = 0xf1fa;
+const PALETTE: u16 = 0x2019;
+const OLD_PALETTES: [u16; 2] = [0x0004, 0x0011];
+// entry flag: a name follows the color
+const HAS_NAME: u16 = 1;
+
+fn malformed(reason: &'static str) -> SchemeReaderError {
+    SchemeReaderError::MalformedPalette {
+        format: "aseprite",
+        reason,
+    }
+}
+
+// a cursor over the file that fails with "truncated" past its end
+struct Bytes<'a>(&'a [u8]);
+
+impl<'a> Bytes<'a> {
+    fn take(&mut self, n: usize) -> Result<&'a [u8], SchemeReaderError> {
+        if self.0.len() < n {
+            return Err(malformed("truncated"));
+        }
+        let (taken, rest) = self.0.split_at(n);
+        self.0 = rest;
+        Ok(taken)
+    }
+
+    fn u16(&mut self) -> Result<u16, SchemeReaderError> {
+        self.take(2).map(|b| u16::from_le_bytes([b[0], b[1]]))
+    }
+
+    fn u32(&mut self) -> Result<u32, SchemeReaderError> {
+        self.take(4)
+            .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+    }
+}
+
+// the data of the first frame's palette chunk
+fn palette_chunk(bytes: &[u8]) -> Result<&[u8], SchemeReaderError> {
+    let mut header = Bytes(bytes);
+    header.take(4)?;
+    if header.u16()? != FILE_MAGIC {
+        return Err(malformed("not an Aseprite file"));
+    }
+    let mut frame = Bytes(&bytes[128.min(bytes.len())..]);
+    frame.take(4)?;
+    if frame.u16()? != FRAME_MAGIC {
+        return Err(malformed("no frame after the header"));
+    }
+    let old_count = frame.u16()?;
+    frame.take(4)?;
+    let count = match frame.u32()? {
+        0 => old_count as u32,
+        count => count,
+    };
+
+    let mut old_style = false;
+    for _ in 0..count {
+        let size = frame.u32()? as usize;
+        let kind = frame.u16()?;
+        let size = size.checked_sub(6).ok_or_else(|| malformed("truncated"))?;
+        let data = frame.take(size)?;
+        if kind == PALETTE {
+            return Ok(data);
+        }
+        old_style |= OLD_PALETTES.contains(&kind);
+    }
+    Err(malformed(match old_style {
+        true => "only an old style palette chunk, save it with Aseprite 1.1 or later",
+        false => "no palette chunk in the first frame",
+    }))
+}
+
+// the entries in index order, named ones with roles inferred from the names
+pub fn aseprite_entries(bytes: &[u8]) -> Result<Vec<Entry>, SchemeReaderError> {
+    let mut palette = Bytes(palette_chunk(bytes)?);
+    palette.u32()?;
+    let first = palette.u32()?;
+    let last = palette.u32()?;
+    palette.take(8)?;
+    let count = last
+        .checked_sub(first)
+        .ok_or_else(|| malformed("last index before the first"))?
+        .checked_add(1)
+        .ok_or_else(|| malformed("truncated"))?;
+    // every entry takes 6 bytes at least, a count the chunk can't hold is
+    // rejected before looping over it
+    if count as usize > palette.0.len() / 6 {
+        return Err(malformed("truncated"));
+    }
+
+    let mut entries = Vec::new();
+    for _ in 0..count {
+        let flags = palette.u16()?;
+        let rgba = palette.take(4)?;
+        let color = Canonical::new(rgba[0], rgba[1], rgba[2], rgba[3]);
+        let entry = if flags & HAS_NAME != 0 {
+            let len = palette.u16()? as usize;
+            let name = std::str::from_utf8(palette.take(len)?)
+                .map_err(|_| malformed("a name is not UTF-8"))?;
+            match name.trim() {
+                "" => Entry::new(color),
+                name => Entry::named(name, color),
+            }
+        } else {
+            Entry::new(color)
+        };
+        entries.push(entry);
+    }
+    Ok(entries)
+}
+
+// a scheme named after the file
+pub fn parse(path: &str) -> Result<Scheme, SchemeReaderError> {
+    let path = Path::new(path);
+    let bytes = fs::read(path).map_err(|e| SchemeReaderError::io(e, Some(path)))?;
+    let name = path.file_stem().unwrap_or_default().to_string_lossy();
+    Ok(Scheme::from_entries(
+        name.into_owned(),
+        None,
+        aseprite_entries(&bytes)?,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::errors::ErrorKind;
+    use crate::temp::TempDir;
+
+    fn chunk(kind: u16, data: &[u8]) -> Vec<u8> {
+        let mut chunk = ((data.len() + 6) as u32).to_le_bytes().to_vec();
+        chunk.extend_from_slice(&kind.to_le_bytes());
+        chunk.extend_from_slice(data);
+        chunk
+    }
+
+    fn palette(entries: &[([u8; 4], Option<&str>)]) -> Vec<u8> {
+        let mut data = Vec::new();
+        data.extend_from_slice(&(entries.len() as u32).to_le_bytes());
+        data.extend_from_slice(&0u32.to_le_bytes());
+        data.extend_from_slice(&(entries.len() as u32 - 1).to_le_bytes());
+        data.extend_from_slice(&[0; 8]);
+        for (rgba, name) in entries {
+            match name {
+                Some(name) => {
+                    data.extend_from_slice(&HAS_NAME.to_le_bytes());
+                    data.extend_from_slice(rgba);
+                    data.extend_from_slice(&(name.len() as u16).to_le_bytes());
+                    data.extend_from_slice(name.as_bytes());
+                }
+                None => {
+                    data.extend_from_slice(&0u16.to_le_bytes());
+                    data.extend_from_slice(rgba);
+                }
+            }
+        }
+        chunk(PALETTE, &data)
+    }
+
+    // a minimal file for the cases SPEC_SPRITE can't show, truncations and
+    // missing chunks: the header, then the first frame with a layer, the
+    // palette and a cel of bytes that aren't zlib
+    fn file(chunks: &[Vec<u8>]) -> Vec<u8> {
+        let body: Vec<u8> = chunks.concat();
+        let mut frame = ((body.len() + 16) as u32).to_le_bytes().to_vec();
+        frame.extend_from_slice(&FRAME_MAGIC.to_le_bytes());
+        frame.extend_from_slice(&0xffffu16.to_le_bytes());
+        frame.extend_from_slice(&[100, 0, 0, 0]);
+        frame.extend_from_slice(&(chunks.len() as u32).to_le_bytes());
+        frame.extend_from_slice(&body);
+
+        let mut header = vec![0; 128];
+        header[..4].copy_from_slice(&((128 + frame.len()) as u32).to_le_bytes());
+        header[4..6].copy_from_slice(&FILE_MAGIC.to_le_bytes());
+        header[6..8].copy_from_slice(&1u16.to_le_bytes());
+        [header, frame].concat()
+    }
+
+    // where the palette chunk's data starts in sprite(): after the header,
+    // the frame header and the 11 byte layer chunk, and its own 6 bytes
+    const SPRITE_PALETTE: usize = 128 + 16 + 11 + 6;
+
+    fn sprite() -> Vec<u8> {
+        file(&[
+            chunk(0x2004, b"layer"),
+            palette(&[
+                ([0, 0, 0, 0], Some("transparent")),
+                ([0x28, 0x28, 0x28, 255], Some("background")),
+                ([0xcc, 0x24, 0x1d, 128], None),
+            ]),
+            chunk(0x2005, &[0xde, 0xad, 0xbe, 0xef]),
+        ])
+    }
+
+    // a 2x2 indexed sprite with one layer and one cel, written out field by
+    // field from the file format spec (aseprite/docs/ase-file-specs.md) in
+    // the chunk order Aseprite 1.3 saves: the color profile, the palette,
+    // the old palette it adds when no color has alpha, the layer and a cel of
+    // real zlib data. no encoder of this module is involved
+    #[rustfmt::skip]
+    const SPEC_SPRITE: &[u8] = &[
+        // header: file size 327, magic, 1 frame, 2x2, 8 bpp, flags 1, speed
+        0x47, 0x01, 0x00, 0x00, 0xe0, 0xa5, 0x01, 0x00, 0x02, 0x00, 0x02, 0x00,
+        0x08, 0x00, 0x01, 0x00, 0x00, 0x00, 0x64, 0x00,
+        // two zero dwords, transparent index 0, 3 unused, 4 colors
+        0, 0, 0, 0, 0, 0, 0, 0, 0x00, 0, 0, 0, 0x04, 0x00,
+        // pixel ratio 1:1, grid at 0,0 of 16x16, 84 reserved
+        0x01, 0x01, 0x00, 0x00, 0x00, 0x00, 0x10, 0x00, 0x10, 0x00,
+        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+        // frame: 199 bytes, magic, 5 chunks, 100 ms, 2 reserved, 5 chunks
+        0xc7, 0x00, 0x00, 0x00, 0xfa, 0xf1, 0x05, 0x00, 0x64, 0x00, 0x00, 0x00,
+        0x05, 0x00, 0x00, 0x00,
+        // color profile 0x2007: sRGB, no flags, gamma 0, 8 reserved
+        0x16, 0x00, 0x00, 0x00, 0x07, 0x20, 0x01, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0, 0, 0, 0, 0, 0, 0, 0,
+        // palette 0x2019: 4 entries, 0 to 3, 8 reserved
+        0x46, 0x00, 0x00, 0x00, 0x19, 0x20, 0x04, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x03, 0x00, 0x00, 0x00, 0, 0, 0, 0, 0, 0, 0, 0,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0xff,
+        0x01, 0x00, 0x28, 0x28, 0x28, 0xff, 0x0a, 0x00,
+        b'b', b'a', b'c', b'k', b'g', b'r', b'o', b'u', b'n', b'd',
+        0x00, 0x00, 0xcc, 0x24, 0x1d, 0xff,
+        0x01, 0x00, 0xfa, 0xbd, 0x2f, 0xff, 0x06, 0x00,
+        b'y', b'e', b'l', b'l', b'o', b'w',
+        // old palette 0x0004: 1 packet, skip 0, 4 colors
+        0x16, 0x00, 0x00, 0x00, 0x04, 0x00, 0x01, 0x00, 0x00, 0x04,
+        0x00, 0x00, 0x00, 0x28, 0x28, 0x28, 0xcc, 0x24, 0x1d, 0xfa, 0xbd, 0x2f,
+        // layer 0x2004: visible and editable, normal image layer, level 0,
+        // blend normal, opaque, 3 reserved, "Layer 1"
+        0x1f, 0x00, 0x00, 0x00, 0x04, 0x20, 0x03, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0xff, 0, 0, 0, 0x07, 0x00,
+        b'L', b'a', b'y', b'e', b'r', b' ', b'1',
+        // cel 0x2005: layer 0 at 0,0, opaque, compressed image, z-index 0,
+        // 5 reserved, 2x2 of indexes 1 2 3 0 deflated
+        0x26, 0x00, 0x00, 0x00, 0x05, 0x20, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0xff, 0x02, 0x00, 0x00, 0x00, 0, 0, 0, 0, 0, 0x02, 0x00, 0x02, 0x00,
+        0x78, 0x9c, 0x63, 0x64, 0x62, 0x66, 0x00, 0x00, 0x00, 0x14, 0x00, 0x07,
+    ];
+
+    #[test]
+    fn test_spec_sprite() {
+        assert_eq!(SPEC_SPRITE.len(), 327);
+        let rgb = |hex: u32| Canonical::new((hex >> 16) as u8, (hex >> 8) as u8, hex as u8, 255);
+        assert_eq!(
+            aseprite_entries(SPEC_SPRITE).unwrap(),
+            [
+                Entry::new(rgb(0x000000)),
+                Entry::named("background", rgb(0x282828)),
+                Entry::new(rgb(0xcc241d)),
+                Entry::named("yellow", rgb(0xfabd2f)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse() {
+        let dir = TempDir::new("aseprite_parse");
+        let path = dir.join("cool_rs_sprite.aseprite");
+        fs::write(&path, sprite()).unwrap();
+        let scheme = parse(path.to_str().unwrap()).unwrap();
+        assert_eq!(scheme.name, "cool_rs_sprite");
+        assert_eq!(
+            scheme.entries,
+            [
+                Entry::named("transparent", Canonical::new(0, 0, 0, 0)),
+                Entry::named("background", Canonical::new(0x28, 0x28, 0x28, 255)),
+                Entry::new(Canonical::new(0xcc, 0x24, 0x1d, 128)),
+            ]
+        );
+        assert_eq!(
+            scheme.get("background"),
+            Some(&Canonical::new(0x28, 0x28, 0x28, 255))
+        );
+    }
+
+    #[test]
+    fn test_malformed() {
+        let reason = |bytes: &[u8]| match aseprite_entries(bytes).unwrap_err() {
+            SchemeReaderError::MalformedPalette { reason, .. } => reason,
+            e => panic!("{:?}", e),
+        };
+        // cut anywhere before the end of the palette chunk, the cel after it
+        // is never read
+        let sprite = sprite();
+        let palette_end = sprite.len() - 10;
+        assert!(aseprite_entries(&sprite[..palette_end]).is_ok());
+        for len in [0, 3, 100, 130, 150, 160, palette_end - 20, palette_end - 1] {
+            assert_eq!(reason(&sprite[..len]), "truncated", "{}", len);
+        }
+        assert_eq!(reason(&[0; 200]), "not an Aseprite file");
+
+        // entries 0 to u32::MAX, one more than a u32 counts
+        let mut huge = sprite.clone();
+        assert_eq!(
+            huge[SPRITE_PALETTE..SPRITE_PALETTE + 12],
+            [3, 0, 0, 0, 0, 0, 0, 0, 2, 0, 0, 0]
+        );
+        let last = SPRITE_PALETTE + 8;
+        huge[last..last + 4].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert_eq!(reason(&huge), "truncated");
+        // entries 0 to 9999 in a chunk with room for 3
+        huge[last..last + 4].copy_from_slice(&9999u32.to_le_bytes());
+        assert_eq!(reason(&huge), "truncated");
+
+        let old = file(&[chunk(0x0004, &[1, 0, 0, 1, 0, 0, 0])]);
+        let e = aseprite_entries(&old).unwrap_err();
+        assert_eq!(e.kind(), ErrorKind::MalformedPalette);
+        assert_eq!(
+            e.to_string(),
+            "Malformed aseprite palette: only an old style palette chunk, save it with \
+             Aseprite 1.1 or later"
+        );
+        let none = file(&[chunk(0x2004, b"layer")]);
+        assert_eq!(reason(&none), "no palette chunk in the first frame");
+    }
+}
@@ -0,0 +1,295 @@
+// finding the clutter in a directory of schemes: files with the same entries,
+// files with nearly the same colors and files that don't make a usable
+// scheme. every file directly in the directory is read as parse_dir reads
+// it, but one that fails is reported instead of ending the audit
+use std::fmt::Display;
+use std::path::{Path, PathBuf};
+
+use super::{dir_files, read_file, ReaderOptions};
+use crate::color::{DistanceMetric, Scheme, ValidationReport, ValidationRules};
+use crate::errors::SchemeReaderError;
+
+#[derive(PartialEq, Debug, Clone)]
+pub struct NearDuplicate {
+    pub first: PathBuf,
+    pub second: PathBuf,
+    // the mean of the distances between entries at the same index
+    pub distance: f32,
+}
+
+#[derive(Debug)]
+pub enum AuditProblem {
+    Unreadable(SchemeReaderError),
+    Invalid(ValidationReport),
+}
+
+#[derive(Debug)]
+pub struct InvalidScheme {
+    pub path: PathBuf,
+    pub problem: AuditProblem,
+}
+
+#[derive(Debug)]
+pub struct DirAudit {
+    pub dir: PathBuf,
+    // how many files were read, valid or not
+    pub files: usize,
+    // groups of files with the same fingerprint, each in file name order
+    pub duplicates: Vec<Vec<PathBuf>>,
+    // pairs of schemes with as many entries that are close but not the same,
+    // closest first
+    pub near_duplicates: Vec<NearDuplicate>,
+    pub invalid: Vec<InvalidScheme>,
+}
+
+impl DirAudit {
+    pub fn is_clean(&self) -> bool {
+        self.duplicates.is_empty() && self.near_duplicates.is_empty() && self.invalid.is_empty()
+    }
+}
+
+// a scheme worth comparing has colors. names are unique already, the
+// strict reader fails on a repeated one
+fn basic_rules() -> ValidationRules {
+    ValidationRules {
+        min_colors: Some(1),
+        ..Default::default()
+    }
+}
+
+fn mean_distance(a: &Scheme, b: &Scheme, metric: &impl DistanceMetric) -> f32 {
+    let total: f32 = a
+        .colors()
+        .zip(b.colors())
+        .map(|(a, b)| metric.distance(a, b))
+        .sum();
+    total / a.len() as f32
+}
+
+// schemes of different lengths are never near duplicates, their entries
+// don't pair up. only the directory itself failing to read is an error
+pub fn audit_dir(
+    path: &str,
+    threshold: f32,
+    metric: &impl DistanceMetric,
+) -> Result<DirAudit, SchemeReaderError> {
+    let files = dir_files(Path::new(path))?;
+    let mut invalid = Vec::new();
+    let mut schemes: Vec<(PathBuf, Scheme, u64)> = Vec::new();
+    for file in &files {
+        match read_file(file, &[], &ReaderOptions::default()) {
+            Ok(scheme) => {
+                let report = scheme.validate(&basic_rules());
+                if report.is_ok() {
+                    let fingerprint = scheme.fingerprint();
+                    schemes.push((file.clone(), scheme, fingerprint));
+                } else {
+                    invalid.push(InvalidScheme {
+                        path: file.clone(),
+                        problem: AuditProblem::Invalid(report),
+                    });
+                }
+            }
+            Err(e) => invalid.push(InvalidScheme {
+                path: file.clone(),
+                problem: AuditProblem::Unreadable(e),
+            }),
+        }
+    }
+
+    let mut duplicates: Vec<Vec<PathBuf>> = Vec::new();
+    let mut near_duplicates = Vec::new();
+    // the first file of each fingerprint, later copies are only reported as
+    // duplicates of it
+    let mut originals: Vec<&(PathBuf, Scheme, u64)> = Vec::new();
+    for file in &schemes {
+        let (path, scheme, fingerprint) = file;
+        if let Some((first, _, _)) = originals.iter().find(|(_, _, f)| f == fingerprint) {
+            match duplicates.iter_mut().find(|group| &group[0] == first) {
+                Some(group) => group.push(path.clone()),
+                None => duplicates.push(vec![first.clone(), path.clone()]),
+            }
+            continue;
+        }
+        for (other_path, other, _) in &originals {
+            if other.len() != scheme.len() {
+                continue;
+            }
+            let distance = mean_distance(other, scheme, metric);
+            if distance < threshold {
+                near_duplicates.push(NearDuplicate {
+                    first: other_path.clone(),
+                    second: path.clone(),
+                    distance,
+                });
+            }
+        }
+        originals.push(file);
+    }
+    near_duplicates.sort_by(|a, b| a.distance.total_cmp(&b.distance));
+
+    Ok(DirAudit {
+        dir: PathBuf::from(path),
+        files: files.len(),
+        duplicates,
+        near_duplicates,
+        invalid,
+    })
+}
+
+impl Display for AuditProblem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AuditProblem::Unreadable(e) => write!(f, "{}", e),
+            AuditProblem::Invalid(report) => {
+                let violations: Vec<_> = report
+                    .violations
+                    .iter()
+                    .zip(&report.sources)
+                    .map(|(violation, source)| match source {
+                        Some(source) => format!("{} at {}", violation, source),
+                        None => violation.to_string(),
+                    })
+                    .collect();
+                write!(f, "{}", violations.join(", "))
+            }
+        }
+    }
+}
+
+// a summary line, then a line per finding with the paths relative to the
+// directory
+impl Display for DirAudit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = |path: &PathBuf| {
+            path.strip_prefix(&self.dir)
+                .unwrap_or(path)
+                .display()
+                .to_string()
+        };
+        write!(
+            f,
+            "{}: {} files, {} duplicate groups, {} near duplicates, {} invalid",
+            self.dir.display(),
+            self.files,
+            self.duplicates.len(),
+            self.near_duplicates.len(),
+            self.invalid.len()
+        )?;
+        for group in &self.duplicates {
+            let names: Vec<_> = group.iter().map(name).collect();
+            write!(f, "\n  duplicates: {}", names.join(", "))?;
+        }
+        for near in &self.near_duplicates {
+            write!(
+                f,
+                "\n  near duplicates: {} and {}, {:.2} apart",
+                name(&near.first),
+                name(&near.second),
+                near.distance
+            )?;
+        }
+        for scheme in &self.invalid {
+            write!(f, "\n  invalid: {}: {}", name(&scheme.path), scheme.problem)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::Metric;
+    use crate::errors::ErrorKind;
+    use crate::temp::TempDir;
+
+    fn audit_fixture(name: &str) -> TempDir {
+        let dir = TempDir::new(name);
+        let files = [
+            ("a.txt", "gruvbox\nbg: #282828\nred: #cc241d\n"),
+            // the same entries written differently
+            ("b.txt", "gruvbox copy\nbg: rgb(40, 40, 40)\nred: #CC241D\n"),
+            ("c.txt", "almost\nbg: #292828\nred: #cc251d\n"),
+            ("d.txt", "solarized\nbase03: #002b36\nyellow: #b58900\n"),
+            ("e.txt", "nord\n#2e3440\n#88c0d0\n#bf616a\n"),
+            ("f.txt", "broken\nbg: #zz\n"),
+            ("g.txt", "twice\nbg: #000000\nbg: #ffffff\n"),
+            ("h.txt", "empty\n"),
+        ];
+        for (file, contents) in files {
+            dir.write(file, contents);
+        }
+        dir
+    }
+
+    #[test]
+    fn test_audit_dir() {
+        let dir = audit_fixture("reader_audit");
+        let audit = audit_dir(dir.path().to_str().unwrap(), 5.0, &Metric::RgbEuclidean).unwrap();
+        assert_eq!(audit.files, 8);
+        assert!(!audit.is_clean());
+        assert_eq!(
+            audit.duplicates,
+            vec![vec![dir.join("a.txt"), dir.join("b.txt")]]
+        );
+        assert_eq!(audit.near_duplicates.len(), 1);
+        let near = &audit.near_duplicates[0];
+        assert_eq!(
+            (&near.first, &near.second),
+            (&dir.join("a.txt"), &dir.join("c.txt"))
+        );
+        assert_eq!(near.distance, 1.0);
+
+        let invalid: Vec<_> = audit.invalid.iter().map(|s| &s.path).collect();
+        assert_eq!(
+            invalid,
+            [&dir.join("f.txt"), &dir.join("g.txt"), &dir.join("h.txt")]
+        );
+        let kind = |problem: &AuditProblem| match problem {
+            AuditProblem::Unreadable(e) => Some(e.kind()),
+            AuditProblem::Invalid(_) => None,
+        };
+        assert_eq!(kind(&audit.invalid[0].problem), Some(ErrorKind::ParseColor));
+        assert_eq!(
+            kind(&audit.invalid[1].problem),
+            Some(ErrorKind::DuplicateName)
+        );
+        assert_eq!(kind(&audit.invalid[2].problem), None);
+
+        let report = audit.to_string();
+        let lines: Vec<_> = report.lines().collect();
+        assert_eq!(
+            lines[0],
+            format!(
+                "{}: 8 files, 1 duplicate groups, 1 near duplicates, 3 invalid",
+                dir.path().display()
+            )
+        );
+        assert_eq!(lines[1], "  duplicates: a.txt, b.txt");
+        assert_eq!(lines[2], "  near duplicates: a.txt and c.txt, 1.00 apart");
+        assert_eq!(
+            lines[4],
+            "  invalid: g.txt: bg is defined on line 2 and again on line 3"
+        );
+        assert_eq!(
+            lines[5],
+            "  invalid: h.txt: expected at least 1 colors, found 0"
+        );
+    }
+
+    #[test]
+    fn test_audit_dir_threshold() {
+        let dir = audit_fixture("reader_audit_threshold");
+        let path = dir.path().to_str().unwrap();
+        // a threshold of 0 only finds exact duplicates
+        let audit = audit_dir(path, 0.0, &Metric::RgbEuclidean).unwrap();
+        assert_eq!(audit.duplicates.len(), 1);
+        assert!(audit.near_duplicates.is_empty());
+        // solarized is far from gruvbox, nord has another length
+        let audit = audit_dir(path, 200.0, &Metric::DeltaE2000).unwrap();
+        assert_eq!(audit.near_duplicates.len(), 3);
+
+        let e = audit_dir("/no/such/cool_rs/dir", 5.0, &Metric::RgbEuclidean).unwrap_err();
+        assert_eq!(e.kind(), ErrorKind::Io);
+    }
+}
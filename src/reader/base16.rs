@@ -0,0 +1,152 @@
+// base16 and base24 schemes, the YAML files of the tinted-theming project.
+// the old layout has `scheme:`, `author:` and `base00:` to `base0F:` with
+// bare hex, `"282828"`, the new one `name:` and the colors in a `palette:`
+// block as `"#282828"`. both are read line by line, no YAML beyond that.
+// entries are named `base00` and so on in the order of the file and have no
+// roles, TERMINAL is how the base16 templates lay them out for terminals
+use std::fs;
+use std::path::Path;
+
+use crate::color::{Entry, Scheme};
+use crate::errors::SchemeReaderError;
+use crate::formats::{ColorFormat, HexFormat};
+
+// the terminal color of each base16 color as base16-shell and the
+// Xresources template write them. base01, 02, 04, 06, 09 and 0F have none
+pub const TERMINAL: [(&str, &str); 19] = [
+    ("background", "base00"),
+    ("foreground", "base05"),
+    ("cursor", "base05"),
+    ("color0", "base00"),
+    ("color1", "base08"),
+    ("color2", "base0b"),
+    ("color3", "base0a"),
+    ("color4", "base0d"),
+    ("color5", "base0e"),
+    ("color6", "base0c"),
+    ("color7", "base05"),
+    ("color8", "base03"),
+    ("color9", "base08"),
+    ("color10", "base0b"),
+    ("color11", "base0a"),
+    ("color12", "base0d"),
+    ("color13", "base0e"),
+    ("color14", "base0c"),
+    ("color15", "base07"),
+];
+
+// base00 to base0f, and base10 to base17 of base24
+fn is_base(key: &str) -> bool {
+    match key.strip_prefix("base") {
+        Some(digits) => {
+            let digits = digits.as_bytes();
+            match digits {
+                [b'0', low] => low.is_ascii_hexdigit(),
+                [b'1', low] => (b'0'..=b'7').contains(low),
+                _ => false,
+            }
+        }
+        None => false,
+    }
+}
+
+// the value of a `key: value` line, unquoted, without a trailing comment
+fn value(raw: &str) -> &str {
+    let raw = raw.trim();
+    for quote in ['"', '\''] {
+        if let Some(quoted) = raw.strip_prefix(quote) {
+            return quoted.split(quote).next().unwrap_or_default();
+        }
+    }
+    match raw.find(" #") {
+        Some(comment) => raw[..comment].trim_end(),
+        None => raw,
+    }
+}
+
+// the entries in the order of the file, the name empty without one
+pub fn base16_scheme(text: &str) -> Result<Scheme, SchemeReaderError> {
+    let mut name = String::new();
+    let mut author = None;
+    let mut entries: Vec<Entry> = Vec::new();
+    for line in text.lines() {
+        let line = line.trim();
+        if line.starts_with('#') {
+            continue;
+        }
+        let Some((key, raw)) = line.split_once(':') else {
+            continue;
+        };
+        let key = key.trim().to_ascii_lowercase();
+        let value = value(raw);
+        match key.as_str() {
+            "scheme" | "name" => name = value.to_string(),
+            "author" if !value.is_empty() => author = Some(value.to_string()),
+            key if is_base(key) => {
+                let hex = format!("#{}", value.trim_start_matches('#'));
+                let color =
+                    HexFormat::parse(&hex).map_err(|source| SchemeReaderError::InvalidToken {
+                        token: key.to_string(),
+                        source,
+                    })?;
+                match entries.iter_mut().find(|e| e.name.as_deref() == Some(key)) {
+                    Some(entry) => entry.color = color,
+                    None => entries.push(Entry::named(key, color)),
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(Scheme::from_entries(name, author, entries))
+}
+
+// named after the file if the scheme has no name
+pub fn parse(path: &str) -> Result<Scheme, SchemeReaderError> {
+    let path = Path::new(path);
+    let text = fs::read_to_string(path).map_err(|e| SchemeReaderError::io(e, Some(path)))?;
+    let mut scheme = base16_scheme(&text)?;
+    if scheme.name.is_empty() {
+        let stem = path.file_stem().unwrap_or_default();
+        scheme.name = stem.to_string_lossy().into_owned();
+    }
+    Ok(scheme)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::Canonical;
+    use crate::errors::ErrorKind;
+
+    #[test]
+    fn test_base16_scheme() {
+        let old = "scheme: \"Gruvbox dark, medium\"\nauthor: \"Dawid Kurek\"\n\
+                   base00: \"282828\" # ----\nBASE0F: 'd65d0e'\n";
+        let scheme = base16_scheme(old).unwrap();
+        assert_eq!(scheme.name, "Gruvbox dark, medium");
+        assert_eq!(scheme.author.as_deref(), Some("Dawid Kurek"));
+        assert_eq!(
+            scheme.entries,
+            [
+                Entry::named("base00", Canonical::new(0x28, 0x28, 0x28, 255)),
+                Entry::named("base0f", Canonical::new(0xd6, 0x5d, 0x0e, 255)),
+            ]
+        );
+
+        let new = "system: \"base24\"\nname: \"One Dark\"\nvariant: \"dark\"\npalette:\n  \
+                   base00: \"#282c34\" # background\n  base17: \"#be5046\"\n  \
+                   base18: \"#000000\"\n";
+        let scheme = base16_scheme(new).unwrap();
+        assert_eq!(scheme.name, "One Dark");
+        assert_eq!(scheme.author, None);
+        let names: Vec<_> = scheme
+            .entries
+            .iter()
+            .flat_map(|e| e.name.as_deref())
+            .collect();
+        assert_eq!(names, ["base00", "base17"]);
+
+        let e = base16_scheme("base00: \"28282\"").unwrap_err();
+        assert_eq!(e.kind(), ErrorKind::InvalidToken);
+    }
+}
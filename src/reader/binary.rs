@@ -0,0 +1,184 @@
+// binary palette files: Adobe color tables (.act) and RIFF palettes (.pal),
+// as Photoshop and Windows write them. neither has names, schemes are named
+// after the file. the _compact functions give a CompactScheme, for palettes
+// that repeat colors, without building every Entry first
+//
+// .act: 256 rgb triplets, optionally followed by the number of colors used
+// and the index of the transparent one, both big endian u16, 0xffff for
+// none. the transparent color is read with alpha 0
+//
+// .pal: a RIFF `PAL ` form whose `data` chunk holds a version (0x0300), a
+// count and count `r g b flags` quads, little endian
+use std::fs;
+use std::path::Path;
+
+use crate::color::{Canonical, CompactScheme, Scheme};
+use crate::errors::SchemeReaderError;
+
+fn read(path: &str) -> Result<(String, Vec<u8>), SchemeReaderError> {
+    let path = Path::new(path);
+    let bytes = fs::read(path).map_err(|e| SchemeReaderError::io(e, Some(path)))?;
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    Ok((stem.into_owned(), bytes))
+}
+
+fn malformed(format: &'static str, reason: &'static str) -> SchemeReaderError {
+    SchemeReaderError::MalformedPalette { format, reason }
+}
+
+pub fn act_colors(bytes: &[u8]) -> Result<Vec<Canonical>, SchemeReaderError> {
+    let (count, transparent) = match bytes.len() {
+        768 => (256, None),
+        772 => {
+            let count = u16::from_be_bytes([bytes[768], bytes[769]]) as usize;
+            let transparent = u16::from_be_bytes([bytes[770], bytes[771]]);
+            if count > 256 {
+                return Err(malformed("act", "more than 256 colors"));
+            }
+            (
+                count,
+                (transparent != 0xffff).then_some(transparent as usize),
+            )
+        }
+        _ => return Err(malformed("act", "not 768 or 772 bytes long")),
+    };
+    Ok(bytes[..count * 3]
+        .chunks_exact(3)
+        .enumerate()
+        .map(|(i, rgb)| {
+            let alpha = if transparent == Some(i) { 0 } else { u8::MAX };
+            Canonical::new(rgb[0], rgb[1], rgb[2], alpha)
+        })
+        .collect())
+}
+
+pub fn pal_colors(bytes: &[u8]) -> Result<Vec<Canonical>, SchemeReaderError> {
+    let truncated = || malformed("pal", "truncated");
+    if bytes.len() < 12 || &bytes[..4] != b"RIFF" || &bytes[8..12] != b"PAL " {
+        return Err(malformed("pal", "not a RIFF palette"));
+    }
+    let mut rest = &bytes[12..];
+    let data = loop {
+        if rest.len() < 8 {
+            return Err(malformed("pal", "no data chunk"));
+        }
+        let size = u32::from_le_bytes([rest[4], rest[5], rest[6], rest[7]]) as usize;
+        let body = rest.get(8..8 + size).ok_or_else(truncated)?;
+        if &rest[..4] == b"data" {
+            break body;
+        }
+        // chunks are padded to an even length
+        rest = rest.get(8 + size + size % 2..).unwrap_or_default();
+    };
+    if data.len() < 4 {
+        return Err(truncated());
+    }
+    let count = u16::from_le_bytes([data[2], data[3]]) as usize;
+    let quads = data.get(4..4 + count * 4).ok_or_else(truncated)?;
+    Ok(quads
+        .chunks_exact(4)
+        .map(|q| Canonical::new(q[0], q[1], q[2], u8::MAX))
+        .collect())
+}
+
+pub fn parse_act(path: &str) -> Result<Scheme, SchemeReaderError> {
+    let (name, bytes) = read(path)?;
+    Ok(Scheme::from_colors(&name, act_colors(&bytes)?))
+}
+
+pub fn parse_act_compact(path: &str) -> Result<CompactScheme, SchemeReaderError> {
+    let (name, bytes) = read(path)?;
+    Ok(CompactScheme::from_colors(&name, None, act_colors(&bytes)?))
+}
+
+pub fn parse_pal(path: &str) -> Result<Scheme, SchemeReaderError> {
+    let (name, bytes) = read(path)?;
+    Ok(Scheme::from_colors(&name, pal_colors(&bytes)?))
+}
+
+pub fn parse_pal_compact(path: &str) -> Result<CompactScheme, SchemeReaderError> {
+    let (name, bytes) = read(path)?;
+    Ok(CompactScheme::from_colors(&name, None, pal_colors(&bytes)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::errors::ErrorKind;
+    use crate::temp::TempDir;
+
+    fn riff(colors: &[[u8; 3]], extra_chunk: bool) -> Vec<u8> {
+        let mut data = vec![0x00, 0x03];
+        data.extend_from_slice(&(colors.len() as u16).to_le_bytes());
+        for rgb in colors {
+            data.extend_from_slice(&[rgb[0], rgb[1], rgb[2], 0]);
+        }
+        let mut chunks = Vec::new();
+        if extra_chunk {
+            chunks.extend_from_slice(b"offl\x03\x00\x00\x00abc\x00");
+        }
+        chunks.extend_from_slice(b"data");
+        chunks.extend_from_slice(&(data.len() as u32).to_le_bytes());
+        chunks.extend_from_slice(&data);
+        let mut bytes = b"RIFF".to_vec();
+        bytes.extend_from_slice(&(chunks.len() as u32 + 4).to_le_bytes());
+        bytes.extend_from_slice(b"PAL ");
+        bytes.extend_from_slice(&chunks);
+        bytes
+    }
+
+    #[test]
+    fn test_act() {
+        let dir = TempDir::new("binary_act");
+        let mut bytes: Vec<u8> = (0..=255u8).flat_map(|i| [i, 0, 255 - i]).collect();
+        let colors = act_colors(&bytes).unwrap();
+        assert_eq!(colors.len(), 256);
+        assert_eq!(colors[1], Canonical::new(1, 0, 254, 255));
+
+        bytes.extend_from_slice(&[0, 16, 0, 2]);
+        let colors = act_colors(&bytes).unwrap();
+        assert_eq!(colors.len(), 16);
+        assert_eq!(colors[2], Canonical::new(2, 0, 253, 0));
+
+        let path = dir.join("cool_rs_binary.act");
+        fs::write(&path, &bytes).unwrap();
+        let path = path.to_str().unwrap();
+        let scheme = parse_act(path).unwrap();
+        assert_eq!(scheme.name, "cool_rs_binary");
+        assert_eq!(parse_act_compact(path).unwrap().to_scheme(), scheme);
+
+        let e = act_colors(&bytes[..700]).unwrap_err();
+        assert_eq!(e.kind(), ErrorKind::MalformedPalette);
+        assert_eq!(
+            e.to_string(),
+            "Malformed act palette: not 768 or 772 bytes long"
+        );
+    }
+
+    #[test]
+    fn test_pal() {
+        let dir = TempDir::new("binary_pal");
+        // a long palette of a few colors, with a chunk to skip before data
+        let colors: Vec<[u8; 3]> = (0..20_000u32).map(|i| [(i % 3) as u8, 0, 0]).collect();
+        let bytes = riff(&colors, true);
+        let read = pal_colors(&bytes).unwrap();
+        assert_eq!(read.len(), 20_000);
+        assert_eq!(read[4], Canonical::new(1, 0, 0, 255));
+
+        let path = dir.join("cool_rs_binary.pal");
+        fs::write(&path, &bytes).unwrap();
+        let path = path.to_str().unwrap();
+        let compact = parse_pal_compact(path).unwrap();
+        assert_eq!(compact.table().len(), 3);
+        assert!(compact.iter().eq(read.iter()));
+        assert_eq!(parse_pal(path).unwrap(), compact.to_scheme());
+
+        let reason = |bytes: &[u8]| match pal_colors(bytes).unwrap_err() {
+            SchemeReaderError::MalformedPalette { reason, .. } => reason,
+            e => panic!("{:?}", e),
+        };
+        assert_eq!(reason(&bytes[..bytes.len() - 1]), "truncated");
+        assert_eq!(reason(b"RIFF\x04\x00\x00\x00PAL "), "no data chunk");
+        assert_eq!(reason(b"GIMP Palette\n"), "not a RIFF palette");
+    }
+}
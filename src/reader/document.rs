@@ -0,0 +1,340 @@
+// a scheme file kept as written, for editing colors without losing comments,
+// blank lines or formatting. set_color replaces only the text of one color
+// and write puts the bytes back, so unchanged documents are written back
+// byte for byte
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use super::{feed_slice, read_file, LineParser, ReaderOptions, ValueSpan};
+use crate::color::{Canonical, EntryKey, Scheme};
+use crate::errors::{DocumentEditError, SchemeReaderError};
+use crate::formats::{ColorFormat, EmitOptions, HexFormat};
+
+#[derive(Debug, Clone)]
+pub struct SchemeDocument {
+    path: PathBuf,
+    options: ReaderOptions,
+    bytes: Vec<u8>,
+    scheme: Scheme,
+    // per entry of the scheme
+    spans: Vec<Option<ValueSpan>>,
+}
+
+pub fn parse_document(path: &str) -> Result<SchemeDocument, SchemeReaderError> {
+    parse_document_with(path, &ReaderOptions::default())
+}
+
+pub fn parse_document_with(
+    path: &str,
+    options: &ReaderOptions,
+) -> Result<SchemeDocument, SchemeReaderError> {
+    let path = PathBuf::from(path);
+    let bytes = fs::read(&path).map_err(|e| SchemeReaderError::io(e, Some(&path)))?;
+    let (scheme, spans) = parse(&bytes, &path, options)?;
+    Ok(SchemeDocument {
+        path,
+        options: options.clone(),
+        bytes,
+        scheme,
+        spans,
+    })
+}
+
+// what read_file does, keeping the spans
+fn parse(
+    bytes: &[u8],
+    path: &Path,
+    options: &ReaderOptions,
+) -> Result<(Scheme, Vec<Option<ValueSpan>>), SchemeReaderError> {
+    let canonical = path
+        .canonicalize()
+        .map_err(|e| SchemeReaderError::io(e, Some(path)))?;
+    let chain = [canonical];
+    let mut parser = LineParser::new(Some(path), &chain, options)?;
+    parser.track_spans();
+    feed_slice(&mut parser, bytes, &chain, options, read_file)?;
+    let spans = parser.take_spans();
+    Ok((parser.finish()?, spans))
+}
+
+impl SchemeDocument {
+    // the scheme as reading the document now would give it
+    pub fn scheme(&self) -> &Scheme {
+        &self.scheme
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    // writes the color where the entry's color is written, as #rrggbb or
+    // #rrggbbaa, or as `r g b` on a GIMP row. a named entry whose color
+    // comes from an include gets an overriding line at the end instead.
+    // `key` is a name or the position of an entry
+    pub fn set_color(
+        &mut self,
+        key: impl Into<EntryKey>,
+        color: Canonical,
+    ) -> Result<(), DocumentEditError> {
+        self.set_color_with(key, color, &EmitOptions::default())
+    }
+
+    // hex colors are written with `options`, GIMP rows have no choices
+    pub fn set_color_with(
+        &mut self,
+        key: impl Into<EntryKey>,
+        color: Canonical,
+        options: &EmitOptions,
+    ) -> Result<(), DocumentEditError> {
+        let key = key.into();
+        let index = match key {
+            EntryKey::Name(ref name) => self.scheme.position(name),
+            EntryKey::Index(i) => Some(i).filter(|&i| i < self.scheme.len()),
+        };
+        let Some(index) = index else {
+            return Err(DocumentEditError::UnknownEntry {
+                key: key.to_string(),
+            });
+        };
+        let unrepresentable = || DocumentEditError::Unrepresentable {
+            key: key.to_string(),
+        };
+
+        let mut bytes = self.bytes.clone();
+        match (&self.spans[index], &self.scheme.entries[index].name) {
+            (Some(span), _) => {
+                let text = match span.gimp_row {
+                    true if color.a != u8::MAX => return Err(unrepresentable()),
+                    true => format!("{:>3} {:>3} {:>3}", color.r, color.g, color.b),
+                    false => HexFormat::emit_with(&color, options),
+                };
+                bytes.splice(span.range.clone(), text.into_bytes());
+            }
+            (None, Some(name)) => {
+                let newline: &[u8] = match bytes.windows(2).any(|w| w == b"\r\n") {
+                    true => b"\r\n",
+                    false => b"\n",
+                };
+                if !bytes.is_empty() && !bytes.ends_with(b"\n") {
+                    bytes.extend_from_slice(newline);
+                }
+                let hex = HexFormat::emit_with(&color, options);
+                bytes.extend_from_slice(format!("{}: {}", name, hex).as_bytes());
+                bytes.extend_from_slice(newline);
+            }
+            (None, None) => {
+                return Err(DocumentEditError::NotInDocument {
+                    key: key.to_string(),
+                })
+            }
+        }
+
+        // read back, so the model is what the written file will give
+        let (scheme, spans) =
+            parse(&bytes, &self.path, &self.options).map_err(|_| unrepresentable())?;
+        if scheme.entries.get(index).map(|e| e.color) != Some(color) {
+            return Err(unrepresentable());
+        }
+        self.bytes = bytes;
+        self.scheme = scheme;
+        self.spans = spans;
+        Ok(())
+    }
+
+    // back to the file it was read from
+    pub fn write(&self) -> io::Result<()> {
+        fs::write(&self.path, &self.bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::errors::ErrorKind;
+    use crate::reader::DuplicateNamePolicy;
+    use crate::temp::TempDir;
+
+    const HAND_WRITTEN: &str = "\u{feff}gruvbox (mine)\r
+// the usual\r
+@author: someone\r
+\r
+background:   #282828   \r
+foreground: rgb(235, 219, 178);\r
+    color4 : #458588\r
+accent: @color4\r
+ 69 133 136  teal\r
+#ff000080\r
+";
+
+    #[test]
+    fn test_unchanged() {
+        let dir = TempDir::new("document_unchanged");
+        let path = dir.write("cool_rs_document_unchanged.txt", HAND_WRITTEN);
+        let document = parse_document(&path).unwrap();
+        assert_eq!(document.as_bytes(), HAND_WRITTEN.as_bytes());
+        assert_eq!(document.scheme(), &crate::reader::parse(&path).unwrap());
+        document.write().unwrap();
+        assert_eq!(fs::read(&path).unwrap(), HAND_WRITTEN.as_bytes());
+    }
+
+    #[test]
+    fn test_comments() {
+        let dir = TempDir::new("document_comments");
+        let text = "commented\n// used for errors\n//   keep red-ish\nred: #cc241d\n\n\
+                    green: #98971a  // not too bright\nblue: #458588\n";
+        let path = dir.write("cool_rs_document_comments.txt", text);
+        let mut document = parse_document(&path).unwrap();
+        document
+            .set_color("red", Canonical::new(251, 73, 52, 255))
+            .unwrap();
+        document
+            .set_color("green", Canonical::new(184, 187, 38, 255))
+            .unwrap();
+        document.write().unwrap();
+        let expected = text
+            .replace("#cc241d", "#fb4934")
+            .replace("#98971a", "#b8bb26");
+        assert_eq!(fs::read_to_string(&path).unwrap(), expected);
+        let comments: Vec<_> = document
+            .scheme()
+            .entries
+            .iter()
+            .map(|e| e.comment.as_deref())
+            .collect();
+        assert_eq!(
+            comments,
+            [
+                Some("used for errors\nkeep red-ish"),
+                Some("not too bright"),
+                None
+            ]
+        );
+    }
+
+    #[test]
+    fn test_set_color() {
+        let dir = TempDir::new("document_set_color");
+        let path = dir.write("cool_rs_document_set_color.txt", HAND_WRITTEN);
+        let mut document = parse_document(&path).unwrap();
+        let teal = Canonical::new(104, 157, 106, 255);
+
+        document
+            .set_color("color4", Canonical::new(131, 165, 152, 255))
+            .unwrap();
+        document.set_color("foreground", teal).unwrap();
+        document.set_color("teal", teal).unwrap();
+        document
+            .set_color(5, Canonical::new(255, 0, 0, 255))
+            .unwrap();
+        document.write().unwrap();
+
+        let expected = HAND_WRITTEN
+            .replace(": #458588", ": #83a598")
+            .replace("rgb(235, 219, 178);", "#689d6a;")
+            .replace("69 133 136", "104 157 106")
+            .replace("#ff000080", "#ff0000");
+        assert_eq!(
+            String::from_utf8(fs::read(&path).unwrap()).unwrap(),
+            expected
+        );
+        let scheme = crate::reader::parse(&path).unwrap();
+        assert_eq!(document.scheme(), &scheme);
+        // the reference follows what it refers to
+        assert_eq!(
+            scheme.get("accent"),
+            Some(&Canonical::new(131, 165, 152, 255))
+        );
+    }
+
+    #[test]
+    fn test_set_color_with() {
+        let dir = TempDir::new("document_set_color_with");
+        let path = dir.write("cool_rs_document_set_color_with.txt", HAND_WRITTEN);
+        let mut document = parse_document(&path).unwrap();
+        let options = EmitOptions {
+            uppercase_hex: true,
+            always_alpha: true,
+            ..Default::default()
+        };
+        document
+            .set_color_with("background", Canonical::new(29, 32, 33, 255), &options)
+            .unwrap();
+        document
+            .set_color_with("teal", Canonical::new(104, 157, 106, 255), &options)
+            .unwrap();
+        let expected = HAND_WRITTEN
+            .replace("#282828", "#1D2021FF")
+            .replace("69 133 136", "104 157 106");
+        assert_eq!(document.as_bytes(), expected.as_bytes());
+    }
+
+    #[test]
+    fn test_set_color_errors() {
+        let dir = TempDir::new("document_set_color_errors");
+        let path = dir.write("cool_rs_document_errors.txt", HAND_WRITTEN);
+        let mut document = parse_document(&path).unwrap();
+        let e = document
+            .set_color("cursor", Canonical::new(0, 0, 0, 255))
+            .unwrap_err();
+        assert_eq!(e.kind(), ErrorKind::UnknownEntry);
+        assert_eq!(e.to_string(), "No entry cursor");
+        let e = document
+            .set_color(6, Canonical::new(0, 0, 0, 255))
+            .unwrap_err();
+        assert_eq!(e.kind(), ErrorKind::UnknownEntry);
+        let e = document
+            .set_color("teal", Canonical::new(0, 0, 0, 128))
+            .unwrap_err();
+        assert_eq!(e.kind(), ErrorKind::Unrepresentable);
+        assert_eq!(document.as_bytes(), HAND_WRITTEN.as_bytes());
+    }
+
+    #[test]
+    fn test_duplicates() {
+        let dir = TempDir::new("document_duplicates");
+        let text = "dupes\nbg: #000000\nbg: #111111\n";
+        let path = dir.write("cool_rs_document_duplicates.txt", text);
+        let options = ReaderOptions {
+            duplicate_names: DuplicateNamePolicy::LastWins,
+            ..Default::default()
+        };
+        let mut document = parse_document_with(&path, &options).unwrap();
+        document
+            .set_color("bg", Canonical::new(34, 34, 34, 255))
+            .unwrap();
+        assert_eq!(document.as_bytes(), b"dupes\nbg: #000000\nbg: #222222\n");
+    }
+
+    #[test]
+    fn test_included() {
+        let dir = TempDir::new("document_included");
+        dir.write("base.txt", "base\nbg: #000000\n#ffffff\n");
+        let main = dir.write("main.txt", "main\n@include base.txt\nfg: #eeeeee");
+
+        let mut document = parse_document(&main).unwrap();
+        document
+            .set_color("bg", Canonical::new(17, 17, 17, 255))
+            .unwrap();
+        document
+            .set_color("bg", Canonical::new(34, 34, 34, 255))
+            .unwrap();
+        assert_eq!(
+            document.as_bytes(),
+            b"main\n@include base.txt\nfg: #eeeeee\nbg: #222222\n"
+        );
+        assert_eq!(
+            document.scheme().get("bg"),
+            Some(&Canonical::new(34, 34, 34, 255))
+        );
+
+        let e = document
+            .set_color(1, Canonical::new(0, 0, 0, 255))
+            .unwrap_err();
+        assert_eq!(e.kind(), ErrorKind::NotInDocument);
+    }
+}
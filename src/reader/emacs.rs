@@ -0,0 +1,339 @@
+// the colors of an Emacs theme, read off its `custom-theme-set-faces` and
+// `custom-set-faces` forms without evaluating any elisp. every face with a
+// `:foreground "#rrggbb"` gives an entry `face.foreground`, `:background`
+// `face.background`, from the first display spec that has them. colors
+// that are symbols are looked up in the `(name "#rrggbb")` bindings of the
+// file, which is how most themes keep their palette in a `let`, taking the
+// graphic branch of a binding to an `if`. color names are left out, and
+// `#rrrrggggbbbb` is read to its top bytes
+//
+// the tokenizer knows lists, strings with escapes, `;` comments, `?c`
+// character literals and the quote marks, which are dropped. a face set
+// twice keeps its first position with the last color, the theme is named by
+// `deftheme`
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use crate::color::{Canonical, Entry, Scheme};
+use crate::errors::{ParseFormatError, SchemeReaderError};
+use crate::formats::{ColorFormat, HexFormat};
+
+const SET_FACES: [&str; 2] = ["custom-theme-set-faces", "custom-set-faces"];
+// the attribute and the suffix of its entries
+const ATTRIBUTES: [(&str, &str); 2] =
+    [(":foreground", "foreground"), (":background", "background")];
+
+fn malformed(reason: &'static str) -> SchemeReaderError {
+    SchemeReaderError::MalformedPalette {
+        format: "emacs",
+        reason,
+    }
+}
+
+#[derive(PartialEq, Debug)]
+enum Sexp {
+    List(Vec<Sexp>),
+    Symbol(String),
+    String(String),
+}
+
+impl Sexp {
+    fn symbol(&self) -> Option<&str> {
+        match self {
+            Sexp::Symbol(s) => Some(s),
+            _ => None,
+        }
+    }
+}
+
+// every top level form of the text
+fn read(text: &str) -> Result<Vec<Sexp>, SchemeReaderError> {
+    // the open lists, the top level at the bottom
+    let mut stack: Vec<Vec<Sexp>> = vec![Vec::new()];
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            ';' => {
+                for c in chars.by_ref() {
+                    if c == '\n' {
+                        break;
+                    }
+                }
+            }
+            '(' | '[' => stack.push(Vec::new()),
+            ')' | ']' => {
+                let list = stack.pop().unwrap();
+                match stack.last_mut() {
+                    Some(parent) => parent.push(Sexp::List(list)),
+                    None => return Err(malformed("unbalanced parentheses")),
+                }
+            }
+            '"' => {
+                let mut s = String::new();
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some('n') => s.push('\n'),
+                            // an escaped line break continues the string
+                            Some('\n') => {}
+                            Some(c) => s.push(c),
+                            None => return Err(malformed("unterminated string")),
+                        },
+                        Some(c) => s.push(c),
+                        None => return Err(malformed("unterminated string")),
+                    }
+                }
+                stack.last_mut().unwrap().push(Sexp::String(s));
+            }
+            // ?a, ?\( and ?\" are characters, whatever they are
+            '?' => {
+                if chars.next() == Some('\\') {
+                    chars.next();
+                }
+                stack.last_mut().unwrap().push(Sexp::Symbol("?".into()));
+            }
+            '\'' | '`' | ',' | '@' | '#' => {}
+            c if c.is_whitespace() => {}
+            c => {
+                let mut symbol = String::from(c);
+                while let Some(&c) = chars.peek() {
+                    if c.is_whitespace() || "()[]\";'`,".contains(c) {
+                        break;
+                    }
+                    symbol.push(c);
+                    chars.next();
+                }
+                stack.last_mut().unwrap().push(Sexp::Symbol(symbol));
+            }
+        }
+    }
+    match (stack.pop(), stack.is_empty()) {
+        (Some(forms), true) => Ok(forms),
+        _ => Err(malformed("unbalanced parentheses")),
+    }
+}
+
+// the lists of every form, depth first, the form itself included
+fn lists<'a>(form: &'a Sexp, out: &mut Vec<&'a [Sexp]>) {
+    if let Sexp::List(items) = form {
+        out.push(items);
+        for item in items {
+            lists(item, out);
+        }
+    }
+}
+
+// the value after `key` in the first plist of the spec that has it
+fn attribute<'a>(spec: &'a Sexp, key: &str) -> Option<&'a Sexp> {
+    let mut all = Vec::new();
+    lists(spec, &mut all);
+    all.into_iter().find_map(|items| {
+        let at = items.iter().position(|item| item.symbol() == Some(key))?;
+        items.get(at + 1)
+    })
+}
+
+// the color a `let` binds, the graphic branch of an `(if cond "#.." ..)`
+fn binding(value: &Sexp) -> Option<&str> {
+    let value = match value {
+        Sexp::List(items) => match items.as_slice() {
+            [Sexp::Symbol(head), _, then, ..] if head == "if" => then,
+            _ => return None,
+        },
+        value => value,
+    };
+    match value {
+        Sexp::String(value) if value.starts_with('#') => Some(value),
+        _ => None,
+    }
+}
+
+fn hex(value: &str) -> Result<Canonical, ParseFormatError> {
+    let digits = &value[1..];
+    match digits.len() {
+        12 if digits.is_ascii() => {
+            let top: String = (0..3).map(|i| &digits[i * 4..i * 4 + 2]).collect();
+            HexFormat::parse(&format!("#{}", top))
+        }
+        _ => HexFormat::parse(value),
+    }
+}
+
+// the entries in the order their faces first appear, the name empty without
+// a deftheme
+pub fn emacs_scheme(text: &str) -> Result<Scheme, SchemeReaderError> {
+    let forms = read(text)?;
+    let mut all = Vec::new();
+    for form in &forms {
+        lists(form, &mut all);
+    }
+
+    let mut name = None;
+    let mut bindings: HashMap<&str, &str> = HashMap::new();
+    for items in &all {
+        match items {
+            [Sexp::Symbol(deftheme), Sexp::Symbol(theme), ..] if deftheme == "deftheme" => {
+                name = Some(theme.clone());
+            }
+            [Sexp::Symbol(var), value] => {
+                if let Some(value) = binding(value) {
+                    bindings.insert(var, value);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let mut entries: Vec<Entry> = Vec::new();
+    let faces = all.iter().filter(|items| {
+        let head = items.first().and_then(Sexp::symbol);
+        head.is_some_and(|head| SET_FACES.contains(&head))
+    });
+    // the theme name is a symbol and skipped like any other non list
+    for items in faces {
+        for face in items.iter().skip(1) {
+            let Sexp::List(spec) = face else {
+                continue;
+            };
+            let Some(face_name) = spec.first().and_then(Sexp::symbol) else {
+                continue;
+            };
+            for (key, suffix) in ATTRIBUTES {
+                let value = match attribute(face, key) {
+                    Some(Sexp::String(value)) => value.as_str(),
+                    Some(Sexp::Symbol(var)) => match bindings.get(var.as_str()) {
+                        Some(value) => value,
+                        None => continue,
+                    },
+                    _ => continue,
+                };
+                if !value.starts_with('#') {
+                    continue;
+                }
+                let entry_name = format!("{}.{}", face_name, suffix);
+                let color = hex(value).map_err(|source| SchemeReaderError::InvalidToken {
+                    token: entry_name.clone(),
+                    source,
+                })?;
+                match entries
+                    .iter_mut()
+                    .find(|e| e.name.as_deref() == Some(&entry_name))
+                {
+                    Some(entry) => entry.color = color,
+                    None => entries.push(Entry::named(&entry_name, color)),
+                }
+            }
+        }
+    }
+    Ok(Scheme::from_entries(
+        name.unwrap_or_default(),
+        None,
+        entries,
+    ))
+}
+
+// named after the file if there is no deftheme
+pub fn parse(path: &str) -> Result<Scheme, SchemeReaderError> {
+    let path = Path::new(path);
+    let text = fs::read_to_string(path).map_err(|e| SchemeReaderError::io(e, Some(path)))?;
+    let mut scheme = emacs_scheme(&text)?;
+    if scheme.name.is_empty() {
+        let stem = path.file_stem().unwrap_or_default();
+        scheme.name = stem.to_string_lossy().into_owned();
+    }
+    Ok(scheme)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::errors::ErrorKind;
+    use crate::temp::TempDir;
+
+    // nord, trimmed
+    const NORD: &str = r##";;; nord-theme.el --- An arctic, north-bluish clean and elegant theme
+
+;; Copyright (C) 2016-present Arctic Ice Studio <development@arcticicestudio.com>
+
+(deftheme nord "An arctic, north-bluish clean and elegant theme")
+
+(let ((class '((class color) (min-colors 89)))
+      (nord0 (if (nord-display-truecolor-or-graphic-p) "#2E3440" nil))
+      (nord4 (if (nord-display-truecolor-or-graphic-p) "#D8DEE9" "#D8DEE9"))
+      (nord8 "#88C0D0")
+      (nord-comment "#616E88"))
+
+  (custom-theme-set-faces
+    'nord
+    `(default ((,class (:foreground ,nord4 :background ,nord0))))
+    ;; (cursor ((t (:background "#000000")))) is commented out
+    `(cursor ((,class (:background ,nord4))))
+    `(font-lock-comment-face ((,class (:foreground ,nord-comment :slant italic))))
+    '(font-lock-string-face ((t (:foreground "#A3BE8C"))))
+    `(font-lock-doc-face ((t (:foreground "#A3BE8C" :box (:color "#ffffff")))))
+    '(mode-line ((((background dark)) (:foreground "#8FBCBB" :background "#3B4252"))
+                 (t (:foreground "#000000"))))
+    `(link ((t (:foreground ,nord8 :underline t))))
+    '(help-key-binding ((t (:foreground "LightSkyBlue" :background "#ffffffffffff"))))
+    '(escaped ((t (:inherit "a \"quoted\" (string)" :foreground "#ABCDEF"))))
+    '(char ((t (:weight ?\) :background "#010203"))))))
+
+(provide-theme 'nord)
+"##;
+
+    #[test]
+    fn test_emacs_scheme() {
+        let dir = TempDir::new("emacs_scheme");
+        let path = dir.join("cool_rs_nord-theme.el");
+        fs::write(&path, NORD).unwrap();
+        let scheme = parse(path.to_str().unwrap()).unwrap();
+        assert_eq!(scheme.name, "nord");
+        let entries: Vec<_> = scheme
+            .entries
+            .iter()
+            .map(|e| (e.name.as_deref().unwrap(), e.color))
+            .collect();
+        let rgb = |hex: u32| Canonical::new((hex >> 16) as u8, (hex >> 8) as u8, hex as u8, 255);
+        assert_eq!(
+            entries,
+            [
+                // nord0 is bound to its graphic color
+                ("default.foreground", rgb(0xd8dee9)),
+                ("default.background", rgb(0x2e3440)),
+                ("cursor.background", rgb(0xd8dee9)),
+                ("font-lock-comment-face.foreground", rgb(0x616e88)),
+                ("font-lock-string-face.foreground", rgb(0xa3be8c)),
+                ("font-lock-doc-face.foreground", rgb(0xa3be8c)),
+                ("mode-line.foreground", rgb(0x8fbcbb)),
+                ("mode-line.background", rgb(0x3b4252)),
+                ("link.foreground", rgb(0x88c0d0)),
+                ("help-key-binding.background", rgb(0xffffff)),
+                ("escaped.foreground", rgb(0xabcdef)),
+                ("char.background", rgb(0x010203)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_malformed() {
+        let dir = TempDir::new("emacs_malformed");
+        let reason = |text: &str| match emacs_scheme(text).unwrap_err() {
+            SchemeReaderError::MalformedPalette { reason, .. } => reason,
+            e => panic!("{:?}", e),
+        };
+        assert_eq!(reason("(deftheme x"), "unbalanced parentheses");
+        assert_eq!(reason("(deftheme x))"), "unbalanced parentheses");
+        assert_eq!(reason("(deftheme x \"doc)"), "unterminated string");
+
+        let unnamed = dir.join("cool_rs_emacs_unnamed.el");
+        fs::write(&unnamed, "(custom-set-faces)").unwrap();
+        let scheme = parse(unnamed.to_str().unwrap()).unwrap();
+        assert_eq!(scheme.name, "cool_rs_emacs_unnamed");
+        assert!(scheme.entries.is_empty());
+
+        let e = emacs_scheme("(custom-set-faces '(default ((t (:foreground \"#12345\")))))");
+        assert_eq!(e.unwrap_err().kind(), ErrorKind::InvalidToken);
+    }
+}
@@ -0,0 +1,201 @@
+// schemes in the formats of other programs. none of them has alpha, each
+// exporter has a _with variant taking the AlphaPolicy for translucent
+// colors, the plain one drops it. Xresources and kitty place entries by
+// their role, entries without one are left out, as are later entries with
+// the role of an earlier one
+use std::fmt::Write;
+
+use crate::color::diff::hex;
+use crate::color::{AlphaPolicy, Canonical, EntryKey, Role, Scheme};
+use crate::errors::ColorError;
+
+// the first entry of each role by index, in the order of the entries
+pub(crate) fn role_entries(scheme: &Scheme) -> Vec<(Role, usize)> {
+    let mut placed: Vec<(Role, usize)> = Vec::new();
+    for (i, entry) in scheme.entries.iter().enumerate() {
+        let Some(role) = entry.role else { continue };
+        if placed.iter().all(|&(r, _)| r != role) {
+            placed.push((role, i));
+        }
+    }
+    placed
+}
+
+// each entry with a role once, opaque as `alpha` makes it
+fn by_role(scheme: &Scheme, alpha: &AlphaPolicy) -> Result<Vec<(Role, Canonical)>, ColorError> {
+    role_entries(scheme)
+        .into_iter()
+        .map(|(role, i)| {
+            let entry = &scheme.entries[i];
+            Ok((
+                role,
+                alpha.apply(key(i, entry.name.as_deref()), &entry.color)?,
+            ))
+        })
+        .collect()
+}
+
+pub(crate) fn key(index: usize, name: Option<&str>) -> EntryKey {
+    match name {
+        Some(name) => EntryKey::Name(name.into()),
+        None => EntryKey::Index(index),
+    }
+}
+
+impl Scheme {
+    pub fn to_xresources(&self) -> String {
+        self.to_xresources_with(&AlphaPolicy::Drop)
+            .expect("Drop never fails")
+    }
+
+    // `*.background`, `*.foreground`, `*.cursorColor` and `*.color0` to
+    // `*.color255`, accents have no resource
+    pub fn to_xresources_with(&self, alpha: &AlphaPolicy) -> Result<String, ColorError> {
+        let mut text = format!("! {}\n", self.name);
+        for (role, color) in by_role(self, alpha)? {
+            let resource = match role {
+                Role::Background => "background".to_string(),
+                Role::Foreground => "foreground".to_string(),
+                Role::Cursor => "cursorColor".to_string(),
+                Role::AnsiColor(i) => format!("color{}", i),
+                Role::Accent => continue,
+            };
+            let _ = writeln!(text, "*.{}: {}", resource, hex(&color));
+        }
+        Ok(text)
+    }
+
+    pub fn to_kitty(&self) -> String {
+        self.to_kitty_with(&AlphaPolicy::Drop)
+            .expect("Drop never fails")
+    }
+
+    // a kitty.conf include, `background`, `foreground`, `cursor` and
+    // `color0` to `color255`. kitty has no accent either
+    pub fn to_kitty_with(&self, alpha: &AlphaPolicy) -> Result<String, ColorError> {
+        let mut text = format!("# {}\n", self.name);
+        for (role, color) in by_role(self, alpha)? {
+            let option = match role {
+                Role::Background => "background".to_string(),
+                Role::Foreground => "foreground".to_string(),
+                Role::Cursor => "cursor".to_string(),
+                Role::AnsiColor(i) => format!("color{}", i),
+                Role::Accent => continue,
+            };
+            let _ = writeln!(text, "{} {}", option, hex(&color));
+        }
+        Ok(text)
+    }
+
+    pub fn to_gpl(&self) -> String {
+        self.to_gpl_with(&AlphaPolicy::Drop)
+            .expect("Drop never fails")
+    }
+
+    // a GIMP palette with every entry, unnamed ones as `Untitled` as GIMP
+    // names them
+    pub fn to_gpl_with(&self, alpha: &AlphaPolicy) -> Result<String, ColorError> {
+        let mut text = format!("GIMP Palette\nName: {}\n#\n", self.name);
+        for (i, entry) in self.entries.iter().enumerate() {
+            let color = alpha.apply(key(i, entry.name.as_deref()), &entry.color)?;
+            let name = entry.name.as_deref().unwrap_or("Untitled");
+            let _ = writeln!(
+                text,
+                "{:>3} {:>3} {:>3}\t{}",
+                color.r, color.g, color.b, name
+            );
+        }
+        Ok(text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::Entry;
+    use crate::errors::ErrorKind;
+
+    // a translucent red in the middle, a translucent entry without a role
+    fn scheme() -> Scheme {
+        let entries = vec![
+            Entry::named("background", Canonical::new(40, 40, 40, 255)),
+            Entry::named("color1", Canonical::new(204, 36, 29, 128)),
+            Entry::named("background", Canonical::new(0, 0, 0, 255)),
+            Entry::named("accent", Canonical::new(69, 133, 136, 255)),
+            Entry::new(Canonical::new(255, 255, 255, 0)),
+        ];
+        Scheme::from_entries("gruvbox".into(), None, entries)
+    }
+
+    fn over() -> AlphaPolicy {
+        AlphaPolicy::CompositeOver(Canonical::new(40, 40, 40, 255))
+    }
+
+    #[test]
+    fn test_xresources() {
+        let scheme = scheme();
+        assert_eq!(
+            scheme.to_xresources(),
+            "! gruvbox\n*.background: #282828\n*.color1: #cc241d\n"
+        );
+        assert_eq!(
+            scheme.to_xresources_with(&over()).unwrap(),
+            "! gruvbox\n*.background: #282828\n*.color1: #7a2622\n"
+        );
+        let e = scheme.to_xresources_with(&AlphaPolicy::Error).unwrap_err();
+        assert_eq!(e.kind(), ErrorKind::AlphaLost);
+        assert_eq!(
+            e.to_string(),
+            "color1 is translucent, alpha 128, and the format has no alpha"
+        );
+    }
+
+    #[test]
+    fn test_kitty() {
+        let scheme = scheme();
+        assert_eq!(
+            scheme.to_kitty(),
+            "# gruvbox\nbackground #282828\ncolor1 #cc241d\n"
+        );
+        assert_eq!(
+            scheme.to_kitty_with(&over()).unwrap(),
+            "# gruvbox\nbackground #282828\ncolor1 #7a2622\n"
+        );
+        let e = scheme.to_kitty_with(&AlphaPolicy::Error).unwrap_err();
+        assert!(
+            matches!(e, ColorError::AlphaLost { entry: EntryKey::Name(ref n), .. } if n == "color1")
+        );
+    }
+
+    #[test]
+    fn test_gpl() {
+        let scheme = scheme();
+        assert_eq!(
+            scheme.to_gpl(),
+            "GIMP Palette\nName: gruvbox\n#\n 40  40  40\tbackground\n204  36  29\tcolor1\n  \
+             0   0   0\tbackground\n 69 133 136\taccent\n255 255 255\tUntitled\n"
+        );
+        assert!(scheme.to_gpl_with(&over()).unwrap().ends_with(
+            "122  38  34\tcolor1\n  0   0   0\tbackground\n 69 133 136\taccent\n \
+                        40  40  40\tUntitled\n"
+        ));
+        // the first translucent entry is named
+        let e = scheme.to_gpl_with(&AlphaPolicy::Error).unwrap_err();
+        assert_eq!(
+            e.to_string(),
+            "color1 is translucent, alpha 128, and the format has no alpha"
+        );
+        let unnamed = Scheme::from_colors("x", vec![Canonical::new(1, 2, 3, 4)]);
+        let e = unnamed.to_gpl_with(&AlphaPolicy::Error).unwrap_err();
+        assert_eq!(
+            e.to_string(),
+            "[0] is translucent, alpha 4, and the format has no alpha"
+        );
+        // opaque schemes are the same under every policy
+        let opaque = Scheme::from_colors("x", vec![Canonical::new(1, 2, 3, 255)]);
+        assert_eq!(
+            opaque.to_gpl_with(&AlphaPolicy::Error).unwrap(),
+            opaque.to_gpl()
+        );
+    }
+}
@@ -0,0 +1,423 @@
+// the window colors of an i3 or sway config, the `client.*` lines. every
+// slot of `client.focused border background text indicator child_border`
+// gives an entry `client.focused.border` and so on, `client.background` its
+// single color. `$variables` are resolved through their `set` lines, and
+// through other variables, the last `set` of a name winning as in i3.
+// `set_from_resource` gives its fallback. bar colors aren't read
+//
+// a class set twice keeps its first position with the colors of the last
+// line. the config has no name, parse names the scheme after the file
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::fs;
+use std::path::Path;
+
+use crate::color::diff::hex;
+use crate::color::{Canonical, Entry, Role, Scheme};
+use crate::errors::SchemeReaderError;
+use crate::formats::{ColorFormat, HexFormat};
+
+// the classes to_i3 writes, in this order, client.background after them
+pub const CLASSES: [&str; 6] = [
+    "focused",
+    "focused_inactive",
+    "focused_tab_title",
+    "unfocused",
+    "urgent",
+    "placeholder",
+];
+pub const SLOTS: [&str; 5] = ["border", "background", "text", "indicator", "child_border"];
+
+// logical lines with their line numbers, continuations joined and comments
+// dropped
+fn lines(text: &str) -> Vec<(usize, String)> {
+    let mut lines: Vec<(usize, String)> = Vec::new();
+    let mut continued = false;
+    for (i, line) in text.lines().enumerate() {
+        let line = line.trim();
+        let (line, continues) = match line.strip_suffix('\\') {
+            Some(line) => (line, true),
+            None => (line, false),
+        };
+        match lines.last_mut() {
+            Some((_, last)) if continued => {
+                last.push(' ');
+                last.push_str(line);
+            }
+            _ if line.is_empty() || line.starts_with('#') => {}
+            _ => lines.push((i + 1, line.to_string())),
+        }
+        continued = continues;
+    }
+    lines
+}
+
+fn unquote(value: &str) -> &str {
+    value.trim_matches(|c| c == '"' || c == '\'')
+}
+
+// every variable with its value and line
+fn variables(lines: &[(usize, String)]) -> HashMap<&str, (&str, usize)> {
+    let mut variables = HashMap::new();
+    for (line, text) in lines {
+        let mut words = text.split_whitespace();
+        let value = match words.next() {
+            Some("set") => words.clone().nth(1),
+            // the resource, then the fallback if there is one
+            Some("set_from_resource") => Some(words.clone().nth(2).unwrap_or("")),
+            _ => continue,
+        };
+        if let (Some(name), Some(value)) = (words.next(), value) {
+            if let Some(name) = name.strip_prefix('$') {
+                variables.insert(name, (unquote(value), *line));
+            }
+        }
+    }
+    variables
+}
+
+// the value with variables followed to one that isn't
+fn resolve<'a>(
+    variables: &HashMap<&str, (&'a str, usize)>,
+    value: &'a str,
+    line: usize,
+) -> Result<&'a str, SchemeReaderError> {
+    let (mut value, mut line) = (value, line);
+    let mut chain: Vec<String> = Vec::new();
+    while let Some(name) = value.strip_prefix('$') {
+        let repeated = chain.iter().any(|n| n == name);
+        chain.push(name.to_string());
+        if repeated {
+            return Err(SchemeReaderError::VariableCycle { chain });
+        }
+        let undefined = || SchemeReaderError::UndefinedVariable {
+            name: name.to_string(),
+            line,
+        };
+        (value, line) = *variables.get(name).ok_or_else(undefined)?;
+    }
+    Ok(value)
+}
+
+// the entries in the order their classes first appear, the name empty
+pub fn i3_scheme(text: &str) -> Result<Scheme, SchemeReaderError> {
+    let lines = lines(text);
+    let variables = variables(&lines);
+    let mut entries: Vec<Entry> = Vec::new();
+    for (line, text) in &lines {
+        let mut words = text.split_whitespace();
+        let Some(class) = words.next().filter(|word| word.starts_with("client.")) else {
+            continue;
+        };
+        // client.background has one color, which is the class itself
+        let names: Vec<String> = match class {
+            "client.background" => vec![class.to_string()],
+            _ => SLOTS
+                .iter()
+                .map(|slot| format!("{}.{}", class, slot))
+                .collect(),
+        };
+        for (entry_name, value) in names.into_iter().zip(words) {
+            let value = unquote(resolve(&variables, unquote(value), *line)?);
+            let color =
+                HexFormat::parse(value).map_err(|source| SchemeReaderError::InvalidToken {
+                    token: entry_name.clone(),
+                    source,
+                })?;
+            match entries
+                .iter_mut()
+                .find(|e| e.name.as_deref() == Some(&entry_name))
+            {
+                Some(entry) => entry.color = color,
+                None => entries.push(Entry::named(&entry_name, color)),
+            }
+        }
+    }
+    Ok(Scheme::from_entries(String::new(), None, entries))
+}
+
+// named after the file
+pub fn parse(path: &str) -> Result<Scheme, SchemeReaderError> {
+    let path = Path::new(path);
+    let text = fs::read_to_string(path).map_err(|e| SchemeReaderError::io(e, Some(path)))?;
+    let mut scheme = i3_scheme(&text)?;
+    let stem = path.file_stem().unwrap_or_default();
+    scheme.name = stem.to_string_lossy().into_owned();
+    Ok(scheme)
+}
+
+// where to_i3 takes the color of a slot from
+#[derive(PartialEq, Debug, Clone)]
+pub enum I3Source {
+    // the first entry with the role
+    Role(Role),
+    // the first entry with the name
+    Name(String),
+}
+
+// the source of each slot, by the entry name the reader gives it, e.g.
+// `client.focused.border`. the default takes them from roles, the focused
+// window in blue and urgent ones in red
+#[derive(PartialEq, Debug, Clone)]
+pub struct I3Mapping {
+    slots: Vec<(String, I3Source)>,
+}
+
+impl Default for I3Mapping {
+    fn default() -> Self {
+        const BG: Role = Role::Background;
+        const FG: Role = Role::Foreground;
+        const RED: Role = Role::AnsiColor(1);
+        const BLUE: Role = Role::AnsiColor(4);
+        const CYAN: Role = Role::AnsiColor(6);
+        const WHITE: Role = Role::AnsiColor(7);
+        const GRAY: Role = Role::AnsiColor(8);
+        let classes = [
+            ("focused", [BLUE, BLUE, FG, CYAN, BLUE]),
+            ("focused_inactive", [GRAY, GRAY, FG, GRAY, GRAY]),
+            ("unfocused", [BG, BG, WHITE, BG, BG]),
+            ("urgent", [RED, RED, FG, RED, RED]),
+            ("placeholder", [BG, BG, FG, BG, BG]),
+        ];
+        let mut mapping = I3Mapping::new();
+        for (class, roles) in classes {
+            for (slot, role) in SLOTS.iter().zip(roles) {
+                mapping = mapping.set(&format!("client.{}.{}", class, slot), I3Source::Role(role));
+            }
+        }
+        mapping.set("client.background", I3Source::Role(BG))
+    }
+}
+
+impl I3Mapping {
+    // no slot mapped
+    pub fn new() -> Self {
+        I3Mapping { slots: Vec::new() }
+    }
+
+    // every slot from the entry named after it, which writes a scheme read
+    // from a config back as it was
+    pub fn by_name() -> Self {
+        let mut mapping = I3Mapping::new();
+        for class in CLASSES {
+            for slot in SLOTS {
+                let name = format!("client.{}.{}", class, slot);
+                mapping = mapping.set(&name, I3Source::Name(name.clone()));
+            }
+        }
+        mapping.set(
+            "client.background",
+            I3Source::Name("client.background".into()),
+        )
+    }
+
+    // replaces the slot's source if it has one
+    pub fn set(mut self, slot: &str, source: I3Source) -> Self {
+        match self.slots.iter_mut().find(|(s, _)| s == slot) {
+            Some((_, old)) => *old = source,
+            None => self.slots.push((slot.to_string(), source)),
+        }
+        self
+    }
+
+    pub fn get(&self, slot: &str) -> Option<&I3Source> {
+        self.slots
+            .iter()
+            .find(|(s, _)| s == slot)
+            .map(|(_, source)| source)
+    }
+}
+
+// the lines to_i3 writes, each class with the entries of its slots by index
+pub(crate) fn i3_lines(scheme: &Scheme, mapping: &I3Mapping) -> Vec<(String, Vec<usize>)> {
+    let index = |slot: &str| -> Option<usize> {
+        let entries = &scheme.entries;
+        match mapping.get(slot)? {
+            I3Source::Role(role) => entries.iter().position(|e| e.role == Some(*role)),
+            I3Source::Name(name) => entries.iter().position(|e| e.name.as_deref() == Some(name)),
+        }
+    };
+    let mut lines = Vec::new();
+    for class in CLASSES {
+        let class = format!("client.{}", class);
+        let slots: Vec<usize> = SLOTS
+            .iter()
+            .map_while(|slot| index(&format!("{}.{}", class, slot)))
+            .collect();
+        if slots.len() >= 3 {
+            lines.push((class, slots));
+        }
+    }
+    if let Some(background) = index("client.background") {
+        lines.push(("client.background".to_string(), vec![background]));
+    }
+    lines
+}
+
+impl Scheme {
+    // `client.*` lines for the slots the mapping finds a color for, alpha
+    // dropped as i3 has none. a class needs border, background and text, the
+    // indicator and child_border follow while they are found
+    pub fn to_i3(&self, mapping: &I3Mapping) -> String {
+        let mut text = format!("# {}\n", self.name);
+        for (class, slots) in i3_lines(self, mapping) {
+            let colors: Vec<String> = slots
+                .into_iter()
+                .map(|i| {
+                    let color = self.entries[i].color;
+                    hex(&Canonical {
+                        a: u8::MAX,
+                        ..color
+                    })
+                })
+                .collect();
+            let _ = writeln!(text, "{} {}", class, colors.join(" "));
+        }
+        text
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::errors::ErrorKind;
+    use crate::temp::TempDir;
+
+    const CONFIG: &str = r##"# i3 config file (v4)
+set $mod Mod4
+font pango:monospace 8
+
+# colors
+set $bg     #282828
+set $red    #cc241d
+set $blue   "#458588"
+set $accent $blue
+set $focus  $accent
+set_from_resource $fg i3wm.foreground #ebdbb2
+set_from_resource $unset i3wm.unset
+
+# class                 border  backgr. text    indicator child_border
+client.focused          $focus  $focus  $fg     #83a598   $accent
+client.focused_inactive $bg     $bg     #a89984
+client.unfocused        $bg     $bg     #a89984 $bg \
+                        $bg
+client.urgent           $red    $red    $fg     $red      $red
+client.background       $bg
+client.focused          $focus  $focus  #fbf1c7 #83a598   $accent
+
+bar {
+    colors {
+        background $bg
+        statusline #ebdbb2
+    }
+}
+
+bindsym $mod+Return exec i3-sensible-terminal
+"##;
+
+    fn rgb(hex: u32) -> Canonical {
+        Canonical::new((hex >> 16) as u8, (hex >> 8) as u8, hex as u8, 255)
+    }
+
+    #[test]
+    fn test_i3_scheme() {
+        let dir = TempDir::new("i3_scheme");
+        let path = dir.join("cool_rs_i3.config");
+        fs::write(&path, CONFIG).unwrap();
+        let scheme = parse(path.to_str().unwrap()).unwrap();
+        assert_eq!(scheme.name, "cool_rs_i3");
+        let entries: Vec<_> = scheme
+            .entries
+            .iter()
+            .map(|e| (e.name.as_deref().unwrap(), e.color))
+            .collect();
+        assert_eq!(
+            entries,
+            [
+                ("client.focused.border", rgb(0x458588)),
+                ("client.focused.background", rgb(0x458588)),
+                // the second focused line
+                ("client.focused.text", rgb(0xfbf1c7)),
+                ("client.focused.indicator", rgb(0x83a598)),
+                ("client.focused.child_border", rgb(0x458588)),
+                ("client.focused_inactive.border", rgb(0x282828)),
+                ("client.focused_inactive.background", rgb(0x282828)),
+                ("client.focused_inactive.text", rgb(0xa89984)),
+                ("client.unfocused.border", rgb(0x282828)),
+                ("client.unfocused.background", rgb(0x282828)),
+                ("client.unfocused.text", rgb(0xa89984)),
+                ("client.unfocused.indicator", rgb(0x282828)),
+                ("client.unfocused.child_border", rgb(0x282828)),
+                ("client.urgent.border", rgb(0xcc241d)),
+                ("client.urgent.background", rgb(0xcc241d)),
+                ("client.urgent.text", rgb(0xebdbb2)),
+                ("client.urgent.indicator", rgb(0xcc241d)),
+                ("client.urgent.child_border", rgb(0xcc241d)),
+                ("client.background", rgb(0x282828)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_variable_errors() {
+        let e = i3_scheme("set $a $b\nset $b $c\nset $c $b\nclient.background $a\n").unwrap_err();
+        assert_eq!(e.kind(), ErrorKind::VariableCycle);
+        assert_eq!(e.to_string(), "Variable cycle: $a -> $b -> $c -> $b");
+        let e = i3_scheme("set $a $a\nclient.background $a\n").unwrap_err();
+        assert_eq!(e.to_string(), "Variable cycle: $a -> $a");
+
+        // the line of the set naming the missing variable
+        let e = i3_scheme("set $a $b\n\nclient.background $a\n").unwrap_err();
+        assert!(matches!(
+            e,
+            SchemeReaderError::UndefinedVariable { ref name, line: 1 } if name == "b"
+        ));
+        assert_eq!(e.to_string(), "$b on line 1 is not set");
+
+        // a fallback-less resource is empty, not a color
+        let e = i3_scheme("set_from_resource $a x\nclient.background $a\n").unwrap_err();
+        assert_eq!(e.kind(), ErrorKind::InvalidToken);
+        // cycles among variables no client line uses don't matter
+        assert!(i3_scheme("set $a $b\nset $b $a\n").is_ok());
+    }
+
+    #[test]
+    fn test_to_i3() {
+        let scheme = Scheme::from_entries(
+            "gruvbox".into(),
+            None,
+            vec![
+                Entry::named("background", rgb(0x282828)),
+                Entry::named("foreground", rgb(0xebdbb2)),
+                Entry::named("red", rgb(0xcc241d)),
+                Entry::named("blue", Canonical::new(0x45, 0x85, 0x88, 128)),
+                Entry::named("white", rgb(0xa89984)),
+                Entry::named("bright_black", rgb(0x928374)),
+            ],
+        );
+        // focused has no indicator without a cyan, and so no child_border
+        assert_eq!(
+            scheme.to_i3(&I3Mapping::default()),
+            "# gruvbox\n\
+             client.focused #458588 #458588 #ebdbb2\n\
+             client.focused_inactive #928374 #928374 #ebdbb2 #928374 #928374\n\
+             client.unfocused #282828 #282828 #a89984 #282828 #282828\n\
+             client.urgent #cc241d #cc241d #ebdbb2 #cc241d #cc241d\n\
+             client.placeholder #282828 #282828 #ebdbb2 #282828 #282828\n\
+             client.background #282828\n"
+        );
+        let mapping = I3Mapping::new()
+            .set("client.urgent.border", I3Source::Name("red".into()))
+            .set("client.urgent.background", I3Source::Name("red".into()))
+            .set("client.urgent.text", I3Source::Role(Role::Background));
+        assert_eq!(
+            scheme.to_i3(&mapping),
+            "# gruvbox\nclient.urgent #cc241d #cc241d #282828\n"
+        );
+
+        // a config written back reads as it was
+        let read = i3_scheme(CONFIG).unwrap();
+        let written = read.to_i3(&I3Mapping::by_name());
+        assert_eq!(i3_scheme(&written).unwrap().entries, read.entries);
+    }
+}
@@ -0,0 +1,478 @@
+// schemes as JSON, for tools that would rather not parse the text format:
+//
+//   {
+//     "name": "gruvbox",
+//     "author": "morhetz",
+//     "entries": [
+//       { "name": "bg", "color": "#282828", "comment": "the darkest" },
+//       { "color": 3425907967 }
+//     ]
+//   }
+//
+// author, an entry's name and comment are optional. a color is a `#rrggbb`
+// or `#rrggbbaa` string, or the packed 0xRRGGBBAA integer as in
+// color::Packed. Scheme::to_json writes strings, Scheme::json_schema
+// describes all of it for validators in other languages
+use std::fs;
+use std::path::Path;
+
+use crate::color::diff::hex;
+use crate::color::{Canonical, Entry, Packed, Scheme};
+use crate::errors::SchemeReaderError;
+use crate::formats::{ColorFormat, HexFormat};
+use crate::json::{self, Value};
+
+const JSON_SCHEMA: &str = r##"{
+  "$schema": "https://json-schema.org/draft/2020-12/schema",
+  "$id": "https://github.com/Foesec/cool_rs/scheme.schema.json",
+  "title": "cool_rs scheme",
+  "type": "object",
+  "required": ["name", "entries"],
+  "additionalProperties": false,
+  "properties": {
+    "name": { "type": "string" },
+    "author": { "type": "string" },
+    "entries": {
+      "type": "array",
+      "items": {
+        "type": "object",
+        "required": ["color"],
+        "additionalProperties": false,
+        "properties": {
+          "name": { "type": "string" },
+          "color": {
+            "oneOf": [
+              {
+                "description": "#rrggbb, or #rrggbbaa with alpha",
+                "type": "string",
+                "pattern": "^#[0-9a-fA-F]{6}([0-9a-fA-F]{2})?$"
+              },
+              {
+                "description": "packed 0xRRGGBBAA, red in the most significant byte",
+                "type": "integer",
+                "minimum": 0,
+                "maximum": 4294967295
+              }
+            ]
+          },
+          "comment": { "type": "string" }
+        }
+      }
+    }
+  }
+}
+"##;
+
+fn invalid(at: &str, expected: &'static str) -> SchemeReaderError {
+    SchemeReaderError::InvalidJsonScheme {
+        at: at.into(),
+        expected,
+    }
+}
+
+// members other than `allowed` are an error, as the schema has it
+fn object<'v>(
+    value: &'v Value,
+    at: &str,
+    allowed: &[&str],
+) -> Result<&'v [(String, Value)], SchemeReaderError> {
+    let Value::Object(members) = value else {
+        return Err(invalid(at, "an object"));
+    };
+    match members
+        .iter()
+        .find(|(key, _)| !allowed.contains(&key.as_str()))
+    {
+        Some((key, _)) => Err(invalid(&format!("{}/{}", at, key), "no such member")),
+        None => Ok(members),
+    }
+}
+
+fn string(value: &Value, key: &str, at: &str) -> Result<Option<String>, SchemeReaderError> {
+    match value.get(key) {
+        None => Ok(None),
+        Some(Value::String(s)) => Ok(Some(s.clone())),
+        Some(_) => Err(invalid(&format!("{}/{}", at, key), "a string")),
+    }
+}
+
+fn color(value: &Value, at: &str) -> Result<Canonical, SchemeReaderError> {
+    let expected = "a #rrggbb or #rrggbbaa string or a packed 0xRRGGBBAA integer";
+    match value {
+        Value::String(s) => HexFormat::parse(s).map_err(|_| invalid(at, expected)),
+        &Value::Number(n) if n.fract() == 0.0 && (0.0..=u32::MAX as f64).contains(&n) => {
+            Ok(Packed(n as u32).to_canonical())
+        }
+        _ => Err(invalid(at, expected)),
+    }
+}
+
+pub fn parse(path: &str) -> Result<Scheme, SchemeReaderError> {
+    let path = Path::new(path);
+    let text = fs::read_to_string(path).map_err(|e| SchemeReaderError::io(e, Some(path)))?;
+    parse_str(&text)
+}
+
+// what json_schema accepts and nothing else
+pub fn parse_str(s: &str) -> Result<Scheme, SchemeReaderError> {
+    let root = json::parse(s).map_err(|e| SchemeReaderError::InvalidJson {
+        line: e.line,
+        column: e.column,
+        message: e.message,
+    })?;
+    from_value(&root)
+}
+
+fn from_value(root: &Value) -> Result<Scheme, SchemeReaderError> {
+    object(root, "", &["name", "author", "entries"])?;
+    let name = string(root, "name", "")?.ok_or_else(|| invalid("/name", "a string"))?;
+    let author = string(root, "author", "")?;
+    let Some(Value::Array(items)) = root.get("entries") else {
+        return Err(invalid("/entries", "an array"));
+    };
+
+    let mut entries = Vec::with_capacity(items.len());
+    for (i, item) in items.iter().enumerate() {
+        let at = format!("/entries/{}", i);
+        object(item, &at, &["name", "color", "comment"])?;
+        let color_at = format!("{}/color", at);
+        let color = item
+            .get("color")
+            .ok_or_else(|| invalid(&color_at, "a color"))
+            .and_then(|value| color(value, &color_at))?;
+        let mut entry = match string(item, "name", &at)? {
+            Some(name) => Entry::named(&name, color),
+            None => Entry::new(color),
+        };
+        entry.comment = string(item, "comment", &at)?;
+        entries.push(entry);
+    }
+    Ok(Scheme::from_entries(name, author, entries))
+}
+
+impl Scheme {
+    // colors as hex strings, members left out rather than null
+    pub fn to_json(&self) -> String {
+        json::to_pretty_string(&self.json_value())
+    }
+
+    fn json_value(&self) -> Value {
+        let string = |s: &str| Value::String(s.into());
+        let entries = self
+            .entries
+            .iter()
+            .map(|entry| {
+                let mut members = Vec::new();
+                if let Some(name) = &entry.name {
+                    members.push(("name".to_string(), string(name)));
+                }
+                members.push(("color".to_string(), string(&hex(&entry.color))));
+                if let Some(comment) = &entry.comment {
+                    members.push(("comment".to_string(), string(comment)));
+                }
+                Value::Object(members)
+            })
+            .collect();
+        let mut root = vec![("name".to_string(), string(&self.name))];
+        if let Some(author) = &self.author {
+            root.push(("author".to_string(), string(author)));
+        }
+        root.push(("entries".to_string(), Value::Array(entries)));
+        Value::Object(root)
+    }
+
+    // a JSON Schema, draft 2020-12, of what to_json writes and parse_str reads
+    pub fn json_schema() -> String {
+        JSON_SCHEMA.into()
+    }
+}
+
+// the document to_json writes, and what parse_str reads, in any serde format
+#[cfg(feature = "serde")]
+impl serde::Serialize for Scheme {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serde::Serialize::serialize(&self.json_value(), serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Scheme {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Scheme, D::Error> {
+        let value = <Value as serde::Deserialize>::deserialize(deserializer)?;
+        from_value(&value).map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use regex::Regex;
+
+    use super::*;
+    use crate::errors::ErrorKind;
+
+    // the keywords validate checks, and the ones that only annotate
+    const KEYWORDS: [&str; 9] = [
+        "type",
+        "required",
+        "properties",
+        "additionalProperties",
+        "items",
+        "oneOf",
+        "pattern",
+        "minimum",
+        "maximum",
+    ];
+    const ANNOTATIONS: [&str; 4] = ["$schema", "$id", "title", "description"];
+
+    // the keywords JSON_SCHEMA uses, enough to hold documents against it. a
+    // schema with any other keyword or type rejects everything, so one added
+    // to JSON_SCHEMA fails the tests instead of being ignored
+    fn validate(schema: &Value, value: &Value) -> bool {
+        if let Value::Object(members) = schema {
+            let known = |key: &str| KEYWORDS.contains(&key) || ANNOTATIONS.contains(&key);
+            if !members.iter().all(|(key, _)| known(key)) {
+                return false;
+            }
+        }
+        let keyword = |key| schema.get(key);
+        let type_ok = match keyword("type").and_then(Value::as_str) {
+            None => true,
+            Some("object") => matches!(value, Value::Object(_)),
+            Some("array") => matches!(value, Value::Array(_)),
+            Some("string") => matches!(value, Value::String(_)),
+            Some("integer") => matches!(value, Value::Number(n) if n.fract() == 0.0),
+            Some(_) => false,
+        };
+        if !type_ok {
+            return false;
+        }
+        if let Some(Value::Array(required)) = keyword("required") {
+            let missing = required.iter().filter_map(Value::as_str);
+            if missing.into_iter().any(|key| value.get(key).is_none()) {
+                return false;
+            }
+        }
+        if let (Some(properties), Value::Object(members)) = (keyword("properties"), value) {
+            for (key, member) in members {
+                match properties.get(key) {
+                    Some(property) if !validate(property, member) => return false,
+                    None if keyword("additionalProperties") == Some(&Value::Bool(false)) => {
+                        return false
+                    }
+                    _ => {}
+                }
+            }
+        }
+        if let (Some(items), Value::Array(values)) = (keyword("items"), value) {
+            if !values.iter().all(|item| validate(items, item)) {
+                return false;
+            }
+        }
+        if let Some(Value::Array(options)) = keyword("oneOf") {
+            if options
+                .iter()
+                .filter(|option| validate(option, value))
+                .count()
+                != 1
+            {
+                return false;
+            }
+        }
+        if let (Some(pattern), Value::String(s)) = (keyword("pattern"), value) {
+            if !Regex::new(pattern.as_str().unwrap()).unwrap().is_match(s) {
+                return false;
+            }
+        }
+        if let Value::Number(n) = value {
+            let minimum = keyword("minimum").and_then(Value::as_f64);
+            let maximum = keyword("maximum").and_then(Value::as_f64);
+            if minimum.is_some_and(|m| *n < m) || maximum.is_some_and(|m| *n > m) {
+                return false;
+            }
+        }
+        true
+    }
+
+    fn schema() -> Value {
+        json::parse(&Scheme::json_schema()).unwrap()
+    }
+
+    fn gruvbox() -> Scheme {
+        let entries = vec![
+            Entry::named("bg", Canonical::new(0x28, 0x28, 0x28, 255)).with_comment("the darkest"),
+            Entry::new(Canonical::new(0xcc, 0x24, 0x1d, 128)),
+        ];
+        Scheme::from_entries("gruvbox".into(), Some("morhetz".into()), entries)
+    }
+
+    #[test]
+    fn test_roundtrip_validates() {
+        let schema = schema();
+        let mut schemes = vec![gruvbox(), Scheme::new("empty")];
+        #[cfg(feature = "builtin-schemes")]
+        schemes.extend(crate::schemes::all());
+        for scheme in schemes {
+            let text = scheme.to_json();
+            let back = parse_str(&text).unwrap();
+            assert_eq!(back, scheme);
+            assert!(
+                validate(&schema, &json::parse(&back.to_json()).unwrap()),
+                "{}",
+                text
+            );
+        }
+        assert_eq!(
+            gruvbox().to_json(),
+            r##"{
+  "name": "gruvbox",
+  "author": "morhetz",
+  "entries": [
+    {
+      "name": "bg",
+      "color": "#282828",
+      "comment": "the darkest"
+    },
+    {
+      "color": "#cc241d80"
+    }
+  ]
+}
+"##
+        );
+    }
+
+    // parse_str takes a document exactly when the schema does
+    #[test]
+    fn test_schema_agrees_with_parse_str() {
+        let schema = schema();
+        let entries = |entries: &str| format!(r#"{{"name": "x", "entries": [{}]}}"#, entries);
+        let documents = [
+            entries(r##"{"color": "#ff8000"}"##),
+            entries(r##"{"color": "#FF800080", "name": "a", "comment": "b"}"##),
+            entries(r#"{"color": 4286578943}"#),
+            entries(r#"{"color": 0}, {"color": 4294967295}"#),
+            entries(""),
+            // colors
+            entries(r##"{"color": "#fff"}"##),
+            entries(r##"{"color": "ff8000"}"##),
+            entries(r##"{"color": "rgb(255, 128, 0)"}"##),
+            entries(r#"{"color": 4294967296}"#),
+            entries(r#"{"color": -1}"#),
+            entries(r#"{"color": 1.5}"#),
+            entries(r#"{"color": null}"#),
+            entries(r#"{"name": "a"}"#),
+            // members
+            entries(r##"{"color": "#ff8000", "role": "background"}"##),
+            entries(r##"{"color": "#ff8000", "name": 1}"##),
+            entries(r###""#ff8000""###),
+            r#"{"name": "x"}"#.into(),
+            r#"{"entries": []}"#.into(),
+            r#"{"name": "x", "entries": {}}"#.into(),
+            r#"{"name": "x", "author": null, "entries": []}"#.into(),
+            r#"{"name": "x", "entries": [], "version": 1}"#.into(),
+            r#"[]"#.into(),
+        ];
+        for document in documents {
+            let value = json::parse(&document).unwrap();
+            assert_eq!(
+                validate(&schema, &value),
+                parse_str(&document).is_ok(),
+                "{}",
+                document
+            );
+            #[cfg(feature = "serde")]
+            assert_eq!(
+                serde::Deserialize::deserialize(value).ok(),
+                parse_str(&document).ok(),
+                "{}",
+                document
+            );
+        }
+    }
+
+    // serde reports what parse_str would, at the same place
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_deserialize() {
+        use serde::Deserialize;
+
+        let value = json::parse(&gruvbox().to_json()).unwrap();
+        assert_eq!(Scheme::deserialize(value).unwrap(), gruvbox());
+
+        let document = r##"{"name": "x", "entries": [{"color": "#fff"}]}"##;
+        let error = Scheme::deserialize(json::parse(document).unwrap()).unwrap_err();
+        assert_eq!(error.to_string(), parse_str(document).unwrap_err().to_string());
+        assert!(error.to_string().contains("/entries/0/color"), "{}", error);
+    }
+
+    // every subschema of JSON_SCHEMA, the root included
+    fn subschemas<'a>(schema: &'a Value, out: &mut Vec<&'a Value>) {
+        out.push(schema);
+        let nested = |key| match schema.get(key) {
+            Some(Value::Object(members)) if key == "properties" => {
+                members.iter().map(|(_, member)| member).collect()
+            }
+            Some(Value::Array(options)) => options.iter().collect(),
+            Some(items) if key == "items" => vec![items],
+            _ => vec![],
+        };
+        for key in ["properties", "items", "oneOf"] {
+            for subschema in nested(key) {
+                subschemas(subschema, out);
+            }
+        }
+    }
+
+    #[test]
+    fn test_validate_knows_the_schema() {
+        let schema = schema();
+        let mut all = Vec::new();
+        subschemas(&schema, &mut all);
+        assert_eq!(all.len(), 10);
+        for subschema in all {
+            let Value::Object(members) = subschema else {
+                panic!("{:?} is not a schema", subschema)
+            };
+            for (key, value) in members {
+                let known = KEYWORDS.contains(&key.as_str()) || ANNOTATIONS.contains(&key.as_str());
+                assert!(known, "validate doesn't implement {}", key);
+                if key == "type" {
+                    let types = ["object", "array", "string", "integer"];
+                    assert!(types.contains(&value.as_str().unwrap()), "{:?}", value);
+                }
+            }
+        }
+
+        // and rejects what it doesn't know
+        let value = Value::String("abc".into());
+        let string = json::parse(r#"{"type": "string"}"#).unwrap();
+        assert!(validate(&string, &value));
+        let max_length = json::parse(r#"{"type": "string", "maxLength": 5}"#).unwrap();
+        assert!(!validate(&max_length, &value));
+        let number = json::parse(r#"{"type": "number"}"#).unwrap();
+        assert!(!validate(&number, &Value::Number(1.0)));
+    }
+
+    #[test]
+    fn test_parse_str() {
+        let scheme = parse_str(r#"{"name": "x", "entries": [{"color": 4286578943}]}"#).unwrap();
+        assert_eq!(
+            scheme.colors().next(),
+            Some(&Canonical::new(255, 128, 0, 255))
+        );
+
+        let error = parse_str(r##"{"name": "x", "entries": [{}, {"color": "#zz"}]}"##);
+        let error = error.unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::InvalidJsonScheme);
+        assert_eq!(
+            error.to_string(),
+            "Invalid JSON scheme at /entries/0/color: expected a color"
+        );
+        let error = parse_str(r#"{"name": "x", "entries": [], "version": 1}"#).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Invalid JSON scheme at /version: expected no such member"
+        );
+        assert_eq!(parse_str("{").unwrap_err().kind(), ErrorKind::InvalidJson);
+    }
+}
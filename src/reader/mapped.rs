@@ -0,0 +1,239 @@
+// parse and parse_with over a memory mapped file, parsing the map in place
+// instead of copying every line into a buffer. files that can't
+// be mapped, like pipes, are read the buffered way.
+//
+// a map is only sound while nothing truncates the file. reading a page past
+// the end of a file that shrank after it was mapped raises SIGBUS, which
+// kills the process, and writes to the file during reading can show up in
+// the middle of a line. only map files nothing else changes while they are
+// being read
+use std::fs::File;
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+
+use super::{read_slice, ReaderOptions};
+use crate::color::{Scheme, SchemeRef};
+use crate::errors::SchemeReaderError;
+
+pub fn parse_mmap(path: &str) -> Result<Scheme, SchemeReaderError> {
+    parse_mmap_with(path, &ReaderOptions::default())
+}
+
+pub fn parse_mmap_with(path: &str, options: &ReaderOptions) -> Result<Scheme, SchemeReaderError> {
+    read_file(Path::new(path), &[], options)
+}
+
+// includes are mapped as well
+fn read_file(
+    path: &Path,
+    chain: &[PathBuf],
+    options: &ReaderOptions,
+) -> Result<Scheme, SchemeReaderError> {
+    let io_error = |e| SchemeReaderError::io(e, Some(path));
+    debug_event!(path:% = path.display(), format = "native"; "reading {}", path.display());
+    let file = File::open(path).map_err(io_error)?;
+    let mut chain = chain.to_vec();
+    chain.push(path.canonicalize().map_err(io_error)?);
+    let metadata = file.metadata().map_err(io_error)?;
+    let scheme = match map::Mmap::new(&file, &metadata).map_err(io_error)? {
+        Some(map) => {
+            read_slice(&map, Some(path), &chain, options, read_file).map(SchemeRef::into_owned)
+        }
+        None => super::read(BufReader::new(file), Some(path), &chain, options),
+    }?;
+    debug_event!(
+        path:% = path.display(), format = "native", colors = scheme.len();
+        "read {}: {} colors", path.display(), scheme.len()
+    );
+    Ok(scheme)
+}
+
+#[cfg(unix)]
+mod map {
+    use std::fs::{File, Metadata};
+    use std::io;
+    use std::ops::Deref;
+    use std::os::unix::io::AsRawFd;
+    use std::{ptr, slice};
+
+    // a private read only mapping of a whole regular file
+    pub struct Mmap {
+        ptr: *mut libc::c_void,
+        len: usize,
+    }
+
+    impl Mmap {
+        // None for anything but a regular file, and for empty files which
+        // can't be mapped
+        pub fn new(file: &File, metadata: &Metadata) -> io::Result<Option<Mmap>> {
+            if !metadata.is_file() || metadata.len() == 0 {
+                return Ok(None);
+            }
+            let Ok(len) = usize::try_from(metadata.len()) else {
+                return Ok(None);
+            };
+            // SAFETY: maps `len` bytes of an open file read only, the result
+            // is checked before it is used
+            let ptr = unsafe {
+                libc::mmap(
+                    ptr::null_mut(),
+                    len,
+                    libc::PROT_READ,
+                    libc::MAP_PRIVATE,
+                    file.as_raw_fd(),
+                    0,
+                )
+            };
+            if ptr == libc::MAP_FAILED {
+                return Err(io::Error::last_os_error());
+            }
+            Ok(Some(Mmap { ptr, len }))
+        }
+    }
+
+    impl Deref for Mmap {
+        type Target = [u8];
+
+        fn deref(&self) -> &[u8] {
+            // SAFETY: the mapping is `len` readable bytes until it is dropped,
+            // as long as the file isn't truncated meanwhile, see above
+            unsafe { slice::from_raw_parts(self.ptr as *const u8, self.len) }
+        }
+    }
+
+    impl Drop for Mmap {
+        fn drop(&mut self) {
+            // SAFETY: unmaps exactly what new mapped, nothing borrows it anymore
+            unsafe {
+                libc::munmap(self.ptr, self.len);
+            }
+        }
+    }
+}
+
+// without mmap every file is read the buffered way
+#[cfg(not(unix))]
+mod map {
+    use std::fs::{File, Metadata};
+    use std::io;
+    use std::ops::Deref;
+
+    pub enum Mmap {}
+
+    impl Mmap {
+        pub fn new(_file: &File, _metadata: &Metadata) -> io::Result<Option<Mmap>> {
+            Ok(None)
+        }
+    }
+
+    impl Deref for Mmap {
+        type Target = [u8];
+
+        fn deref(&self) -> &[u8] {
+            match *self {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+    use crate::reader::{parse_with, Limits, NameSource};
+    use crate::temp::TempDir;
+
+    // parse_mmap_with has to give the same scheme or error as parse_with
+    #[test]
+    fn test_same_as_parse() {
+        let dir = TempDir::new("mapped_same_as_parse");
+        dir.write("base.txt", "base\nblack: #000000\n");
+
+        let limited = ReaderOptions {
+            limits: Limits {
+                max_line_len: 16,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let by_filename = ReaderOptions {
+            name_source: NameSource::Filename,
+            ..Default::default()
+        };
+        let cases: [(&str, &[u8], ReaderOptions); 11] = [
+            (
+                "plain",
+                b"mono\nbg: #000000\nfg: rgb(255, 255, 255)\n",
+                Default::default(),
+            ),
+            ("no_newline", b"mono\nbg: #000000", Default::default()),
+            (
+                "crlf_bom",
+                b"\xef\xbb\xbfmono\r\nbg: #000000\r\n",
+                Default::default(),
+            ),
+            ("empty", b"", Default::default()),
+            ("bad_color", b"mono\nbg: #zz0000\n", Default::default()),
+            (
+                "lenient",
+                b"mono\nbg: #zz0000\nfg: #ffffff\n",
+                ReaderOptions::lenient(),
+            ),
+            ("bad_utf8", b"mono\nbg: #000000 \xff\n", Default::default()),
+            ("long_line", b"mono\nbackground: #000000\n", limited.clone()),
+            ("fits", b"mono\nbg: #000000\n", limited),
+            (
+                "include",
+                b"mono\n@include \"base.txt\"\nfg: @black\n",
+                Default::default(),
+            ),
+            ("by_filename", b"bg: #000000\n", by_filename),
+        ];
+        for (name, contents, options) in cases {
+            let path = dir.join(format!("{}.txt", name));
+            fs::write(&path, contents).unwrap();
+            let path = path.to_str().unwrap();
+            match (parse_with(path, &options), parse_mmap_with(path, &options)) {
+                (Ok(buffered), Ok(mapped)) => assert_eq!(buffered, mapped, "{}", name),
+                (Err(buffered), Err(mapped)) => {
+                    assert_eq!(buffered.kind(), mapped.kind(), "{}", name);
+                    assert_eq!(buffered.to_string(), mapped.to_string(), "{}", name);
+                }
+                (buffered, mapped) => panic!("{}: {:?} but {:?}", name, buffered, mapped),
+            }
+        }
+
+        let missing = dir.join("missing.txt");
+        let missing = missing.to_str().unwrap();
+        assert_eq!(
+            parse_mmap(missing).unwrap_err().to_string(),
+            crate::reader::parse(missing).unwrap_err().to_string()
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_pipe() {
+        use std::ffi::CString;
+        use std::io::Write;
+        use std::thread;
+
+        let dir = TempDir::new("mapped_pipe");
+        let path = dir.join("cool_rs_mapped_fifo");
+        let c_path = CString::new(path.to_str().unwrap()).unwrap();
+        // SAFETY: a valid nul terminated path
+        assert_eq!(unsafe { libc::mkfifo(c_path.as_ptr(), 0o600) }, 0);
+        let writer_path = path.clone();
+        let writer = thread::spawn(move || {
+            let mut fifo = fs::OpenOptions::new()
+                .write(true)
+                .open(writer_path)
+                .unwrap();
+            fifo.write_all(b"mono\nbg: #000000\n").unwrap();
+        });
+        let scheme = parse_mmap(path.to_str().unwrap()).unwrap();
+        writer.join().unwrap();
+        assert_eq!(scheme.name, "mono");
+        assert_eq!(scheme.len(), 1);
+    }
+}
@@ -0,0 +1,155 @@
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+
+use tokio::fs::{self, File};
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncReadExt, BufReader};
+
+use super::{LineParser, ReaderOptions};
+use crate::color::Scheme;
+use crate::errors::SchemeReaderError;
+
+// async counterparts of parse, parse_with and parse_from_reader, driving the
+// same LineParser
+
+pub async fn parse_async(path: &str) -> Result<Scheme, SchemeReaderError> {
+    parse_async_with(path, &ReaderOptions::default()).await
+}
+
+pub async fn parse_async_with(
+    path: &str,
+    options: &ReaderOptions,
+) -> Result<Scheme, SchemeReaderError> {
+    read_file(Path::new(path), &[], options).await
+}
+
+pub async fn parse_from_async_reader<R: AsyncBufRead + Unpin>(
+    reader: R,
+    options: &ReaderOptions,
+) -> Result<Scheme, SchemeReaderError> {
+    read(reader, None, &[], options).await
+}
+
+type SchemeFuture<'a> =
+    Pin<Box<dyn Future<Output = Result<Scheme, SchemeReaderError>> + Send + 'a>>;
+
+// boxed since includes make reading recursive
+fn read_file<'a>(
+    path: &'a Path,
+    chain: &'a [PathBuf],
+    options: &'a ReaderOptions,
+) -> SchemeFuture<'a> {
+    Box::pin(async move {
+        let io_error = |e| SchemeReaderError::io(e, Some(path));
+        let file = File::open(path).await.map_err(io_error)?;
+        let mut chain = chain.to_vec();
+        chain.push(fs::canonicalize(path).await.map_err(io_error)?);
+        read(BufReader::new(file), Some(path), &chain, options).await
+    })
+}
+
+async fn read<R: AsyncBufRead + Unpin>(
+    mut reader: R,
+    path: Option<&Path>,
+    chain: &[PathBuf],
+    options: &ReaderOptions,
+) -> Result<Scheme, SchemeReaderError> {
+    let mut parser = LineParser::new(path, chain, options)?;
+    let mut buf = Vec::new();
+    loop {
+        buf.clear();
+        let limit = parser.read_limit();
+        if (&mut reader)
+            .take(limit)
+            .read_until(b'\n', &mut buf)
+            .await
+            .map_err(|e| SchemeReaderError::io(e, path))?
+            == 0
+        {
+            break;
+        }
+        if let Some(include) = parser.feed(&buf)? {
+            let canonical = fs::canonicalize(&include.path)
+                .await
+                .map_err(|e| include.io_error(e))?;
+            parser.check_include(&canonical)?;
+            let included = read_file(&canonical, chain, options).await?;
+            parser.merge(included, include.line)?;
+        }
+    }
+    parser.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::{Canonical, Entry};
+    use crate::errors::ErrorKind;
+    use crate::reader::{parse_from_reader, NameSource};
+    use crate::temp::TempDir;
+
+    #[tokio::test]
+    async fn test_parse_from_async_reader() {
+        let input =
+            "\u{feff}mono\r\nbackground: #000000\r\n@author: someone\r\ncursor: @background\r\n";
+        let options = ReaderOptions::default();
+        let scheme = parse_from_async_reader(BufReader::new(input.as_bytes()), &options)
+            .await
+            .unwrap();
+
+        assert_eq!(scheme.name, "mono");
+        assert_eq!(scheme.author.as_deref(), Some("someone"));
+        assert_eq!(
+            scheme.entries,
+            vec![
+                Entry::named("background", Canonical::new(0, 0, 0, 255)),
+                Entry::named("cursor", Canonical::new(0, 0, 0, 255)),
+            ]
+        );
+        assert_eq!(
+            parse_from_reader(input.as_bytes(), &options).unwrap(),
+            scheme
+        );
+    }
+
+    #[tokio::test]
+    async fn test_async_errors() {
+        let options = ReaderOptions::default();
+        assert_eq!(
+            parse_from_async_reader(BufReader::new(&b""[..]), &options)
+                .await
+                .unwrap_err()
+                .kind(),
+            ErrorKind::NoLines
+        );
+        assert!(matches!(
+            parse_from_async_reader(BufReader::new(&b"mono\n#zz0000\n"[..]), &options).await,
+            Err(SchemeReaderError::ParseColorError { line: 2, .. })
+        ));
+
+        let options = ReaderOptions {
+            name_source: NameSource::Filename,
+            ..Default::default()
+        };
+        assert_eq!(
+            parse_from_async_reader(BufReader::new(&b"#000000\n"[..]), &options)
+                .await
+                .unwrap_err()
+                .kind(),
+            ErrorKind::FilenameWithoutPath
+        );
+    }
+
+    #[tokio::test]
+    async fn test_parse_async_include() {
+        let dir = TempDir::new("async_include");
+        dir.write("base.txt", "base\nred: #ff0000\n");
+        let path = dir.write("theme.txt", "theme\n@include base.txt\naccent: @red\n");
+        let scheme = parse_async(&path).await.unwrap();
+        assert_eq!(
+            scheme,
+            crate::reader::parse(&path).unwrap()
+        );
+        assert_eq!(scheme.get("accent"), Some(&Canonical::new(255, 0, 0, 255)));
+    }
+}
@@ -0,0 +1,329 @@
+// the palette of an indexed PNG, its PLTE chunk and the alphas in tRNS, read
+// and written without decoding the image. parse reads it into a scheme named
+// after the file, write_palette puts a scheme's colors in its place, which
+// recolors an indexed sprite sheet without touching a pixel. every chunk's
+// CRC is checked on the way in, the chunks written get new ones
+use std::fs;
+use std::path::Path;
+
+use crate::color::{Canonical, Scheme};
+use crate::errors::SchemeReaderError;
+
+const SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+
+// the IHDR color type of images whose pixels are palette indices
+const INDEXED: u8 = 3;
+
+// chunks that come after PLTE, where one goes that wasn't there
+const AFTER_PLTE: [&[u8; 4]; 4] = [b"tRNS", b"bKGD", b"hIST", b"IDAT"];
+
+const CRC_TABLE: [u32; 256] = crc_table();
+
+const fn crc_table() -> [u32; 256] {
+    let mut table = [0; 256];
+    let mut n = 0;
+    while n < 256 {
+        let mut c = n as u32;
+        let mut k = 0;
+        while k < 8 {
+            c = if c & 1 == 1 {
+                0xedb8_8320 ^ (c >> 1)
+            } else {
+                c >> 1
+            };
+            k += 1;
+        }
+        table[n] = c;
+        n += 1;
+    }
+    table
+}
+
+// the CRC-32 of ISO 3309 over the chunk type and data, as PNG has it
+fn crc(kind: &[u8; 4], data: &[u8]) -> u32 {
+    let c = kind.iter().chain(data).fold(u32::MAX, |c, &byte| {
+        CRC_TABLE[((c ^ byte as u32) & 0xff) as usize] ^ (c >> 8)
+    });
+    c ^ u32::MAX
+}
+
+struct Chunk<'a> {
+    kind: [u8; 4],
+    data: &'a [u8],
+}
+
+impl Chunk<'_> {
+    fn write_to(&self, out: &mut Vec<u8>) {
+        write_chunk(out, &self.kind, self.data);
+    }
+}
+
+fn write_chunk(out: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    out.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    out.extend_from_slice(kind);
+    out.extend_from_slice(data);
+    out.extend_from_slice(&crc(kind, data).to_be_bytes());
+}
+
+fn malformed(reason: &'static str) -> SchemeReaderError {
+    SchemeReaderError::MalformedPalette {
+        format: "PNG",
+        reason,
+    }
+}
+
+// every chunk up to IEND, IHDR first
+fn chunks(bytes: &[u8]) -> Result<Vec<Chunk<'_>>, SchemeReaderError> {
+    if !bytes.starts_with(SIGNATURE) {
+        return Err(malformed("not a PNG"));
+    }
+    let mut chunks = Vec::new();
+    let mut offset = SIGNATURE.len();
+    loop {
+        let truncated = || SchemeReaderError::TruncatedPng { offset };
+        let header = bytes.get(offset..offset + 8).ok_or_else(truncated)?;
+        let len = u32::from_be_bytes([header[0], header[1], header[2], header[3]]) as usize;
+        let kind = [header[4], header[5], header[6], header[7]];
+        let end = (offset + 8).saturating_add(len);
+        let data = bytes.get(offset + 8..end).ok_or_else(truncated)?;
+        let stored = bytes.get(end..end + 4).ok_or_else(truncated)?;
+        let stored = u32::from_be_bytes([stored[0], stored[1], stored[2], stored[3]]);
+        let computed = crc(&kind, data);
+        if stored != computed {
+            return Err(SchemeReaderError::PngChecksum {
+                chunk: String::from_utf8_lossy(&kind).into_owned(),
+                stored,
+                computed,
+            });
+        }
+        chunks.push(Chunk { kind, data });
+        offset = end + 4;
+        if &kind == b"IEND" {
+            break;
+        }
+    }
+    match chunks.first() {
+        Some(ihdr) if &ihdr.kind == b"IHDR" && ihdr.data.len() == 13 => Ok(chunks),
+        _ => Err(malformed("no IHDR chunk first")),
+    }
+}
+
+fn find<'c, 'a>(chunks: &'c [Chunk<'a>], kind: &[u8; 4]) -> Option<&'c Chunk<'a>> {
+    chunks.iter().find(|chunk| &chunk.kind == kind)
+}
+
+// the palette with the alphas of tRNS, which only indexed images have. a
+// truecolor image's PLTE, a suggestion for displays with few colors, is read
+// as well, opaque
+pub fn plte_colors(bytes: &[u8]) -> Result<Vec<Canonical>, SchemeReaderError> {
+    let chunks = chunks(bytes)?;
+    let indexed = chunks[0].data[9] == INDEXED;
+    let plte = find(&chunks, b"PLTE").ok_or_else(|| malformed("no PLTE chunk"))?;
+    if plte.data.is_empty() || plte.data.len() % 3 != 0 || plte.data.len() > 256 * 3 {
+        return Err(malformed("PLTE is not 1 to 256 rgb triplets"));
+    }
+    let alphas = match find(&chunks, b"tRNS") {
+        Some(trns) if indexed => trns.data,
+        _ => &[],
+    };
+    Ok(plte
+        .data
+        .chunks_exact(3)
+        .enumerate()
+        .map(|(i, rgb)| {
+            let alpha = alphas.get(i).copied().unwrap_or(u8::MAX);
+            Canonical::new(rgb[0], rgb[1], rgb[2], alpha)
+        })
+        .collect())
+}
+
+pub fn parse(path: &str) -> Result<Scheme, SchemeReaderError> {
+    let path = Path::new(path);
+    let bytes = fs::read(path).map_err(|e| SchemeReaderError::io(e, Some(path)))?;
+    let name = path.file_stem().unwrap_or_default().to_string_lossy();
+    Ok(Scheme::from_colors(&name, plte_colors(&bytes)?))
+}
+
+// the png with the scheme's colors as its palette, and a tRNS chunk for
+// their alphas if any is translucent. every other chunk is copied as is.
+// the image keeps its indices, a palette shorter than the largest index
+// makes an image that decoders reject
+pub fn with_palette(bytes: &[u8], scheme: &Scheme) -> Result<Vec<u8>, SchemeReaderError> {
+    let chunks = chunks(bytes)?;
+    let ihdr = chunks[0].data;
+    if ihdr[9] != INDEXED {
+        return Err(malformed("not an indexed image"));
+    }
+    let bit_depth = ihdr[8].min(8);
+    if scheme.is_empty() || scheme.len() > 1 << bit_depth {
+        return Err(malformed("more colors than the bit depth allows, or none"));
+    }
+
+    let plte: Vec<u8> = scheme.colors().flat_map(|c| [c.r, c.g, c.b]).collect();
+    let mut trns: Vec<u8> = scheme.colors().map(|c| c.a).collect();
+    while trns.last() == Some(&u8::MAX) {
+        trns.pop();
+    }
+    // where the old PLTE was, or before the first chunk that comes after it
+    let at = chunks
+        .iter()
+        .position(|chunk| &chunk.kind == b"PLTE")
+        .or_else(|| {
+            chunks
+                .iter()
+                .position(|chunk| AFTER_PLTE.contains(&&chunk.kind))
+        })
+        .ok_or_else(|| malformed("no IDAT chunk"))?;
+
+    let mut out = SIGNATURE.to_vec();
+    for (i, chunk) in chunks.iter().enumerate() {
+        if i == at {
+            write_chunk(&mut out, b"PLTE", &plte);
+            if !trns.is_empty() {
+                write_chunk(&mut out, b"tRNS", &trns);
+            }
+        }
+        if &chunk.kind != b"PLTE" && &chunk.kind != b"tRNS" {
+            chunk.write_to(&mut out);
+        }
+    }
+    Ok(out)
+}
+
+// with_palette on the file at path, in place
+pub fn write_palette(path: &str, scheme: &Scheme) -> Result<(), SchemeReaderError> {
+    let path = Path::new(path);
+    let io_error = |e| SchemeReaderError::io(e, Some(path));
+    let bytes = fs::read(path).map_err(io_error)?;
+    fs::write(path, with_palette(&bytes, scheme)?).map_err(io_error)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::errors::ErrorKind;
+    use crate::temp::TempDir;
+
+    // a 2x1 image at bit depth 2, pixels 0 and 1, its IDAT a stored zlib
+    // block so it decodes too
+    fn indexed_png(plte: &[u8], trns: Option<&[u8]>) -> Vec<u8> {
+        let mut out = SIGNATURE.to_vec();
+        write_chunk(
+            &mut out,
+            b"IHDR",
+            &[0, 0, 0, 2, 0, 0, 0, 1, 2, INDEXED, 0, 0, 0],
+        );
+        write_chunk(&mut out, b"gAMA", &45455u32.to_be_bytes());
+        write_chunk(&mut out, b"PLTE", plte);
+        if let Some(trns) = trns {
+            write_chunk(&mut out, b"tRNS", trns);
+        }
+        let raw = [0u8, 0b0001_0000];
+        let (a, b) = raw.iter().fold((1u32, 0u32), |(a, b), &byte| {
+            let a = (a + byte as u32) % 65521;
+            (a, (b + a) % 65521)
+        });
+        let mut idat = vec![0x78, 0x01, 0x01, 2, 0, !2, !0];
+        idat.extend_from_slice(&raw);
+        idat.extend_from_slice(&(b << 16 | a).to_be_bytes());
+        write_chunk(&mut out, b"IDAT", &idat);
+        write_chunk(&mut out, b"IEND", &[]);
+        out
+    }
+
+    fn sprite() -> Vec<u8> {
+        indexed_png(&[255, 0, 0, 0, 255, 0, 0, 0, 255], Some(&[0, 128]))
+    }
+
+    #[test]
+    fn test_crc() {
+        // the IEND chunk of every PNG
+        assert_eq!(crc(b"IEND", &[]), 0xae42_6082);
+    }
+
+    #[test]
+    fn test_parse() {
+        let dir = TempDir::new("png_parse");
+        let path = dir.join("cool_rs_png_sprite.png");
+        fs::write(&path, sprite()).unwrap();
+        let scheme = parse(path.to_str().unwrap()).unwrap();
+        assert_eq!(scheme.name, "cool_rs_png_sprite");
+        assert_eq!(
+            scheme.colors().copied().collect::<Vec<_>>(),
+            [
+                Canonical::new(255, 0, 0, 0),
+                Canonical::new(0, 255, 0, 128),
+                Canonical::new(0, 0, 255, 255),
+            ]
+        );
+        let opaque = indexed_png(&[1, 2, 3], None);
+        assert_eq!(
+            plte_colors(&opaque).unwrap(),
+            [Canonical::new(1, 2, 3, 255)]
+        );
+    }
+
+    #[test]
+    fn test_write_palette() {
+        let dir = TempDir::new("png_write_palette");
+        let path = dir.join("cool_rs_png_recolor.png");
+        fs::write(&path, sprite()).unwrap();
+        let path = path.to_str().unwrap();
+        let colors = vec![
+            Canonical::new(10, 20, 30, 255),
+            Canonical::new(40, 50, 60, 255),
+        ];
+        write_palette(path, &Scheme::from_colors("recolored", colors.clone())).unwrap();
+        let written = fs::read(path).unwrap();
+        // opaque colors need no tRNS, everything else is where it was
+        assert_eq!(written, indexed_png(&[10, 20, 30, 40, 50, 60], None));
+        assert_eq!(
+            parse(path).unwrap().colors().copied().collect::<Vec<_>>(),
+            colors
+        );
+
+        let translucent = Scheme::from_colors("t", vec![Canonical::new(1, 2, 3, 4)]);
+        assert_eq!(
+            with_palette(&written, &translucent).unwrap(),
+            indexed_png(&[1, 2, 3], Some(&[4]))
+        );
+
+        // bit depth 2 has room for 4 colors
+        let five = Scheme::from_colors("five", vec![Canonical::new(0, 0, 0, 255); 5]);
+        let e = with_palette(&written, &five).unwrap_err();
+        assert_eq!(e.kind(), ErrorKind::MalformedPalette);
+    }
+
+    #[test]
+    fn test_damaged() {
+        let png = sprite();
+        let e = plte_colors(&png[..4]).unwrap_err();
+        assert_eq!(e.kind(), ErrorKind::MalformedPalette);
+        // inside IHDR, inside IEND and without IEND
+        let iend = png.len() - 12;
+        for (len, at) in [(20, 8), (png.len() - 1, iend), (iend, iend)] {
+            match plte_colors(&png[..len]) {
+                Err(SchemeReaderError::TruncatedPng { offset }) => assert_eq!(offset, at),
+                other => panic!("{}: unexpected result {:?}", len, other),
+            }
+        }
+
+        // a byte of the PLTE data flipped, 8 signature, 25 IHDR, 16 gAMA and
+        // the PLTE length and type before it
+        let mut damaged = png.clone();
+        damaged[8 + 25 + 16 + 8] ^= 0xff;
+        match plte_colors(&damaged) {
+            Err(SchemeReaderError::PngChecksum {
+                chunk,
+                stored,
+                computed,
+            }) => {
+                assert_eq!(chunk, "PLTE");
+                assert_ne!(stored, computed);
+            }
+            other => panic!("unexpected result {:?}", other),
+        }
+        let e = with_palette(&damaged, &Scheme::new("x")).unwrap_err();
+        assert_eq!(e.kind(), ErrorKind::PngChecksum);
+    }
+}
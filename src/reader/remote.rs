@@ -0,0 +1,120 @@
+use std::io::BufReader;
+use std::time::Duration;
+
+use super::{parse_from_reader, ReaderOptions};
+use crate::color::Scheme;
+use crate::errors::SchemeReaderError;
+
+// for connecting and for reading the whole response
+pub const HTTP_TIMEOUT: Duration = Duration::from_secs(30);
+
+pub fn parse_url(url: &str) -> Result<Scheme, SchemeReaderError> {
+    parse_url_with(url, &ReaderOptions::default())
+}
+
+// the body is parsed as is whatever its content type, the reader limits
+// double as the response size cap. there is no file path, so includes and
+// NameSource::Filename fail as with parse_from_reader
+pub fn parse_url_with(url: &str, options: &ReaderOptions) -> Result<Scheme, SchemeReaderError> {
+    let agent = ureq::AgentBuilder::new()
+        .timeout_connect(HTTP_TIMEOUT)
+        .timeout(HTTP_TIMEOUT)
+        .build();
+    let response = agent.get(url).call().map_err(|e| match e {
+        ureq::Error::Status(status, _) => SchemeReaderError::HttpError {
+            url: url.into(),
+            status: Some(status),
+            message: format!("status {}", status),
+        },
+        ureq::Error::Transport(transport) => SchemeReaderError::HttpError {
+            url: url.into(),
+            status: None,
+            message: transport.to_string(),
+        },
+    })?;
+    parse_from_reader(BufReader::new(response.into_reader()), options)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+    use std::thread;
+
+    use super::*;
+    use crate::reader::{Limit, Limits};
+
+    // serves a single canned response and returns the url to fetch it from
+    fn serve(status: &str, body: &'static str) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/theme.txt", listener.local_addr().unwrap());
+        let status = status.to_string();
+        thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut request = [0; 1024];
+            let _ = stream.read(&mut request).unwrap();
+            write!(
+                stream,
+                "HTTP/1.1 {}\r\nContent-Type: application/octet-stream\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                status,
+                body.len(),
+                body
+            )
+            .unwrap();
+        });
+        url
+    }
+
+    #[test]
+    fn test_parse_url() {
+        let url = serve("200 OK", "remote\nbackground: #000000\n#ffffff\n");
+        let scheme = parse_url(&url).unwrap();
+        assert_eq!(scheme.name, "remote");
+        assert_eq!(scheme.len(), 2);
+    }
+
+    #[test]
+    fn test_http_status() {
+        let url = serve("404 Not Found", "gone");
+        assert!(matches!(
+            parse_url(&url),
+            Err(SchemeReaderError::HttpError {
+                status: Some(404),
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_size_cap() {
+        let url = serve("200 OK", "remote\n#000000\n#ffffff\n");
+        let options = ReaderOptions {
+            limits: Limits {
+                max_file_bytes: 10,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        assert!(matches!(
+            parse_url_with(&url, &options),
+            Err(SchemeReaderError::LimitExceeded {
+                limit: Limit::FileBytes,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_connection_refused() {
+        // bind and drop to get a port nothing listens on
+        let port = TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        assert!(matches!(
+            parse_url(&format!("http://127.0.0.1:{}/", port)),
+            Err(SchemeReaderError::HttpError { status: None, .. })
+        ));
+    }
+}
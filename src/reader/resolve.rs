@@ -0,0 +1,262 @@
+// finding the user's scheme, so every tool on this crate looks in the same
+// places. the default steps, in order:
+//
+// 1. $COOL_SCHEME, the path of a scheme file or the name of a builtin scheme
+// 2. cool_rs/scheme in the config directory, see config_dir
+// 3. the builtin gruvbox-dark
+//
+// a step that finds nothing passes on to the next, and if none finds
+// anything the error lists why each didn't. a file that is there but can't
+// be read or parsed ends the search with its error instead, falling back
+// would hide the mistake
+use std::env;
+use std::path::{Path, PathBuf};
+
+use super::{parse_with, ReaderOptions};
+use crate::color::Scheme;
+use crate::errors::SchemeReaderError;
+
+pub const SCHEME_VAR: &str = "COOL_SCHEME";
+
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub enum ResolveStep {
+    // a path or a builtin name in the variable, a file of that name wins
+    Env(String),
+    // relative to config_dir
+    ConfigFile(PathBuf),
+    File(PathBuf),
+    // case insensitive, never found without the builtin-schemes feature
+    Builtin(String),
+}
+
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct ResolveOptions {
+    pub steps: Vec<ResolveStep>,
+    // for the files found
+    pub reader: ReaderOptions,
+}
+
+impl Default for ResolveOptions {
+    fn default() -> Self {
+        ResolveOptions {
+            steps: vec![
+                ResolveStep::Env(SCHEME_VAR.into()),
+                ResolveStep::ConfigFile(Path::new("cool_rs").join("scheme")),
+                ResolveStep::Builtin("gruvbox-dark".into()),
+            ],
+            reader: ReaderOptions::default(),
+        }
+    }
+}
+
+// where the dirs crate puts config_dir, without depending on it. relative
+// paths in the variables are ignored
+//
+// - linux and the other unixes: $XDG_CONFIG_HOME, else ~/.config
+// - macos: ~/Library/Application Support
+// - windows: %APPDATA%. dirs asks the shell for the roaming app data folder,
+//   which is the same directory unless the variable was changed
+pub fn config_dir() -> Option<PathBuf> {
+    let absolute = |var| {
+        env::var_os(var)
+            .map(PathBuf::from)
+            .filter(|path| path.is_absolute())
+    };
+    if cfg!(windows) {
+        absolute("APPDATA")
+    } else if cfg!(target_os = "macos") {
+        absolute("HOME").map(|home| home.join("Library").join("Application Support"))
+    } else {
+        absolute("XDG_CONFIG_HOME").or_else(|| absolute("HOME").map(|home| home.join(".config")))
+    }
+}
+
+#[cfg(feature = "builtin-schemes")]
+fn builtin(name: &str) -> Option<Scheme> {
+    crate::schemes::by_name(name)
+}
+
+#[cfg(not(feature = "builtin-schemes"))]
+fn builtin(_: &str) -> Option<Scheme> {
+    None
+}
+
+// None if there is no file at path
+fn file(path: &Path, options: &ReaderOptions) -> Option<Result<Scheme, SchemeReaderError>> {
+    if !path.is_file() {
+        return None;
+    }
+    Some(match path.to_str() {
+        Some(path) => parse_with(path, options),
+        None => Err(SchemeReaderError::io(
+            std::io::Error::new(std::io::ErrorKind::InvalidInput, "path is not UTF-8"),
+            Some(path),
+        )),
+    })
+}
+
+type Found = Result<Result<Scheme, SchemeReaderError>, String>;
+
+fn step(step: &ResolveStep, options: &ReaderOptions) -> Found {
+    match step {
+        ResolveStep::Env(var) => {
+            let value = env::var(var).unwrap_or_default();
+            if value.is_empty() {
+                return Err(format!("${} is not set", var));
+            }
+            file(Path::new(&value), options)
+                .or_else(|| builtin(&value).map(Ok))
+                .ok_or_else(|| {
+                    format!("${} is {}, neither a file nor a builtin scheme", var, value)
+                })
+        }
+        ResolveStep::ConfigFile(relative) => {
+            let unset = if cfg!(windows) { "%APPDATA%" } else { "$HOME" };
+            let dir = config_dir()
+                .ok_or_else(|| format!("there is no config directory, {} is not set", unset))?;
+            let path = dir.join(relative);
+            file(&path, options).ok_or_else(|| format!("there is no {}", path.display()))
+        }
+        ResolveStep::File(path) => {
+            file(path, options).ok_or_else(|| format!("there is no {}", path.display()))
+        }
+        ResolveStep::Builtin(name) => builtin(name)
+            .map(Ok)
+            .ok_or_else(|| format!("there is no builtin scheme {}", name)),
+    }
+}
+
+pub fn resolve_default() -> Result<Scheme, SchemeReaderError> {
+    resolve_with(&ResolveOptions::default())
+}
+
+pub fn resolve_with(options: &ResolveOptions) -> Result<Scheme, SchemeReaderError> {
+    let mut tried = Vec::new();
+    for resolve_step in &options.steps {
+        match step(resolve_step, &options.reader) {
+            Ok(found) => return found,
+            Err(why) => tried.push(why),
+        }
+    }
+    Err(SchemeReaderError::NotResolved { tried })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::sync::Mutex;
+
+    use super::*;
+    use crate::errors::ErrorKind;
+    use crate::temp::TempDir;
+
+    // the environment is shared by all tests of the process
+    static ENV: Mutex<()> = Mutex::new(());
+
+    // a fresh HOME and APPDATA without a config directory, the variables
+    // cleared
+    fn home(test: &str) -> TempDir {
+        let home = TempDir::new(&format!("resolve_{}", test));
+        env::set_var("HOME", home.path());
+        env::set_var("APPDATA", home.join("AppData"));
+        env::remove_var("XDG_CONFIG_HOME");
+        env::remove_var(SCHEME_VAR);
+        home
+    }
+
+    fn write_config(contents: &str) {
+        let dir = config_dir().unwrap().join("cool_rs");
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("scheme"), contents).unwrap();
+    }
+
+    #[test]
+    fn test_resolve_order() {
+        let _env = ENV.lock().unwrap_or_else(|e| e.into_inner());
+        let home = home("order");
+        #[cfg(feature = "builtin-schemes")]
+        assert_eq!(resolve_default().unwrap().name, "gruvbox-dark");
+
+        write_config("configured\n#000000\n");
+        assert_eq!(resolve_default().unwrap().name, "configured");
+
+        env::set_var(SCHEME_VAR, "Nord");
+        #[cfg(feature = "builtin-schemes")]
+        assert_eq!(resolve_default().unwrap().name, "nord");
+
+        let path = home.join("from_env");
+        fs::write(&path, "from env\n#ffffff\n").unwrap();
+        env::set_var(SCHEME_VAR, &path);
+        assert_eq!(resolve_default().unwrap().name, "from env");
+
+        // XDG_CONFIG_HOME goes before ~/.config
+        env::remove_var(SCHEME_VAR);
+        if cfg!(any(windows, target_os = "macos")) {
+            return;
+        }
+        let xdg = home.join("xdg");
+        fs::create_dir_all(xdg.join("cool_rs")).unwrap();
+        fs::write(xdg.join("cool_rs").join("scheme"), "xdg\n#ffffff\n").unwrap();
+        env::set_var("XDG_CONFIG_HOME", &xdg);
+        assert_eq!(resolve_default().unwrap().name, "xdg");
+        env::remove_var("XDG_CONFIG_HOME");
+    }
+
+    #[test]
+    fn test_resolve_errors() {
+        let _env = ENV.lock().unwrap_or_else(|e| e.into_inner());
+        let _home = home("errors");
+        env::set_var(SCHEME_VAR, "no-such-scheme");
+        let options = ResolveOptions {
+            steps: vec![
+                ResolveStep::Env(SCHEME_VAR.into()),
+                ResolveStep::ConfigFile("cool_rs/scheme".into()),
+                ResolveStep::Builtin("missing".into()),
+            ],
+            ..Default::default()
+        };
+        let error = resolve_with(&options).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::NotResolved);
+        let config = config_dir().unwrap().join("cool_rs").join("scheme");
+        assert_eq!(
+            error.to_string(),
+            format!(
+                "No scheme found: $COOL_SCHEME is no-such-scheme, neither a file nor a builtin \
+                 scheme; there is no {}; there is no builtin scheme missing",
+                config.display()
+            )
+        );
+
+        // a broken file is an error of its own, whatever comes after it
+        write_config("broken\nnot a color\n");
+        let error = resolve_default().unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::ParseColor);
+        env::remove_var(SCHEME_VAR);
+    }
+
+    #[test]
+    fn test_config_dir() {
+        let _env = ENV.lock().unwrap_or_else(|e| e.into_inner());
+        let home = home("config_dir");
+        let expected = if cfg!(windows) {
+            home.join("AppData")
+        } else if cfg!(target_os = "macos") {
+            home.join("Library").join("Application Support")
+        } else {
+            home.join(".config")
+        };
+        assert_eq!(config_dir(), Some(expected));
+
+        // relative paths are no config directory
+        env::set_var("HOME", "relative");
+        env::set_var("APPDATA", "relative");
+        assert_eq!(config_dir(), None);
+        let error = resolve_with(&ResolveOptions {
+            steps: vec![ResolveStep::ConfigFile("cool_rs/scheme".into())],
+            ..Default::default()
+        })
+        .unwrap_err();
+        assert!(error.to_string().contains("there is no config directory"));
+        env::set_var("HOME", home.path());
+    }
+}
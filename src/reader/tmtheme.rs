@@ -0,0 +1,387 @@
+// the colors of a TextMate or Sublime Text theme, a .tmTheme plist. the
+// settings without a scope are the editor's, `background`, `caret` and the
+// like, and keep their keys as names. every scoped setting gives its
+// foreground the scope as name and its background `<scope>.background`.
+// values that aren't colors, font styles among others, are left out
+//
+// colors are `#rgb`, `#rrggbb` or `#rrggbbaa`, as TextMate and Sublime write
+// them. some generators put the alpha first, `#aarrggbb`, which parse_with
+// reads with AlphaPosition::First. the plist reader only knows what themes
+// use: dicts, arrays, keys and strings, anything else is skipped
+use std::fs;
+use std::path::Path;
+
+use crate::color::{Canonical, Entry, Scheme};
+use crate::errors::{ParseFormatError, SchemeReaderError};
+use crate::formats::{ByteOrder, IntegerFormat};
+
+// where 8 digit colors have their alpha
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Default)]
+pub enum AlphaPosition {
+    // #rrggbbaa
+    #[default]
+    Last,
+    // #aarrggbb
+    First,
+}
+
+fn malformed(reason: &'static str) -> SchemeReaderError {
+    SchemeReaderError::MalformedPalette {
+        format: "tmTheme",
+        reason,
+    }
+}
+
+#[derive(PartialEq, Debug)]
+enum Value {
+    Dict(Vec<(String, Value)>),
+    Array(Vec<Value>),
+    String(String),
+    // numbers, booleans, dates and data
+    Other,
+}
+
+impl Value {
+    fn get(&self, key: &str) -> Option<&Value> {
+        match self {
+            Value::Dict(entries) => entries.iter().find(|(k, _)| k == key).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+
+    fn as_str(&self) -> Option<&str> {
+        match self {
+            Value::String(s) => Some(s),
+            _ => None,
+        }
+    }
+}
+
+fn unescape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(amp) = rest.find('&') {
+        out.push_str(&rest[..amp]);
+        rest = &rest[amp..];
+        let entity = rest.find(';').map(|end| &rest[1..end]);
+        let decoded = match entity {
+            Some("amp") => Some('&'),
+            Some("lt") => Some('<'),
+            Some("gt") => Some('>'),
+            Some("quot") => Some('"'),
+            Some("apos") => Some('\''),
+            Some(e) if e.starts_with("#x") => u32::from_str_radix(&e[2..], 16)
+                .ok()
+                .and_then(char::from_u32),
+            Some(e) if e.starts_with('#') => e[1..].parse().ok().and_then(char::from_u32),
+            _ => None,
+        };
+        match (decoded, entity) {
+            (Some(c), Some(entity)) => {
+                out.push(c);
+                rest = &rest[entity.len() + 2..];
+            }
+            _ => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+struct Plist<'a> {
+    rest: &'a str,
+}
+
+impl<'a> Plist<'a> {
+    // the next tag's name and whether it closes or is empty, after skipping
+    // text, declarations and comments
+    fn tag(&mut self) -> Result<(&'a str, bool, bool), SchemeReaderError> {
+        loop {
+            let open = self.rest.find('<').ok_or_else(|| malformed("truncated"))?;
+            self.rest = &self.rest[open..];
+            let end = match self.rest.starts_with("<!--") {
+                true => self.rest.find("-->").map(|end| end + 2),
+                false => self.rest.find('>'),
+            }
+            .ok_or_else(|| malformed("truncated"))?;
+            let inner = &self.rest[1..end];
+            self.rest = &self.rest[end + 1..];
+            if inner.starts_with('?') || inner.starts_with('!') {
+                continue;
+            }
+            let closing = inner.starts_with('/');
+            let empty = inner.ends_with('/');
+            let name = inner.trim_matches('/').split_whitespace().next();
+            return Ok((name.unwrap_or_default(), closing, empty));
+        }
+    }
+
+    // the text up to the closing tag of `name`
+    fn text(&mut self, name: &str) -> Result<String, SchemeReaderError> {
+        let close = format!("</{}>", name);
+        let end = self
+            .rest
+            .find(&close)
+            .ok_or_else(|| malformed("truncated"))?;
+        let text = unescape(&self.rest[..end]);
+        self.rest = &self.rest[end + close.len()..];
+        Ok(text)
+    }
+
+    // the value whose opening tag was just read
+    fn value(&mut self, name: &str, empty: bool) -> Result<Value, SchemeReaderError> {
+        match (name, empty) {
+            ("dict", true) => Ok(Value::Dict(Vec::new())),
+            ("array", true) => Ok(Value::Array(Vec::new())),
+            ("string", true) => Ok(Value::String(String::new())),
+            (_, true) => Ok(Value::Other),
+            ("dict", false) => {
+                let mut entries = Vec::new();
+                loop {
+                    match self.tag()? {
+                        ("dict", true, _) => return Ok(Value::Dict(entries)),
+                        ("key", false, false) => {
+                            let key = self.text("key")?;
+                            let (name, closing, empty) = self.tag()?;
+                            if closing {
+                                return Err(malformed("a key without a value"));
+                            }
+                            entries.push((key, self.value(name, empty)?));
+                        }
+                        _ => return Err(malformed("a dict value without a key")),
+                    }
+                }
+            }
+            ("array", false) => {
+                let mut values = Vec::new();
+                loop {
+                    match self.tag()? {
+                        ("array", true, _) => return Ok(Value::Array(values)),
+                        (_, true, _) => return Err(malformed("mismatched tags")),
+                        (name, false, empty) => values.push(self.value(name, empty)?),
+                    }
+                }
+            }
+            ("string", false) => self.text("string").map(Value::String),
+            (name, false) => self.text(name).map(|_| Value::Other),
+        }
+    }
+}
+
+// the top level value of a plist document
+fn plist(text: &str) -> Result<Value, SchemeReaderError> {
+    let mut plist = Plist { rest: text };
+    let (mut name, mut closing, mut empty) = plist.tag()?;
+    if name == "plist" && !closing && !empty {
+        (name, closing, empty) = plist.tag()?;
+    }
+    if closing {
+        return Err(malformed("not a plist"));
+    }
+    plist.value(name, empty)
+}
+
+// the color of a `#` value, None for anything else
+fn color(value: &str, alpha: AlphaPosition) -> Option<Result<Canonical, ParseFormatError>> {
+    let digits = value.trim().strip_prefix('#')?;
+    let (digits, order) = match (digits.len(), alpha) {
+        (3, _) => (
+            digits.chars().flat_map(|c| [c, c]).collect(),
+            ByteOrder::Rgb,
+        ),
+        (8, AlphaPosition::Last) => (digits.to_string(), ByteOrder::Rgba),
+        (8, AlphaPosition::First) => (digits.to_string(), ByteOrder::Argb),
+        _ => (digits.to_string(), ByteOrder::Rgb),
+    };
+    Some(IntegerFormat::parse_with(&format!("0x{}", digits), order))
+}
+
+// the named entries of the theme, the editor's first, then the scopes in
+// the order of the file. the scheme's name is empty if the theme has none
+pub fn tmtheme_scheme(text: &str, alpha: AlphaPosition) -> Result<Scheme, SchemeReaderError> {
+    let theme = plist(text)?;
+    let Some(Value::Array(settings)) = theme.get("settings") else {
+        return Err(malformed("no settings array"));
+    };
+    let mut entries = Vec::new();
+    let mut push = |name: String, value: &Value| -> Result<(), SchemeReaderError> {
+        if let Some(parsed) = value.as_str().and_then(|value| color(value, alpha)) {
+            let color = parsed.map_err(|source| SchemeReaderError::InvalidToken {
+                token: name.clone(),
+                source,
+            })?;
+            entries.push(Entry::named(&name, color));
+        }
+        Ok(())
+    };
+    for item in settings {
+        let Some(Value::Dict(colors)) = item.get("settings") else {
+            continue;
+        };
+        match item.get("scope").and_then(Value::as_str) {
+            None => {
+                for (key, value) in colors {
+                    push(key.clone(), value)?;
+                }
+            }
+            Some(scope) => {
+                let scope = scope.trim();
+                for (key, value) in colors {
+                    match key.as_str() {
+                        "foreground" => push(scope.to_string(), value)?,
+                        "background" => push(format!("{}.background", scope), value)?,
+                        _ => {}
+                    }
+                }
+            }
+        }
+    }
+    let text = |key| theme.get(key).and_then(Value::as_str).map(String::from);
+    Ok(Scheme::from_entries(
+        text("name").unwrap_or_default(),
+        text("author"),
+        entries,
+    ))
+}
+
+pub fn parse(path: &str) -> Result<Scheme, SchemeReaderError> {
+    parse_with(path, AlphaPosition::default())
+}
+
+// named after the theme, or the file if it has no name
+pub fn parse_with(path: &str, alpha: AlphaPosition) -> Result<Scheme, SchemeReaderError> {
+    let path = Path::new(path);
+    let text = fs::read_to_string(path).map_err(|e| SchemeReaderError::io(e, Some(path)))?;
+    let mut scheme = tmtheme_scheme(&text, alpha)?;
+    if scheme.name.is_empty() {
+        let stem = path.file_stem().unwrap_or_default();
+        scheme.name = stem.to_string_lossy().into_owned();
+    }
+    Ok(scheme)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::errors::ErrorKind;
+    use crate::temp::TempDir;
+
+    const THEME: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+<plist version="1.0">
+<dict>
+  <key>name</key>
+  <string>Monokai &amp; friends</string>
+  <key>author</key>
+  <string>someone</string>
+  <key>settings</key>
+  <array>
+    <dict>
+      <key>settings</key>
+      <dict>
+        <key>background</key>
+        <string>#272822</string>
+        <key>caret</key>
+        <string>#F8F8F0</string>
+        <key>selection</key>
+        <string>#49483E80</string>
+        <key>invisibles</key>
+        <string>#fff</string>
+      </dict>
+    </dict>
+    <!-- <dict><key>scope</key><string>commented out</string></dict> -->
+    <dict>
+      <key>name</key>
+      <string>Comment</string>
+      <key>scope</key>
+      <string>comment</string>
+      <key>settings</key>
+      <dict>
+        <key>fontStyle</key>
+        <string>italic</string>
+        <key>foreground</key>
+        <string>#75715E</string>
+      </dict>
+    </dict>
+    <dict>
+      <key>scope</key>
+      <string>invalid</string>
+      <key>settings</key>
+      <dict>
+        <key>foreground</key>
+        <string>#F8F8F0</string>
+        <key>background</key>
+        <string>#F92672CC</string>
+        <key>bold</key>
+        <true/>
+      </dict>
+    </dict>
+  </array>
+  <key>uuid</key>
+  <string>D8D5E82E-3D5B-46B5-B38E-8C841C21347D</string>
+</dict>
+</plist>
+"#;
+
+    #[test]
+    fn test_parse() {
+        let dir = TempDir::new("tmtheme_parse");
+        let path = dir.join("cool_rs_monokai.tmTheme");
+        fs::write(&path, THEME).unwrap();
+        let scheme = parse(path.to_str().unwrap()).unwrap();
+        assert_eq!(scheme.name, "Monokai & friends");
+        assert_eq!(scheme.author.as_deref(), Some("someone"));
+        let names: Vec<_> = scheme
+            .entries
+            .iter()
+            .map(|e| e.name.as_deref().unwrap())
+            .collect();
+        assert_eq!(
+            names,
+            [
+                "background",
+                "caret",
+                "selection",
+                "invisibles",
+                "comment",
+                "invalid",
+                "invalid.background"
+            ]
+        );
+        let get = |name| *scheme.get(name).unwrap();
+        assert_eq!(get("background"), Canonical::new(0x27, 0x28, 0x22, 255));
+        assert_eq!(get("invisibles"), Canonical::new(255, 255, 255, 255));
+        // the alpha is the last byte, not the red channel
+        assert_eq!(get("selection"), Canonical::new(0x49, 0x48, 0x3e, 0x80));
+        assert_eq!(
+            get("invalid.background"),
+            Canonical::new(0xf9, 0x26, 0x72, 0xcc)
+        );
+    }
+
+    #[test]
+    fn test_alpha_first() {
+        let scheme = tmtheme_scheme(THEME, AlphaPosition::First).unwrap();
+        let get = |name| *scheme.get(name).unwrap();
+        assert_eq!(get("selection"), Canonical::new(0x48, 0x3e, 0x80, 0x49));
+        // 6 digits never have an alpha to place
+        assert_eq!(get("caret"), Canonical::new(0xf8, 0xf8, 0xf0, 255));
+    }
+
+    #[test]
+    fn test_malformed() {
+        let e = tmtheme_scheme(&THEME[..600], AlphaPosition::Last).unwrap_err();
+        assert_eq!(e.to_string(), "Malformed tmTheme palette: truncated");
+        let e = tmtheme_scheme("<plist><dict></dict></plist>", AlphaPosition::Last);
+        assert_eq!(e.unwrap_err().kind(), ErrorKind::MalformedPalette);
+
+        let bad = THEME.replace("#75715E", "#75715G");
+        match tmtheme_scheme(&bad, AlphaPosition::Last) {
+            Err(SchemeReaderError::InvalidToken { token, .. }) => assert_eq!(token, "comment"),
+            other => panic!("unexpected result {:?}", other),
+        }
+    }
+}
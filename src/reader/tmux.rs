@@ -0,0 +1,226 @@
+// the colors of a tmux config, read off its `set` lines. a style option,
+// `set -g status-style "fg=colour246,bg=#1d2021"`, gives an entry for each
+// color attribute, `status-style.fg` and `status-style.bg`, the underscore
+// color `us` too. options that are a single color, `clock-mode-colour` and
+// the `status-bg` of tmux before 2.9, give one named by the option
+//
+// colors are `colourN` or `colorN` through the xterm table, `#rrggbb`, or
+// the names of the 16 system colors, `brightred` as colour9. `default`,
+// `terminal` and other words aren't colors and give no entry. an option set
+// twice keeps its first position with the last color
+use std::fs;
+use std::path::Path;
+
+use crate::color::palettes::XTERM_256;
+use crate::color::{Canonical, Entry, Role, Scheme};
+use crate::errors::SchemeReaderError;
+use crate::formats::{parse_any_with, ColorFormat, ColorFormats, XtermIndexFormat};
+
+const SET: [&str; 4] = ["set", "set-option", "setw", "set-window-option"];
+const STYLE_ATTRIBUTES: [&str; 3] = ["fg", "bg", "us"];
+// option suffixes of single colors
+const COLOR_OPTIONS: [&str; 4] = ["-colour", "-color", "-fg", "-bg"];
+
+// logical lines, continuations joined and comments dropped
+fn lines(text: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    let mut continued = false;
+    for line in text.lines() {
+        let line = line.trim();
+        let (line, continues) = match line.strip_suffix('\\') {
+            Some(line) => (line, true),
+            None => (line, false),
+        };
+        match lines.last_mut() {
+            Some(last) if continued => last.push_str(line),
+            _ if line.is_empty() || line.starts_with('#') => {}
+            _ => lines.push(line.to_string()),
+        }
+        continued = continues;
+    }
+    lines
+}
+
+// the words of a line, quotes removed, up to a comment
+fn words(line: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut chars = line.chars().peekable();
+    loop {
+        while chars.next_if(|c| c.is_whitespace()).is_some() {}
+        match chars.peek() {
+            None | Some('#') => break,
+            _ => {}
+        }
+        let mut word = String::new();
+        let mut quote = None;
+        while let Some(c) = chars.next() {
+            match (quote, c) {
+                (None, '"' | '\'') => quote = Some(c),
+                (Some(q), c) if c == q => quote = None,
+                (None, c) if c.is_whitespace() => break,
+                (Some('"'), '\\') => word.extend(chars.next()),
+                (_, c) => word.push(c),
+            }
+        }
+        words.push(word);
+    }
+    words
+}
+
+// the option and value of a `set` line
+fn option(words: &[String]) -> Option<(&str, &str)> {
+    let (command, mut rest) = words.split_first()?;
+    if !SET.contains(&command.as_str()) {
+        return None;
+    }
+    while let Some((flags, after)) = rest.split_first() {
+        let Some(flags) = flags.strip_prefix('-') else {
+            break;
+        };
+        // -t takes the target
+        rest = match flags.contains('t') {
+            true => after.get(1..)?,
+            false => after,
+        };
+    }
+    match rest {
+        [option, value, ..] => Some((option, value)),
+        _ => None,
+    }
+}
+
+// None for values that aren't colors, an error for ones that look like a
+// color but aren't one
+fn color(entry_name: &str, value: &str) -> Result<Option<Canonical>, SchemeReaderError> {
+    let allowed = [ColorFormats::XtermIndex, ColorFormats::Hex];
+    match parse_any_with(value, &allowed) {
+        Ok(color) => Ok(Some(color)),
+        Err(source) if value.starts_with('#') || XtermIndexFormat::matches(value) => {
+            Err(SchemeReaderError::InvalidToken {
+                token: entry_name.to_string(),
+                source,
+            })
+        }
+        Err(_) => Ok(match Role::from_name(value) {
+            Some(Role::AnsiColor(i)) if i < 16 => Some(XTERM_256[i as usize]),
+            _ => None,
+        }),
+    }
+}
+
+// the entries in the order their options first appear, the name empty
+pub fn tmux_scheme(text: &str) -> Result<Scheme, SchemeReaderError> {
+    let mut entries: Vec<Entry> = Vec::new();
+    for line in lines(text) {
+        let words = words(&line);
+        let Some((option, value)) = option(&words) else {
+            continue;
+        };
+        let colors: Vec<(String, &str)> = if option.ends_with("-style") {
+            value
+                .split(|c: char| c == ',' || c.is_whitespace())
+                .filter_map(|attribute| attribute.split_once('='))
+                .filter(|(key, _)| STYLE_ATTRIBUTES.contains(key))
+                .map(|(key, value)| (format!("{}.{}", option, key), value))
+                .collect()
+        } else if COLOR_OPTIONS.iter().any(|suffix| option.ends_with(suffix)) {
+            vec![(option.to_string(), value)]
+        } else {
+            continue;
+        };
+        for (entry_name, value) in colors {
+            let Some(color) = color(&entry_name, value)? else {
+                continue;
+            };
+            match entries
+                .iter_mut()
+                .find(|e| e.name.as_deref() == Some(&entry_name))
+            {
+                Some(entry) => entry.color = color,
+                None => entries.push(Entry::named(&entry_name, color)),
+            }
+        }
+    }
+    Ok(Scheme::from_entries(String::new(), None, entries))
+}
+
+// named after the file
+pub fn parse(path: &str) -> Result<Scheme, SchemeReaderError> {
+    let path = Path::new(path);
+    let text = fs::read_to_string(path).map_err(|e| SchemeReaderError::io(e, Some(path)))?;
+    let mut scheme = tmux_scheme(&text)?;
+    let stem = path.file_stem().unwrap_or_default();
+    scheme.name = stem.to_string_lossy().into_owned();
+    Ok(scheme)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::errors::ErrorKind;
+    use crate::temp::TempDir;
+
+    // gruvbox, trimmed
+    const CONFIG: &str = r##"# gruvbox colors for tmux
+set-option -g status "on"
+
+# default statusbar color
+set-option -g status-style "bg=colour237,fg=colour223" # bg=bg1, fg=fg1
+set -g window-status-current-style bg=#fabd2f,fg=colour237,bold
+set-window-option -g window-status-activity-style 'bg=color237 fg=colour248 us=brightred'
+set -t main pane-border-style fg=default
+set -ag pane-active-border-style "fg=#D79921"
+
+# pane number display
+set-option -g display-panes-active-colour colour250
+set-option -g clock-mode-colour colour109
+set -g message-fg \
+    terminal
+set -g status-left "#[bg=colour241,fg=colour248] #S "
+set-option -g status-style bg=colour236
+"##;
+
+    #[test]
+    fn test_tmux_scheme() {
+        let dir = TempDir::new("tmux_scheme");
+        let path = dir.join("cool_rs_tmux.conf");
+        fs::write(&path, CONFIG).unwrap();
+        let scheme = parse(path.to_str().unwrap()).unwrap();
+        assert_eq!(scheme.name, "cool_rs_tmux");
+        let entries: Vec<_> = scheme
+            .entries
+            .iter()
+            .map(|e| (e.name.as_deref().unwrap(), e.color))
+            .collect();
+        let gray = |v: u8| Canonical::new(v, v, v, 255);
+        let rgb = |hex: u32| Canonical::new((hex >> 16) as u8, (hex >> 8) as u8, hex as u8, 255);
+        assert_eq!(
+            entries,
+            [
+                // the last line sets the background again
+                ("status-style.bg", gray(0x30)),
+                ("status-style.fg", rgb(0xffd7af)),
+                ("window-status-current-style.bg", rgb(0xfabd2f)),
+                ("window-status-current-style.fg", gray(0x3a)),
+                ("window-status-activity-style.bg", gray(0x3a)),
+                ("window-status-activity-style.fg", gray(0xa8)),
+                ("window-status-activity-style.us", rgb(0xff0000)),
+                ("pane-active-border-style.fg", rgb(0xd79921)),
+                ("display-panes-active-colour", gray(0xbc)),
+                ("clock-mode-colour", rgb(0x87afaf)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_invalid_colors() {
+        let e = tmux_scheme("set -g status-style fg=colour256").unwrap_err();
+        assert_eq!(e.kind(), ErrorKind::InvalidToken);
+        assert!(e.to_string().contains("status-style.fg"), "{}", e);
+        let e = tmux_scheme("set -g clock-mode-colour '#12345'").unwrap_err();
+        assert_eq!(e.kind(), ErrorKind::InvalidToken);
+        // words that aren't colors are left out
+        let scheme = tmux_scheme("set -g status-style fg=orange,bg=default").unwrap();
+        assert!(scheme.entries.is_empty());
+    }
+}
@@ -0,0 +1,294 @@
+// W3C design tokens, the community group's draft format. color tokens
+// anywhere in the tree of groups become entries named by their dotted path,
+// so `{"color": {"accent": {"$type": "color", "$value": "#ff8800"}}}` gives
+// an entry `color.accent`. a token's $type may come from an enclosing group
+// and tokens of other types are skipped. a value `{color.accent}` is an alias
+// for the value of the token it names, which can't be an alias itself
+use std::fs;
+use std::path::Path;
+
+use crate::color::diff::hex;
+use crate::color::{Canonical, Entry, Scheme};
+use crate::errors::{ParseFormatError, SchemeReaderError};
+use crate::formats::{parse_any, ColorFormats};
+use crate::json::{self, Value};
+
+// the scheme is named after the file, without `.tokens.json` or `.json`
+pub fn parse(path: &str) -> Result<Scheme, SchemeReaderError> {
+    let path = Path::new(path);
+    let text = fs::read_to_string(path).map_err(|e| SchemeReaderError::io(e, Some(path)))?;
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    parse_str(stem.strip_suffix(".tokens").unwrap_or(&stem), &text)
+}
+
+pub fn parse_str(name: &str, s: &str) -> Result<Scheme, SchemeReaderError> {
+    let root = json::parse(s).map_err(|e| SchemeReaderError::InvalidJson {
+        line: e.line,
+        column: e.column,
+        message: e.message,
+    })?;
+    let mut tokens = Vec::new();
+    collect(&root, &mut Vec::new(), None, &mut tokens);
+
+    let mut entries = Vec::new();
+    for token in &tokens {
+        let target = match alias(token.value) {
+            Some(name) => {
+                let unresolved = || SchemeReaderError::UnresolvedAlias {
+                    token: token.path.clone(),
+                    alias: name.into(),
+                };
+                let target = tokens
+                    .iter()
+                    .find(|t| t.path == name)
+                    .ok_or_else(unresolved)?;
+                if alias(target.value).is_some() {
+                    return Err(unresolved());
+                }
+                target
+            }
+            None => token,
+        };
+        // an untyped alias takes the type of what it refers to
+        if token.kind.or(target.kind) != Some("color") {
+            continue;
+        }
+        let color = color(target.value).map_err(|source| SchemeReaderError::InvalidToken {
+            token: token.path.clone(),
+            source,
+        })?;
+        entries.push(Entry::named(&token.path, color));
+    }
+    Ok(Scheme::from_entries(name.into(), None, entries))
+}
+
+struct Token<'v> {
+    path: String,
+    kind: Option<&'v str>,
+    value: &'v Value,
+}
+
+// in document order. an object with a $value is a token, any other members
+// not starting with `$` are still walked as groups
+fn collect<'v>(
+    value: &'v Value,
+    path: &mut Vec<&'v str>,
+    inherited: Option<&'v str>,
+    tokens: &mut Vec<Token<'v>>,
+) {
+    let Value::Object(members) = value else {
+        return;
+    };
+    let kind = value.get("$type").and_then(Value::as_str).or(inherited);
+    if let Some(token) = value.get("$value") {
+        tokens.push(Token {
+            path: path.join("."),
+            kind,
+            value: token,
+        });
+    }
+    for (key, member) in members {
+        if !key.starts_with('$') {
+            path.push(key);
+            collect(member, path, kind, tokens);
+            path.pop();
+        }
+    }
+}
+
+fn alias(value: &Value) -> Option<&str> {
+    value.as_str()?.strip_prefix('{')?.strip_suffix('}')
+}
+
+// any color parse_any reads, or an object with a `hex` member and an optional
+// `alpha` in [0, 1] as in later drafts
+fn color(value: &Value) -> Result<Canonical, ParseFormatError> {
+    if let Some(s) = value.as_str() {
+        return parse_any(s);
+    }
+    match value.get("hex").and_then(Value::as_str) {
+        Some(s) => {
+            let color = parse_any(s)?;
+            Ok(match value.get("alpha").and_then(Value::as_f64) {
+                Some(alpha) => Canonical {
+                    a: (alpha.clamp(0.0, 1.0) * 255.0).round() as u8,
+                    ..color
+                },
+                None => color,
+            })
+        }
+        None => Err(ParseFormatError::NoMatch {
+            format: ColorFormats::Hex,
+            input: json::to_pretty_string(value).trim_end().into(),
+        }),
+    }
+}
+
+impl Scheme {
+    // every entry as a color token, grouped by the `.` separated segments of
+    // its name. unnamed entries are named by their index. what parse_str
+    // reads back as the same entries
+    pub fn to_design_tokens(&self) -> String {
+        let mut root = Vec::new();
+        for (i, entry) in self.entries.iter().enumerate() {
+            let name = entry.name.clone().unwrap_or_else(|| i.to_string());
+            let mut group = &mut root;
+            for segment in name.split('.') {
+                let position = match group.iter().position(|(key, _)| key == segment) {
+                    Some(position) => position,
+                    None => {
+                        group.push((segment.to_string(), Value::Object(Vec::new())));
+                        group.len() - 1
+                    }
+                };
+                let Value::Object(members) = &mut group[position].1 else {
+                    unreachable!("only objects are inserted")
+                };
+                group = members;
+            }
+            // the first of several entries of the same name, as Scheme::get
+            if group.iter().all(|(key, _)| key != "$value") {
+                group.splice(
+                    0..0,
+                    [
+                        ("$type".to_string(), Value::String("color".into())),
+                        ("$value".to_string(), Value::String(hex(&entry.color))),
+                    ],
+                );
+            }
+        }
+        json::to_pretty_string(&Value::Object(root))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::errors::ErrorKind;
+    use crate::temp::TempDir;
+    // the examples of the format's draft, with tokens of other types mixed in
+    const SPEC_EXAMPLE: &str = r##"{
+  "color": {
+    "$type": "color",
+    "brand": {
+      "primary": {
+        "$value": "#ff8800",
+        "$description": "the main brand color"
+      },
+      "secondary": { "$value": "#0080ff80" }
+    },
+    "accent": { "$value": "{color.brand.primary}" },
+    "muted": {
+      "$value": {
+        "colorSpace": "srgb",
+        "components": [0.5, 0.5, 0.5],
+        "alpha": 0.5,
+        "hex": "#808080"
+      }
+    }
+  },
+  "alias": { "link": { "$value": "{color.brand.secondary}" } },
+  "spacing": {
+    "small": { "$type": "dimension", "$value": "4px" },
+    "medium": { "$value": "{spacing.small}" }
+  },
+  "font": {
+    "heading": { "$type": "fontFamily", "$value": ["Helvetica", "sans-serif"] }
+  },
+  "border": {
+    "$type": "color",
+    "$extensions": { "org.example": { "$value": "ignored" } },
+    "default": { "$value": "rgb(20, 30, 40)" }
+  }
+}"##;
+
+    fn rgba(r: u8, g: u8, b: u8, a: u8) -> Canonical {
+        Canonical::new(r, g, b, a)
+    }
+
+    #[test]
+    fn test_parse() {
+        let dir = TempDir::new("tokens_parse");
+        let path = dir.join("cool_rs_brand.tokens.json");
+        fs::write(&path, SPEC_EXAMPLE).unwrap();
+        let scheme = parse(path.to_str().unwrap()).unwrap();
+        assert_eq!(scheme.name, "cool_rs_brand");
+        assert_eq!(
+            scheme.entries,
+            vec![
+                Entry::named("color.brand.primary", rgba(255, 136, 0, 255)),
+                Entry::named("color.brand.secondary", rgba(0, 128, 255, 128)),
+                Entry::named("color.accent", rgba(255, 136, 0, 255)),
+                Entry::named("color.muted", rgba(128, 128, 128, 128)),
+                Entry::named("alias.link", rgba(0, 128, 255, 128)),
+                Entry::named("border.default", rgba(20, 30, 40, 255)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        let scheme = parse_str("brand", SPEC_EXAMPLE).unwrap();
+        let tokens = scheme.to_design_tokens();
+        assert_eq!(parse_str("brand", &tokens).unwrap(), scheme);
+        assert!(tokens.starts_with(
+            r##"{
+  "color": {
+    "brand": {
+      "primary": {
+        "$type": "color",
+        "$value": "#ff8800"
+      },
+      "secondary": {
+        "$type": "color",
+        "$value": "#0080ff80"
+      }
+    },"##
+        ));
+
+        // a token that is also a group, and unnamed entries
+        let mut nested = Scheme::new("nested");
+        nested.push_named("bg", rgba(0, 0, 0, 255)).unwrap();
+        nested.push_named("bg.dim", rgba(10, 10, 10, 255)).unwrap();
+        nested.push(rgba(1, 2, 3, 255));
+        let back = parse_str("nested", &nested.to_design_tokens()).unwrap();
+        assert_eq!(
+            back.entries,
+            vec![
+                Entry::named("bg", rgba(0, 0, 0, 255)),
+                Entry::named("bg.dim", rgba(10, 10, 10, 255)),
+                Entry::named("2", rgba(1, 2, 3, 255)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_errors() {
+        let e = parse_str("bad", "{\n  \"color\": \n}").unwrap_err();
+        assert_eq!(e.kind(), ErrorKind::InvalidJson);
+        assert_eq!(
+            e.to_string(),
+            "Invalid JSON on line 3, column 1: expected a value"
+        );
+
+        let e = parse_str("bad", r#"{"a": {"$type": "color", "$value": "{b}"}}"#).unwrap_err();
+        assert_eq!(e.kind(), ErrorKind::UnresolvedAlias);
+        assert_eq!(e.to_string(), "Token a refers to {b}, which isn't a token");
+        // one level only
+        let chained = r##"{"$type": "color", "a": {"$value": "#000000"},
+            "b": {"$value": "{a}"}, "c": {"$value": "{b}"}}"##;
+        assert_eq!(
+            parse_str("bad", chained).unwrap_err().kind(),
+            ErrorKind::UnresolvedAlias
+        );
+
+        let e = parse_str("bad", r##"{"a": {"$type": "color", "$value": "#12345"}}"##).unwrap_err();
+        assert_eq!(e.kind(), ErrorKind::InvalidToken);
+        assert_eq!(e.to_string(), "Token a is not a color");
+        let e = parse_str("bad", r#"{"a": {"$type": "color", "$value": 12}}"#).unwrap_err();
+        assert_eq!(e.kind(), ErrorKind::InvalidToken);
+
+        let e = parse("/nonexistent/cool_rs.tokens.json").unwrap_err();
+        assert_eq!(e.kind(), ErrorKind::Io);
+    }
+}
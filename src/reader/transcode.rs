@@ -0,0 +1,645 @@
+// a file in any format the crate reads written in one it writes, in one
+// call. parse_auto picks the reader, transcode pairs what it read with the
+// writer and says what didn't survive: alpha the format can't hold, entries
+// it has no place for, names it can't hold as they are
+//
+// the role based formats, Xresources, kitty and i3, take a base16 scheme,
+// which has no roles, in the layout of base16::TERMINAL. PNG palettes are
+// left out, they are written into an existing image
+use std::fmt::Display;
+use std::fs;
+#[cfg(feature = "aseprite")]
+use std::io::Read;
+use std::path::Path;
+
+use super::export::{key, role_entries};
+use super::i3::{i3_lines, I3Mapping};
+use super::{ase, base16, binary, emacs, i3, png_palette, tmux, vim, write_to_string};
+use crate::color::{AlphaPolicy, Canonical, Entry, EntryKey, Role, Scheme};
+use crate::errors::SchemeReaderError;
+
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum SchemeFileFormat {
+    // the crate's own, reader::write
+    Native,
+    Xresources,
+    Kitty,
+    Gpl,
+    I3,
+    #[cfg(feature = "json")]
+    Json,
+    #[cfg(feature = "json")]
+    DesignTokens,
+}
+
+impl SchemeFileFormat {
+    pub const ALL: &'static [SchemeFileFormat] = &[
+        SchemeFileFormat::Native,
+        SchemeFileFormat::Xresources,
+        SchemeFileFormat::Kitty,
+        SchemeFileFormat::Gpl,
+        SchemeFileFormat::I3,
+        #[cfg(feature = "json")]
+        SchemeFileFormat::Json,
+        #[cfg(feature = "json")]
+        SchemeFileFormat::DesignTokens,
+    ];
+
+    // stable, meant for command line flags
+    pub fn name(&self) -> &'static str {
+        match *self {
+            SchemeFileFormat::Native => "native",
+            SchemeFileFormat::Xresources => "xresources",
+            SchemeFileFormat::Kitty => "kitty",
+            SchemeFileFormat::Gpl => "gpl",
+            SchemeFileFormat::I3 => "i3",
+            #[cfg(feature = "json")]
+            SchemeFileFormat::Json => "json",
+            #[cfg(feature = "json")]
+            SchemeFileFormat::DesignTokens => "design-tokens",
+        }
+    }
+
+    // the inverse of name, ignoring case
+    pub fn from_name(name: &str) -> Option<SchemeFileFormat> {
+        let name = name.trim().to_ascii_lowercase();
+        SchemeFileFormat::ALL
+            .iter()
+            .copied()
+            .find(|format| format.name() == name)
+    }
+
+    fn has_alpha(&self) -> bool {
+        match *self {
+            SchemeFileFormat::Native => true,
+            #[cfg(feature = "json")]
+            SchemeFileFormat::Json | SchemeFileFormat::DesignTokens => true,
+            _ => false,
+        }
+    }
+}
+
+impl Display for SchemeFileFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+#[derive(PartialEq, Debug, Clone, Default)]
+pub struct TranscodeOptions {
+    // how translucent colors are written in formats without alpha.
+    // AlphaPolicy::Error fails with LossyTranscode instead
+    pub alpha: AlphaPolicy,
+    // the slots of SchemeFileFormat::I3
+    pub i3: I3Mapping,
+    // fail with LossyTranscode rather than lose anything
+    pub strict: bool,
+}
+
+#[derive(PartialEq, Debug, Clone)]
+pub enum Lossy {
+    // a translucent entry written opaque, as the AlphaPolicy makes it
+    AlphaDropped { entry: EntryKey, alpha: u8 },
+    // an entry the format has no place for, it isn't written
+    NoSlot { entry: EntryKey },
+    // a name the format can't hold as it is, written as `to`
+    Slugified { from: String, to: String },
+}
+
+impl Display for Lossy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Lossy::AlphaDropped { entry, alpha } => {
+                write!(f, "{} loses its alpha {}", entry, alpha)
+            }
+            Lossy::NoSlot { entry } => write!(f, "{} has no place in the format", entry),
+            Lossy::Slugified { from, to } => write!(f, "{} is written as {}", from, to),
+        }
+    }
+}
+
+// what a transcode lost, by entry in the order of the scheme read. the
+// renamed ones after the others
+#[derive(PartialEq, Debug, Clone, Default)]
+pub struct TranscodeReport {
+    pub lossy: Vec<Lossy>,
+}
+
+impl TranscodeReport {
+    pub fn is_lossless(&self) -> bool {
+        self.lossy.is_empty()
+    }
+}
+
+type Reader = fn(&str) -> Result<Scheme, SchemeReaderError>;
+
+// json schemes first, then design tokens
+#[cfg(feature = "json")]
+fn parse_json(path: &str) -> Result<Scheme, SchemeReaderError> {
+    match super::json_scheme::parse(path) {
+        Err(SchemeReaderError::InvalidJsonScheme { .. }) => {
+            debug_event!(
+                path = path, from = "json", to = "design-tokens";
+                "{} isn't a json scheme, trying design tokens", path
+            );
+            super::tokens::parse(path)
+        }
+        result => result,
+    }
+}
+
+// .ase is Adobe swatches and Aseprite sprites alike, the swatches start
+// with `ASEF`
+#[cfg(feature = "aseprite")]
+fn parse_ase(path: &str) -> Result<Scheme, SchemeReaderError> {
+    let mut signature = [0; 4];
+    let read = fs::File::open(path).and_then(|mut file| file.read_exact(&mut signature));
+    if read.is_err() || &signature == b"ASEF" {
+        return ase::parse(path);
+    }
+    debug_event!(
+        path = path, from = "ase", to = "aseprite";
+        "{} isn't an ASE swatch file, trying an Aseprite sprite", path
+    );
+    super::aseprite::parse(path)
+}
+
+// the reader and format name for an extension
+fn by_extension(extension: &str) -> Option<(&'static str, Reader)> {
+    let reader: (&str, Reader) = match extension {
+        "yaml" | "yml" => ("base16", base16::parse),
+        #[cfg(feature = "json")]
+        "json" => ("json", parse_json),
+        "act" => ("act", binary::parse_act),
+        "pal" => ("pal", binary::parse_pal),
+        "png" => ("png", png_palette::parse),
+        #[cfg(feature = "aseprite")]
+        "aseprite" => ("aseprite", super::aseprite::parse),
+        #[cfg(feature = "aseprite")]
+        "ase" => ("ase", parse_ase),
+        #[cfg(not(feature = "aseprite"))]
+        "ase" => ("ase", ase::parse),
+        #[cfg(feature = "tmtheme")]
+        "tmtheme" => ("tmtheme", super::tmtheme::parse),
+        "vim" => ("vim", vim::parse),
+        "el" => ("emacs", emacs::parse),
+        _ => return None,
+    };
+    Some(reader)
+}
+
+// the scheme in `path`, read by the reader its extension names. other
+// files are i3 configs if they have `client.` lines, tmux configs if they
+// have `set -g` lines or tmux in their name, and native schemes otherwise
+pub fn parse_auto(path: &str) -> Result<Scheme, SchemeReaderError> {
+    let file = Path::new(path);
+    let extension = file.extension().unwrap_or_default();
+    let extension = extension.to_string_lossy().to_ascii_lowercase();
+    let ((format, parse), detected_by): ((&str, Reader), &str) = match by_extension(&extension) {
+        Some(reader) => (reader, "extension"),
+        None => {
+            let text =
+                fs::read_to_string(file).map_err(|e| SchemeReaderError::io(e, Some(file)))?;
+            let starts = |prefixes: &[&str]| {
+                let mut lines = text.lines().map(str::trim);
+                lines.any(|line| prefixes.iter().any(|prefix| line.starts_with(prefix)))
+            };
+            let name = file.file_name().unwrap_or_default().to_string_lossy();
+            if starts(&["client."]) {
+                (("i3", i3::parse), "content")
+            } else if name.contains("tmux") {
+                (("tmux", tmux::parse), "file name")
+            } else if starts(&["set -g", "set-option", "setw"]) {
+                (("tmux", tmux::parse), "content")
+            } else {
+                (("native", super::parse), "fallback")
+            }
+        }
+    };
+    debug_event!(
+        path = path, format = format, detected_by = detected_by;
+        "{} is read as {}, detected by {}", path, format, detected_by
+    );
+    parse(path)
+}
+
+// reads `input` with parse_auto and writes it to `output` in `format`
+pub fn transcode(
+    input: &str,
+    output: &str,
+    format: SchemeFileFormat,
+    options: &TranscodeOptions,
+) -> Result<TranscodeReport, SchemeReaderError> {
+    let scheme = parse_auto(input)?;
+    let (text, report) = transcode_scheme(&scheme, format, options)?;
+    let output = Path::new(output);
+    fs::write(output, text).map_err(|e| SchemeReaderError::io(e, Some(output)))?;
+    Ok(report)
+}
+
+// the text transcode writes, for writing somewhere other than a file
+pub fn transcode_scheme(
+    scheme: &Scheme,
+    format: SchemeFileFormat,
+    options: &TranscodeOptions,
+) -> Result<(String, TranscodeReport), SchemeReaderError> {
+    let entry_key = |i: usize| key(i, scheme.entries[i].name.as_deref());
+    let written = written(scheme, format, options);
+    let mut lossy = Vec::new();
+    for (i, entry) in scheme.entries.iter().enumerate() {
+        if !written.contains(&i) {
+            lossy.push(Lossy::NoSlot {
+                entry: entry_key(i),
+            });
+        } else if entry.color.a != u8::MAX && !format.has_alpha() {
+            lossy.push(Lossy::AlphaDropped {
+                entry: entry_key(i),
+                alpha: entry.color.a,
+            });
+        }
+    }
+
+    let renamed = match format {
+        SchemeFileFormat::Native => renamed(scheme, native_name, &mut lossy),
+        #[cfg(feature = "json")]
+        SchemeFileFormat::DesignTokens => renamed(scheme, token_name, &mut lossy),
+        _ => scheme.clone(),
+    };
+    let alpha_lost = lossy
+        .iter()
+        .any(|lost| matches!(lost, Lossy::AlphaDropped { .. }));
+    if (options.strict && !lossy.is_empty()) || (options.alpha == AlphaPolicy::Error && alpha_lost)
+    {
+        return Err(SchemeReaderError::LossyTranscode { format, lossy });
+    }
+
+    let scheme = match format.has_alpha() {
+        true => renamed,
+        false => opaque(&renamed, &options.alpha),
+    };
+    let text = match format {
+        SchemeFileFormat::Native => write_to_string(&scheme),
+        SchemeFileFormat::Xresources => paired(&scheme).0.to_xresources(),
+        SchemeFileFormat::Kitty => paired(&scheme).0.to_kitty(),
+        SchemeFileFormat::Gpl => scheme.to_gpl(),
+        SchemeFileFormat::I3 => paired(&scheme).0.to_i3(&options.i3),
+        #[cfg(feature = "json")]
+        SchemeFileFormat::Json => scheme.to_json(),
+        #[cfg(feature = "json")]
+        SchemeFileFormat::DesignTokens => scheme.to_design_tokens(),
+    };
+    Ok((text, TranscodeReport { lossy }))
+}
+
+// a scheme without roles that has the colors of base16::TERMINAL gets an
+// entry for each terminal color after its own, with the index of the entry
+// every entry comes from
+fn paired(scheme: &Scheme) -> (Scheme, Vec<usize>) {
+    let mut entries = scheme.entries.clone();
+    let mut origin: Vec<usize> = (0..entries.len()).collect();
+    let index = |name: &str| {
+        let names = scheme.entries.iter().map(|e| e.name.as_deref());
+        names.into_iter().position(|n| n == Some(name))
+    };
+    let layout: Option<Vec<usize>> = base16::TERMINAL
+        .iter()
+        .map(|(_, base)| index(base))
+        .collect();
+    if let (Some(layout), false) = (layout, scheme.entries.iter().any(|e| e.role.is_some())) {
+        for ((terminal, _), i) in base16::TERMINAL.iter().zip(layout) {
+            entries.push(Entry::named(terminal, scheme.entries[i].color));
+            origin.push(i);
+        }
+    }
+    let paired = Scheme::from_entries(scheme.name.clone(), scheme.author.clone(), entries);
+    (paired, origin)
+}
+
+// the entries of `scheme` the format writes, by index
+fn written(scheme: &Scheme, format: SchemeFileFormat, options: &TranscodeOptions) -> Vec<usize> {
+    let (paired, origin) = paired(scheme);
+    let used: Vec<usize> = match format {
+        SchemeFileFormat::Xresources | SchemeFileFormat::Kitty => role_entries(&paired)
+            .into_iter()
+            .filter(|&(role, _)| role != Role::Accent)
+            .map(|(_, i)| i)
+            .collect(),
+        SchemeFileFormat::I3 => i3_lines(&paired, &options.i3)
+            .into_iter()
+            .flat_map(|(_, slots)| slots)
+            .collect(),
+        // the first of several entries of the same name
+        #[cfg(feature = "json")]
+        SchemeFileFormat::DesignTokens => {
+            let renamed = renamed(scheme, token_name, &mut Vec::new());
+            let name = |i: usize| {
+                let entry: &Entry = &renamed.entries[i];
+                entry.name.clone().unwrap_or_else(|| i.to_string())
+            };
+            (0..scheme.entries.len())
+                .filter(|&i| (0..i).all(|earlier| name(earlier) != name(i)))
+                .collect()
+        }
+        _ => (0..scheme.entries.len()).collect(),
+    };
+    used.into_iter().map(|i| origin[i]).collect()
+}
+
+// every entry opaque as `alpha` makes it, Error as Drop
+fn opaque(scheme: &Scheme, alpha: &AlphaPolicy) -> Scheme {
+    let mut entries = scheme.entries.clone();
+    for (i, entry) in entries.iter_mut().enumerate() {
+        let drop = Canonical {
+            a: u8::MAX,
+            ..entry.color
+        };
+        let key = key(i, entry.name.as_deref());
+        entry.color = alpha.apply(key, &entry.color).unwrap_or(drop);
+    }
+    Scheme::from_entries(scheme.name.clone(), scheme.author.clone(), entries)
+}
+
+fn renamed(scheme: &Scheme, rename: fn(&str) -> String, lossy: &mut Vec<Lossy>) -> Scheme {
+    let mut entries = scheme.entries.clone();
+    for entry in &mut entries {
+        let Some(name) = entry.name.as_mut() else {
+            continue;
+        };
+        let to = rename(name);
+        if to != *name {
+            lossy.push(Lossy::Slugified {
+                from: std::mem::replace(name, to.clone()),
+                to,
+            });
+        }
+    }
+    Scheme::from_entries(scheme.name.clone(), scheme.author.clone(), entries)
+}
+
+// a `:` would end the name early and `//` start a comment, a leading `@`
+// makes a directive of the line
+fn native_name(name: &str) -> String {
+    let name = name.replace(':', "-").replace("//", "-");
+    name.trim().trim_start_matches('@').to_string()
+}
+
+// `.` separates groups, none of which may be empty, start with `$` or have
+// the braces of an alias in it
+#[cfg(feature = "json")]
+fn token_name(name: &str) -> String {
+    let segments: Vec<String> = name
+        .split('.')
+        .map(|segment| segment.trim_start_matches('$').replace(['{', '}'], "-"))
+        .filter(|segment| !segment.is_empty())
+        .collect();
+    segments.join(".")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::errors::ErrorKind;
+    use crate::reader::{parse_from_str, NameSource};
+    use crate::temp::TempDir;
+
+    // gruvbox dark, medium, as the base16 schemes repository has it
+    const GRUVBOX: &str = r##"scheme: "Gruvbox dark, medium"
+author: "Dawid Kurek (dawikur@gmail.com), morhetz (https://github.com/morhetz/gruvbox)"
+base00: "282828" # ----
+base01: "3c3836" # ---
+base02: "504945" # --
+base03: "665c54" # -
+base04: "bdae93" # +
+base05: "d5c4a1" # ++
+base06: "ebdbb2" # +++
+base07: "fbf1c7" # ++++
+base08: "fb4934" # red
+base09: "fe8019" # orange
+base0A: "fabd2f" # yellow
+base0B: "b8bb26" # green
+base0C: "8ec07c" # aqua/cyan
+base0D: "83a598" # blue
+base0E: "d3869b" # purple
+base0F: "d65d0e" # brown
+"##;
+
+    fn no_slot(names: &[&str]) -> Vec<Lossy> {
+        let entry = |name: &&str| EntryKey::Name(name.to_string());
+        names
+            .iter()
+            .map(|name| Lossy::NoSlot { entry: entry(name) })
+            .collect()
+    }
+
+    #[test]
+    fn test_transcode_base16_to_xresources() {
+        let dir = TempDir::new("transcode_base16_to_xresources");
+        let input = dir.join("cool_rs_gruvbox-dark-medium.yaml");
+        let output = dir.join("cool_rs_gruvbox.Xresources");
+        fs::write(&input, GRUVBOX).unwrap();
+        let (input, output) = (input.to_str().unwrap(), output.to_str().unwrap());
+        let options = TranscodeOptions::default();
+        let report = transcode(input, output, SchemeFileFormat::Xresources, &options).unwrap();
+        assert_eq!(
+            report.lossy,
+            no_slot(&["base01", "base02", "base04", "base06", "base09", "base0f"])
+        );
+        assert!(!report.is_lossless());
+
+        let text = fs::read_to_string(output).unwrap();
+        assert!(text.starts_with(
+            "! Gruvbox dark, medium\n*.background: #282828\n*.foreground: #d5c4a1\n\
+             *.cursorColor: #d5c4a1\n*.color0: #282828\n*.color1: #fb4934\n"
+        ));
+        assert!(text.ends_with("*.color14: #8ec07c\n*.color15: #fbf1c7\n"));
+
+        // the output reads back, every resource with the color of its slot
+        let written = parse_auto(output).unwrap();
+        let source = base16::parse(input).unwrap();
+        assert_eq!(written.name, "! Gruvbox dark, medium");
+        assert_eq!(written.len(), base16::TERMINAL.len());
+        let resource = |terminal: &str| match terminal {
+            "cursor" => "*.cursorColor".to_string(),
+            terminal => format!("*.{}", terminal),
+        };
+        for (terminal, base) in base16::TERMINAL {
+            assert_eq!(
+                written.get(&resource(terminal)),
+                source.get(base),
+                "{}",
+                terminal
+            );
+        }
+    }
+
+    #[test]
+    fn test_transcode_scheme() {
+        let entries = vec![
+            Entry::named("background", Canonical::new(0x28, 0x28, 0x28, 255)),
+            Entry::named("red", Canonical::new(0xcc, 0x24, 0x1d, 0x80)),
+            Entry::named("note: value", Canonical::new(255, 255, 255, 255)),
+            Entry::new(Canonical::new(0, 0, 0, 255)),
+        ];
+        let scheme = Scheme::from_entries("mine".into(), None, entries);
+        let options = TranscodeOptions::default();
+
+        // kitty has no place for the unnamed entry or the one without a role
+        let (text, report) = transcode_scheme(&scheme, SchemeFileFormat::Kitty, &options).unwrap();
+        assert_eq!(text, "# mine\nbackground #282828\ncolor1 #cc241d\n");
+        let alpha = Lossy::AlphaDropped {
+            entry: EntryKey::Name("red".into()),
+            alpha: 128,
+        };
+        let mut expected = vec![alpha.clone()];
+        expected.extend(no_slot(&["note: value"]));
+        expected.push(Lossy::NoSlot {
+            entry: EntryKey::Index(3),
+        });
+        assert_eq!(report.lossy, expected);
+        assert_eq!(report.lossy[0].to_string(), "red loses its alpha 128");
+
+        // the native format keeps alpha but not the `:` of a name
+        let (text, report) = transcode_scheme(&scheme, SchemeFileFormat::Native, &options).unwrap();
+        assert_eq!(
+            report.lossy,
+            [Lossy::Slugified {
+                from: "note: value".into(),
+                to: "note- value".into()
+            }]
+        );
+        let read = parse_from_str(NameSource::FirstLine, &text).unwrap();
+        assert_eq!(
+            read.get("red"),
+            Some(&Canonical::new(0xcc, 0x24, 0x1d, 0x80))
+        );
+        assert_eq!(
+            read.get("note- value"),
+            Some(&Canonical::new(255, 255, 255, 255))
+        );
+
+        // every entry has a row in a GIMP palette, opaque over the background
+        let over = TranscodeOptions {
+            alpha: AlphaPolicy::CompositeOver(Canonical::new(0x28, 0x28, 0x28, 255)),
+            ..Default::default()
+        };
+        let (text, report) = transcode_scheme(&scheme, SchemeFileFormat::Gpl, &over).unwrap();
+        assert_eq!(report.lossy, [alpha]);
+        assert!(text.contains("122  38  34\tred\n"), "{}", text);
+
+        let fail = TranscodeOptions {
+            alpha: AlphaPolicy::Error,
+            ..Default::default()
+        };
+        let e = transcode_scheme(&scheme, SchemeFileFormat::Gpl, &fail).unwrap_err();
+        assert_eq!(e.kind(), ErrorKind::LossyTranscode);
+        assert_eq!(
+            e.to_string(),
+            "Transcoding to gpl loses: red loses its alpha 128"
+        );
+        let strict = TranscodeOptions {
+            strict: true,
+            ..Default::default()
+        };
+        let e = transcode_scheme(&scheme, SchemeFileFormat::Native, &strict).unwrap_err();
+        assert_eq!(e.kind(), ErrorKind::LossyTranscode);
+        let opaque = Scheme::from_colors("x", vec![Canonical::new(1, 2, 3, 255)]);
+        let (_, report) = transcode_scheme(&opaque, SchemeFileFormat::Gpl, &strict).unwrap();
+        assert!(report.is_lossless());
+    }
+
+    #[test]
+    fn test_base16_to_i3() {
+        let scheme = base16::base16_scheme(GRUVBOX).unwrap();
+        let options = TranscodeOptions::default();
+        let (text, report) = transcode_scheme(&scheme, SchemeFileFormat::I3, &options).unwrap();
+        assert!(text.contains("client.focused #83a598 #83a598 #d5c4a1 #8ec07c #83a598\n"));
+        // the i3 layout has no bright white or dark gray
+        let lost: Vec<_> = report.lossy.iter().map(ToString::to_string).collect();
+        assert!(lost.contains(&"base07 has no place in the format".to_string()));
+        assert!(!lost.contains(&"base03 has no place in the format".to_string()));
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn test_design_tokens() {
+        let entries = vec![
+            Entry::named("$ui..bg", Canonical::new(0x28, 0x28, 0x28, 255)),
+            Entry::named("ui.bg", Canonical::new(0, 0, 0, 255)),
+        ];
+        let scheme = Scheme::from_entries("mine".into(), None, entries);
+        let options = TranscodeOptions::default();
+        let format = SchemeFileFormat::DesignTokens;
+        let (text, report) = transcode_scheme(&scheme, format, &options).unwrap();
+        assert_eq!(
+            report.lossy,
+            [
+                Lossy::NoSlot {
+                    entry: EntryKey::Name("ui.bg".into())
+                },
+                Lossy::Slugified {
+                    from: "$ui..bg".into(),
+                    to: "ui.bg".into()
+                },
+            ]
+        );
+        let read = crate::reader::tokens::parse_str("mine", &text).unwrap();
+        assert_eq!(
+            read.get("ui.bg"),
+            Some(&Canonical::new(0x28, 0x28, 0x28, 255))
+        );
+    }
+
+    #[test]
+    fn test_formats() {
+        for &format in SchemeFileFormat::ALL {
+            assert_eq!(SchemeFileFormat::from_name(format.name()), Some(format));
+        }
+        assert_eq!(
+            SchemeFileFormat::from_name(" Kitty "),
+            Some(SchemeFileFormat::Kitty)
+        );
+        assert_eq!(SchemeFileFormat::from_name("png"), None);
+    }
+
+    #[test]
+    fn test_parse_auto() {
+        let dir = TempDir::new("transcode_parse_auto");
+        let cases = [
+            (
+                "cool_rs_auto.vim",
+                "let g:colors_name = 'x'\nhi Normal guifg=#ffffff\n",
+            ),
+            (
+                "cool_rs_auto.el",
+                "(deftheme x)\n(custom-set-faces '(default ((t (:foreground \"#ffffff\")))))",
+            ),
+            (
+                "cool_rs_auto_i3",
+                "set $fg #ffffff\nclient.background $fg\n",
+            ),
+            ("cool_rs_auto.conf", "set -g status-style fg=#ffffff\n"),
+            ("cool_rs_auto.txt", "x\nwhite: #ffffff\n"),
+        ];
+        for (file, text) in cases {
+            let path = dir.join(file);
+            fs::write(&path, text).unwrap();
+            let scheme = parse_auto(path.to_str().unwrap()).unwrap();
+            assert_eq!(scheme.len(), 1, "{}", file);
+            assert_eq!(scheme.entries[0].color, Canonical::new(255, 255, 255, 255));
+        }
+    }
+
+    // .ase is read as Adobe swatches when it starts with `ASEF`, with the
+    // aseprite feature as well
+    #[test]
+    fn test_parse_auto_ase() {
+        let dir = TempDir::new("transcode_parse_auto_ase");
+        let mut swatches = b"ASEF\0\x01\0\0\0\0\0\x01".to_vec();
+        swatches.extend_from_slice(b"\0\x01\0\0\0\x16\0\x01\0\0RGB ");
+        swatches.extend_from_slice(&[0x3f, 0x80, 0, 0].repeat(3));
+        swatches.extend_from_slice(b"\0\x02");
+        let path = dir.write("cool_rs_auto.ase", swatches);
+        let scheme = parse_auto(&path).unwrap();
+        assert_eq!(scheme.entries, [Entry::new(Canonical::new(255, 255, 255, 255))]);
+    }
+}
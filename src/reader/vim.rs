@@ -0,0 +1,203 @@
+// the colors of a Vim or Neovim colorscheme, read off its `highlight` lines
+// without evaluating any VimL. every `hi Group guifg=#rrggbb` gives an entry
+// `Group.fg`, guibg `Group.bg` and guisp `Group.sp`. groups with only cterm
+// colors, `hi link` and `hi clear` give none, and neither do values that
+// aren't hex: NONE, fg, bg and the color names. lines starting with `\`
+// continue the line before them, as in Vim
+//
+// branches aren't followed, a group set in both the dark and the light half
+// of a scheme keeps its first position with the color of the last line, as
+// if every line ran. the scheme is named by `g:colors_name`
+use std::fs;
+use std::path::Path;
+
+use crate::color::{Entry, Scheme};
+use crate::errors::SchemeReaderError;
+use crate::formats::{ColorFormat, HexFormat};
+
+// the attribute and the suffix of its entries
+const ATTRIBUTES: [(&str, &str); 3] = [("guifg", "fg"), ("guibg", "bg"), ("guisp", "sp")];
+
+// logical lines, continuations joined and comments dropped
+fn lines(text: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for line in text.lines() {
+        let line = line.trim();
+        match (line.strip_prefix('\\'), lines.last_mut()) {
+            (Some(rest), Some(last)) => last.push_str(rest),
+            _ if line.starts_with('"') => {}
+            _ => lines.push(line.to_string()),
+        }
+    }
+    lines
+}
+
+// the group and arguments of a `hi` line that sets colors
+fn highlight(line: &str) -> Option<(&str, Vec<&str>)> {
+    let mut words = line.split_whitespace();
+    let command = words.next()?.trim_end_matches('!');
+    if !"highlight".starts_with(command) || command.len() < 2 {
+        return None;
+    }
+    let mut group = words.next()?;
+    if matches!(group, "def" | "default") {
+        group = words.next()?;
+    }
+    if matches!(group, "link" | "clear") {
+        return None;
+    }
+    // a `"` after the arguments starts a comment
+    let args = words.take_while(|word| !word.starts_with('"')).collect();
+    Some((group, args))
+}
+
+fn colors_name(line: &str) -> Option<&str> {
+    let rest = line.strip_prefix("let")?.trim_start();
+    let rest = rest
+        .strip_prefix("g:colors_name")
+        .or_else(|| rest.strip_prefix("colors_name"))?;
+    let value = rest.trim_start().strip_prefix('=')?.trim();
+    Some(value.trim_matches(|c| c == '"' || c == '\''))
+}
+
+// the entries in the order their groups first appear, the name empty
+// without g:colors_name
+pub fn vim_scheme(text: &str) -> Result<Scheme, SchemeReaderError> {
+    let mut name = None;
+    let mut entries: Vec<Entry> = Vec::new();
+    for line in lines(text) {
+        if let Some(colors) = colors_name(&line) {
+            name = Some(colors.to_string());
+            continue;
+        }
+        let Some((group, args)) = highlight(&line) else {
+            continue;
+        };
+        for arg in args {
+            let Some((key, value)) = arg.split_once('=') else {
+                continue;
+            };
+            let Some(&(_, suffix)) = ATTRIBUTES.iter().find(|(attr, _)| attr == &key) else {
+                continue;
+            };
+            let value = value.trim_matches('\'');
+            if !value.starts_with('#') {
+                continue;
+            }
+            let entry_name = format!("{}.{}", group, suffix);
+            let color =
+                HexFormat::parse(value).map_err(|source| SchemeReaderError::InvalidToken {
+                    token: entry_name.clone(),
+                    source,
+                })?;
+            match entries
+                .iter_mut()
+                .find(|e| e.name.as_deref() == Some(&entry_name))
+            {
+                Some(entry) => entry.color = color,
+                None => entries.push(Entry::named(&entry_name, color)),
+            }
+        }
+    }
+    Ok(Scheme::from_entries(
+        name.unwrap_or_default(),
+        None,
+        entries,
+    ))
+}
+
+// named after the file if there is no g:colors_name
+pub fn parse(path: &str) -> Result<Scheme, SchemeReaderError> {
+    let path = Path::new(path);
+    let text = fs::read_to_string(path).map_err(|e| SchemeReaderError::io(e, Some(path)))?;
+    let mut scheme = vim_scheme(&text)?;
+    if scheme.name.is_empty() {
+        let stem = path.file_stem().unwrap_or_default();
+        scheme.name = stem.to_string_lossy().into_owned();
+    }
+    Ok(scheme)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::Canonical;
+    use crate::errors::ErrorKind;
+    use crate::temp::TempDir;
+
+    // gruvbox, trimmed
+    const GRUVBOX: &str = r#"" ------------------------------------------------------------------
+" File: gruvbox.vim
+" Description: Retro groove color scheme for Vim
+" ------------------------------------------------------------------
+
+hi clear
+if exists("syntax_on")
+  syntax reset
+endif
+
+let g:colors_name='gruvbox'
+
+if &background ==# 'dark'
+  hi Normal guifg=#ebdbb2 guibg=#282828 ctermfg=223 ctermbg=235
+else
+  hi Normal guifg=#3c3836 guibg=#fbf1c7 ctermfg=237 ctermbg=229
+endif
+
+highlight! CursorLine guibg=#3c3836 cterm=NONE " the current line
+hi def Comment
+      \ guifg=#928374
+      \ gui=italic
+hi SpellBad guisp='#fb4934' gui=undercurl guifg=NONE
+hi Visual ctermbg=241
+hi link Conceal Comment
+" hi Ignored guifg=#000000
+hi Search guifg=bg guibg=#fabd2f
+exe 'hi Todo guifg=' . s:fg
+"#;
+
+    #[test]
+    fn test_vim_scheme() {
+        let dir = TempDir::new("vim_scheme");
+        let path = dir.join("cool_rs_vim.vim");
+        fs::write(&path, GRUVBOX).unwrap();
+        let scheme = parse(path.to_str().unwrap()).unwrap();
+        assert_eq!(scheme.name, "gruvbox");
+        let entries: Vec<_> = scheme
+            .entries
+            .iter()
+            .map(|e| (e.name.as_deref().unwrap(), e.color))
+            .collect();
+        let rgb = |hex: u32| Canonical::new((hex >> 16) as u8, (hex >> 8) as u8, hex as u8, 255);
+        assert_eq!(
+            entries,
+            [
+                // the light half comes last
+                ("Normal.fg", rgb(0x3c3836)),
+                ("Normal.bg", rgb(0xfbf1c7)),
+                ("CursorLine.bg", rgb(0x3c3836)),
+                ("Comment.fg", rgb(0x928374)),
+                ("SpellBad.sp", rgb(0xfb4934)),
+                ("Search.bg", rgb(0xfabd2f)),
+            ]
+        );
+
+        let unnamed = dir.join("cool_rs_vim_unnamed.vim");
+        fs::write(&unnamed, "hi Normal guifg=#ffffff\n").unwrap();
+        assert_eq!(
+            parse(unnamed.to_str().unwrap()).unwrap().name,
+            "cool_rs_vim_unnamed"
+        );
+    }
+
+    #[test]
+    fn test_invalid_hex() {
+        match vim_scheme("hi Normal guifg=#ebdbb guibg=#282828") {
+            Err(e @ SchemeReaderError::InvalidToken { .. }) => {
+                assert_eq!(e.kind(), ErrorKind::InvalidToken);
+                assert!(e.to_string().contains("Normal.fg"), "{}", e);
+            }
+            other => panic!("unexpected result {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,136 @@
+use std::io;
+use std::path::Path;
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+
+use super::{parse_with, ReaderOptions};
+use crate::color::Scheme;
+use crate::errors::SchemeReaderError;
+
+// how long a file has to stay untouched before it is parsed again. editors
+// often write several times in a row or write elsewhere and rename
+pub const DEBOUNCE: Duration = Duration::from_millis(100);
+
+// the file is watched until this is dropped
+pub struct WatchHandle {
+    watcher: Option<RecommendedWatcher>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl Drop for WatchHandle {
+    fn drop(&mut self) {
+        // dropping the watcher closes the event channel, which ends the thread
+        self.watcher.take();
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+pub fn watch<F>(path: &str, callback: F) -> Result<WatchHandle, SchemeReaderError>
+where
+    F: FnMut(Result<Scheme, SchemeReaderError>) + Send + 'static,
+{
+    watch_with(path, ReaderOptions::default(), callback)
+}
+
+// calls `callback` with the freshly parsed scheme, or the error, every time
+// the file changes. the parent directory is watched so the file may also be
+// replaced by a rename
+pub fn watch_with<F>(
+    path: &str,
+    options: ReaderOptions,
+    mut callback: F,
+) -> Result<WatchHandle, SchemeReaderError>
+where
+    F: FnMut(Result<Scheme, SchemeReaderError>) + Send + 'static,
+{
+    let file = Path::new(path)
+        .canonicalize()
+        .map_err(|e| SchemeReaderError::io(e, Some(Path::new(path))))?;
+    let dir = file
+        .parent()
+        .unwrap_or_else(|| Path::new("/"))
+        .to_path_buf();
+    let watch_error = |e: notify::Error| SchemeReaderError::IOError {
+        path: Some(file.clone()),
+        source: io::Error::other(e),
+    };
+
+    let (tx, rx) = mpsc::channel();
+    let mut watcher = notify::recommended_watcher(move |event: notify::Result<Event>| {
+        let _ = tx.send(event);
+    })
+    .map_err(watch_error)?;
+    watcher
+        .watch(&dir, RecursiveMode::NonRecursive)
+        .map_err(watch_error)?;
+
+    let thread = thread::spawn(move || loop {
+        match rx.recv() {
+            Ok(Ok(event)) if is_change_to(&event, &file) => {}
+            Ok(_) => continue,
+            Err(_) => return,
+        }
+        loop {
+            match rx.recv_timeout(DEBOUNCE) {
+                Ok(_) => continue,
+                Err(RecvTimeoutError::Timeout) => break,
+                Err(RecvTimeoutError::Disconnected) => return,
+            }
+        }
+        callback(parse_with(file.to_str().unwrap_or_default(), &options));
+    });
+
+    Ok(WatchHandle {
+        watcher: Some(watcher),
+        thread: Some(thread),
+    })
+}
+
+// reading the file while parsing causes access events, those are ignored
+fn is_change_to(event: &Event, file: &Path) -> bool {
+    matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_))
+        && event.paths.iter().any(|path| path == file)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+    use crate::temp::TempDir;
+
+    #[test]
+    fn test_watch() {
+        let dir = TempDir::new("watch");
+        let path = dir.join("theme.txt");
+        fs::write(&path, "theme\n#000000\n").unwrap();
+
+        let (tx, rx) = mpsc::channel();
+        let handle = watch(path.to_str().unwrap(), move |scheme| {
+            tx.send(scheme).unwrap();
+        })
+        .unwrap();
+
+        let timeout = Duration::from_secs(5);
+        fs::write(&path, "theme\n#111111\n").unwrap();
+        let first = rx.recv_timeout(timeout).unwrap().unwrap();
+        assert_eq!(first.colors().next().unwrap().r, 0x11);
+
+        fs::write(&path, "theme\n#222222\n#333333\n").unwrap();
+        let second = rx.recv_timeout(timeout).unwrap().unwrap();
+        assert_eq!(second.len(), 2);
+
+        // parse errors are reported and watching goes on
+        fs::write(&path, "theme\nnot a color\n").unwrap();
+        assert!(rx.recv_timeout(timeout).unwrap().is_err());
+
+        drop(handle);
+        fs::write(&path, "theme\n#444444\n").unwrap();
+        assert!(rx.recv_timeout(DEBOUNCE * 5).is_err());
+    }
+}
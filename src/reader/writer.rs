@@ -0,0 +1,92 @@
+// schemes back into the native format. every writer gives the same text for
+// the same scheme, however it was written before: the name, the author,
+// then an entry per line as `name: #rrggbb` with its comment on the lines
+// above. colors are hex, with alpha if they aren't opaque. roles aren't
+// written, the reader infers them from the names again. use SchemeDocument
+// to change a file without rewriting it
+use std::fmt::Write;
+use std::fs;
+use std::path::Path;
+
+use crate::color::diff::hex;
+use crate::color::Scheme;
+use crate::errors::SchemeReaderError;
+
+pub fn write_to_string(scheme: &Scheme) -> String {
+    let mut text = format!("{}\n", scheme.name);
+    if let Some(author) = &scheme.author {
+        let _ = writeln!(text, "@author: {}", author);
+    }
+    for entry in &scheme.entries {
+        for line in entry.comment.iter().flat_map(|c| c.lines()) {
+            match line.is_empty() {
+                true => text.push_str("//\n"),
+                false => {
+                    let _ = writeln!(text, "// {}", line);
+                }
+            }
+        }
+        match &entry.name {
+            Some(name) => {
+                let _ = writeln!(text, "{}: {}", name, hex(&entry.color));
+            }
+            None => {
+                let _ = writeln!(text, "{}", hex(&entry.color));
+            }
+        }
+    }
+    text
+}
+
+pub fn write(path: &str, scheme: &Scheme) -> Result<(), SchemeReaderError> {
+    let path = Path::new(path);
+    fs::write(path, write_to_string(scheme)).map_err(|e| SchemeReaderError::io(e, Some(path)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::{Canonical, Entry};
+    use crate::reader::{parse_from_str, NameSource};
+
+    #[test]
+    fn test_write_to_string() {
+        let text = "mine\n@author: someone\n//used for errors\n\
+                    red:  rgb(204, 36, 29);   // keep red-ish\n\
+                    // about blue\n//\n// and more\n\n\
+                    blue: #458588\n#ff000080\n";
+        let scheme = parse_from_str(NameSource::FirstLine, text).unwrap();
+        let written = write_to_string(&scheme);
+        assert_eq!(
+            written,
+            "mine
+@author: someone
+// used for errors
+// keep red-ish
+red: #cc241d
+blue: #458588
+#ff000080
+"
+        );
+        // the comment of blue is lost to the blank line, and what is written
+        // reads back the same
+        assert_eq!(scheme.entries[1].comment, None);
+        assert_eq!(
+            parse_from_str(NameSource::FirstLine, &written).unwrap(),
+            scheme
+        );
+
+        let mut scheme = Scheme::new("made");
+        scheme
+            .entries
+            .push(Entry::new(Canonical::new(0, 0, 0, 255)).with_comment("a\n\nb"));
+        let written = write_to_string(&scheme);
+        assert_eq!(written, "made\n// a\n//\n// b\n#000000\n");
+        assert_eq!(
+            parse_from_str(NameSource::FirstLine, &written)
+                .unwrap()
+                .entries,
+            scheme.entries
+        );
+    }
+}
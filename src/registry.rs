@@ -0,0 +1,134 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+use crate::color::Scheme;
+use crate::errors::SchemeReaderError;
+use crate::reader;
+
+lazy_static! {
+    static ref REGISTRY: SchemeRegistry = SchemeRegistry::new();
+}
+
+// a process wide registry for callers that don't want to pass one around
+pub fn registry() -> &'static SchemeRegistry {
+    &REGISTRY
+}
+
+#[derive(Default)]
+struct Cache {
+    schemes: HashMap<String, Arc<Scheme>>,
+    // the scheme name every file loaded so far resolved to
+    paths: HashMap<PathBuf, String>,
+}
+
+// schemes shared by name across threads
+#[derive(Default)]
+pub struct SchemeRegistry {
+    cache: RwLock<Cache>,
+}
+
+impl SchemeRegistry {
+    pub fn new() -> Self {
+        SchemeRegistry::default()
+    }
+
+    // the file is only read if it wasn't loaded before or its scheme has been
+    // invalidated since
+    pub fn load_or_parse(&self, path: &str) -> Result<Arc<Scheme>, SchemeReaderError> {
+        let key = Path::new(path).to_path_buf();
+        {
+            let cache = self.read();
+            if let Some(scheme) = cache
+                .paths
+                .get(&key)
+                .and_then(|name| cache.schemes.get(name))
+            {
+                debug_event!("{} is cached as {}", path, scheme.name);
+                return Ok(scheme.clone());
+            }
+        }
+
+        let scheme = Arc::new(reader::parse(path)?);
+        let mut cache = self.write();
+        cache.paths.insert(key, scheme.name.clone());
+        cache.schemes.insert(scheme.name.clone(), scheme.clone());
+        Ok(scheme)
+    }
+
+    pub fn get(&self, name: &str) -> Option<Arc<Scheme>> {
+        self.read().schemes.get(name).cloned()
+    }
+
+    // returns the scheme previously registered under the same name
+    pub fn insert(&self, scheme: Scheme) -> Option<Arc<Scheme>> {
+        self.write()
+            .schemes
+            .insert(scheme.name.clone(), Arc::new(scheme))
+    }
+
+    // forgets the scheme, so the next load_or_parse of its file reads it again
+    pub fn invalidate(&self, name: &str) -> Option<Arc<Scheme>> {
+        let mut cache = self.write();
+        cache.paths.retain(|_, loaded| loaded != name);
+        cache.schemes.remove(name)
+    }
+
+    // a panic while holding the lock can't leave the maps inconsistent, so
+    // poisoning is ignored
+    fn read(&self) -> RwLockReadGuard<'_, Cache> {
+        self.cache.read().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn write(&self) -> RwLockWriteGuard<'_, Cache> {
+        self.cache.write().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, thread};
+
+    use super::*;
+    use crate::color::Canonical;
+    use crate::temp::TempDir;
+
+    #[test]
+    fn test_load_or_parse_caches() {
+        let dir = TempDir::new("registry_cached");
+        let path = &dir.write("cool_rs_registry_cached.txt", "cached\n#000000\n");
+
+        let registry = SchemeRegistry::new();
+        let first = registry.load_or_parse(path).unwrap();
+        fs::remove_file(path).unwrap();
+
+        let second = registry.load_or_parse(path).unwrap();
+        assert!(Arc::ptr_eq(&first, &second));
+        assert!(Arc::ptr_eq(&registry.get("cached").unwrap(), &first));
+
+        assert!(registry.invalidate("cached").is_some());
+        assert!(registry.get("cached").is_none());
+        assert!(registry.load_or_parse(path).is_err());
+    }
+
+    #[test]
+    fn test_insert() {
+        let registry = SchemeRegistry::new();
+        let scheme = Scheme::from_colors("mono", vec![Canonical::new(0, 0, 0, 255)]);
+        assert!(registry.insert(scheme.clone()).is_none());
+        assert_eq!(*registry.insert(Scheme::new("mono")).unwrap(), scheme);
+        assert!(registry.get("mono").unwrap().is_empty());
+        assert!(registry.invalidate("other").is_none());
+    }
+
+    #[test]
+    fn test_shared_between_threads() {
+        registry().insert(Scheme::new("cool_rs_registry_global"));
+        let handles: Vec<_> = (0..4)
+            .map(|_| thread::spawn(|| registry().get("cool_rs_registry_global").is_some()))
+            .collect();
+        for handle in handles {
+            assert!(handle.join().unwrap());
+        }
+    }
+}
@@ -0,0 +1,256 @@
+use crate::color::{Canonical, Entry, Scheme};
+
+type Table = [(&'static str, Canonical)];
+type Constructor = fn() -> Scheme;
+
+const fn rgb(hex: u32) -> Canonical {
+    Canonical::new((hex >> 16) as u8, (hex >> 8) as u8, hex as u8, u8::MAX)
+}
+
+const SOLARIZED: [(&str, Canonical); 16] = [
+    ("base03", rgb(0x002b36)),
+    ("base02", rgb(0x073642)),
+    ("base01", rgb(0x586e75)),
+    ("base00", rgb(0x657b83)),
+    ("base0", rgb(0x839496)),
+    ("base1", rgb(0x93a1a1)),
+    ("base2", rgb(0xeee8d5)),
+    ("base3", rgb(0xfdf6e3)),
+    ("yellow", rgb(0xb58900)),
+    ("orange", rgb(0xcb4b16)),
+    ("red", rgb(0xdc322f)),
+    ("magenta", rgb(0xd33682)),
+    ("violet", rgb(0x6c71c4)),
+    ("blue", rgb(0x268bd2)),
+    ("cyan", rgb(0x2aa198)),
+    ("green", rgb(0x859900)),
+];
+
+// base16 gruvbox dark, medium contrast
+const GRUVBOX_DARK: [(&str, Canonical); 16] = [
+    ("base00", rgb(0x282828)),
+    ("base01", rgb(0x3c3836)),
+    ("base02", rgb(0x504945)),
+    ("base03", rgb(0x665c54)),
+    ("base04", rgb(0xbdae93)),
+    ("base05", rgb(0xd5c4a1)),
+    ("base06", rgb(0xebdbb2)),
+    ("base07", rgb(0xfbf1c7)),
+    ("base08", rgb(0xfb4934)),
+    ("base09", rgb(0xfe8019)),
+    ("base0A", rgb(0xfabd2f)),
+    ("base0B", rgb(0xb8bb26)),
+    ("base0C", rgb(0x8ec07c)),
+    ("base0D", rgb(0x83a598)),
+    ("base0E", rgb(0xd3869b)),
+    ("base0F", rgb(0xd65d0e)),
+];
+
+const NORD: [(&str, Canonical); 16] = [
+    ("nord0", rgb(0x2e3440)),
+    ("nord1", rgb(0x3b4252)),
+    ("nord2", rgb(0x434c5e)),
+    ("nord3", rgb(0x4c566a)),
+    ("nord4", rgb(0xd8dee9)),
+    ("nord5", rgb(0xe5e9f0)),
+    ("nord6", rgb(0xeceff4)),
+    ("nord7", rgb(0x8fbcbb)),
+    ("nord8", rgb(0x88c0d0)),
+    ("nord9", rgb(0x81a1c1)),
+    ("nord10", rgb(0x5e81ac)),
+    ("nord11", rgb(0xbf616a)),
+    ("nord12", rgb(0xd08770)),
+    ("nord13", rgb(0xebcb8b)),
+    ("nord14", rgb(0xa3be8c)),
+    ("nord15", rgb(0xb48ead)),
+];
+
+const DRACULA: [(&str, Canonical); 11] = [
+    ("background", rgb(0x282a36)),
+    ("current_line", rgb(0x44475a)),
+    ("foreground", rgb(0xf8f8f2)),
+    ("comment", rgb(0x6272a4)),
+    ("cyan", rgb(0x8be9fd)),
+    ("green", rgb(0x50fa7b)),
+    ("orange", rgb(0xffb86c)),
+    ("pink", rgb(0xff79c6)),
+    ("purple", rgb(0xbd93f9)),
+    ("red", rgb(0xff5555)),
+    ("yellow", rgb(0xf1fa8c)),
+];
+
+const REGISTRY: [(&str, Constructor); 5] = [
+    ("solarized-dark", solarized_dark),
+    ("solarized-light", solarized_light),
+    ("gruvbox-dark", gruvbox_dark),
+    ("nord", nord),
+    ("dracula", dracula),
+];
+
+fn from_table(name: &str, table: &Table) -> Scheme {
+    Scheme::from_entries(
+        name.into(),
+        None,
+        table
+            .iter()
+            .map(|(entry, color)| Entry::named(entry, *color))
+            .collect(),
+    )
+}
+
+// solarized has a single palette for both variants, they only differ in
+// which of the base colors are used for background and foreground
+fn solarized(name: &str, background: &str, foreground: &str) -> Scheme {
+    let mut scheme = from_table(name, &SOLARIZED);
+    let background = *scheme.get(background).unwrap();
+    let foreground = *scheme.get(foreground).unwrap();
+    scheme.entries.push(Entry::named("background", background));
+    scheme.entries.push(Entry::named("foreground", foreground));
+    scheme
+}
+
+pub fn solarized_dark() -> Scheme {
+    solarized("solarized-dark", "base03", "base0")
+}
+
+pub fn solarized_light() -> Scheme {
+    solarized("solarized-light", "base3", "base00")
+}
+
+pub fn gruvbox_dark() -> Scheme {
+    from_table("gruvbox-dark", &GRUVBOX_DARK)
+}
+
+pub fn nord() -> Scheme {
+    from_table("nord", &NORD)
+}
+
+pub fn dracula() -> Scheme {
+    from_table("dracula", &DRACULA)
+}
+
+pub fn all() -> Vec<Scheme> {
+    REGISTRY.iter().map(|(_, scheme)| scheme()).collect()
+}
+
+// case insensitive lookup of a builtin scheme
+pub fn by_name(name: &str) -> Option<Scheme> {
+    REGISTRY
+        .iter()
+        .find(|(builtin, _)| builtin.eq_ignore_ascii_case(name))
+        .map(|(_, scheme)| scheme())
+}
+
+// the candidates ranked by Scheme::similarity to `target`, most similar
+// first. equal scores keep the candidates' order
+pub fn most_similar<'a>(target: &Scheme, candidates: &'a [Scheme]) -> Vec<(&'a Scheme, f32)> {
+    let mut ranked: Vec<_> = candidates
+        .iter()
+        .map(|candidate| (candidate, target.similarity(candidate)))
+        .collect();
+    ranked.sort_by(|a, b| b.1.total_cmp(&a.1));
+    ranked
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_known_values() {
+        let solarized = solarized_dark();
+        assert_eq!(solarized.get("base03"), Some(&rgb(0x002b36)));
+        assert_eq!(solarized.get("background"), Some(&rgb(0x002b36)));
+        assert_eq!(solarized_light().get("background"), Some(&rgb(0xfdf6e3)));
+        assert_eq!(gruvbox_dark().get("base09"), Some(&rgb(0xfe8019)));
+        assert_eq!(nord().get("nord8"), Some(&rgb(0x88c0d0)));
+        assert_eq!(dracula().get("purple"), Some(&rgb(0xbd93f9)));
+        assert_eq!(rgb(0x002b36), Canonical::new(0x00, 0x2b, 0x36, 0xff));
+    }
+
+    #[test]
+    fn test_registry() {
+        let all = all();
+        assert_eq!(all.len(), REGISTRY.len());
+        for scheme in &all {
+            assert_eq!(by_name(&scheme.name).as_ref(), Some(scheme));
+        }
+        assert_eq!(by_name("Solarized-Dark"), Some(solarized_dark()));
+        assert_eq!(by_name("monokai"), None);
+    }
+
+    #[test]
+    fn test_most_similar() {
+        let candidates = all();
+        let names = |target: &Scheme| {
+            most_similar(target, &candidates)
+                .into_iter()
+                .map(|(scheme, _)| scheme.name.as_str())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            names(&solarized_dark()),
+            ["solarized-dark", "solarized-light", "gruvbox-dark", "nord", "dracula"]
+        );
+        // the solarized variants tie, and keep the registry's order
+        assert_eq!(
+            names(&nord()),
+            ["nord", "dracula", "gruvbox-dark", "solarized-dark", "solarized-light"]
+        );
+
+        let ranked = most_similar(&dracula(), &candidates);
+        assert!((ranked[0].1 - 1.0).abs() < 0.0001);
+        assert!(ranked.windows(2).all(|w| w[0].1 >= w[1].1));
+        assert!(most_similar(&dracula(), &[]).is_empty());
+    }
+
+    #[test]
+    fn test_classify() {
+        use crate::color::EntryClass;
+
+        let names = |entries: Vec<&Entry>| {
+            entries
+                .iter()
+                .map(|e| e.name.clone().unwrap())
+                .collect::<Vec<_>>()
+        };
+        let gruvbox = gruvbox_dark();
+        let classification = gruvbox.classify();
+        assert_eq!(
+            names(classification.accents()),
+            ["base08", "base09", "base0A", "base0B", "base0C", "base0D", "base0E", "base0F"]
+        );
+        assert_eq!(
+            names(classification.entries(EntryClass::Background)),
+            ["base00", "base01", "base02"]
+        );
+        assert_eq!(
+            names(classification.entries(EntryClass::Foreground)),
+            ["base05", "base06", "base07"]
+        );
+        assert_eq!(names(classification.neutrals()), ["base03", "base04"]);
+
+        for solarized in [solarized_dark(), solarized_light()] {
+            let classification = solarized.classify();
+            assert_eq!(
+                names(classification.accents()),
+                ["yellow", "orange", "red", "magenta", "violet", "blue", "cyan", "green"]
+            );
+            assert_eq!(
+                classification.class(solarized.position("background").unwrap()),
+                Some(EntryClass::Background)
+            );
+        }
+        // the same palette, only the background differs
+        let dark = solarized_dark();
+        assert_eq!(
+            names(dark.classify().entries(EntryClass::Background)),
+            ["base03", "base02", "background"]
+        );
+        let light = solarized_light();
+        assert_eq!(
+            names(light.classify().entries(EntryClass::Background)),
+            ["base2", "base3", "background"]
+        );
+    }
+}
@@ -0,0 +1,62 @@
+// inputs shared by the benches and the perf tests, included by path from
+// both so they measure the same thing. each user takes only some of them
+#![allow(dead_code)]
+
+use cool_rs::color::Canonical;
+
+// a scheme file of `lines` named hex entries, every color different up to
+// 2^24 lines
+pub fn palette(lines: usize) -> String {
+    let mut palette = String::from("large\n");
+    for i in 0..lines {
+        palette.push_str(&format!(
+            "color{}: #{:02x}{:02x}{:02x}\n",
+            i,
+            i % 256,
+            i / 256 % 256,
+            i / 65536
+        ));
+    }
+    palette
+}
+
+// n colors spread over the cube, with some translucent ones
+pub fn colors(n: usize) -> Vec<Canonical> {
+    (0..n)
+        .map(|i| {
+            let i = (i as u32).wrapping_mul(2_654_435_761);
+            Canonical::new(
+                (i >> 24) as u8,
+                (i >> 16) as u8,
+                (i >> 8) as u8,
+                i as u8 | 0x80,
+            )
+        })
+        .collect()
+}
+
+// the same colors as rgba() with u8 channels and with float channels, for
+// the byte scanner and the regex parser
+pub fn rgb_strings(n: usize) -> (Vec<String>, Vec<String>) {
+    colors(n)
+        .iter()
+        .map(|c| {
+            let u8s = format!("rgba({}, {}, {}, {})", c.r, c.g, c.b, c.a);
+            let floats = format!(
+                "rgba({}, {}, {}, {})",
+                c.r as f32 / 255.0,
+                c.g as f32 / 255.0,
+                c.b as f32 / 255.0,
+                c.a as f32 / 255.0
+            );
+            (u8s, floats)
+        })
+        .unzip()
+}
+
+pub fn hex_strings(n: usize) -> Vec<String> {
+    colors(n)
+        .iter()
+        .map(|c| format!("#{:02x}{:02x}{:02x}{:02x}", c.r, c.g, c.b, c.a))
+        .collect()
+}
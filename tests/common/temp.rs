@@ -0,0 +1,58 @@
+// a directory of its own for a test, included by path from the unit tests
+// and the integration tests. tests running side by side, in one process or
+// in two checkouts, never share one, and it is removed when dropped
+#![allow(dead_code)]
+
+use std::env;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+static COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+pub struct TempDir {
+    path: PathBuf,
+}
+
+impl TempDir {
+    // `cool_rs_<name>_<pid>_<n>` under the system temp dir. a directory left
+    // over by an earlier process with the same pid is skipped, not reused
+    pub fn new(name: &str) -> TempDir {
+        loop {
+            let n = COUNTER.fetch_add(1, Ordering::Relaxed);
+            let file_name = format!("cool_rs_{}_{}_{}", name, process::id(), n);
+            let path = env::temp_dir().join(file_name);
+            match fs::create_dir(&path) {
+                Ok(()) => return TempDir { path },
+                Err(e) if e.kind() == ErrorKind::AlreadyExists => continue,
+                Err(e) => panic!("can't create {}: {}", path.display(), e),
+            }
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn join(&self, file: impl AsRef<Path>) -> PathBuf {
+        self.path.join(file)
+    }
+
+    // `contents` written to `file` in the directory, its path as a string
+    pub fn write(&self, file: impl AsRef<Path>, contents: impl AsRef<[u8]>) -> String {
+        let path = self.join(file);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).unwrap();
+        }
+        fs::write(&path, contents).unwrap();
+        path.to_str().unwrap().to_string()
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.path);
+    }
+}
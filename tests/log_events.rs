@@ -0,0 +1,145 @@
+#![cfg(feature = "log")]
+
+#[path = "common/temp.rs"]
+mod temp;
+
+use std::sync::{Mutex, MutexGuard, Once};
+
+use cool_rs::reader::{parse_auto, parse_with, ReaderOptions};
+use log::kv::{Error, Key, Value, VisitSource};
+use log::{Level, LevelFilter, Log, Metadata, Record};
+use temp::TempDir;
+
+// the message and the fields of a debug event
+#[derive(Debug)]
+struct Event {
+    message: String,
+    fields: Vec<(String, String)>,
+}
+
+impl Event {
+    fn field(&self, key: &str) -> Option<&str> {
+        let field = self.fields.iter().find(|(k, _)| k == key);
+        field.map(|(_, value)| value.as_str())
+    }
+}
+
+struct Fields<'a>(&'a mut Vec<(String, String)>);
+
+impl<'kvs> VisitSource<'kvs> for Fields<'_> {
+    fn visit_pair(&mut self, key: Key<'kvs>, value: Value<'kvs>) -> Result<(), Error> {
+        self.0.push((key.to_string(), value.to_string()));
+        Ok(())
+    }
+}
+
+// collects the crate's debug events
+struct Capture(Mutex<Vec<Event>>);
+
+impl Log for Capture {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.target() == "cool_rs" && metadata.level() <= Level::Debug
+    }
+
+    fn log(&self, record: &Record) {
+        if self.enabled(record.metadata()) {
+            let mut fields = Vec::new();
+            record.key_values().visit(&mut Fields(&mut fields)).unwrap();
+            self.0.lock().unwrap().push(Event {
+                message: record.args().to_string(),
+                fields,
+            });
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+static CAPTURE: Capture = Capture(Mutex::new(Vec::new()));
+static INSTALL: Once = Once::new();
+// the logger is global, the tests take turns
+static SERIAL: Mutex<()> = Mutex::new(());
+
+// the events of the test holding the guard, none before it
+fn capture() -> MutexGuard<'static, ()> {
+    let guard = SERIAL.lock().unwrap_or_else(|e| e.into_inner());
+    INSTALL.call_once(|| {
+        log::set_logger(&CAPTURE).unwrap();
+        log::set_max_level(LevelFilter::Debug);
+    });
+    CAPTURE.0.lock().unwrap().clear();
+    guard
+}
+
+#[test]
+fn test_lenient_skip_events() {
+    let _guard = capture();
+
+    let dir = TempDir::new("log_lenient");
+    let path = dir.write("lenient.txt", "bad\n#000000\nnot a color\nfg: #zz\n");
+    let path = path.as_str();
+    assert_eq!(
+        parse_with(path, &ReaderOptions::lenient()).unwrap().len(),
+        1
+    );
+
+    let events = CAPTURE.0.lock().unwrap();
+    let skipped: Vec<_> = events
+        .iter()
+        .filter(|e| e.message.starts_with("skipping line"))
+        .collect();
+    assert_eq!(skipped.len(), 2, "{:?}", events);
+    assert!(skipped[0].message.starts_with("skipping line 3: "));
+    assert_eq!(skipped[0].field("line"), Some("3"));
+    assert_eq!(skipped[1].field("line"), Some("4"));
+    assert!(skipped[1].field("error").is_some_and(|e| !e.is_empty()));
+
+    let first = events.first().unwrap();
+    assert_eq!(first.message, format!("reading {}", path));
+    assert_eq!(first.field("path"), Some(path));
+    assert_eq!(first.field("format"), Some("native"));
+    assert_eq!(first.field("colors"), None);
+    let last = events.last().unwrap();
+    assert_eq!(last.message, format!("read {}: 1 colors", path));
+    assert_eq!(last.field("path"), Some(path));
+    assert_eq!(last.field("format"), Some("native"));
+    assert_eq!(last.field("colors"), Some("1"));
+}
+
+#[test]
+fn test_detection_events() {
+    let _guard = capture();
+
+    let dir = TempDir::new("log_detect");
+    let path = dir.write("detect.conf", "set -g status-style fg=#ffffff\n");
+    let path = path.as_str();
+    assert_eq!(parse_auto(path).unwrap().len(), 1);
+
+    let events = CAPTURE.0.lock().unwrap();
+    let detected = events.first().unwrap();
+    assert_eq!(detected.field("path"), Some(path));
+    assert_eq!(detected.field("format"), Some("tmux"));
+    assert_eq!(detected.field("detected_by"), Some("content"));
+}
+
+#[cfg(feature = "json")]
+#[test]
+fn test_fallback_events() {
+    let _guard = capture();
+
+    let dir = TempDir::new("log_tokens");
+    let tokens = r##"{"bg": {"$type": "color", "$value": "#282828"}}"##;
+    let path = dir.write("tokens.json", tokens);
+    let path = path.as_str();
+    assert_eq!(parse_auto(path).unwrap().len(), 1);
+
+    let events = CAPTURE.0.lock().unwrap();
+    let detected = &events[0];
+    assert_eq!(detected.field("format"), Some("json"));
+    assert_eq!(detected.field("detected_by"), Some("extension"));
+    let fallback = &events[1];
+    assert_eq!(fallback.field("path"), Some(path));
+    assert_eq!(fallback.field("from"), Some("json"));
+    assert_eq!(fallback.field("to"), Some("design-tokens"));
+    assert_eq!(events.len(), 2, "{:?}", events);
+}
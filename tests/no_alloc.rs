@@ -0,0 +1,51 @@
+// its own test binary, the counting allocator is global
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+
+use cool_rs::color::Canonical;
+use cool_rs::formats::{HexFormat, RGBu8Format};
+
+struct Counting;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.with(|count| count.set(count.get() + 1));
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: Counting = Counting;
+
+fn allocations<T>(f: impl FnOnce() -> T) -> usize {
+    let before = ALLOCATIONS.with(Cell::get);
+    std::hint::black_box(f());
+    ALLOCATIONS.with(Cell::get) - before
+}
+
+#[test]
+fn test_byte_parsers_dont_allocate() {
+    let buffer = b"\xff #c0ffee80 rgba(12, 34, 56, 50%) \xfe";
+    assert_eq!(
+        allocations(|| Canonical::parse_from_hex_bytes(&buffer[2..11]).unwrap()),
+        0
+    );
+    assert_eq!(
+        allocations(|| HexFormat::parse_bytes(&buffer[1..12]).unwrap()),
+        0
+    );
+    assert_eq!(
+        allocations(|| RGBu8Format::parse_bytes(&buffer[12..34]).unwrap()),
+        0
+    );
+    // errors may allocate their message
+    assert!(allocations(|| HexFormat::parse_bytes(b"#zz").unwrap_err()) > 0);
+}
@@ -0,0 +1,173 @@
+// NaN and infinities thrown at every public entry point taking floats. the
+// infallible ones clamp, with NaN at the low end of the range or, for
+// parameters of an operation, leaving the color as it is. the checked ones
+// return ComponentError::NotFinite
+use cool_rs::color::{
+    Canonical, ColorMatrix, ColorSpace, Cvd, GamutPolicy, Lab, Metric, Scheme, HSL, RGB, RGBA, XYZ,
+};
+use cool_rs::errors::{ComponentError, ErrorKind};
+
+const NAN: f32 = f32::NAN;
+const INF: f32 = f32::INFINITY;
+const NEG_INF: f32 = f32::NEG_INFINITY;
+
+fn rgba(r: u8, g: u8, b: u8, a: u8) -> Canonical {
+    Canonical::new(r, g, b, a)
+}
+
+#[test]
+fn test_from_f() {
+    assert_eq!(Canonical::from_f(NAN, NAN, NAN, NAN), rgba(0, 0, 0, 0));
+    assert_eq!(
+        Canonical::from_f(INF, NEG_INF, INF, INF),
+        rgba(255, 0, 255, 255)
+    );
+
+    let e = Canonical::try_from_f(0.5, 0.5, 0.5, NAN).unwrap_err();
+    assert_eq!(e.kind(), ErrorKind::NotFinite);
+    assert_eq!(
+        Canonical::try_from_f(INF, 0.5, 0.5, 1.0),
+        Err(ComponentError::NotFinite {
+            component: "red",
+            value: INF
+        })
+    );
+    assert!(matches!(
+        RGBA::try_new(0.0, NEG_INF, 0.0, 1.0),
+        Err(ComponentError::NotFinite {
+            component: "green",
+            ..
+        })
+    ));
+    assert_eq!(RGB::new_clamped(NAN, INF, NEG_INF), RGB::new(0.0, 1.0, 0.0));
+}
+
+#[test]
+fn test_mix() {
+    let a = rgba(10, 20, 30, 40);
+    let b = rgba(200, 100, 0, 255);
+    assert_eq!(a.mix(&b, NAN), a);
+    assert_eq!(a.mix(&b, NEG_INF), a);
+    assert_eq!(a.mix(&b, INF), b);
+
+    // float channels mix NaN into NaN, clamped() makes that 0
+    let nan = RGBA::new(NAN, 0.5, 0.5, 1.0);
+    let mixed = nan.mix(&RGBA::new(1.0, 0.5, 0.5, 1.0), 0.5).clamped();
+    assert_eq!(mixed.r, 0.0);
+}
+
+#[test]
+fn test_hsl() {
+    let gray = rgba(128, 128, 128, 255);
+    for hue in [NAN, INF, NEG_INF] {
+        // like hue 0
+        assert_eq!(
+            Canonical::from_hsl(HSL::new(hue, 1.0, 0.5), 1.0),
+            rgba(255, 0, 0, 255)
+        );
+        assert_eq!(Canonical::from_hsl(HSL::new(hue, 0.0, 0.5), 1.0), gray);
+    }
+    assert_eq!(Canonical::from_hsl(HSL::new(120.0, NAN, 0.5), 1.0), gray);
+    assert_eq!(
+        Canonical::from_hsl(HSL::new(120.0, INF, INF), 1.0),
+        rgba(255, 255, 255, 255)
+    );
+    assert_eq!(
+        Canonical::from_hsl(HSL::new(120.0, 1.0, NAN), INF),
+        rgba(0, 0, 0, 255)
+    );
+
+    assert_eq!(
+        Canonical::from_hwb(NAN, 0.0, 0.0, 1.0),
+        rgba(255, 0, 0, 255)
+    );
+    assert_eq!(
+        Canonical::from_hwb(0.0, NAN, NAN, 1.0),
+        rgba(255, 0, 0, 255)
+    );
+    assert_eq!(
+        Canonical::from_hwb(0.0, INF, INF, NAN),
+        rgba(128, 128, 128, 0)
+    );
+}
+
+#[test]
+fn test_kelvin() {
+    assert_eq!(Canonical::from_kelvin(NAN), Canonical::from_kelvin(1000.0));
+    assert_eq!(
+        Canonical::from_kelvin(NEG_INF),
+        Canonical::from_kelvin(1000.0)
+    );
+    assert_eq!(Canonical::from_kelvin(INF), Canonical::from_kelvin(40000.0));
+}
+
+#[test]
+fn test_matrices() {
+    let c = rgba(200, 100, 50, 255);
+    assert_eq!(ColorMatrix::saturation(NAN), ColorMatrix::IDENTITY);
+    assert_eq!(c.transformed(&ColorMatrix::saturation(NAN), false), c);
+    assert_eq!(
+        c.transformed(&ColorMatrix::saturation(INF), false),
+        rgba(255, 0, 0, 255)
+    );
+    assert_eq!(
+        c.transformed(&ColorMatrix::saturation(NEG_INF), true),
+        rgba(0, 255, 255, 255)
+    );
+
+    let nan = ColorMatrix([[NAN; 3]; 3]);
+    assert_eq!(c.transformed(&nan, false), rgba(0, 0, 0, 255));
+    assert_eq!(c.transformed(&nan, true), rgba(0, 0, 0, 255));
+}
+
+#[test]
+fn test_conversions() {
+    let black = rgba(0, 0, 0, 255);
+    assert_eq!(
+        Canonical::from_linear(RGB::new(NAN, INF, NEG_INF), 255),
+        rgba(0, 255, 0, 255)
+    );
+    assert_eq!(Canonical::from_lab(Lab::new(NAN, NAN, NAN), 255), black);
+    assert_eq!(
+        Canonical::from_lab(Lab::new(50.0, INF, 0.0), 255),
+        rgba(255, 0, 255, 255)
+    );
+    assert_eq!(Canonical::from_xyz(XYZ::new(NAN, 0.5, 0.5), 255), black);
+
+    for space in [ColorSpace::DisplayP3, ColorSpace::Rec2020] {
+        assert_eq!(
+            Canonical::from_space(space, RGBA::new(NAN, NEG_INF, NAN, NAN)),
+            rgba(0, 0, 0, 0)
+        );
+        assert_eq!(
+            Canonical::from_space(space, RGBA::new(INF, INF, INF, INF)),
+            rgba(255, 255, 255, 255)
+        );
+        assert!(Canonical::from_space_with(
+            space,
+            RGBA::new(NAN, 0.0, 0.0, 1.0),
+            GamutPolicy::Error
+        )
+        .is_ok());
+    }
+}
+
+#[test]
+fn test_thresholds() {
+    let colors = vec![
+        rgba(0, 0, 0, 255),
+        rgba(0, 0, 0, 255),
+        rgba(255, 255, 255, 255),
+    ];
+
+    // nothing is closer than NaN, nothing is distinct at infinity
+    let mut scheme = Scheme::from_colors("nan", colors.clone());
+    assert!(scheme.dedup_within(NAN, &Metric::DeltaE76).is_empty());
+    let mut scheme = Scheme::from_colors("inf", colors.clone());
+    let merges = scheme.dedup_within(INF, &Metric::DeltaE76);
+    assert_eq!(merges[0].count, 2);
+
+    let scheme = Scheme::from_colors("cvd", colors);
+    assert!(scheme.cvd_conflicts(Cvd::Protanopia, NAN).is_empty());
+    assert!(scheme.cvd_conflicts(Cvd::Protanopia, INF).is_empty());
+}
@@ -0,0 +1,49 @@
+// order of magnitude guards, not timings: each bound is far above what the
+// work takes in a debug build on a slow machine, so only a regression of
+// the algorithm trips it. `cargo bench` has the actual numbers
+#![cfg(feature = "perf-tests")]
+
+#[path = "common/fixtures.rs"]
+mod fixtures;
+
+use std::time::{Duration, Instant};
+
+use cool_rs::color::{Canonical, DistanceMetric, Metric};
+use cool_rs::formats::parse_any;
+use cool_rs::reader::parse_from_reader;
+
+fn assert_within(bound: Duration, what: &str, f: impl FnOnce()) {
+    let start = Instant::now();
+    f();
+    let elapsed = start.elapsed();
+    assert!(
+        elapsed < bound,
+        "{} took {:?}, more than {:?}",
+        what,
+        elapsed,
+        bound
+    );
+}
+
+#[test]
+fn test_parse_10k_lines() {
+    let palette = fixtures::palette(10_000);
+    assert_within(Duration::from_secs(5), "parsing 10k lines", || {
+        let scheme = parse_from_reader(palette.as_bytes(), &Default::default()).unwrap();
+        assert_eq!(scheme.len(), 10_000);
+    });
+}
+
+#[test]
+fn test_color_math() {
+    let colors = fixtures::colors(10_000);
+    let hex = fixtures::hex_strings(10_000);
+    assert_within(Duration::from_secs(5), "10k colors of math", || {
+        for (color, hex) in colors.iter().zip(&hex) {
+            assert_eq!(&parse_any(hex).unwrap(), color);
+            let hsl = color.to_hsl();
+            let back = Canonical::from_hsl(hsl, 1.0);
+            assert!(Metric::DeltaE2000.distance(color, &back) < 1.0);
+        }
+    });
+}
@@ -0,0 +1,51 @@
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+use cool_rs::errors::SchemeReaderError;
+use cool_rs::reader;
+
+// set for the child process, which then runs `stdin_child` against its piped stdin
+const CHILD_ENV: &str = "COOL_RS_STDIN_CHILD";
+
+#[test]
+fn stdin_child() {
+    if std::env::var_os(CHILD_ENV).is_none() {
+        return;
+    }
+    match reader::parse_stdin() {
+        Ok(scheme) => println!("parsed {} with {} colors", scheme.name, scheme.len()),
+        Err(SchemeReaderError::NoLinesError) => println!("no lines"),
+        Err(e) => println!("error {}", e),
+    }
+}
+
+fn run_child(input: &str) -> String {
+    let mut child = Command::new(std::env::current_exe().unwrap())
+        .args(["--exact", "stdin_child", "--nocapture", "--test-threads=1"])
+        .env(CHILD_ENV, "1")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(input.as_bytes())
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+    assert!(output.status.success());
+    String::from_utf8(output.stdout).unwrap()
+}
+
+#[test]
+fn parse_stdin_pipe() {
+    let stdout = run_child("mono\nbackground: #000000\n#ffffff\n");
+    assert!(stdout.contains("parsed mono with 2 colors"), "{}", stdout);
+}
+
+#[test]
+fn parse_stdin_empty_pipe() {
+    let stdout = run_child("");
+    assert!(stdout.contains("no lines"), "{}", stdout);
+}